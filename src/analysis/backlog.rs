@@ -0,0 +1,79 @@
+use crate::args::Args;
+
+/// Linux 5.4 之前 `net.core.somaxconn` 的上限
+const MAX_SOMAXCONN: usize = 65535;
+/// 最小监听队列长度(低于此值没有调优意义)
+const MIN_BACKLOG: usize = 1024;
+/// 突发期间需要在队列中排队等待accept的连接比例
+const BURST_QUEUE_RATIO: f64 = 0.25;
+/// 单个accept线程每秒可处理的新建连接数
+const ACCEPTS_PER_THREAD: usize = 20_000;
+
+/// 连接队列(accept backlog)调优建议
+pub struct BacklogRecommendation {
+    pub burst_connections: usize,   // 突发流量下的连接数
+    pub backlog: usize,             // 推荐的服务端accept backlog
+    pub somaxconn: usize,           // net.core.somaxconn
+    pub tcp_max_syn_backlog: usize, // net.ipv4.tcp_max_syn_backlog
+    pub accept_threads: usize,      // 推荐的accept线程数
+}
+
+/// 根据突发连接数计算连接队列调优建议
+///
+/// 突发时新增连接会先进入内核的SYN队列和accept队列，队列过小会导致
+/// 连接被直接丢弃。队列长度按突发连接数的一定比例估算并向上取整到2的幂
+pub fn calculate_backlog(args: &Args) -> BacklogRecommendation {
+    let burst_connections = args.burst_connections();
+
+    let backlog = ((burst_connections as f64 * BURST_QUEUE_RATIO) as usize)
+        .max(MIN_BACKLOG)
+        .next_power_of_two()
+        .min(MAX_SOMAXCONN);
+
+    // SYN队列需要容纳握手未完成的连接，通常设为accept队列的2倍
+    let tcp_max_syn_backlog = backlog * 2;
+
+    // accept线程不超过CPU核心数的一半，避免与IO线程争抢CPU
+    let max_accept_threads = (args.cpu_cores / 2).max(1);
+    let accept_threads = burst_connections
+        .div_ceil(ACCEPTS_PER_THREAD)
+        .clamp(1, max_accept_threads);
+
+    BacklogRecommendation {
+        burst_connections,
+        backlog,
+        somaxconn: backlog,
+        tcp_max_syn_backlog,
+        accept_threads,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calculate_backlog() {
+        let args = Args {
+            cpu_cores: 16,
+            expected_connections: 1000,
+            burst_factor: 3.0,
+            ..Default::default()
+        };
+        let backlog = calculate_backlog(&args);
+        assert_eq!(backlog.burst_connections, 3000);
+        assert_eq!(backlog.backlog, MIN_BACKLOG);
+        assert_eq!(backlog.tcp_max_syn_backlog, MIN_BACKLOG * 2);
+        assert_eq!(backlog.accept_threads, 1);
+
+        let args = Args {
+            cpu_cores: 4,
+            expected_connections: 200_000,
+            burst_factor: 5.0,
+            ..Default::default()
+        };
+        let backlog = calculate_backlog(&args);
+        assert_eq!(backlog.somaxconn, MAX_SOMAXCONN);
+        assert_eq!(backlog.accept_threads, 2); // capped at cpu_cores / 2
+    }
+}
@@ -0,0 +1,102 @@
+use crate::analysis::safety::TheoreticalLimits;
+use crate::args::Args;
+
+/// 由N个相同节点组成的集群容量
+///
+/// 单节点分析不变，集群数值按节点数和负载均衡效率汇总；
+/// `expected_connections` 和 `burst_factor` 均为每个节点的负载
+pub struct ClusterAnalysis {
+    pub nodes: u32,                  // 节点数
+    pub lb_efficiency: f64,          // 负载均衡效率(节点间负载不均的折扣)
+    pub max_connections: usize,      // 集群稳态最大连接数
+    pub max_throughput: f64,         // 集群可持续吞吐量(MB/s)
+    pub burst_connections: usize,    // 集群突发连接数
+    pub failover_connections: usize, // 单节点故障时剩余每个节点需承载的突发连接数
+    pub node_max_connections: usize, // 单节点稳态最大连接数
+    pub survives_node_failure: bool, // 单节点故障(N-1)时能否承受突发负载
+}
+
+/// 按节点数汇总集群容量并检查N-1冗余，单节点部署时返回 `None`
+///
+/// 单节点故障后其负载由剩余N-1个节点分担，且负载均衡效率同样打折，
+/// 每个剩余节点的突发负载为 突发连接数 × N / ((N-1) × 效率)，不能超过单节点稳态极限
+pub fn calculate_cluster(args: &Args, limits: &TheoreticalLimits) -> Option<ClusterAnalysis> {
+    if args.nodes <= 1 {
+        return None;
+    }
+    let nodes = f64::from(args.nodes);
+    let efficiency = args.lb_efficiency;
+    let burst_connections = args.burst_connections() * args.nodes as usize;
+    let failover_connections =
+        (burst_connections as f64 / ((nodes - 1.0) * efficiency)).ceil() as usize;
+
+    Some(ClusterAnalysis {
+        nodes: args.nodes,
+        lb_efficiency: efficiency,
+        max_connections: (limits.steady_state_max as f64 * nodes * efficiency) as usize,
+        max_throughput: limits.max_throughput * nodes * efficiency,
+        burst_connections,
+        failover_connections,
+        node_max_connections: limits.steady_state_max,
+        survives_node_failure: failover_connections <= limits.steady_state_max,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::analyze;
+
+    fn node() -> Args {
+        Args {
+            total_ram: 64.0,
+            cpu_cores: 16,
+            net_gbps: 10.0,
+            disk_type: "nvme".to_string(),
+            avg_file_size: 10.0,
+            expected_connections: 1000,
+            burst_factor: 2.0,
+            complexity: "medium".to_string(),
+            lb_efficiency: 0.9,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_single_node_has_no_cluster() {
+        let limits = analyze(&node()).unwrap().safety.theoretical_limits;
+        assert!(calculate_cluster(&Args { nodes: 1, ..node() }, &limits).is_none());
+    }
+
+    #[test]
+    fn test_cluster_aggregates_and_checks_n_minus_one() {
+        let args = Args { nodes: 4, ..node() };
+        let limits = analyze(&args).unwrap().safety.theoretical_limits;
+        let cluster = calculate_cluster(&args, &limits).unwrap();
+        assert_eq!(
+            cluster.max_connections,
+            (limits.steady_state_max as f64 * 4.0 * 0.9) as usize
+        );
+        assert_eq!(cluster.burst_connections, 8000);
+        // 8000 / (3 × 0.9) 向上取整
+        assert_eq!(cluster.failover_connections, 2963);
+        assert_eq!(
+            cluster.survives_node_failure,
+            limits.steady_state_max >= 2963
+        );
+
+        // 两个节点时剩余节点要承担全部突发负载
+        let pair = Args {
+            nodes: 2,
+            expected_connections: limits.steady_state_max,
+            burst_factor: 1.0,
+            ..node()
+        };
+        let limits = analyze(&pair).unwrap().safety.theoretical_limits;
+        assert!(
+            !calculate_cluster(&pair, &limits)
+                .unwrap()
+                .survives_node_failure
+        );
+    }
+}
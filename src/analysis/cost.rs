@@ -0,0 +1,206 @@
+use crate::args::Args;
+
+/// 每月资源单价
+pub struct CostRates {
+    pub per_gb_ram: f64, // 每GB内存
+    pub per_core: f64,   // 每个CPU核心
+    pub per_gbps: f64,   // 每Gbps带宽
+}
+
+impl CostRates {
+    /// 由命令行参数构建单价，未指定任何单价时返回 `None`(不输出成本估算)
+    pub fn from_args(args: &Args) -> Option<CostRates> {
+        if args.cost_per_gb_ram.is_none()
+            && args.cost_per_core.is_none()
+            && args.cost_per_gbps.is_none()
+        {
+            return None;
+        }
+        Some(CostRates {
+            per_gb_ram: args.cost_per_gb_ram.unwrap_or(0.0),
+            per_core: args.cost_per_core.unwrap_or(0.0),
+            per_gbps: args.cost_per_gbps.unwrap_or(0.0),
+        })
+    }
+}
+
+/// 扩容建议给出的目标资源量，未建议扩容的维度为 `None`
+pub struct ScalingSuggestion {
+    pub ram_gb: Option<f64>,
+    pub cpu_cores: Option<usize>,
+    pub net_gbps: Option<f64>,
+}
+
+/// 扩容带来的每月增量成本
+pub struct CostEstimate {
+    pub ram: f64,
+    pub cpu: f64,
+    pub network: f64,
+}
+
+impl CostEstimate {
+    pub fn total(&self) -> f64 {
+        self.ram + self.cpu + self.network
+    }
+}
+
+/// 按扩容建议与当前配置的差值计算每月增量成本
+pub fn estimate_scaling_cost(
+    args: &Args,
+    rates: &CostRates,
+    suggestion: &ScalingSuggestion,
+) -> CostEstimate {
+    let ram = suggestion
+        .ram_gb
+        .map_or(0.0, |gb| (gb - args.total_ram).max(0.0) * rates.per_gb_ram);
+    let cpu = suggestion.cpu_cores.map_or(0.0, |cores| {
+        cores.saturating_sub(args.cpu_cores) as f64 * rates.per_core
+    });
+    let network = suggestion
+        .net_gbps
+        .map_or(0.0, |gbps| (gbps - args.net_gbps).max(0.0) * rates.per_gbps);
+
+    CostEstimate { ram, cpu, network }
+}
+
+/// 成本估算的单行描述
+pub fn format_cost(estimate: &CostEstimate, currency: &str) -> String {
+    let currency = if currency.is_empty() { "CNY" } else { currency };
+    format!(
+        "{:.2} {currency}/月 (内存 {:.2}, CPU {:.2}, 带宽 {:.2})",
+        estimate.total(),
+        estimate.ram,
+        estimate.cpu,
+        estimate.network
+    )
+}
+
+/// 按当前配置和单价估算的每月成本，`--disk-cost` 中未列出的磁盘类型按0计
+pub fn monthly_cost(args: &Args, rates: &CostRates) -> f64 {
+    let disk = args
+        .disk_cost
+        .iter()
+        .find(|(name, _)| *name == args.disk_type)
+        .map_or(0.0, |(_, cost)| *cost);
+    args.total_ram * rates.per_gb_ram
+        + args.cpu_cores as f64 * rates.per_core
+        + args.net_gbps * rates.per_gbps
+        + disk
+}
+
+/// 单个磁盘类型的性价比评估
+pub struct DiskRanking {
+    pub disk_type: String,
+    pub max_connections: usize,       // 理论最大连接数
+    pub meets_target: bool,           // 是否满足预期连接数
+    pub monthly_cost: f64,            // 整机每月成本
+    pub cost_per_k_conn: Option<f64>, // 每千连接每月成本，成本或连接数为0时为 `None`
+}
+
+/// 固定其他参数，逐个分析所有已注册的磁盘类型，按每千连接月成本从低到高排序
+///
+/// 分层存储参数会被清除，使每种磁盘单独评估
+pub fn rank_disks(args: &Args) -> anyhow::Result<Vec<DiskRanking>> {
+    let rates = CostRates::from_args(args).unwrap_or(CostRates {
+        per_gb_ram: 0.0,
+        per_core: 0.0,
+        per_gbps: 0.0,
+    });
+    let mut rankings = Vec::new();
+    for name in crate::config::disk_type_names() {
+        let candidate = Args {
+            disk_type: name.to_string(),
+            hot_disk: None,
+            cold_disk: None,
+            hot_ratio: None,
+            sustained_write_speed: None,
+            ..args.clone()
+        };
+        let result = crate::analysis::analyze(&candidate)?;
+        let max_connections = result.safety.theoretical_limits.max_connections;
+        let monthly_cost = monthly_cost(&candidate, &rates);
+        rankings.push(DiskRanking {
+            disk_type: name.to_string(),
+            max_connections,
+            meets_target: result.safety.meets_target,
+            monthly_cost,
+            cost_per_k_conn: (monthly_cost > 0.0 && max_connections > 0)
+                .then(|| monthly_cost / max_connections as f64 * 1000.0),
+        });
+    }
+    // 无法计算单位成本的排在最后，其余按单位成本升序，相同时连接数多者优先
+    rankings.sort_by(|a, b| match (a.cost_per_k_conn, b.cost_per_k_conn) {
+        (Some(x), Some(y)) => x.total_cmp(&y),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => b.max_connections.cmp(&a.max_connections),
+    });
+    Ok(rankings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_scaling_cost() {
+        let args = Args {
+            total_ram: 32.0,
+            cpu_cores: 16,
+            net_gbps: 1.0,
+            cost_per_gb_ram: Some(10.0),
+            cost_per_core: Some(50.0),
+            ..Default::default()
+        };
+        assert!(CostRates::from_args(&Args::default()).is_none());
+
+        let rates = CostRates::from_args(&args).unwrap();
+        let estimate = estimate_scaling_cost(
+            &args,
+            &rates,
+            &ScalingSuggestion {
+                ram_gb: Some(64.0),
+                cpu_cores: Some(20),
+                net_gbps: Some(10.0),
+            },
+        );
+        assert_eq!(estimate.ram, 320.0);
+        assert_eq!(estimate.cpu, 200.0);
+        assert_eq!(estimate.network, 0.0); // no bandwidth rate given
+        assert_eq!(estimate.total(), 520.0);
+    }
+
+    #[test]
+    fn test_rank_disks_by_cost_per_connection() {
+        let args = Args {
+            total_ram: 32.0,
+            cpu_cores: 16,
+            net_gbps: 10.0,
+            disk_type: "sata_ssd".to_string(),
+            avg_file_size: 10.0,
+            expected_connections: 1000,
+            burst_factor: 3.0,
+            complexity: "medium".to_string(),
+            cost_per_gb_ram: Some(10.0),
+            disk_cost: vec![
+                ("sata_hdd".to_string(), 20.0),
+                ("sata_ssd".to_string(), 60.0),
+                ("nvme".to_string(), 5000.0),
+            ],
+            ..Default::default()
+        };
+        let rates = CostRates::from_args(&args).unwrap();
+        assert_eq!(monthly_cost(&args, &rates), 320.0 + 60.0);
+
+        let rankings = rank_disks(&args).unwrap();
+        assert_eq!(rankings.len(), 3);
+        let costs: Vec<f64> = rankings
+            .iter()
+            .map(|r| r.cost_per_k_conn.unwrap())
+            .collect();
+        assert!(costs.windows(2).all(|pair| pair[0] <= pair[1]));
+        // 过高的NVMe溢价使其排在最后
+        assert_eq!(rankings[2].disk_type, "nvme");
+        assert_eq!(rankings[2].monthly_cost, 5320.0);
+    }
+}
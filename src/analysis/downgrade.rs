@@ -0,0 +1,144 @@
+use crate::analysis::cost::{CostRates, monthly_cost};
+use crate::analysis::safety::ResourceUtilization;
+use crate::analysis::{allocate_memory, calculate_safety};
+use crate::args::Args;
+
+/// 各资源利用率均低于此值(%)时视为配置过剩
+pub const OVERPROVISIONED_BELOW_PCT: f64 = 25.0;
+/// 缩容后突发负载下的目标利用率，与低风险阈值保留相同的余量
+pub const TARGET_BURST_UTILIZATION: f64 = 0.6;
+/// 缩容的下限
+const MIN_RAM_GB: f64 = 4.0;
+const MIN_CPU_CORES: usize = 2;
+/// 缩容后的配置无法承载突发负载时，每次放大的比例
+const STEP_UP: f64 = 1.25;
+
+/// 配置过剩时的缩容建议
+pub struct DowngradeSuggestion {
+    pub ram_gb: f64,                   // 建议的服务器内存(GB)
+    pub cpu_cores: usize,              // 建议的CPU核心数
+    pub max_connections: usize,        // 缩容后的稳态最大连接数
+    pub tightest: (&'static str, f64), // 当前利用率最高的资源及其利用率(%)
+    pub monthly_savings: Option<f64>,  // 每月节省的成本，未指定单价时为 `None`
+}
+
+/// 各资源利用率均远低于阈值时，给出仍能承载 预期连接数 × 突发倍数 的较小内存和CPU
+///
+/// 内存和CPU分别按 利用率 × 突发倍数 / 目标利用率 折算，重新计算稳态极限验证；
+/// 模型中的固定开销使容量不完全线性，验证不通过时逐步放大，回到当前配置则不建议缩容
+pub fn calculate_downgrade(
+    args: &Args,
+    utilization: &ResourceUtilization,
+) -> Option<DowngradeSuggestion> {
+    let tightest = utilization.tightest();
+    if tightest.1 >= OVERPROVISIONED_BELOW_PCT {
+        return None;
+    }
+    let burst_connections = args.burst_connections();
+    let scale = |pct: f64| pct / 100.0 * args.burst_factor.max(1.0) / TARGET_BURST_UTILIZATION;
+    let memory_pct = utilization.heap_pct.max(utilization.direct_pct);
+    let mut ram_gb = (args.total_ram * scale(memory_pct))
+        .ceil()
+        .max(MIN_RAM_GB)
+        .min(args.total_ram);
+    let mut cpu_cores = ((args.cpu_cores as f64 * scale(utilization.cpu_pct)).ceil() as usize)
+        .max(MIN_CPU_CORES)
+        .min(args.cpu_cores);
+
+    while ram_gb < args.total_ram || cpu_cores < args.cpu_cores {
+        let candidate = Args {
+            total_ram: ram_gb,
+            cpu_cores,
+            ..args.clone()
+        };
+        if let Some(max_connections) =
+            steady_state_max(&candidate).filter(|max| *max >= burst_connections)
+        {
+            return Some(DowngradeSuggestion {
+                ram_gb,
+                cpu_cores,
+                max_connections,
+                tightest,
+                monthly_savings: CostRates::from_args(args)
+                    .map(|rates| monthly_cost(args, &rates) - monthly_cost(&candidate, &rates)),
+            });
+        }
+        ram_gb = (ram_gb * STEP_UP).ceil().min(args.total_ram);
+        cpu_cores = ((cpu_cores as f64 * STEP_UP).ceil() as usize).min(args.cpu_cores);
+    }
+    None
+}
+
+/// 只重新计算内存分配和安全分析，得到配置的稳态最大连接数
+fn steady_state_max(args: &Args) -> Option<usize> {
+    let (direct_mem_gb, heap_mem_gb) = allocate_memory(args).ok()?;
+    Some(
+        calculate_safety(args, direct_mem_gb, heap_mem_gb)
+            .theoretical_limits
+            .steady_state_max,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::analyze;
+
+    fn oversized() -> Args {
+        Args {
+            total_ram: 256.0,
+            cpu_cores: 64,
+            net_gbps: 40.0,
+            disk_type: "nvme".to_string(),
+            avg_file_size: 10.0,
+            expected_connections: 200,
+            burst_factor: 2.0,
+            complexity: "medium".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_downgrade_still_carries_burst() {
+        let args = oversized();
+        let result = analyze(&args).unwrap();
+        let suggestion = result.downgrade.unwrap();
+        assert!(suggestion.ram_gb < args.total_ram || suggestion.cpu_cores < args.cpu_cores);
+        assert!(suggestion.max_connections >= args.burst_connections());
+        assert!(suggestion.tightest.1 < OVERPROVISIONED_BELOW_PCT);
+        assert!(suggestion.monthly_savings.is_none());
+        assert!(
+            result
+                .safety
+                .recommendations
+                .iter()
+                .any(|rec| rec.summary.starts_with("配置过剩"))
+        );
+    }
+
+    #[test]
+    fn test_no_downgrade_when_any_resource_is_busy() {
+        let args = Args {
+            expected_connections: 5000,
+            cpu_cores: 2,
+            ..oversized()
+        };
+        let result = analyze(&args).unwrap();
+        let (_, tightest_pct) = result.safety.theoretical_limits.utilization.tightest();
+        assert!(tightest_pct >= OVERPROVISIONED_BELOW_PCT);
+        assert!(result.downgrade.is_none());
+    }
+
+    #[test]
+    fn test_downgrade_reports_savings() {
+        let args = Args {
+            cost_per_gb_ram: Some(2.0),
+            cost_per_core: Some(20.0),
+            ..oversized()
+        };
+        let suggestion = analyze(&args).unwrap().downgrade.unwrap();
+        let expected = (args.total_ram - suggestion.ram_gb) * 2.0
+            + (args.cpu_cores - suggestion.cpu_cores) as f64 * 20.0;
+        assert!((suggestion.monthly_savings.unwrap() - expected).abs() < 1e-9);
+    }
+}
@@ -1,16 +1,910 @@
+use crate::analysis::cost::{CostRates, ScalingSuggestion, estimate_scaling_cost, format_cost};
+use crate::analysis::safety::{SafetyPolicy, memory_guard_thresholds, required_fds};
+use crate::analysis::{
+    BASE_METASPACE, CONNECTION_FACTOR, CONNECTIONS_BASE, THREAD_FACTOR,
+    calculate_metaspace_detailed,
+};
 use crate::utils::Repeated;
 use crate::{SafetyAnalysis, analysis::performance::PerformanceReport, args::Args};
 use colored::Colorize; // Bring trait implementation into scope
+use std::io::Write;
+use std::path::Path;
+
+/// 每秒可加载的类元数据量(MB)
+const CLASS_LOAD_MB_PER_SEC: f64 = 100.0;
+
+/// 短生命周期进程的启动预热开销估算
+pub struct WarmupEstimate {
+    pub class_loading_secs: f64, // 类加载耗时(秒)
+    pub jit_secs: f64,           // JIT编译达到稳态的耗时(秒)
+}
+
+impl WarmupEstimate {
+    pub fn total_secs(&self) -> f64 {
+        self.class_loading_secs + self.jit_secs
+    }
+}
+
+/// 根据元空间大小和应用复杂度估算启动预热开销
+///
+/// 类加载耗时与需要加载的类元数据量(以元空间大小近似)成正比，
+/// JIT编译耗时主要取决于热点代码的多少(以应用复杂度近似)
+pub fn estimate_warmup(metaspace_size_mb: i32, complexity: &str) -> WarmupEstimate {
+    let jit_secs = match complexity {
+        "high" => 10.0,
+        "low" => 2.0,
+        _ => 5.0,
+    };
+    WarmupEstimate {
+        class_loading_secs: metaspace_size_mb as f64 / CLASS_LOAD_MB_PER_SEC,
+        jit_secs,
+    }
+}
+
+/// 代码缓存下限(MB)，低于此值JIT编译容易因缓存满而停止
+const MIN_CODE_CACHE_MB: u32 = 48;
+/// 代码缓存上限(MB)，JVM限制 `ReservedCodeCacheSize` 不超过2GB
+const MAX_CODE_CACHE_MB: u32 = 2048;
+/// 每个CPU核心(JIT编译线程随核心数增加)额外预留的代码缓存(MB)
+const CODE_CACHE_PER_CORE_MB: u32 = 4;
+
+/// 计算 `-XX:ReservedCodeCacheSize` (MB)
+///
+/// 复杂度越高热点方法越多；核心数越多JIT编译线程越多，编译产出的代码也越多。
+/// `--code-cache-mb` 指定时直接使用该值
+pub fn code_cache_mb(args: &Args) -> u32 {
+    if let Some(mb) = args.code_cache_mb {
+        return mb;
+    }
+    let base = match args.complexity.as_str() {
+        "high" => 320,
+        "low" => 96,
+        _ => 192,
+    };
+    (base + args.cpu_cores as u32 * CODE_CACHE_PER_CORE_MB)
+        .clamp(MIN_CODE_CACHE_MB, MAX_CODE_CACHE_MB)
+}
+
+/// 代码缓存配置的来源说明
+fn code_cache_note(args: &Args) -> &'static str {
+    if args.code_cache_mb.is_some() {
+        "手动指定"
+    } else {
+        "按复杂度和CPU核心数计算"
+    }
+}
+
+/// 垃圾收集器
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Collector {
+    G1,
+    Zgc,
+    Shenandoah,
+}
+
+impl Collector {
+    /// 该收集器可用的最低JDK版本
+    fn min_jdk(self) -> u32 {
+        match self {
+            Collector::G1 => 8,
+            Collector::Zgc => 11,
+            Collector::Shenandoah => 12,
+        }
+    }
+}
+
+/// 目标JDK版本，未指定时按17计算
+pub(crate) fn jdk_version(args: &Args) -> u32 {
+    if args.jdk_version == 0 {
+        17
+    } else {
+        args.jdk_version
+    }
+}
+
+/// 根据 `--gc` 和应用复杂度选择收集器
+///
+/// `auto` 时沿用按复杂度选择的规则；所选收集器在目标JDK上不可用时回退到G1，
+/// 由 [`jvm_compat_warnings`] 给出提示
+pub fn selected_collector(args: &Args) -> Collector {
+    let requested = match args.gc.as_str() {
+        "g1" => Collector::G1,
+        "zgc" => Collector::Zgc,
+        "shenandoah" => Collector::Shenandoah,
+        _ => match args.complexity.as_str() {
+            "high" => Collector::Zgc,
+            "low" => Collector::G1,
+            _ => Collector::Shenandoah,
+        },
+    };
+    if jdk_version(args) < requested.min_jdk() {
+        Collector::G1
+    } else {
+        requested
+    }
+}
+
+/// ZGC在JDK 15之前不支持压缩类指针
+fn supports_compressed_class_pointers(args: &Args) -> bool {
+    selected_collector(args) != Collector::Zgc || jdk_version(args) >= 15
+}
+
+/// 所选收集器对应的GC参数
+pub fn gc_flags(args: &Args) -> Vec<JvmFlag> {
+    let mut flags = Vec::new();
+    match selected_collector(args) {
+        Collector::Zgc => {
+            // JDK 15之前ZGC仍是实验特性
+            if jdk_version(args) < 15 {
+                flags.push(JvmFlag::new("-XX:+UnlockExperimentalVMOptions"));
+            }
+            flags.push(JvmFlag::new("-XX:+UseZGC").note("低延迟GC，适合高复杂度应用"));
+            flags.push(JvmFlag::new("-XX:ZCollectionInterval=5").note("每5秒一次ZGC"));
+        }
+        Collector::G1 => {
+            flags.push(JvmFlag::new("-XX:+UseG1GC").note("平衡型GC"));
+            flags.push(JvmFlag::new("-XX:MaxGCPauseMillis=200"));
+        }
+        Collector::Shenandoah => {
+            flags.push(JvmFlag::new("-XX:+UseShenandoahGC").note("并发GC"));
+            flags.push(JvmFlag::new("-XX:ShenandoahGCHeuristics=adaptive"));
+        }
+    }
+    flags
+}
+
+/// 单条参数适用的JDK版本范围
+struct FlagSupport {
+    prefix: &'static str,     // 参数前缀(不含取值)
+    since: u32,               // 最早支持的JDK主版本
+    removed_in: Option<u32>,  // 被移除或取代的JDK主版本
+    substitute: &'static str, // 不可用时的替代写法
+}
+
+/// 推荐参数中与JDK版本相关的条目，未列出的参数视为所有版本可用
+const FLAG_SUPPORT: &[FlagSupport] = &[
+    FlagSupport {
+        prefix: "-XX:MaxMetaspaceSize",
+        since: 8,
+        removed_in: None,
+        substitute: "-XX:MaxPermSize",
+    },
+    FlagSupport {
+        prefix: "-XX:+UseCompressedClassPointers",
+        since: 8,
+        removed_in: None,
+        substitute: "省略(JDK 8之前无压缩类空间)",
+    },
+    FlagSupport {
+        prefix: "-XX:CompressedClassSpaceSize",
+        since: 8,
+        removed_in: None,
+        substitute: "省略(JDK 8之前无压缩类空间)",
+    },
+    FlagSupport {
+        prefix: "-XX:NativeMemoryTracking",
+        since: 8,
+        removed_in: None,
+        substitute: "省略",
+    },
+    FlagSupport {
+        prefix: "-XX:+UseG1GC",
+        since: 7,
+        removed_in: None,
+        substitute: "-XX:+UseConcMarkSweepGC",
+    },
+    FlagSupport {
+        prefix: "-XX:+UseZGC",
+        since: 11,
+        removed_in: None,
+        substitute: "-XX:+UseG1GC",
+    },
+    FlagSupport {
+        prefix: "-XX:ZCollectionInterval",
+        since: 11,
+        removed_in: None,
+        substitute: "省略",
+    },
+    FlagSupport {
+        prefix: "-XX:+UseShenandoahGC",
+        since: 12,
+        removed_in: None,
+        substitute: "-XX:+UseG1GC",
+    },
+    FlagSupport {
+        prefix: "-XX:ShenandoahGCHeuristics",
+        since: 12,
+        removed_in: None,
+        substitute: "省略",
+    },
+    FlagSupport {
+        prefix: "-Xlog:",
+        since: 9,
+        removed_in: None,
+        substitute: "-XX:+PrintGCDetails -Xloggc:/var/log/jvm_gc.log",
+    },
+    FlagSupport {
+        prefix: "-XX:+PrintGCDetails",
+        since: 6,
+        removed_in: Some(9),
+        substitute: "-Xlog:gc*",
+    },
+    FlagSupport {
+        prefix: "-XX:+PrintGCDateStamps",
+        since: 6,
+        removed_in: Some(9),
+        substitute: "-Xlog:gc*::time",
+    },
+    FlagSupport {
+        prefix: "-XX:+PrintClassHistogram",
+        since: 6,
+        removed_in: Some(9),
+        substitute: "-Xlog:classhisto*=trace",
+    },
+    FlagSupport {
+        prefix: "-XX:+PrintReferenceGC",
+        since: 6,
+        removed_in: Some(9),
+        substitute: "-Xlog:gc+ref=debug",
+    },
+    FlagSupport {
+        prefix: "-XX:+PrintTenuringDistribution",
+        since: 6,
+        removed_in: Some(9),
+        substitute: "-Xlog:gc+age=trace",
+    },
+    FlagSupport {
+        prefix: "-XX:+UseAppCDS",
+        since: 10,
+        removed_in: Some(11),
+        substitute: "省略(JDK 10之前为商业特性，JDK 11起默认可用)",
+    },
+    FlagSupport {
+        prefix: "-XX:SharedArchiveFile",
+        since: 10,
+        removed_in: None,
+        substitute: "省略(JDK 10之前不支持应用类CDS)",
+    },
+];
+
+/// 在目标JDK上无法使用的参数
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlagIssue {
+    pub flag: String,       // 推荐输出中的参数
+    pub reason: String,     // 不可用的原因
+    pub substitute: String, // 替代写法
+}
+
+/// 逐条检查推荐参数在目标JDK(`--jdk-version`)上是否可用
+///
+/// 除版本范围外，还检查JDK 15之前的ZGC是否同时解锁了实验特性
+pub fn flag_compat_issues(flags: &JvmFlags, args: &Args) -> Vec<FlagIssue> {
+    let jdk = jdk_version(args);
+    let mut issues = Vec::new();
+    for flag in flags.iter() {
+        let Some(support) = FLAG_SUPPORT
+            .iter()
+            .find(|s| flag.value.starts_with(s.prefix))
+        else {
+            continue;
+        };
+        let reason = if jdk < support.since {
+            format!("需要JDK {}+", support.since)
+        } else if let Some(removed) = support.removed_in.filter(|&removed| jdk >= removed) {
+            format!("JDK {removed}起已移除或被取代")
+        } else {
+            continue;
+        };
+        issues.push(FlagIssue {
+            flag: flag.value.clone(),
+            reason,
+            substitute: support.substitute.to_string(),
+        });
+    }
+    let unlocked = flags
+        .iter()
+        .any(|f| f.value == "-XX:+UnlockExperimentalVMOptions");
+    if (11..15).contains(&jdk) && !unlocked && flags.iter().any(|f| f.value == "-XX:+UseZGC") {
+        issues.push(FlagIssue {
+            flag: "-XX:+UseZGC".to_string(),
+            reason: "JDK 15之前ZGC为实验特性".to_string(),
+            substitute: "-XX:+UnlockExperimentalVMOptions -XX:+UseZGC".to_string(),
+        });
+    }
+    issues
+}
+
+/// 压缩类空间占元空间的比例
+const COMPRESSED_CLASS_SPACE_RATIO: f32 = 0.4;
+/// 压缩类空间下限(MB)
+const MIN_COMPRESSED_CLASS_SPACE_MB: f32 = 256.0;
+
+/// 压缩类空间大小(MB)，按元空间的固定比例划分并设置下限
+pub fn compressed_class_space_mb(metaspace_size_mb: i32) -> i32 {
+    (metaspace_size_mb as f32 * COMPRESSED_CLASS_SPACE_RATIO).max(MIN_COMPRESSED_CLASS_SPACE_MB)
+        as i32
+}
+
+/// 元空间优化参数，收集器不支持压缩类指针时为空
+pub fn metaspace_flags(args: &Args, metaspace_size_mb: i32) -> Vec<JvmFlag> {
+    if !supports_compressed_class_pointers(args) {
+        return Vec::new();
+    }
+    vec![
+        JvmFlag::new("-XX:+UseCompressedClassPointers"),
+        JvmFlag::new(format!(
+            "-XX:CompressedClassSpaceSize={}m",
+            compressed_class_space_mb(metaspace_size_mb)
+        )),
+    ]
+}
+
+/// 收集器与JDK版本的不兼容组合提示
+pub fn jvm_compat_warnings(args: &Args) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let jdk = jdk_version(args);
+    let requested = match args.gc.as_str() {
+        "zgc" => Some(Collector::Zgc),
+        "shenandoah" => Some(Collector::Shenandoah),
+        _ => None,
+    };
+    if let Some(collector) = requested
+        && jdk < collector.min_jdk()
+    {
+        warnings.push(format!(
+            "{collector:?}需要JDK {}+，目标JDK {jdk}不可用，已改用G1",
+            collector.min_jdk()
+        ));
+    }
+    if args.complexity == "high" && !supports_compressed_class_pointers(args) {
+        warnings.push(format!(
+            "ZGC在JDK 15之前不支持压缩类指针，目标JDK {jdk}下已省略-XX:+UseCompressedClassPointers"
+        ));
+    }
+    warnings
+}
+
+/// 未设置 `-XX:MaxDirectMemorySize` 时HotSpot的默认行为说明
+pub const IMPLICIT_DIRECT_MEMORY_NOTE: &str = "未设置-XX:MaxDirectMemorySize: HotSpot默认直接内存上限约等于-Xmx，堆与直接内存合计最多可达2倍堆";
+
+/// 一条JVM参数
+#[derive(Debug, Clone, PartialEq)]
+pub struct JvmFlag {
+    pub value: String,        // 参数本身，如 -Xmx8g
+    pub note: Option<String>, // 行尾说明，仅用于展示
+}
+
+impl JvmFlag {
+    pub fn new(value: impl Into<String>) -> Self {
+        JvmFlag {
+            value: value.into(),
+            note: None,
+        }
+    }
+
+    pub fn note(mut self, note: impl Into<String>) -> Self {
+        self.note = Some(note.into());
+        self
+    }
+}
+
+impl std::fmt::Display for JvmFlag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.note {
+            Some(note) => write!(f, "{}  # {note}", self.value),
+            None => write!(f, "{}", self.value),
+        }
+    }
+}
+
+/// 一组带标题的JVM参数
+pub struct JvmFlagGroup {
+    pub title: &'static str,   // 分组标题
+    pub comments: Vec<String>, // 分组说明(兼容性提示、预热估算等)
+    pub flags: Vec<JvmFlag>,   // 参数列表
+}
+
+/// 推荐的JVM参数集合
+///
+/// 控制台、markdown报告和 `--emit` 输出共用同一份参数，避免各处手写不一致
+pub struct JvmFlags {
+    pub groups: Vec<JvmFlagGroup>,
+}
+
+impl JvmFlags {
+    /// 根据当前内存分配和安全性分析生成推荐参数
+    pub fn recommended(
+        args: &Args,
+        direct_mem_gb: f64,
+        heap_mem_gb: f64,
+        metaspace_size_mb: i32,
+        safety: &SafetyAnalysis,
+    ) -> Self {
+        let mut groups = Vec::new();
+
+        let mut basic = vec![
+            JvmFlag::new(format!("-Xms{}g", heap_mem_gb as i32)),
+            JvmFlag::new(format!("-Xmx{}g", heap_mem_gb as i32)),
+        ];
+        if !args.no_explicit_direct {
+            basic.push(JvmFlag::new(format!(
+                "-XX:MaxDirectMemorySize={}g",
+                direct_mem_gb as i32
+            )));
+        }
+        if args.thread_stack_kb > 0
+            && args.thread_stack_kb != crate::analysis::native::DEFAULT_THREAD_STACK_KB
+        {
+            basic.push(JvmFlag::new(format!("-Xss{}k", args.thread_stack_kb)));
+        }
+        basic.extend([
+            JvmFlag::new(format!("-XX:MaxMetaspaceSize={metaspace_size_mb}m")),
+            JvmFlag::new(format!(
+                "-XX:ReservedCodeCacheSize={}m",
+                code_cache_mb(args)
+            ))
+            .note(code_cache_note(args)),
+        ]);
+        groups.push(JvmFlagGroup {
+            title: "基础配置",
+            comments: if args.no_explicit_direct {
+                vec![IMPLICIT_DIRECT_MEMORY_NOTE.to_string()]
+            } else {
+                Vec::new()
+            },
+            flags: basic,
+        });
+
+        let mut gc = gc_flags(args);
+        gc.push(JvmFlag::new(format!(
+            "-XX:ParallelGCThreads={}",
+            (args.cpu_cores as f64 * 0.5).ceil() as i32
+        )));
+        gc.push(JvmFlag::new(format!(
+            "-XX:ConcGCThreads={}",
+            (args.cpu_cores as f64 * 0.25).ceil() as i32
+        )));
+        groups.push(JvmFlagGroup {
+            title: "GC配置",
+            comments: jvm_compat_warnings(args)
+                .into_iter()
+                .map(|w| format!("⚠️ {w}"))
+                .collect(),
+            flags: gc,
+        });
+
+        let mut memory = vec![if safety.direct_mem_safety < 0.4 {
+            JvmFlag::new("-Djdk.nio.maxCachedBufferSize=131072").note("降低缓存阈值至128KB")
+        } else {
+            JvmFlag::new("-Djdk.nio.maxCachedBufferSize=262144").note("256KB缓存阈值")
+        }];
+        if args.overcommit_ratio.is_some_and(|r| r < 1.0) {
+            memory.push(JvmFlag::new("-XX:+AlwaysPreTouch").note("启动时预先分配物理页"));
+        }
+        if args.enable_memory_guard {
+            memory.push(JvmFlag::new("-Dapp.memory.guard.enabled=true"));
+            let guard = memory_guard_thresholds(
+                args,
+                &SafetyPolicy::from_args(args),
+                direct_mem_gb,
+                heap_mem_gb,
+            );
+            memory.push(JvmFlag::new(format!(
+                "-Dapp.memory.guard.direct.threshold={:.1}g",
+                guard.direct_gb
+            )));
+            memory.push(JvmFlag::new(format!(
+                "-Dapp.memory.guard.heap.threshold={:.1}g",
+                guard.heap_gb
+            )));
+        }
+        groups.push(JvmFlagGroup {
+            title: "内存优化",
+            comments: Vec::new(),
+            flags: memory,
+        });
+
+        // 元空间优化（针对高复杂度应用）
+        let metaspace = metaspace_flags(args, metaspace_size_mb);
+        if args.complexity == "high" && !metaspace.is_empty() {
+            let mut comments = Vec::new();
+            if selected_collector(args) != Collector::Zgc {
+                comments.push("可选: 针对大堆内存使用 -XX:+UseZGC".to_string());
+            }
+            groups.push(JvmFlagGroup {
+                title: "元空间优化",
+                comments,
+                flags: metaspace,
+            });
+        }
+
+        groups.push(if args.workload_lifetime == "short" {
+            // 短生命周期: 预热开销占主导，用启动优化替代长期运行的监控参数
+            let warmup = estimate_warmup(metaspace_size_mb, &args.complexity);
+            let mut flags =
+                vec![JvmFlag::new("-XX:TieredStopAtLevel=1").note("仅使用C1编译，缩短JIT预热")];
+            let mut comments = vec![format!(
+                "预计预热开销: ~{:.1}s (类加载 {:.1}s + JIT {:.1}s)",
+                warmup.total_secs(),
+                warmup.class_loading_secs,
+                warmup.jit_secs
+            )];
+            // 应用类CDS从JDK 10起可用，JDK 11起默认开启，-XX:+UseAppCDS已废弃
+            let jdk = jdk_version(args);
+            if jdk == 10 {
+                flags.push(JvmFlag::new("-XX:+UseAppCDS").note("类数据共享，减少类加载时间"));
+            }
+            if jdk >= 10 {
+                flags.push(JvmFlag::new("-XX:SharedArchiveFile=/opt/app/app-cds.jsa"));
+            } else {
+                comments.push("JDK 10之前不支持应用类CDS，升级JDK后可进一步缩短类加载".to_string());
+            }
+            comments.push("可选: GraalVM native-image AOT编译彻底消除预热".to_string());
+            JvmFlagGroup {
+                title: "启动预热优化（短生命周期进程）",
+                comments,
+                flags,
+            }
+        } else {
+            JvmFlagGroup {
+                title: "监控与诊断",
+                comments: Vec::new(),
+                flags: monitoring_flags(args),
+            }
+        });
+
+        if args.avg_file_size > 50.0 {
+            groups.push(JvmFlagGroup {
+                title: "大文件优化",
+                comments: Vec::new(),
+                flags: vec![
+                    JvmFlag::new("-Djdk.nio.enableFastFileTransfer=true"),
+                    JvmFlag::new("-Dapp.file.maxChunkSize=2097152").note("2MB分块"),
+                    JvmFlag::new("-Dapp.file.useDirectIO=true"),
+                ],
+            });
+        }
+
+        JvmFlags { groups }
+    }
+
+    /// 指定标题的分组
+    pub fn group(&self, title: &str) -> Option<&JvmFlagGroup> {
+        self.groups.iter().find(|g| g.title == title)
+    }
+
+    /// 按顺序遍历所有参数
+    pub fn iter(&self) -> impl Iterator<Item = &JvmFlag> {
+        self.groups.iter().flat_map(|g| g.flags.iter())
+    }
+
+    /// 空格分隔的参数列表，即 `JAVA_TOOL_OPTIONS` 的值
+    pub fn options_string(&self) -> String {
+        self.iter()
+            .map(|f| f.value.as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// 可直接在shell中执行的 `export JAVA_TOOL_OPTIONS="..."`
+    pub fn shell_export(&self) -> String {
+        format!(
+            "export JAVA_TOOL_OPTIONS=\"{}\"",
+            escape_double_quoted(&self.options_string(), &['"', '\\', '$', '`'])
+        )
+    }
+
+    /// Dockerfile中的 `ENV JAVA_TOOL_OPTIONS="..."`
+    pub fn dockerfile_env(&self) -> String {
+        format!(
+            "ENV JAVA_TOOL_OPTIONS=\"{}\"",
+            escape_double_quoted(&self.options_string(), &['"', '\\', '$'])
+        )
+    }
+}
+
+/// JVM自身的常驻线程数(JIT编译、GC协调、信号处理等)
+const JVM_INTERNAL_THREADS: usize = 32;
+
+/// 进程的常驻线程数: 业务线程、数据库连接池线程、GC线程和JVM内部线程
+pub fn jvm_thread_count(args: &Args) -> usize {
+    let gc_threads = (args.cpu_cores as f64 * 0.5).ceil() as usize
+        + (args.cpu_cores as f64 * 0.25).ceil() as usize;
+    args.cpu_cores * 2 + args.db_pool_size + gc_threads + JVM_INTERNAL_THREADS
+}
+
+/// cgroup内存上限在进程峰值内存之上保留的余量比例
+const CGROUP_MEMORY_HEADROOM: f64 = 0.1;
+
+/// cgroup内存上限(MB)
+///
+/// 静态对账只含JVM各内存区域和Native预留，突发时的整机预算还包括计入cgroup的
+/// socket缓冲区和分配器开销；取两者较大值再留余量，避免报告判定可承受的突发被OOM终止
+fn cgroup_memory_limit_mb(allocated_gb: f64, safety: &SafetyAnalysis) -> u64 {
+    let peak_gb = allocated_gb.max(safety.memory_budget.total_gb);
+    (peak_gb * (1.0 + CGROUP_MEMORY_HEADROOM) * 1024.0).ceil() as u64
+}
+
+/// systemd单元中与分析结果对应的资源限制
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SystemdLimits {
+    pub memory_max_mb: u64, // MemoryMax: 静态对账与突发内存预算的较大值，再留10%余量
+    pub limit_nofile: usize, // LimitNOFILE: 突发连接、空闲连接和连接池所需的文件描述符
+    pub tasks_max: usize,   // TasksMax: 业务线程、GC线程和JVM内部线程，留一倍余量
+}
+
+impl SystemdLimits {
+    /// `allocated_gb` 为静态内存对账的合计值
+    pub fn new(args: &Args, allocated_gb: f64, safety: &SafetyAnalysis) -> Self {
+        SystemdLimits {
+            memory_max_mb: cgroup_memory_limit_mb(allocated_gb, safety),
+            limit_nofile: required_fds(args),
+            tasks_max: jvm_thread_count(args) * 2,
+        }
+    }
+}
+
+impl JvmFlags {
+    /// systemd单元的 `[Service]` 段，`ExecStart` 使用推荐参数启动
+    ///
+    /// java和jar路径由 `--java-bin`/`--app-jar` 指定；
+    /// `%` 是systemd的说明符前缀，参数中出现时转义为 `%%`
+    pub fn systemd_service(&self, args: &Args, limits: &SystemdLimits) -> String {
+        let java_bin = args
+            .java_bin
+            .as_deref()
+            .unwrap_or(Path::new("/usr/bin/java"));
+        let app_jar = args
+            .app_jar
+            .as_deref()
+            .unwrap_or(Path::new("/opt/app/app.jar"));
+        [
+            "[Service]".to_string(),
+            format!("MemoryMax={}M", limits.memory_max_mb),
+            format!("LimitNOFILE={}", limits.limit_nofile),
+            format!("TasksMax={}", limits.tasks_max),
+            format!(
+                "ExecStart={} {} -jar {}",
+                java_bin.display(),
+                self.options_string().replace('%', "%%"),
+                app_jar.display()
+            ),
+        ]
+        .join("\n")
+    }
+}
+
+/// Kubernetes容器资源与HPA目标，与systemd限制使用相同的内存对账结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct K8sResources {
+    pub memory_request_mb: u64, // requests.memory: JVM各内存区域之和
+    pub memory_limit_mb: u64,   // limits.memory: 与systemd的MemoryMax相同
+    pub cpu: usize,             // requests/limits.cpu
+    pub target_cpu_pct: u32,    // HPA的CPU平均利用率目标
+}
+
+impl K8sResources {
+    /// `allocated_gb` 为静态内存对账的合计值，`native_gb` 为其中的Native预留
+    ///
+    /// HPA目标取低风险余量对应的利用率(默认60%)；预期负载下若其他资源比CPU更紧张，
+    /// 按比例提前扩容，保证该资源达到同一利用率之前已经触发扩容
+    pub fn new(args: &Args, allocated_gb: f64, native_gb: f64, safety: &SafetyAnalysis) -> Self {
+        let low_risk_pct = (1.0 - SafetyPolicy::from_args(args).low_risk_above) * 100.0;
+        let utilization = &safety.theoretical_limits.utilization;
+        let (_, tightest_pct) = utilization.tightest();
+        let target = if tightest_pct > utilization.cpu_pct && tightest_pct > 0.0 {
+            low_risk_pct * utilization.cpu_pct / tightest_pct
+        } else {
+            low_risk_pct
+        };
+        K8sResources {
+            memory_request_mb: ((allocated_gb - native_gb) * 1024.0).ceil() as u64,
+            memory_limit_mb: cgroup_memory_limit_mb(allocated_gb, safety),
+            cpu: args.cpu_cores,
+            target_cpu_pct: target.round().clamp(10.0, 100.0) as u32,
+        }
+    }
+
+    /// 容器的 `resources` 段和 `autoscaling/v2` 的HPA清单
+    pub fn manifest(&self) -> String {
+        [
+            "resources:".to_string(),
+            "  requests:".to_string(),
+            format!("    cpu: \"{}\"", self.cpu),
+            format!("    memory: {}Mi", self.memory_request_mb),
+            "  limits:".to_string(),
+            format!("    cpu: \"{}\"", self.cpu),
+            format!("    memory: {}Mi", self.memory_limit_mb),
+            "---".to_string(),
+            "apiVersion: autoscaling/v2".to_string(),
+            "kind: HorizontalPodAutoscaler".to_string(),
+            "metadata:".to_string(),
+            "  name: app".to_string(),
+            "spec:".to_string(),
+            "  scaleTargetRef:".to_string(),
+            "    apiVersion: apps/v1".to_string(),
+            "    kind: Deployment".to_string(),
+            "    name: app".to_string(),
+            "  minReplicas: 1".to_string(),
+            "  maxReplicas: 10".to_string(),
+            "  metrics:".to_string(),
+            "    - type: Resource".to_string(),
+            "      resource:".to_string(),
+            "        name: cpu".to_string(),
+            "        target:".to_string(),
+            "          type: Utilization".to_string(),
+            format!("          averageUtilization: {}", self.target_cpu_pct),
+        ]
+        .join("\n")
+    }
+}
+
+/// 转义双引号字符串中有特殊含义的字符
+fn escape_double_quoted(value: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if special.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// 长期运行进程的监控与诊断参数
+///
+/// JDK 9起GC日志统一为 `-Xlog`，旧的 `-XX:+PrintGC*` 系列参数会导致JVM无法启动
+fn monitoring_flags(args: &Args) -> Vec<JvmFlag> {
+    let mut flags = vec![JvmFlag::new("-XX:NativeMemoryTracking=detail")];
+    if jdk_version(args) >= 9 {
+        flags.push(JvmFlag::new(
+            "-Xlog:gc*,gc+ref=debug,gc+age=trace:file=/var/log/jvm_gc.log:time,uptime",
+        ));
+    } else {
+        flags.push(JvmFlag::new("-XX:+PrintGCDetails"));
+        flags.push(JvmFlag::new("-XX:+PrintGCDateStamps"));
+        flags.push(JvmFlag::new("-XX:+PrintClassHistogramBeforeFullGC"));
+        flags.push(JvmFlag::new("-XX:+PrintClassHistogramAfterFullGC"));
+        flags.push(JvmFlag::new("-XX:+PrintReferenceGC"));
+        flags.push(JvmFlag::new("-XX:+PrintTenuringDistribution"));
+    }
+    flags.push(JvmFlag::new("-XX:+HeapDumpOnOutOfMemoryError"));
+    flags.push(JvmFlag::new("-XX:HeapDumpPath=/var/log/jvm_dumps"));
+    flags.push(JvmFlag::new("-XX:+UnlockDiagnosticVMOptions"));
+    flags.push(JvmFlag::new("-XX:+LogCompilation"));
+    flags.push(JvmFlag::new("-XX:LogFile=/var/log/jvm_compilation.log"));
+    flags
+}
+
+/// 输出元空间推荐值的完整推导过程
+///
+/// 逐项列出 [`calculate_metaspace_detailed`] 的各组成部分、压缩类空间的划分，
+/// 以及相关参数适用的JDK版本
+pub fn print_metaspace_explanation(out: &mut impl Write, args: &Args) -> std::io::Result<()> {
+    let detail = calculate_metaspace_detailed(args);
+    let raw_total = detail.base + detail.connection_factor + detail.file_size_factor;
+
+    writeln!(
+        out,
+        "\n{}{}",
+        "▬".green().bold().reversed(),
+        " 元空间计算明细 ".green().bold().reversed()
+    )?;
+    writeln!(out, "{}", "▬".green().bold().repeated(50))?;
+
+    writeln!(out, "\n{}", "  # 计算过程".bold())?;
+    writeln!(
+        out,
+        "    - {}: {BASE_METASPACE:.0}MB × 复杂度因子{:.1} = {:.1}MB",
+        "基础值".cyan(),
+        detail.complexity_factor,
+        detail.base - detail.thread_mb
+    )?;
+    writeln!(
+        out,
+        "    - {}: (CPU核心数{}×2 + 数据库连接池{}) × {THREAD_FACTOR:.0}MB = {:.1}MB",
+        "线程".cyan(),
+        args.cpu_cores,
+        args.db_pool_size,
+        detail.thread_mb
+    )?;
+    writeln!(
+        out,
+        "    - {}: ⌊{}/{CONNECTIONS_BASE:.0}⌋ × {CONNECTION_FACTOR:.0}MB = {:.1}MB",
+        "连接数".cyan(),
+        args.expected_connections,
+        detail.connection_factor
+    )?;
+    writeln!(
+        out,
+        "    - {}: 平均{:.1}MB ({}) = {:.1}MB",
+        "文件大小".cyan(),
+        args.avg_file_size,
+        match args.avg_file_size {
+            fs if fs <= 10.0 => "≤10MB固定增量",
+            fs if fs <= 100.0 => "10-100MB按ln(大小)×10",
+            _ => "大于100MB按每100MB递增",
+        },
+        detail.file_size_factor
+    )?;
+    writeln!(
+        out,
+        "    - {}: {raw_total:.1}MB{} × 安全边际{:.1} = {:.1}MB",
+        "合计".cyan(),
+        if detail.floor_hit {
+            format!(" (低于下限，按{:.0}MB)", detail.min_mb)
+        } else {
+            String::new()
+        },
+        detail.safety_margin,
+        detail.uncapped_mb
+    )?;
+    writeln!(
+        out,
+        "    - {}: {}MB{}",
+        "推荐值".cyan(),
+        detail.final_mb,
+        if detail.ceiling_hit {
+            format!(
+                " (已按上限{:.0}MB截断，可用--max-metaspace-mb提高)",
+                detail.max_mb
+            )
+            .red()
+            .to_string()
+        } else {
+            String::new()
+        }
+    )?;
+
+    writeln!(out, "\n{}", "  # 压缩类空间".bold())?;
+    if supports_compressed_class_pointers(args) {
+        writeln!(
+            out,
+            "    - CompressedClassSpaceSize = max({}MB × {COMPRESSED_CLASS_SPACE_RATIO}, \
+             {MIN_COMPRESSED_CLASS_SPACE_MB:.0}MB) = {}MB",
+            detail.final_mb,
+            compressed_class_space_mb(detail.final_mb)
+        )?;
+        writeln!(
+            out,
+            "    - 压缩类空间从元空间中划分，不额外占用MaxMetaspaceSize之外的内存"
+        )?;
+    } else {
+        writeln!(
+            out,
+            "    - ZGC在JDK 15之前不支持压缩类指针，不设置CompressedClassSpaceSize"
+        )?;
+    }
+
+    writeln!(out, "\n{}", "  # 适用JDK版本".bold())?;
+    writeln!(
+        out,
+        "    - 目标JDK {}，收集器 {:?}",
+        jdk_version(args),
+        selected_collector(args)
+    )?;
+    writeln!(
+        out,
+        "    - -XX:MaxMetaspaceSize: JDK 8+ (JDK 7及以下为-XX:MaxPermSize)"
+    )?;
+    writeln!(
+        out,
+        "    - -XX:+UseCompressedClassPointers/-XX:CompressedClassSpaceSize: JDK 8+"
+    )?;
+    writeln!(out, "    - ZGC使用压缩类指针: JDK 15+")?;
+    Ok(())
+}
 
 /// 基于全面分析生成最终JVM配置建议
 pub fn print_jvm_recommendations(
+    out: &mut impl Write,
     args: &Args,
     direct_mem_gb: f64,
     heap_mem_gb: f64,
     metaspace_size_mb: i32,
     safety: &SafetyAnalysis,
     _performance: &PerformanceReport,
-) {
+) -> std::io::Result<()> {
     // 1. 评估当前配置是否满足6个月稳定运行
     let meets_requirements = safety
         .theoretical_limits
@@ -24,108 +918,172 @@ pub fn print_jvm_recommendations(
     // 2. 计算理论最大连接数(基于最严格限制资源)
     let max_sustainable_conn = safety.theoretical_limits.max_connections;
     let target_conn = args.expected_connections;
-    let needs_scaling = target_conn > max_sustainable_conn;
+    let needs_scaling = !safety.meets_target;
 
     // 3. 打印配置摘要
-    println!(
+    writeln!(
+        out,
         "\n{}{}",
         "▬".green().bold().reversed(),
         " JVM配置建议 ".green().bold().reversed()
-    );
-    println!("{}", "▬".green().bold().repeated(50));
+    )?;
+    writeln!(out, "{}", "▬".green().bold().repeated(50))?;
 
-    println!("\n{}", "  # 系统能力评估".bold());
-    println!("  - 当前配置理论最大连接数: {max_sustainable_conn}");
-    println!("  - 目标连接数: {target_conn}");
-    println!(
+    writeln!(out, "\n{}", "  # 系统能力评估".bold())?;
+    writeln!(out, "  - 当前配置理论最大连接数: {max_sustainable_conn}")?;
+    writeln!(out, "  - 目标连接数: {target_conn}")?;
+    writeln!(
+        out,
         "  - 稳定运行预期: {}",
         safety.theoretical_limits.estimated_uptime
-    );
-    println!(
+    )?;
+    writeln!(
+        out,
         "  - 主要瓶颈资源: {}",
         safety.theoretical_limits.limiting_factor
-    );
+    )?;
 
     if !meets_requirements {
-        println!(
+        writeln!(
+            out,
             "\n{}",
             "  ❗ 警告: 当前配置无法满足6个月稳定运行要求".red().bold()
-        );
+        )?;
     }
 
     if needs_scaling {
-        println!(
+        writeln!(
+            out,
             "\n{}",
             "  ⚠️ 注意: 目标连接数超过理论最大值".yellow().bold()
-        );
-        println!("  - 需要调整资源配置或优化应用");
-        println!("  - 理论可达到连接数: {max_sustainable_conn}");
+        )?;
+        writeln!(out, "  - 需要调整资源配置或优化应用")?;
+        writeln!(out, "  - 理论可达到连接数: {max_sustainable_conn}")?;
     }
 
     // 4. 生成最终配置建议
-    println!("\n{}", "  # 最终JVM配置建议".bold());
-    println!(
+    writeln!(out, "\n{}", "  # 最终JVM配置建议".bold())?;
+    writeln!(
+        out,
         "\n{}{}",
         "▬".green().bold().reversed(),
         " JVM配置建议 ".green().bold().reversed()
-    );
-    println!("{}", "▬".green().bold().repeated(50));
+    )?;
+    writeln!(out, "{}", "▬".green().bold().repeated(50))?;
 
     // JDK版本兼容性评估
-    println!("\n{}", "  ## JDK版本兼容矩阵".bold());
-    println!("  {:<45} {:<15} {:<15}", "参数".cyan(), "最低JDK".cyan(), "生产推荐".cyan());
-    println!("  {:-<80}", "-");
-    println!("  {:<45} {:<15} {:<15}", "-Xms/-Xmx", "JDK 1.0", "JDK 8+");
-    println!("  {:<45} {:<15} {:<15}", "-XX:MaxDirectMemorySize", "JDK 1.4", "JDK 11+");
-    println!("  {:<45} {:<15} {:<15}", "-XX:MaxMetaspaceSize", "JDK 8", "JDK 11+");
-    println!("  {:<45} {:<15} {:<15}", "-XX:+UseG1GC", "JDK 7u4", "JDK 11+");
-    println!("  {:<45} {:<15} {:<15}", "-XX:+UseZGC", "JDK 11", "JDK 17+");
-    println!("  {:<45} {:<15} {:<15}", "-XX:+UseShenandoahGC", "JDK 12", "JDK 17+");
-    println!("  {:<45} {:<15} {:<15}", "-XX:NativeMemoryTracking", "JDK 8", "JDK 11+");
-    println!("  {:<45} {:<15} {:<15}", "-Djdk.nio.enableFastFileTransfer", "JDK 9", "JDK 17+");
-    println!("  {:<45} {:<15} {:<15}", "-XX:+UnlockExperimentalVMOptions", "JDK 7", "JDK 11+");
-    println!("  {:<45} {:<15} {:<15}", "-XX:+UseCompressedClassPointers", "JDK 6", "JDK 11+");
-
-    println!("\n{}", "  ## JDK版本建议".bold());
+    writeln!(out, "\n{}", "  ## JDK版本兼容矩阵".bold())?;
+    writeln!(
+        out,
+        "  {:<45} {:<15} {:<15}",
+        "参数".cyan(),
+        "最低JDK".cyan(),
+        "生产推荐".cyan()
+    )?;
+    writeln!(out, "  {:-<80}", "-")?;
+    writeln!(
+        out,
+        "  {:<45} {:<15} {:<15}",
+        "-Xms/-Xmx", "JDK 1.0", "JDK 8+"
+    )?;
+    writeln!(
+        out,
+        "  {:<45} {:<15} {:<15}",
+        "-XX:MaxDirectMemorySize", "JDK 1.4", "JDK 11+"
+    )?;
+    writeln!(
+        out,
+        "  {:<45} {:<15} {:<15}",
+        "-XX:MaxMetaspaceSize", "JDK 8", "JDK 11+"
+    )?;
+    writeln!(
+        out,
+        "  {:<45} {:<15} {:<15}",
+        "-XX:+UseG1GC", "JDK 7u4", "JDK 11+"
+    )?;
+    writeln!(
+        out,
+        "  {:<45} {:<15} {:<15}",
+        "-XX:+UseZGC", "JDK 11", "JDK 17+"
+    )?;
+    writeln!(
+        out,
+        "  {:<45} {:<15} {:<15}",
+        "-XX:+UseShenandoahGC", "JDK 12", "JDK 17+"
+    )?;
+    writeln!(
+        out,
+        "  {:<45} {:<15} {:<15}",
+        "-XX:NativeMemoryTracking", "JDK 8", "JDK 11+"
+    )?;
+    writeln!(
+        out,
+        "  {:<45} {:<15} {:<15}",
+        "-Djdk.nio.enableFastFileTransfer", "JDK 9", "JDK 17+"
+    )?;
+    writeln!(
+        out,
+        "  {:<45} {:<15} {:<15}",
+        "-XX:+UnlockExperimentalVMOptions", "JDK 7", "JDK 11+"
+    )?;
+    writeln!(
+        out,
+        "  {:<45} {:<15} {:<15}",
+        "-XX:+UseCompressedClassPointers", "JDK 6", "JDK 11+"
+    )?;
+
+    writeln!(out, "\n{}", "  ## JDK版本建议".bold())?;
     if args.complexity == "high" {
-        println!("  - 建议使用JDK 17+ (包含ZGC和元空间优化)");
+        writeln!(out, "  - 建议使用JDK 17+ (包含ZGC和元空间优化)")?;
     } else {
-        println!("  - 最低要求: JDK 11");
-        println!("  - 推荐版本: JDK 17+ (更好的性能与内存管理)");
-    }
-
-    println!("\n{}", "  ## 参数兼容性详情".bold());
-    println!("  - 基础配置:");
-    println!("    - -Xms/-Xmx: 所有版本支持");
-    println!("    - -XX:MaxDirectMemorySize: JDK 6+ 支持");
-    println!("    - -XX:MaxMetaspaceSize: JDK 8+ 支持 (JDK 7及以下使用-XX:MaxPermSize)");
-    println!("    - -XX:ReservedCodeCacheSize: JDK 6+ 支持");
-
-    println!("  - 内存防护增强:");
-    println!("    - -XX:+UseG1GC: JDK 7u4+ 完全支持");
-    println!("    - -XX:MaxGCPauseMillis: JDK 6u14+ 支持");
-    println!("    - -XX:ParallelGCThreads/-XX:ConcGCThreads: JDK 6+ 支持");
-    println!("    - -Djdk.nio.maxCachedBufferSize: JDK 7+ 支持");
-
-    println!("  - 元空间优化:");
-    println!("    - -XX:+UseCompressedClassPointers: JDK 6+ 支持64位系统");
-    println!("    - -XX:CompressedClassSpaceSize: JDK 8+ 支持");
-    println!("    - -XX:+UnlockExperimentalVMOptions: JDK 7+ 支持");
-    println!("    - -XX:+UseZGC: JDK 11+ 支持 (JDK 15+ 生产可用)");
-
-    println!("  - 监控配置:");
-    println!("    - -XX:NativeMemoryTracking: JDK 8+ 支持");
-    println!("    - -XX:+PrintGCDetails: JDK 6+ 支持 (JDK 9+ 使用-Xlog:gc*)");
-    println!("    - -XX:+HeapDumpOnOutOfMemoryError: JDK 6+ 支持");
-
-    println!("  - 大文件优化:");
-    println!("    - -Djdk.nio.enableFastFileTransfer: JDK 9+ 支持");
-    println!("    - DirectIO相关参数: 需要特定JDK实现或第三方库");
+        writeln!(out, "  - 最低要求: JDK 11")?;
+        writeln!(out, "  - 推荐版本: JDK 17+ (更好的性能与内存管理)")?;
+    }
+
+    writeln!(out, "\n{}", "  ## 参数兼容性详情".bold())?;
+    writeln!(out, "  - 基础配置:")?;
+    writeln!(out, "    - -Xms/-Xmx: 所有版本支持")?;
+    writeln!(out, "    - -XX:MaxDirectMemorySize: JDK 6+ 支持")?;
+    writeln!(
+        out,
+        "    - -XX:MaxMetaspaceSize: JDK 8+ 支持 (JDK 7及以下使用-XX:MaxPermSize)"
+    )?;
+    writeln!(out, "    - -XX:ReservedCodeCacheSize: JDK 6+ 支持")?;
+
+    writeln!(out, "  - 内存防护增强:")?;
+    writeln!(out, "    - -XX:+UseG1GC: JDK 7u4+ 完全支持")?;
+    writeln!(out, "    - -XX:MaxGCPauseMillis: JDK 6u14+ 支持")?;
+    writeln!(
+        out,
+        "    - -XX:ParallelGCThreads/-XX:ConcGCThreads: JDK 6+ 支持"
+    )?;
+    writeln!(out, "    - -Djdk.nio.maxCachedBufferSize: JDK 7+ 支持")?;
+
+    writeln!(out, "  - 元空间优化:")?;
+    writeln!(
+        out,
+        "    - -XX:+UseCompressedClassPointers: JDK 6+ 支持64位系统"
+    )?;
+    writeln!(out, "    - -XX:CompressedClassSpaceSize: JDK 8+ 支持")?;
+    writeln!(out, "    - -XX:+UnlockExperimentalVMOptions: JDK 7+ 支持")?;
+    writeln!(out, "    - -XX:+UseZGC: JDK 11+ 支持 (JDK 15+ 生产可用)")?;
+
+    writeln!(out, "  - 监控配置:")?;
+    writeln!(out, "    - -XX:NativeMemoryTracking: JDK 8+ 支持")?;
+    writeln!(
+        out,
+        "    - -XX:+PrintGCDetails: JDK 6+ 支持 (JDK 9+ 使用-Xlog:gc*)"
+    )?;
+    writeln!(out, "    - -XX:+HeapDumpOnOutOfMemoryError: JDK 6+ 支持")?;
+
+    writeln!(out, "  - 大文件优化:")?;
+    writeln!(out, "    - -Djdk.nio.enableFastFileTransfer: JDK 9+ 支持")?;
+    writeln!(out, "    - DirectIO相关参数: 需要特定JDK实现或第三方库")?;
 
     // 基础配置(根据需求调整)
     let (final_heap, final_direct, server_ram_needed) = if needs_scaling {
         // 按比例扩大内存配置以达到目标
-        let scale_factor = target_conn as f64 / max_sustainable_conn as f64;
+        let scale_factor = 1.0 + safety.shortfall_pct / 100.0;
         let new_heap = (heap_mem_gb * scale_factor).max(heap_mem_gb * 1.2);
         let new_direct = (direct_mem_gb * scale_factor).max(direct_mem_gb * 1.3);
         let total_ram_needed = (new_heap + new_direct) / 0.85; // 保留15%给系统
@@ -139,8 +1097,9 @@ pub fn print_jvm_recommendations(
         (heap_mem_gb as i32, direct_mem_gb as i32, None)
     };
 
-    println!("{}", "  ## 基础配置".bold());
-    println!(
+    writeln!(out, "{}", "  ## 基础配置".bold())?;
+    writeln!(
+        out,
         "  -Xms{}g -Xmx{}g  # {}",
         final_heap,
         final_heap,
@@ -149,146 +1108,406 @@ pub fn print_jvm_recommendations(
         } else {
             "基于当前负载"
         }
-    );
-    println!(
-        "  -XX:MaxDirectMemorySize={}g  # {}",
-        final_direct,
-        if needs_scaling {
-            "已按目标调整"
-        } else {
-            "基于当前负载"
-        }
-    );
-    println!("  -XX:MaxDirectMemorySize={}g  # 必须显式设置且小于物理内存", 
-        final_direct.min(args.total_ram as i32 - 2)  // 保留2GB给系统
-    );
-    println!("  -XX:MaxMetaspaceSize={metaspace_size_mb}m  # 动态计算值");
-    println!("  -XX:ReservedCodeCacheSize=256m  # 固定值");
+    )?;
+    if args.no_explicit_direct {
+        writeln!(out, "  # {IMPLICIT_DIRECT_MEMORY_NOTE}")?;
+    } else {
+        writeln!(
+            out,
+            "  -XX:MaxDirectMemorySize={}g  # {}",
+            final_direct,
+            if needs_scaling {
+                "已按目标调整"
+            } else {
+                "基于当前负载"
+            }
+        )?;
+        writeln!(
+            out,
+            "  -XX:MaxDirectMemorySize={}g  # 必须显式设置且小于物理内存",
+            final_direct.min(args.total_ram as i32 - 2) // 保留2GB给系统
+        )?;
+        writeln!(
+            out,
+            "  # 不设置时HotSpot默认上限约等于-Xmx，容易被重复计入内存预算"
+        )?;
+    }
+    writeln!(
+        out,
+        "  -XX:MaxMetaspaceSize={metaspace_size_mb}m  # 动态计算值"
+    )?;
+    writeln!(
+        out,
+        "  -XX:ReservedCodeCacheSize={}m  # {}",
+        code_cache_mb(args),
+        code_cache_note(args)
+    )?;
 
     // 添加容量说明
-    println!("\n{}", "  ## 容量说明".bold());
-    println!("  - 配置支持最大连接数: {max_sustainable_conn}");
+    writeln!(out, "\n{}", "  ## 容量说明".bold())?;
+    writeln!(out, "  - 配置支持最大连接数: {max_sustainable_conn}")?;
     if needs_scaling {
-        println!(
+        writeln!(
+            out,
             "  - {}: 需要额外 {}% 资源以达到目标连接数",
             "资源缺口".red(),
-            ((target_conn as f64 / max_sustainable_conn as f64 - 1.0) * 100.0) as i32
-        );
+            safety.shortfall_pct as i32
+        )?;
 
         if let Some(ram_needed) = server_ram_needed {
-            println!(
+            writeln!(
+                out,
                 "  - {}: 建议服务器内存至少 {}GB (当前 {}GB)",
                 "内存扩容建议".yellow(),
                 ram_needed,
                 args.total_ram as i32
-            );
+            )?;
 
             // CPU核心建议 (每1000连接需要1核)
             let suggested_cores = (target_conn as f64 / 1000.0).ceil() as i32;
             if suggested_cores > args.cpu_cores as i32 {
-                println!(
+                writeln!(
+                    out,
                     "  - {}: 建议CPU核心数 {} (当前 {})",
                     "CPU扩容建议".yellow(),
                     suggested_cores,
                     args.cpu_cores
-                );
+                )?;
             }
 
             // 网络带宽建议 (每连接0.2Mbps)
             let suggested_bandwidth = (target_conn as f64 * 0.2 / 1000.0).ceil() as i32;
             if suggested_bandwidth > args.net_gbps as i32 {
-                println!(
+                writeln!(
+                    out,
                     "  - {}: 建议网络带宽 {}Gbps (当前 {}Gbps)",
                     "网络扩容建议".yellow(),
                     suggested_bandwidth,
                     args.net_gbps
-                );
+                )?;
+            }
+
+            if let Some(rates) = CostRates::from_args(args) {
+                let suggestion = ScalingSuggestion {
+                    ram_gb: Some(ram_needed as f64),
+                    cpu_cores: (suggested_cores > args.cpu_cores as i32)
+                        .then_some(suggested_cores as usize),
+                    net_gbps: (suggested_bandwidth > args.net_gbps as i32)
+                        .then_some(suggested_bandwidth as f64),
+                };
+                let estimate = estimate_scaling_cost(args, &rates, &suggestion);
+                writeln!(
+                    out,
+                    "  - {}: {}",
+                    "预计增量成本".yellow(),
+                    format_cost(&estimate, &args.currency)
+                )?;
+            }
+        }
+    }
+
+    // 其余参数分组与markdown报告、--emit共用
+    let flags = JvmFlags::recommended(args, direct_mem_gb, heap_mem_gb, metaspace_size_mb, safety);
+    for group in flags.groups.iter().skip(1) {
+        writeln!(out, "\n{}", format!("  # {}", group.title).bold())?;
+        for comment in &group.comments {
+            if comment.starts_with("⚠️") {
+                writeln!(out, "  {}", format!("# {comment}").yellow())?;
+            } else {
+                writeln!(out, "  # {comment}")?;
             }
         }
+        for flag in &group.flags {
+            writeln!(out, "  {flag}")?;
+        }
     }
 
-    // 内存防护增强
-    println!("\n{}", "  # 内存防护增强".bold());
-    match args.complexity.as_str() {
-        "high" => {
-            println!("  -XX:+UseZGC  # 低延迟GC，适合高复杂度应用");
-            println!("  -XX:ZCollectionInterval=5  # 每5秒一次ZGC");
+    if args.compat_report {
+        writeln!(
+            out,
+            "\n{}",
+            format!("  # 参数兼容性检查 (目标JDK {})", jdk_version(args)).bold()
+        )?;
+        let issues = flag_compat_issues(&flags, args);
+        if issues.is_empty() {
+            writeln!(out, "  {}", "所有推荐参数均兼容目标JDK".green())?;
         }
-        "low" => {
-            println!("  -XX:+UseG1GC  # 平衡型GC");
-            println!("  -XX:MaxGCPauseMillis=200");
+        for issue in issues {
+            writeln!(
+                out,
+                "  {} {}: {}，替代: {}",
+                "✖".red(),
+                issue.flag.red(),
+                issue.reason,
+                issue.substitute
+            )?;
         }
-        _ => {
-            println!("  -XX:+UseShenandoahGC  # 并发GC");
-            println!("  -XX:ShenandoahGCHeuristics=adaptive");
+    }
+
+    writeln!(out, "\n{}", "  # 启动命令示例".bold())?;
+    writeln!(out, "  java \\")?;
+    if let Some(base) = flags.group("基础配置") {
+        for flag in &base.flags {
+            writeln!(out, "    {} \\", flag.value)?;
         }
     }
-    println!(
-        "  -XX:ParallelGCThreads={}",
-        (args.cpu_cores as f64 * 0.5).ceil() as i32
-    );
-    println!(
-        "  -XX:ConcGCThreads={}",
-        (args.cpu_cores as f64 * 0.25).ceil() as i32
-    );
-
-    if safety.direct_mem_safety < 0.4 {
-        println!("  -Djdk.nio.maxCachedBufferSize=131072  # 降低缓存阈值至128KB");
-    } else {
-        println!("  -Djdk.nio.maxCachedBufferSize=262144  # 256KB缓存阈值");
+    writeln!(out, "    -jar your-application.jar")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zgc_on_old_jdk_omits_compressed_class_pointers() {
+        let args = Args {
+            complexity: "high".to_string(),
+            gc: "auto".to_string(),
+            jdk_version: 11,
+            ..Default::default()
+        };
+        assert_eq!(selected_collector(&args), Collector::Zgc);
+        assert!(metaspace_flags(&args, 512).is_empty());
+        assert!(
+            gc_flags(&args)
+                .iter()
+                .any(|f| f.value == "-XX:+UnlockExperimentalVMOptions")
+        );
+        assert_eq!(jvm_compat_warnings(&args).len(), 1);
+
+        let modern = Args {
+            jdk_version: 21,
+            ..args.clone()
+        };
+        assert_eq!(metaspace_flags(&modern, 512).len(), 2);
+        assert!(jvm_compat_warnings(&modern).is_empty());
+
+        let shenandoah_on_11 = Args {
+            gc: "shenandoah".to_string(),
+            ..args
+        };
+        assert_eq!(selected_collector(&shenandoah_on_11), Collector::G1);
+        assert!(jvm_compat_warnings(&shenandoah_on_11)[0].contains("已改用G1"));
     }
 
-    if args.enable_memory_guard {
-        println!("  -Dapp.memory.guard.enabled=true");
-        println!(
-            "  -Dapp.memory.guard.direct.threshold={:.1}g",
-            direct_mem_gb * 0.85
+    #[test]
+    fn test_java_tool_options_escaping() {
+        let flags = JvmFlags {
+            groups: vec![JvmFlagGroup {
+                title: "基础配置",
+                comments: vec!["不输出".to_string()],
+                flags: vec![
+                    JvmFlag::new("-Xmx4g").note("不输出"),
+                    JvmFlag::new("-Dapp.name=\"a$b`c\\d\""),
+                ],
+            }],
+        };
+        assert_eq!(
+            flags.shell_export(),
+            r#"export JAVA_TOOL_OPTIONS="-Xmx4g -Dapp.name=\"a\$b\`c\\d\"""#
         );
-        println!(
-            "  -Dapp.memory.guard.heap.threshold={:.1}g",
-            heap_mem_gb * 0.8
+        assert_eq!(
+            flags.dockerfile_env(),
+            r#"ENV JAVA_TOOL_OPTIONS="-Xmx4g -Dapp.name=\"a\$b`c\\d\"""#
         );
     }
 
-    // 元空间优化（针对高复杂度应用）
-    if args.complexity == "high" {
-        println!("\n{}", "  # 元空间优化（高复杂度应用）".bold());
-        println!("  -XX:+UseCompressedClassPointers");
-        println!(
-            "  -XX:CompressedClassSpaceSize={}m",
-            (metaspace_size_mb as f32 * 0.4).max(256.0) as i32
+    #[test]
+    fn test_code_cache_scales_with_inputs() {
+        let medium = Args {
+            cpu_cores: 16,
+            complexity: "medium".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(code_cache_mb(&medium), 256);
+
+        let high = Args {
+            complexity: "high".to_string(),
+            ..medium.clone()
+        };
+        assert!(code_cache_mb(&high) > code_cache_mb(&medium));
+
+        let huge = Args {
+            cpu_cores: 1024,
+            ..high
+        };
+        assert_eq!(code_cache_mb(&huge), MAX_CODE_CACHE_MB);
+
+        let pinned = Args {
+            code_cache_mb: Some(100),
+            ..medium
+        };
+        assert_eq!(code_cache_mb(&pinned), 100);
+    }
+
+    #[test]
+    fn test_systemd_service_mirrors_limits() {
+        let args = Args {
+            cpu_cores: 8,
+            expected_connections: 1000,
+            burst_factor: 2.0,
+            idle_connections: 500,
+            ..Default::default()
+        };
+        let analyze = |args: &Args| {
+            crate::analysis::analyze(&Args {
+                total_ram: 16.0,
+                net_gbps: 10.0,
+                disk_type: "nvme".to_string(),
+                avg_file_size: 10.0,
+                complexity: "medium".to_string(),
+                ..args.clone()
+            })
+            .unwrap()
+            .safety
+        };
+        let safety = analyze(&args);
+        let limits = SystemdLimits::new(&args, 12.5, &safety);
+        assert!(safety.memory_budget.total_gb < 12.5);
+        assert_eq!(
+            limits.memory_max_mb,
+            (12.5 * 1.1 * 1024.0_f64).ceil() as u64
+        );
+        assert_eq!(limits.limit_nofile, 2500 * 2 + 1024);
+        // 16业务线程 + 4+2 GC线程 + 32内部线程，留一倍余量
+        assert_eq!(limits.tasks_max, 108);
+
+        let flags = JvmFlags {
+            groups: vec![JvmFlagGroup {
+                title: "基础配置",
+                comments: Vec::new(),
+                flags: vec![JvmFlag::new("-Xmx4g"), JvmFlag::new("-Dapp.pct=50%")],
+            }],
+        };
+        let unit = flags.systemd_service(&args, &limits);
+        assert!(unit.starts_with(&format!("[Service]\nMemoryMax={}M\n", limits.memory_max_mb)));
+        assert!(
+            unit.ends_with("ExecStart=/usr/bin/java -Xmx4g -Dapp.pct=50%% -jar /opt/app/app.jar")
+        );
+
+        let custom = Args {
+            java_bin: Some("/opt/jdk-17/bin/java".into()),
+            app_jar: Some("/srv/transfer/server.jar".into()),
+            ..args.clone()
+        };
+        assert!(flags.systemd_service(&custom, &limits).ends_with(
+            "ExecStart=/opt/jdk-17/bin/java -Xmx4g -Dapp.pct=50%% -jar /srv/transfer/server.jar"
+        ));
+
+        // socket缓冲区使突发内存预算超过静态对账时，按预算设置上限
+        let sockets = Args {
+            expected_connections: 100_000,
+            socket_buffer_kb: 256,
+            ..args
+        };
+        let safety = analyze(&sockets);
+        assert!(safety.memory_budget.total_gb > 12.5);
+        assert!(
+            SystemdLimits::new(&sockets, 12.5, &safety).memory_max_mb as f64
+                > safety.memory_budget.total_gb * 1024.0
+        );
+    }
+
+    #[test]
+    fn test_k8s_resources_follow_reconciliation_and_headroom() {
+        use clap::Parser;
+
+        let args = Args::parse_from(["sa", "-c", "8"]);
+        let result = crate::analysis::analyze(&args).unwrap();
+        let reconciliation = crate::analysis::reconcile_memory(
+            &args,
+            result.direct_mem_gb,
+            result.heap_mem_gb,
+            result.metaspace_size_mb,
         );
-        println!("  -XX:+UnlockExperimentalVMOptions");
-        println!("  -XX:+UseZGC  # 可选：针对大堆内存使用ZGC");
-    }
-
-    // 监控配置
-    println!("\n{}", "  # 监控与诊断".bold());
-    println!("  -XX:NativeMemoryTracking=detail");
-    println!("  -XX:+PrintGCDetails -XX:+PrintGCDateStamps");
-    println!("  -XX:+HeapDumpOnOutOfMemoryError");
-    println!("  -XX:HeapDumpPath=/var/log/jvm_dumps");
-    println!("  -XX:+PrintClassHistogramBeforeFullGC");
-    println!("  -XX:+PrintClassHistogramAfterFullGC");
-    println!("  -XX:+PrintReferenceGC");
-    println!("  -XX:+PrintTenuringDistribution");
-    println!("  -XX:+UnlockDiagnosticVMOptions");
-    println!("  -XX:+LogCompilation");
-    println!("  -XX:LogFile=/var/log/jvm_compilation.log");
-
-    // 大文件优化
-    if args.avg_file_size > 50.0 {
-        println!("\n{}", "  # 大文件优化".bold());
-        println!("  -Djdk.nio.enableFastFileTransfer=true");
-        println!("  -Dapp.file.maxChunkSize=2097152  # 2MB分块");
-        println!("  -Dapp.file.useDirectIO=true");
-    }
-
-    println!("\n{}", "  # 启动命令示例".bold());
-    println!("  java \\");
-    println!("    -Xms{0}g -Xmx{0}g \\", heap_mem_gb as i32);
-    println!("    -XX:MaxDirectMemorySize={}g \\", direct_mem_gb as i32);
-    println!("    -XX:MaxMetaspaceSize={metaspace_size_mb}m \\");
-    println!("    -XX:ReservedCodeCacheSize=256m \\");
-    println!("    -jar your-application.jar");
+        let native_gb = crate::analysis::native::native_reserved_gb(&args, result.heap_mem_gb);
+        let resources = K8sResources::new(
+            &args,
+            reconciliation.allocated_gb,
+            native_gb,
+            &result.safety,
+        );
+        assert_eq!(
+            resources.memory_limit_mb,
+            SystemdLimits::new(&args, reconciliation.allocated_gb, &result.safety).memory_max_mb
+        );
+        assert!(resources.memory_request_mb < resources.memory_limit_mb);
+        assert_eq!(resources.cpu, 8);
+
+        // 其他资源比CPU更紧张时提前扩容
+        let utilization = &result.safety.theoretical_limits.utilization;
+        let (_, tightest_pct) = utilization.tightest();
+        let expected = if tightest_pct > utilization.cpu_pct {
+            (60.0 * utilization.cpu_pct / tightest_pct)
+                .round()
+                .clamp(10.0, 100.0) as u32
+        } else {
+            60
+        };
+        assert_eq!(resources.target_cpu_pct, expected);
+
+        let manifest = resources.manifest();
+        assert!(manifest.starts_with("resources:\n  requests:\n    cpu: \"8\"\n"));
+        assert!(manifest.contains(&format!("    memory: {}Mi", resources.memory_limit_mb)));
+        assert!(manifest.ends_with(&format!("averageUtilization: {expected}")));
+    }
+
+    #[test]
+    fn test_flag_compat_issues_against_target_jdk() {
+        let flags = JvmFlags {
+            groups: vec![JvmFlagGroup {
+                title: "基础配置",
+                comments: Vec::new(),
+                flags: vec![
+                    JvmFlag::new("-Xmx4g"),
+                    JvmFlag::new("-XX:MaxMetaspaceSize=256m"),
+                    JvmFlag::new("-XX:+UseZGC"),
+                    JvmFlag::new("-XX:+PrintGCDetails"),
+                ],
+            }],
+        };
+        let on = |jdk_version| Args {
+            jdk_version,
+            ..Default::default()
+        };
+
+        let jdk8: Vec<String> = flag_compat_issues(&flags, &on(8))
+            .into_iter()
+            .map(|i| i.flag)
+            .collect();
+        assert_eq!(jdk8, ["-XX:+UseZGC"]);
+
+        let jdk11 = flag_compat_issues(&flags, &on(11));
+        assert_eq!(jdk11.len(), 2);
+        assert_eq!(jdk11[0].substitute, "-Xlog:gc*");
+        assert!(
+            jdk11[1]
+                .substitute
+                .starts_with("-XX:+UnlockExperimentalVMOptions")
+        );
+
+        let jdk7 = flag_compat_issues(&flags, &on(7));
+        assert_eq!(jdk7[0].flag, "-XX:MaxMetaspaceSize=256m");
+        assert_eq!(jdk7[0].substitute, "-XX:MaxPermSize");
+
+        // 生成的推荐参数已按目标JDK调整，在所有支持的JDK上都可用
+        for jdk_version in [8, 9, 10, 11, 17, 21] {
+            let args = Args {
+                complexity: "high".to_string(),
+                workload_lifetime: "short".to_string(),
+                ..on(jdk_version)
+            };
+            let safety = crate::analysis::calculate_safety(&args, 4.0, 12.0);
+            let recommended = JvmFlags::recommended(&args, 4.0, 12.0, 512, &safety);
+            let issues = flag_compat_issues(&recommended, &args);
+            assert!(issues.is_empty(), "JDK {jdk_version}: {issues:?}");
+            let cds = recommended
+                .groups
+                .iter()
+                .flat_map(|g| &g.flags)
+                .any(|f| f.value.starts_with("-XX:SharedArchiveFile"));
+            assert_eq!(cds, jdk_version >= 10);
+        }
+    }
 }
@@ -1,23 +1,361 @@
+pub mod backlog;
+pub mod cluster;
+pub mod cost;
+pub mod downgrade;
 pub mod jvm;
+pub mod native;
 pub mod performance;
 pub mod safety;
 pub mod scenarios;
+pub mod sensitivity;
+#[cfg(test)]
+mod snapshot_tests;
+pub mod upgrade;
 
-pub use jvm::print_jvm_recommendations;
-pub use safety::Scenario;
-pub use safety::{SafetyAnalysis, calculate_safety};
+pub use jvm::{print_jvm_recommendations, print_metaspace_explanation};
+pub use safety::{BurstPoint, Scenario, ScenarioStatus};
+pub use safety::{
+    DirectMemBreakdown, MemoryFitFailure, Recommendation, ResourceUtilization, SafetyAnalysis,
+    SafetyPolicy, Severity, Warning, calculate_safety, calculate_safety_with_policy, safety_at,
+};
 pub use scenarios::print_scenarios;
 
-use crate::args::Args;
+use crate::args::{AnalysisError, Args};
+use crate::config;
+use backlog::BacklogRecommendation;
+use cluster::ClusterAnalysis;
+use downgrade::DowngradeSuggestion;
+use performance::PerformanceReport;
+
+/// 一次完整分析的计算结果
+///
+/// 汇总内存分配、元空间、安全性与性能分析，供控制台、markdown
+/// 以及交互模式等各类输出复用
+pub struct AnalysisResult {
+    pub direct_mem_gb: f64,
+    pub heap_mem_gb: f64,
+    pub metaspace_size_mb: i32,
+    pub disk_read_speed: f64,  // MB/s
+    pub disk_write_speed: f64, // MB/s
+    pub safety: SafetyAnalysis,
+    pub performance: PerformanceReport,
+    pub backlog: BacklogRecommendation,
+    pub cluster: Option<ClusterAnalysis>, // 集群汇总，单节点部署时为 `None`
+    pub downgrade: Option<DowngradeSuggestion>, // 配置过剩时的缩容建议
+}
+
+/// 根据命令行参数执行完整分析
+///
+/// 依次计算内存分配、元空间、安全系数和性能报告，不产生任何输出
+pub fn analyze(args: &Args) -> anyhow::Result<AnalysisResult> {
+    // 直接构造的Args没有经过命令行校验，先做同样的检查；
+    // 无法识别的取值会在各处按默认值处理，严格模式下直接报错
+    args.validate()?;
+
+    // 获取磁盘配置
+    let disk_config = config::effective_disk(args)?;
+
+    let policy = SafetyPolicy::from_args(args);
+    policy.validate()?;
+    let (min, max) = metaspace_bounds(args);
+    if min > max {
+        return Err(AnalysisError::InvalidMetaspaceBounds { min, max }.into());
+    }
+
+    // 1. 计算内存分配
+    let (direct_mem_gb, heap_mem_gb) = allocate_memory(args)?;
+    log::debug!(
+        "内存分配计算: 总内存={}GB, 直接内存={:.1}GB, 堆内存={:.1}GB",
+        args.total_ram,
+        direct_mem_gb,
+        heap_mem_gb
+    );
+
+    // 2. 动态计算元空间大小
+    let metaspace_size_mb = calculate_metaspace(args);
+
+    // 3. 计算安全系数
+    let mut safety = calculate_safety_with_policy(args, direct_mem_gb, heap_mem_gb, &policy);
+
+    // 4. 计算性能报告
+    let mut performance =
+        performance::calculate_performance(args, &disk_config, direct_mem_gb, heap_mem_gb);
+
+    // 延迟SLO以稳态资源极限为容量求解，预期连接数已违反时告警
+    performance.slo = performance::slo_check(
+        args,
+        &disk_config,
+        safety.theoretical_limits.steady_state_max,
+    );
+    if let Some(slo) = performance.slo.filter(|slo| slo.violated()) {
+        safety.warnings.push(Warning::new(
+            Severity::Warning,
+            format!(
+                "延迟SLO: 预期{}连接时p99{}，超过目标{:.0}ms，满足SLO的最大连接数为{}",
+                args.expected_connections,
+                if slo.saturated() {
+                    "无上限(已达稳态容量，排队饱和)".to_string()
+                } else {
+                    format!("约{:.0}ms", slo.expected_p99_ms)
+                },
+                slo.target_p99_ms,
+                slo.max_connections
+            ),
+            format!(
+                "单次传输耗时 / (1 - 连接数/稳态容量{}) × ln(100)",
+                safety.theoretical_limits.steady_state_max
+            ),
+        ));
+    }
+
+    // 5. 计算连接队列调优建议
+    let backlog = backlog::calculate_backlog(args);
+
+    // 6. 集群汇总: 单节点故障时剩余节点必须承受突发负载
+    let cluster = cluster::calculate_cluster(args, &safety.theoretical_limits);
+    if let Some(cluster) = cluster
+        .as_ref()
+        .filter(|cluster| !cluster.survives_node_failure)
+    {
+        safety.warnings.push(Warning::new(
+            Severity::Critical,
+            format!(
+                "N-1冗余不足: 单节点故障时剩余{}个节点每个需承载{}个突发连接，超过单节点稳态极限{}",
+                cluster.nodes - 1,
+                cluster.failover_connections,
+                cluster.node_max_connections
+            ),
+            format!(
+                "突发连接数{} × {}节点 / ({}节点 × 负载均衡效率{})",
+                args.burst_connections(),
+                cluster.nodes,
+                cluster.nodes - 1,
+                cluster.lb_efficiency
+            ),
+        ));
+    }
+
+    // 7. 各资源利用率均远低于阈值时建议缩容
+    let downgrade = downgrade::calculate_downgrade(args, &safety.theoretical_limits.utilization);
+    if let Some(downgrade) = &downgrade {
+        let (resource, pct) = downgrade.tightest;
+        let savings = match (downgrade.monthly_savings, args.currency.as_str()) {
+            (Some(savings), "") => format!("，每月节省约{savings:.2} CNY"),
+            (Some(savings), currency) => format!("，每月节省约{savings:.2} {currency}"),
+            (None, _) => String::new(),
+        };
+        safety.recommendations.push(Recommendation::new(
+            Severity::Info,
+            format!(
+                "配置过剩: 可缩容至{}GB内存 / {}核CPU，稳态最大连接数{}仍可承载{}个突发连接{savings}",
+                downgrade.ram_gb,
+                downgrade.cpu_cores,
+                downgrade.max_connections,
+                args.burst_connections()
+            ),
+            format!(
+                "各资源利用率均低于{}%(最高为{resource} {pct:.0}%)，按 利用率 × 突发倍数{} / 目标利用率{:.0}% 折算后重新计算稳态极限",
+                downgrade::OVERPROVISIONED_BELOW_PCT,
+                args.burst_factor.max(1.0),
+                downgrade::TARGET_BURST_UTILIZATION * 100.0
+            ),
+        ));
+    }
+
+    Ok(AnalysisResult {
+        direct_mem_gb,
+        heap_mem_gb,
+        metaspace_size_mb,
+        disk_read_speed: disk_config.read_speed,
+        disk_write_speed: disk_config.write_speed,
+        safety,
+        performance,
+        backlog,
+        cluster,
+        downgrade,
+    })
+}
+
+/// 负载-风险曲线上的一个采样点
+pub struct SweepPoint {
+    pub connections: usize,
+    pub heap_safety: f64,
+    pub direct_mem_safety: f64,
+    pub risk_level: String,
+}
+
+/// 在 `max_connections / points` 到 `max_connections` 之间等距采样连接数，
+/// 计算每个连接数下的安全系数和风险等级
+pub fn safety_sweep(
+    args: &Args,
+    max_connections: usize,
+    points: usize,
+) -> Result<Vec<SweepPoint>, AnalysisError> {
+    let (direct_mem_gb, heap_mem_gb) = allocate_memory(args)?;
+    let points = points.max(1);
+    Ok((1..=points)
+        .map(|i| {
+            let connections = max_connections * i / points;
+            let (heap_safety, direct_mem_safety, risk_level) =
+                safety::safety_at(args, direct_mem_gb, heap_mem_gb, connections);
+            SweepPoint {
+                connections,
+                heap_safety,
+                direct_mem_safety,
+                risk_level,
+            }
+        })
+        .collect())
+}
+
+/// 对每个突发倍数重新计算突发场景，评估堆和直接内存能否承受
+pub fn burst_sweep(args: &Args, factors: &[f64]) -> Result<Vec<BurstPoint>, AnalysisError> {
+    let (direct_mem_gb, heap_mem_gb) = allocate_memory(args)?;
+    let policy = SafetyPolicy::from_args(args);
+    Ok(factors
+        .iter()
+        .map(|&factor| safety::burst_point(args, direct_mem_gb, heap_mem_gb, factor, &policy))
+        .collect())
+}
+
+/// 按比例计算 (直接内存GB, 堆内存GB)
+///
+/// 指定 `--min-free-memory-gb` 时，最小值下限或比例覆盖挤占保留内存的部分按比例缩减。
+/// 指定 `--no-explicit-direct` 时直接内存取HotSpot的默认上限，即与堆相同
+pub fn allocate_memory(args: &Args) -> Result<(f64, f64), AnalysisError> {
+    let (direct_mem_gb, heap_mem_gb) = proportional_memory(args)?;
+    let scale = match free_memory_floor_excess(args)? {
+        Some((requested_gb, allowed_gb)) => allowed_gb / requested_gb,
+        None => 1.0,
+    };
+    let heap_mem_gb = heap_mem_gb * scale;
+    if args.no_explicit_direct {
+        return Ok((heap_mem_gb, heap_mem_gb));
+    }
+    Ok((direct_mem_gb * scale, heap_mem_gb))
+}
+
+/// 未考虑保留内存时的 (直接内存GB, 堆内存GB)
+fn proportional_memory(args: &Args) -> Result<(f64, f64), AnalysisError> {
+    let (direct_ratio, heap_ratio) = resolve_memory_ratios(args)?;
+    // 保证最小可用内存: 直接内存至少1GB、堆至少4GB。
+    // 总内存极小时下限可能使分配超过按比例计算的值，需结合总内存评估
+    // 超售实例按可信内存分配，避免物理页不足时JVM被换出；保留内存不参与分配
+    let total_ram = args.allocatable_ram();
+    let direct_mem_gb = (total_ram * direct_ratio).max(1.0);
+    let heap_mem_gb = (total_ram * heap_ratio).max(4.0);
+    Ok((direct_mem_gb, heap_mem_gb))
+}
+
+/// 堆和直接内存超出保留内存下限时返回 (请求的堆+直接内存GB, 允许的上限GB)
+///
+/// 上限为可信内存扣除保留值和对账中其余各项(元空间、代码缓存、Native预留、分配器开销)后的剩余，
+/// 未指定 `--min-free-memory-gb` 时不检查
+pub fn free_memory_floor_excess(args: &Args) -> Result<Option<(f64, f64)>, AnalysisError> {
+    if args.min_free_memory_gb.is_none() {
+        return Ok(None);
+    }
+    let (direct_mem_gb, heap_mem_gb) = proportional_memory(args)?;
+    let requested_gb = direct_mem_gb + heap_mem_gb;
+    let allowed_gb = (args.allocatable_ram() - non_heap_reserved_gb(args)?).max(0.0);
+    Ok((requested_gb > allowed_gb).then_some((requested_gb, allowed_gb)))
+}
+
+/// 保留内存检查中堆和直接内存以外的内存(GB)
+///
+/// 按缩减前的堆计算；NMT估算随堆缩减而减小，因此缩减后的对账不会低于保留值
+pub(crate) fn non_heap_reserved_gb(args: &Args) -> Result<f64, AnalysisError> {
+    let (_, heap_mem_gb) = proportional_memory(args)?;
+    Ok(non_heap_items(args, heap_mem_gb, calculate_metaspace(args))
+        .iter()
+        .map(|(_, gb)| gb)
+        .sum())
+}
+
+/// 静态内存分配对账
+///
+/// JVM各内存区域的上限加上Native预留即为进程可能占用的内存，
+/// 用于确认比例覆盖或最小值下限没有使分配超过服务器内存
+pub struct MemoryReconciliation {
+    pub items: Vec<(&'static str, f64)>, // (内存区域, GB)
+    pub allocated_gb: f64,               // 合计(GB)
+    pub free_gb: f64,                    // 服务器内存减去合计，超额时为负
+    pub min_free_gb: f64,                // 必须保持空闲的内存(GB)
+}
+
+impl MemoryReconciliation {
+    /// 剩余内存低于保留值(未指定保留值时即超过服务器内存)
+    pub fn over_subscribed(&self) -> bool {
+        self.free_gb < self.min_free_gb
+    }
+}
+
+/// 汇总各内存区域的配置值并与可信内存(`total_ram` × 超售比例)对账
+pub fn reconcile_memory(
+    args: &Args,
+    direct_mem_gb: f64,
+    heap_mem_gb: f64,
+    metaspace_size_mb: i32,
+) -> MemoryReconciliation {
+    let mut items = vec![("堆内存", heap_mem_gb), ("直接内存", direct_mem_gb)];
+    items.extend(non_heap_items(args, heap_mem_gb, metaspace_size_mb));
+    let allocated_gb: f64 = items.iter().map(|(_, gb)| gb).sum();
+    MemoryReconciliation {
+        items,
+        allocated_gb,
+        free_gb: args.effective_total_ram() - allocated_gb,
+        min_free_gb: args.min_free_memory_gb.unwrap_or(0.0),
+    }
+}
+
+/// 对账中堆和直接内存以外的各项
+fn non_heap_items(
+    args: &Args,
+    heap_mem_gb: f64,
+    metaspace_size_mb: i32,
+) -> Vec<(&'static str, f64)> {
+    let mut items = vec![("元空间", metaspace_size_mb as f64 / 1024.0)];
+    // NMT估算已包含代码缓存，不再单独列出
+    if !native::uses_nmt_model(args) {
+        items.push(("代码缓存", jvm::code_cache_mb(args) as f64 / 1024.0));
+    }
+    // 与内存预算使用同一口径: Native预留含ZGC额外开销，另计原生内存分配器开销
+    items.push((
+        "Native预留",
+        safety::jvm_native_reserved_gb(args, heap_mem_gb),
+    ));
+    items.push(("分配器开销", safety::allocator_overhead_gb(args)));
+    items
+}
+
+/// 确定 (直接内存比例, 堆内存比例)
+///
+/// 默认按应用复杂度和平均文件大小计算，`--direct-ratio`/`--heap-ratio` 可分别覆盖。
+/// 两者与Native预留之和不能超过1.0
+pub fn resolve_memory_ratios(args: &Args) -> Result<(f64, f64), AnalysisError> {
+    let (default_direct, default_heap) =
+        config::memory_ratios(&args.complexity, args.avg_file_size);
+    let direct = args.direct_ratio.unwrap_or(default_direct);
+    let heap = args.heap_ratio.unwrap_or(default_heap);
+
+    if direct + heap + config::NATIVE_MEM_RATIO > 1.0 {
+        return Err(AnalysisError::InvalidMemoryRatio {
+            direct,
+            heap,
+            native: config::NATIVE_MEM_RATIO,
+        });
+    }
+    Ok((direct, heap))
+}
 
 /// 元空间计算模型 (基于文件类型和连接数)
-const BASE_METASPACE: f64 = 256.0; // 基础元空间大小(MB)
-const CONNECTION_FACTOR: f64 = 30.0; // 每1000连接增加的大小(MB)
+pub(crate) const BASE_METASPACE: f64 = 256.0; // 基础元空间大小(MB)
+pub(crate) const CONNECTION_FACTOR: f64 = 30.0; // 每1000连接增加的大小(MB)
 const FILE_SIZE_FACTOR: f64 = 20.0; // 每100MB文件大小增加的大小(MB) 
-const THREAD_FACTOR: f64 = 1.0; // 每个线程增加的大小(MB)
-const MIN_METASPACE: f64 = 128.0; // 最小元空间大小(MB)
-const MAX_METASPACE: f64 = 3072.0; // 最大元空间大小(MB)
-const CONNECTIONS_BASE: f64 = 1000.0; // 连接数计算基准值
+pub(crate) const THREAD_FACTOR: f64 = 1.0; // 每个线程增加的大小(MB)
+pub(crate) const MIN_METASPACE: f64 = 128.0; // 最小元空间大小(MB)
+pub(crate) const MAX_METASPACE: f64 = 3072.0; // 最大元空间大小(MB)
+pub(crate) const CONNECTIONS_BASE: f64 = 1000.0; // 连接数计算基准值
 
 /// 根据文件类型获取复杂度因子
 fn get_complexity_factor(args: &Args) -> f64 {
@@ -40,10 +378,15 @@ fn get_safety_margin(args: &Args) -> f64 {
 
 /// 计算基础元空间大小(考虑文件类型和线程数)
 fn calculate_base_metaspace(args: &crate::args::Args) -> f64 {
-    let base = BASE_METASPACE * get_complexity_factor(args);
-    // 每个线程需要约1MB元空间
-    let threads = (args.cpu_cores * 2) as f64; // IO密集型应用通常需要2*CPU核心数的线程
-    base + threads * THREAD_FACTOR
+    BASE_METASPACE * get_complexity_factor(args) + thread_metaspace(args)
+}
+
+/// 线程相关元空间增量
+///
+/// 每个线程需要约1MB元空间
+/// IO密集型应用通常需要2*CPU核心数的线程，另加数据库连接池线程
+fn thread_metaspace(args: &Args) -> f64 {
+    (args.cpu_cores * 2 + args.db_pool_size) as f64 * THREAD_FACTOR
 }
 
 /// 计算连接数相关元空间增量
@@ -67,17 +410,39 @@ fn calculate_file_size_factor(args: &crate::args::Args) -> f64 {
     (args.avg_file_size / 100.0).floor() * FILE_SIZE_FACTOR
 }
 
-/// 计算推荐的元空间大小
+/// 元空间计算明细
 ///
-/// 综合考虑基础值、连接数、文件大小和安全系数，
-/// 返回一个在合理范围内的元空间大小建议值
+/// 记录各组成部分以及是否触达上限，便于报告中解释最终取值
+pub struct MetaspaceBreakdown {
+    pub complexity_factor: f64, // 复杂度因子
+    pub thread_mb: f64,         // 线程增量(MB)
+    pub base: f64,              // 基础值(含复杂度与线程)(MB)
+    pub connection_factor: f64, // 连接数增量(MB)
+    pub file_size_factor: f64,  // 文件大小增量(MB)
+    pub safety_margin: f64,     // 安全边际倍数
+    pub min_mb: f64,            // 下限(MB)
+    pub max_mb: f64,            // 上限(MB)
+    pub floor_hit: bool,        // 是否被下限抬高
+    pub uncapped_mb: f64,       // 应用安全边际后、上限截断前的值(MB)
+    pub final_mb: i32,          // 最终推荐值(MB)
+    pub ceiling_hit: bool,      // 是否被上限截断
+}
+
+/// 元空间推荐值的 (下限, 上限)(MB)
 ///
-/// # 参数
-/// - args: 命令行参数
+/// `--min-metaspace-mb`/`--max-metaspace-mb` 分别覆盖默认的128MB/3072MB
+pub fn metaspace_bounds(args: &Args) -> (f64, f64) {
+    (
+        args.min_metaspace_mb.unwrap_or(MIN_METASPACE),
+        args.max_metaspace_mb.unwrap_or(MAX_METASPACE),
+    )
+}
+
+/// 计算元空间大小并返回各组成部分
 ///
-/// # 返回值
-/// 推荐的元空间大小(MB)
-pub fn calculate_metaspace(args: &crate::args::Args) -> i32 {
+/// 与 [`calculate_metaspace`] 的计算完全一致，但额外记录
+/// 原始计算值是否超过了上限
+pub fn calculate_metaspace_detailed(args: &crate::args::Args) -> MetaspaceBreakdown {
     let base = calculate_base_metaspace(args);
     let connection_factor = calculate_connection_factor(args);
     let file_size_factor = calculate_file_size_factor(args);
@@ -86,9 +451,41 @@ pub fn calculate_metaspace(args: &crate::args::Args) -> i32 {
 
     // Apply minimum boundary after safety margin
     let safety_margin = get_safety_margin(args);
-    let adjusted_total = (raw_total * safety_margin).max(MIN_METASPACE * safety_margin);
+    let (min_mb, max_mb) = metaspace_bounds(args);
+    let adjusted_total = (raw_total * safety_margin).max(min_mb * safety_margin);
+
+    log::debug!(
+        "元空间计算: 基础={base:.1}MB + 连接={connection_factor:.1}MB + 文件={file_size_factor:.1}MB = {raw_total:.1}MB, 安全边际x{safety_margin} => {adjusted_total:.1}MB"
+    );
 
-    adjusted_total.min(MAX_METASPACE).ceil() as i32
+    MetaspaceBreakdown {
+        complexity_factor: get_complexity_factor(args),
+        thread_mb: thread_metaspace(args),
+        base,
+        connection_factor,
+        file_size_factor,
+        safety_margin,
+        min_mb,
+        max_mb,
+        floor_hit: raw_total < min_mb,
+        uncapped_mb: adjusted_total,
+        final_mb: adjusted_total.min(max_mb).ceil() as i32,
+        ceiling_hit: adjusted_total > max_mb,
+    }
+}
+
+/// 计算推荐的元空间大小
+///
+/// 综合考虑基础值、连接数、文件大小和安全系数，
+/// 返回一个在合理范围内的元空间大小建议值
+///
+/// # 参数
+/// - args: 命令行参数
+///
+/// # 返回值
+/// 推荐的元空间大小(MB)
+pub fn calculate_metaspace(args: &crate::args::Args) -> i32 {
+    calculate_metaspace_detailed(args).final_mb
 }
 
 #[cfg(test)]
@@ -173,4 +570,282 @@ mod tests {
         let result = calculate_metaspace(&args);
         assert_approx_eq::assert_approx_eq!(result as f64, expected, 1.0); // Allow 1MB tolerance
     }
+
+    #[test]
+    fn test_calculate_metaspace_ceiling_hit() {
+        let args = create_test_args("medium", 2000, 50.0);
+        let detail = calculate_metaspace_detailed(&args);
+        assert!(!detail.ceiling_hit);
+        assert_eq!(detail.final_mb, calculate_metaspace(&args));
+
+        // 100k connections push the raw value well past MAX_METASPACE
+        let args = create_test_args("high", 100_000, 500.0);
+        let detail = calculate_metaspace_detailed(&args);
+        assert!(detail.ceiling_hit);
+        assert!(detail.uncapped_mb > MAX_METASPACE);
+        assert_eq!(detail.final_mb, MAX_METASPACE as i32);
+
+        // 提高上限后按实际需求推荐
+        let args = Args {
+            max_metaspace_mb: Some(16_384.0),
+            ..args
+        };
+        let raised = calculate_metaspace_detailed(&args);
+        assert!(!raised.ceiling_hit);
+        assert_eq!(raised.final_mb, detail.uncapped_mb.ceil() as i32);
+    }
+
+    #[test]
+    fn test_metaspace_breakdown_components() {
+        let args = create_test_args("high", 1000, 10.0);
+        let detail = calculate_metaspace_detailed(&args);
+        assert_eq!(detail.complexity_factor, 1.5);
+        assert_eq!(detail.thread_mb, (args.cpu_cores * 2) as f64);
+        assert_eq!(detail.base, BASE_METASPACE * 1.5 + detail.thread_mb);
+        assert!(!detail.floor_hit);
+        assert_eq!(jvm::compressed_class_space_mb(1000), 400);
+        assert_eq!(jvm::compressed_class_space_mb(440), 256);
+    }
+
+    #[test]
+    fn test_burst_sweep_matches_burst_scenario() {
+        let args = create_test_args("medium", 1000, 10.0);
+        let points = burst_sweep(&args, &[1.0, args.burst_factor, 40.0]).unwrap();
+        let (direct, heap) = allocate_memory(&args).unwrap();
+        let scenario = &calculate_safety(&args, direct, heap).scenarios[2];
+        assert_eq!(points[1].connections, scenario.connections);
+        assert_eq!(points[1].heap_usage, scenario.heap_usage);
+        assert_eq!(points[1].direct_mem_usage, scenario.direct_mem_usage);
+        assert!(points[0].survives());
+        assert!(!points[2].survives());
+    }
+
+    #[test]
+    fn test_resolve_memory_ratios() {
+        let args = create_test_args("high", 1000, 10.0);
+        assert_eq!(resolve_memory_ratios(&args).unwrap(), (0.12, 0.3));
+
+        let args = Args {
+            heap_ratio: Some(0.5),
+            ..create_test_args("high", 1000, 10.0)
+        };
+        assert_eq!(resolve_memory_ratios(&args).unwrap(), (0.12, 0.5));
+
+        // 0.4 + 0.6 + 0.1 native reservation > 1.0
+        let args = Args {
+            direct_ratio: Some(0.4),
+            heap_ratio: Some(0.6),
+            ..create_test_args("medium", 1000, 10.0)
+        };
+        assert!(matches!(
+            resolve_memory_ratios(&args),
+            Err(AnalysisError::InvalidMemoryRatio { .. })
+        ));
+    }
+
+    #[test]
+    fn test_target_p99_bounds_slo_safe_connections() {
+        let args = Args {
+            total_ram: 32.0,
+            cpu_cores: 16,
+            net_gbps: 10.0,
+            disk_type: "nvme".to_string(),
+            burst_factor: 1.0,
+            ..create_test_args("medium", 1000, 1.0)
+        };
+        assert!(analyze(&args).unwrap().performance.slo.is_none());
+
+        let strict = Args {
+            target_p99_ms: Some(1000.0),
+            ..args.clone()
+        };
+        let result = analyze(&strict).unwrap();
+        let slo = result.performance.slo.unwrap();
+        let capacity = result.safety.theoretical_limits.steady_state_max;
+        assert!(slo.max_connections < capacity);
+        assert!(slo.violated());
+        assert!(
+            result
+                .safety
+                .warnings
+                .iter()
+                .any(|w| w.summary.starts_with("延迟SLO"))
+        );
+        // 二分结果恰好位于目标边界上
+        let disk = config::effective_disk(&strict).unwrap();
+        let p99 = |n| performance::estimated_p99_ms(&strict, &disk, n, capacity);
+        assert!(p99(slo.max_connections) <= 1000.0);
+        assert!(p99(slo.max_connections + 1) > 1000.0);
+
+        let relaxed = Args {
+            target_p99_ms: Some(1_000_000.0),
+            ..args
+        };
+        let result = analyze(&relaxed).unwrap();
+        assert!(!result.performance.slo.unwrap().violated());
+        assert!(
+            !result
+                .safety
+                .warnings
+                .iter()
+                .any(|w| w.summary.starts_with("延迟SLO"))
+        );
+
+        // 预期连接数超过稳态容量时p99无有限值，告警中不出现inf
+        let saturated = Args {
+            expected_connections: capacity * 2,
+            ..relaxed
+        };
+        let result = analyze(&saturated).unwrap();
+        assert!(result.performance.slo.unwrap().saturated());
+        let warning = result
+            .safety
+            .warnings
+            .iter()
+            .find(|w| w.summary.starts_with("延迟SLO"));
+        assert!(!warning.unwrap().summary.contains("inf"));
+    }
+
+    #[test]
+    fn test_min_free_memory_is_kept_unallocated() {
+        let args = Args {
+            total_ram: 32.0,
+            ..create_test_args("medium", 1000, 10.0)
+        };
+        let (direct, heap) = allocate_memory(&args).unwrap();
+
+        // 按比例分配时直接扣除保留值
+        let reserved = Args {
+            min_free_memory_gb: Some(2.0),
+            ..args.clone()
+        };
+        assert_eq!(reserved.allocatable_ram(), args.total_ram - 2.0);
+        let (reserved_direct, reserved_heap) = allocate_memory(&reserved).unwrap();
+        assert!(reserved_direct < direct && reserved_heap < heap);
+        assert!(free_memory_floor_excess(&reserved).unwrap().is_none());
+
+        // 8GB内存保留3GB后，4GB堆下限和1GB直接内存下限放不下，按比例缩减
+        let tight = Args {
+            total_ram: 8.0,
+            min_free_memory_gb: Some(3.0),
+            ..args
+        };
+        let (requested, allowed) = free_memory_floor_excess(&tight).unwrap().unwrap();
+        assert_eq!(requested, 5.0);
+        assert!((allowed - (5.0 - non_heap_reserved_gb(&tight).unwrap())).abs() < 1e-9);
+        assert!(allowed < 4.2);
+        let (tight_direct, tight_heap) = allocate_memory(&tight).unwrap();
+        assert!((tight_direct + tight_heap - allowed).abs() < 1e-9);
+        let safety = calculate_safety(&tight, tight_direct, tight_heap);
+        assert!(
+            safety
+                .warnings
+                .iter()
+                .any(|w| w.summary.starts_with("保留内存不足"))
+        );
+
+        let reconciliation = reconcile_memory(&tight, tight_direct, tight_heap, 256);
+        assert_eq!(reconciliation.min_free_gb, 3.0);
+        assert!(!reconciliation.over_subscribed());
+    }
+
+    #[test]
+    fn test_no_explicit_direct_bounds_direct_memory_by_heap() {
+        let args = Args {
+            total_ram: 32.0,
+            ..create_test_args("medium", 1000, 10.0)
+        };
+        let (explicit_direct, heap) = allocate_memory(&args).unwrap();
+        let implicit = Args {
+            no_explicit_direct: true,
+            ..args.clone()
+        };
+        let (direct, implicit_heap) = allocate_memory(&implicit).unwrap();
+        assert_eq!(implicit_heap, heap);
+        assert_eq!(direct, heap);
+        assert!(direct > explicit_direct);
+
+        // 默认上限使堆和直接内存合计翻倍，对账中可见
+        let reconciliation = reconcile_memory(&implicit, direct, heap, 256);
+        let explicit = reconcile_memory(&args, explicit_direct, heap, 256);
+        assert!(reconciliation.allocated_gb > explicit.allocated_gb);
+
+        let result = analyze(&Args {
+            disk_type: "nvme".to_string(),
+            cpu_cores: 16,
+            burst_factor: 1.0,
+            ..implicit.clone()
+        })
+        .unwrap();
+        assert!(
+            result
+                .safety
+                .warnings
+                .iter()
+                .any(|w| w.summary.starts_with("直接内存未显式限制"))
+        );
+        let flags = jvm::JvmFlags::recommended(
+            &implicit,
+            result.direct_mem_gb,
+            result.heap_mem_gb,
+            result.metaspace_size_mb,
+            &result.safety,
+        );
+        assert!(!flags.options_string().contains("MaxDirectMemorySize"));
+    }
+
+    #[test]
+    fn test_reconcile_memory_flags_floor_on_tiny_box() {
+        let args = Args {
+            total_ram: 4.0,
+            ..create_test_args("medium", 1000, 10.0)
+        };
+        let (direct, heap) = allocate_memory(&args).unwrap();
+        let reconciliation = reconcile_memory(&args, direct, heap, 256);
+        // 堆内存4GB下限已占满全部内存
+        assert!(reconciliation.over_subscribed());
+        assert_eq!(reconciliation.items.len(), 6);
+
+        let args = Args {
+            total_ram: 64.0,
+            ..args
+        };
+        let (direct, heap) = allocate_memory(&args).unwrap();
+        let reconciliation = reconcile_memory(&args, direct, heap, 256);
+        assert!(!reconciliation.over_subscribed());
+        assert!((reconciliation.allocated_gb + reconciliation.free_gb - 64.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_reconcile_memory_native_matches_budget() {
+        let args = Args {
+            total_ram: 64.0,
+            cpu_cores: 16,
+            disk_type: "nvme".to_string(),
+            gc: "zgc".to_string(),
+            burst_factor: 1.0,
+            net_gbps: 10.0,
+            ..create_test_args("medium", 1000, 10.0)
+        };
+        let result = analyze(&args).unwrap();
+        let reconciliation = reconcile_memory(
+            &args,
+            result.direct_mem_gb,
+            result.heap_mem_gb,
+            result.metaspace_size_mb,
+        );
+        let item = |name| {
+            reconciliation
+                .items
+                .iter()
+                .find(|(n, _)| *n == name)
+                .unwrap()
+                .1
+        };
+        let budget = &result.safety.memory_budget;
+        // ZGC额外的常驻内存和分配器开销与内存预算一致
+        assert!(item("Native预留") > native::native_reserved_gb(&args, result.heap_mem_gb));
+        assert!((item("Native预留") - budget.native_reserved_gb).abs() < 1e-9);
+        assert!((item("分配器开销") - budget.allocator_gb).abs() < 1e-9);
+    }
 }
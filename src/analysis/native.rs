@@ -0,0 +1,118 @@
+use crate::analysis::jvm::{Collector, code_cache_mb, jvm_thread_count, selected_collector};
+use crate::analysis::safety::ZGC_NATIVE_RATIO;
+use crate::args::Args;
+use crate::config;
+
+/// 未指定 `--thread-stack-kb` 时的线程栈大小(KB)，即64位Linux上 `-Xss` 的默认值
+pub const DEFAULT_THREAD_STACK_KB: u32 = 1024;
+/// G1的记忆集和卡表相对堆大小的比例
+const G1_NATIVE_RATIO: f64 = 0.03;
+/// Shenandoah的标记位图和转发指针相对堆大小的比例
+const SHENANDOAH_NATIVE_RATIO: f64 = 0.02;
+/// 符号表和字符串表(MB)
+const SYMBOL_MB: f64 = 32.0;
+/// JVM内部数据结构、编译器arena等其余开销(MB)
+const INTERNAL_MB: f64 = 64.0;
+
+/// 按NMT(Native Memory Tracking)分类估算的Native内存
+///
+/// 元空间(Class)和直接内存(Other)在内存分配中单独列出，不计入此估算
+pub struct NativeMemoryEstimate {
+    pub categories: Vec<(&'static str, f64)>, // (NMT分类, MB)
+}
+
+impl NativeMemoryEstimate {
+    /// 各分类合计(GB)
+    pub fn total_gb(&self) -> f64 {
+        self.categories.iter().map(|(_, mb)| mb).sum::<f64>() / 1024.0
+    }
+}
+
+/// 线程栈大小(KB)
+pub fn thread_stack_kb(args: &Args) -> u32 {
+    if args.thread_stack_kb == 0 {
+        DEFAULT_THREAD_STACK_KB
+    } else {
+        args.thread_stack_kb
+    }
+}
+
+/// 逐项估算Native内存: 线程栈随线程数和 `-Xss`，代码缓存随 `ReservedCodeCacheSize`，
+/// GC数据结构随堆大小和收集器变化
+pub fn estimate_native_memory(args: &Args, heap_mem_gb: f64) -> NativeMemoryEstimate {
+    let gc_ratio = match selected_collector(args) {
+        Collector::G1 => G1_NATIVE_RATIO,
+        Collector::Zgc => ZGC_NATIVE_RATIO,
+        Collector::Shenandoah => SHENANDOAH_NATIVE_RATIO,
+    };
+    let thread_mb = jvm_thread_count(args) as f64 * thread_stack_kb(args) as f64 / 1024.0;
+    NativeMemoryEstimate {
+        categories: vec![
+            ("Thread", thread_mb),
+            ("Code", code_cache_mb(args) as f64),
+            ("GC", heap_mem_gb * 1024.0 * gc_ratio),
+            ("Symbol", SYMBOL_MB),
+            ("Internal", INTERNAL_MB),
+        ],
+    }
+}
+
+/// 进程的Native内存预留(GB)
+///
+/// `--native-model nmt` 时为逐项估算的合计(已包含代码缓存和GC开销)，
+/// 否则为可信内存的固定比例
+pub fn native_reserved_gb(args: &Args, heap_mem_gb: f64) -> f64 {
+    if uses_nmt_model(args) {
+        estimate_native_memory(args, heap_mem_gb).total_gb()
+    } else {
+        args.effective_total_ram() * config::NATIVE_MEM_RATIO
+    }
+}
+
+/// 是否按NMT分类估算Native内存
+pub fn uses_nmt_model(args: &Args) -> bool {
+    args.native_model == "nmt"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_native_estimate_tracks_threads_and_stack_size() {
+        let args = Args {
+            total_ram: 64.0,
+            cpu_cores: 8,
+            complexity: "low".to_string(),
+            native_model: "nmt".to_string(),
+            ..Default::default()
+        };
+        let estimate = estimate_native_memory(&args, 16.0);
+        // 16业务线程 + 4+2 GC线程 + 32内部线程，每个1MB栈
+        assert_eq!(estimate.categories[0], ("Thread", 54.0));
+        assert_eq!(estimate.categories[1], ("Code", 96.0 + 8.0 * 4.0));
+        // 低复杂度使用G1
+        assert!((estimate.categories[2].1 - 16.0 * 1024.0 * 0.03).abs() < 1e-9);
+        assert!((native_reserved_gb(&args, 16.0) - estimate.total_gb()).abs() < 1e-12);
+
+        let small_stacks = Args {
+            thread_stack_kb: 256,
+            db_pool_size: 46,
+            ..args.clone()
+        };
+        assert_eq!(
+            estimate_native_memory(&small_stacks, 16.0).categories[0],
+            ("Thread", 25.0)
+        );
+
+        // 默认仍按固定比例预留
+        let flat = Args {
+            native_model: String::new(),
+            ..args
+        };
+        assert_eq!(
+            native_reserved_gb(&flat, 16.0),
+            64.0 * config::NATIVE_MEM_RATIO
+        );
+    }
+}
@@ -1,19 +1,87 @@
+use crate::analysis::DirectMemBreakdown;
+use crate::analysis::safety::{
+    TRANSFER_MB_PER_CORE, checksum_mb_per_core, cpu_efficiency, cpu_transfer_mb,
+    directional_net_gbps, disk_io_factor, offload_cpu_factor, page_cache_model, read_share,
+    throughput_model,
+};
 use crate::args::Args;
 use crate::config::DiskConfig;
 
 /// 资源瓶颈分析
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct ResourceLimit {
-    pub name: String,          // 资源名称
-    pub limiting_factor: bool,  // 是否为当前限制因素
-    pub max_connections: usize, // 最大并发量
-    pub qps: Option<usize>,     // 每秒查询数(对大文件可能为None)
+    pub name: String,                       // 资源名称
+    pub limiting_factor: bool,              // 是否为当前限制因素
+    pub max_connections: usize,             // 最大并发量
+    pub qps: Option<usize>,                 // 每秒查询数(对大文件可能为None)
+    pub sustained_qps: Option<usize>,       // 按单次传输耗时折算的持续QPS
+    pub detail: Option<DirectMemBreakdown>, // 直接内存的组成明细(仅直接内存项)
 }
 
 /// 性能分析结果
 pub struct PerformanceReport {
-    pub scenarios: Vec<ScenarioAnalysis>, // 不同场景分析
-    pub test_config: TestConfig,          // 性能测试建议配置
+    pub scenarios: Vec<ScenarioAnalysis>,      // 不同场景分析
+    pub test_config: TestConfig,               // 性能测试建议配置
+    pub distribution: Vec<BucketContribution>, // 文件大小分布各区间的贡献(未指定分布时为空)
+    pub slo: Option<SloCheck>,                 // 延迟SLO检查(指定 --target-p99-ms 时)
+}
+
+/// 延迟SLO检查结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SloCheck {
+    pub target_p99_ms: f64,     // p99延迟目标(ms)
+    pub expected_p99_ms: f64,   // 预期连接数下的p99估算(ms)，超出稳态容量时为无穷大
+    pub max_connections: usize, // p99不超过目标的最大连接数(SLO安全上限)
+}
+
+impl SloCheck {
+    /// 预期连接数下已违反SLO
+    pub fn violated(&self) -> bool {
+        self.expected_p99_ms > self.target_p99_ms
+    }
+
+    /// 预期连接数已达到稳态容量，排队无界，p99没有有限值
+    pub fn saturated(&self) -> bool {
+        self.expected_p99_ms.is_infinite()
+    }
+}
+
+/// 文件大小分布中单个区间对连接数、直接内存和带宽的贡献
+pub struct BucketContribution {
+    pub size_mb: f64,          // 文件大小(MB)
+    pub connection_share: f64, // 连接数占比
+    pub direct_kb: f64,        // 单连接直接内存(KB)
+    pub direct_share: f64,     // 直接内存占比
+    pub bandwidth_share: f64,  // 传输字节(带宽)占比
+}
+
+/// 计算文件大小分布中各区间的贡献
+///
+/// 权重即该大小文件占连接数的比例；直接内存和带宽按 权重 × 单连接用量 归一化
+pub fn bucket_contributions(args: &Args) -> Vec<BucketContribution> {
+    let Some(distribution) = &args.file_size_distribution else {
+        return Vec::new();
+    };
+    let direct_total: f64 = distribution
+        .buckets
+        .iter()
+        .map(|(size, weight)| DirectMemBreakdown::for_file_size(*size).total_kb() * weight)
+        .sum();
+    let bytes_total = distribution.mean();
+    distribution
+        .buckets
+        .iter()
+        .map(|&(size_mb, weight)| {
+            let direct_kb = DirectMemBreakdown::for_file_size(size_mb).total_kb();
+            BucketContribution {
+                size_mb,
+                connection_share: weight,
+                direct_kb,
+                direct_share: direct_kb * weight / direct_total,
+                bandwidth_share: size_mb * weight / bytes_total,
+            }
+        })
+        .collect()
 }
 
 /// 场景分析
@@ -22,7 +90,9 @@ pub struct ScenarioAnalysis {
     pub avg_file_size: f64,     // 平均文件大小(MB)
     pub resources: Vec<ResourceLimit>, // 各资源限制
     pub final_capacity: ResourceLimit, // 最终能力
-    pub key_findings: Vec<String>, // 关键发现
+    pub transfer_secs: f64,            // 最终能力下单次传输耗时(秒)
+    pub throughput_mb: f64,            // 最终能力下的持续吞吐量(MB/s)，不超过推荐吞吐量
+    pub key_findings: Vec<String>,     // 关键发现
 }
 
 /// 性能测试建议配置
@@ -44,6 +114,40 @@ pub struct TestScenario {
     pub success_criteria: String, // 成功标准
 }
 
+/// 测试脚本随机选择的文件大小(MB)
+pub const TEST_FILE_SIZES_MB: [u32; 5] = [1, 5, 10, 30, 100];
+
+/// 生成测试脚本所需文件的命令(每个大小一条)
+///
+/// 优先使用 `fallocate` 快速分配，不支持时回退到 `dd`
+pub fn testfile_commands() -> Vec<String> {
+    TEST_FILE_SIZES_MB
+        .iter()
+        .map(|size| {
+            format!(
+                "fallocate -l {size}M test_files/{size}mb.dat 2>/dev/null || \
+                 dd if=/dev/urandom of=test_files/{size}mb.dat bs=1M count={size} status=none"
+            )
+        })
+        .collect()
+}
+
+/// 生成创建全部测试文件的独立bash脚本
+pub fn generate_testfiles_script() -> String {
+    let mut script = String::from(
+        "#!/usr/bin/env bash\n\
+         # 生成性能测试脚本引用的测试文件\n\
+         set -euo pipefail\n\
+         mkdir -p test_files\n",
+    );
+    for command in testfile_commands() {
+        script.push_str(&command);
+        script.push('\n');
+    }
+    script.push_str("echo \"测试文件已生成: test_files/\"\n");
+    script
+}
+
 /// 计算性能报告
 pub fn calculate_performance(
     args: &Args,
@@ -56,20 +160,38 @@ pub fn calculate_performance(
     let mem_connections = ((direct_mem_gb + heap_mem_gb) * 1024.0 / mem_per_conn) as usize;
 
     // 定义要分析的场景
-    let scenarios = vec![
+    let mut scenarios = vec![
         analyze_scenario("混合文件大小", 30.0, args, disk_config, mem_connections),
         analyze_scenario("小文件为主", 5.0, args, disk_config, mem_connections),
     ];
+    // 带宽和磁盘开销与文件大小成正比，按加权平均大小计算即为分布下的混合容量；
+    // 直接内存缓冲区随大小分段变化，单独按分布加权
+    if args.file_size_distribution.is_some() {
+        let mut actual = analyze_scenario(
+            "实际分布",
+            args.avg_file_size,
+            args,
+            disk_config,
+            mem_connections,
+        );
+        for resource in &mut actual.resources {
+            if resource.detail.is_some() {
+                resource.detail = Some(DirectMemBreakdown::weighted(args));
+            }
+        }
+        scenarios.push(actual);
+    }
 
     // 生成性能测试建议
     let script_examples = vec![
         format!(
             "# 使用wrk进行混合文件测试\n\
+            # 先生成测试文件: sa --emit-testfiles-script gen_test_files.sh && ./gen_test_files.sh\n\
             wrk -t{} -c{} -d{} -s upload_script.lua http://your-server/upload\n\n\
             # upload_script.lua\n\
             function init()\n\
                 math.randomseed(os.time())\n\
-                sizes = {{1, 5, 10, 30, 100}} -- MB\n\
+                sizes = {{{}}} -- MB\n\
             end\n\n\
             function request()\n\
                 -- 随机选择文件大小\n\
@@ -88,10 +210,12 @@ pub fn calculate_performance(
             end",
             args.cpu_cores,
             args.expected_connections,
-            "10m"
+            "10m",
+            TEST_FILE_SIZES_MB.map(|size| size.to_string()).join(", ")
         ),
         format!(
             "# 使用ab进行固定大小文件测试\n\
+            # 先生成测试文件: mkdir -p test_files && fallocate -l 10M test_files/10mb.dat\n\
             ab -n {} -c {} -T \"application/octet-stream\" -p test_files/10mb.dat http://your-server/upload",
             args.expected_connections * 100,
             args.expected_connections
@@ -136,58 +260,194 @@ pub fn calculate_performance(
     PerformanceReport {
         scenarios,
         test_config,
+        distribution: bucket_contributions(args),
+        slo: None,
     }
 }
 
-fn analyze_scenario(
-    name: &str,
+/// 单连接传输速率上限(MB/s)，按客户端约100Mbps估算
+const CLIENT_MB_PER_SEC: f64 = 12.5;
+
+/// 磁盘可支撑的传输速率(MB/s)，已扣除文件系统开销和JVM IO等待
+///
+/// 页缓存命中的读取不经过磁盘，写放大只作用于写入部分，与稳态极限的磁盘IO使用
+/// 同一个物理IO系数，纯下载负载因此不受写放大影响
+fn disk_transfer_mb(args: &Args, disk_config: &DiskConfig) -> f64 {
+    disk_config.read_speed * 0.75 / (1.1 * disk_io_factor(args))
+}
+
+/// 经网络传输的字节占原始字节的比例: 只有响应(读取)被压缩
+fn wire_bytes_ratio(args: &Args, compression_ratio: f64) -> f64 {
+    1.0 - read_share(&args.workload) * (1.0 - compression_ratio)
+}
+
+/// 网络和磁盘共同决定的总传输速率(按原始文件字节计, MB/s)
+fn aggregate_transfer_mb(args: &Args, disk_config: &DiskConfig, compression_ratio: f64) -> f64 {
+    let network = directional_net_gbps(args) * 125.0 * 0.97
+        / (wire_bytes_ratio(args, compression_ratio) * 1.05);
+    let disk = disk_transfer_mb(args, disk_config);
+    network.min(disk)
+}
+
+/// 在给定并发下传输一个文件的耗时(秒)
+///
+/// 每个连接平分总传输速率，且不超过单连接速率上限
+fn transfer_secs(concurrency: usize, avg_file_size: f64, aggregate_mb: f64) -> f64 {
+    let per_conn = (aggregate_mb / concurrency.max(1) as f64).min(CLIENT_MB_PER_SEC);
+    avg_file_size / per_conn
+}
+
+/// 持续QPS = 并发连接数 / 单次传输耗时
+///
+/// 与 `qps` 的瞬时估算不同，考虑了连接在传输期间被占用的时间，
+/// 大文件的传输耗时以秒计，两者差距明显
+fn sustained_qps(concurrency: usize, avg_file_size: f64, aggregate_mb: f64) -> usize {
+    (concurrency as f64 / transfer_secs(concurrency, avg_file_size, aggregate_mb)) as usize
+}
+
+/// 指数分布下99分位与均值之比(ln 100)
+const P99_TAIL_FACTOR: f64 = 4.605_170_185_988_092;
+
+/// 估算指定并发下单次传输的p99延迟(ms)
+///
+/// 服务时间为平分带宽后的单次传输耗时；排队按M/M/1近似，响应时间均值为
+/// 服务时间/(1-ρ)，ρ为连接数占稳态容量 `capacity` 的比例，99分位为均值的ln(100)倍
+pub fn estimated_p99_ms(
+    args: &Args,
+    disk_config: &DiskConfig,
+    connections: usize,
+    capacity: usize,
+) -> f64 {
+    let utilization = connections as f64 / capacity.max(1) as f64;
+    if utilization >= 1.0 {
+        return f64::INFINITY;
+    }
+    let compression_ratio = args.assume_compression.unwrap_or(1.0);
+    let service_secs = transfer_secs(
+        connections,
+        args.avg_file_size,
+        aggregate_transfer_mb(args, disk_config, compression_ratio),
+    );
+    service_secs * 1000.0 / (1.0 - utilization) * P99_TAIL_FACTOR
+}
+
+/// 按 `--target-p99-ms` 求出p99不超过目标的最大连接数
+///
+/// p99随连接数单调增长(带宽被平分、排队加剧)，在0到稳态容量之间二分查找
+pub fn slo_check(args: &Args, disk_config: &DiskConfig, capacity: usize) -> Option<SloCheck> {
+    let target_p99_ms = args.target_p99_ms?;
+    let (mut low, mut high) = (0, capacity);
+    while low < high {
+        let mid = (low + high).div_ceil(2);
+        if estimated_p99_ms(args, disk_config, mid, capacity) <= target_p99_ms {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    Some(SloCheck {
+        target_p99_ms,
+        expected_p99_ms: estimated_p99_ms(args, disk_config, args.expected_connections, capacity),
+        max_connections: low,
+    })
+}
+
+/// 计算各资源维度的并发上限
+///
+/// `compression_ratio` 小于1.0时表示响应在传输前压缩: 网络只需传输压缩后的字节，
+/// 但每MB响应额外消耗压缩所需的CPU时间；上传的字节不压缩
+///
+/// `max_connections` 始终是同时在线的连接数；启用keep-alive时每个连接
+/// 依次处理 `requests_per_connection` 个请求，QPS按此倍数放大
+fn resource_limits(
     avg_file_size: f64,
     args: &Args,
     disk_config: &DiskConfig,
     mem_connections: usize,
-) -> ScenarioAnalysis {
-    // 计算各资源限制
-    // 考虑TCP/IP协议开销(约3%)和JVM Native内存限制
-    let network_conn = ((args.net_gbps * 125.0 * 0.97) / (avg_file_size * 1.05)) as usize;
-    // 考虑文件系统开销和JVM IO等待
-    let disk_conn = ((disk_config.read_speed * 0.75) / (avg_file_size * 1.1)) as usize;
-    // 考虑GC暂停时间影响(约15%损耗)
-    let cpu_conn = (args.cpu_cores as f64 * (850.0 / avg_file_size.max(1.0))) as usize;
-    
-    let mut resources = vec![
+    compression_ratio: f64,
+) -> Vec<ResourceLimit> {
+    // 考虑TCP/IP协议开销(约3%)和JVM Native内存限制，按传输方向取出口或入口带宽
+    let network_conn = ((directional_net_gbps(args) * 125.0 * 0.97)
+        / (avg_file_size * wire_bytes_ratio(args, compression_ratio) * 1.05))
+        as usize;
+    // 考虑文件系统开销、JVM IO等待和写放大
+    let disk_conn = (disk_transfer_mb(args, disk_config) / avg_file_size) as usize;
+    // 与推荐吞吐量共用同一CPU处理能力模型
+    let cpu_conn = (cpu_transfer_mb(args, compression_ratio) / avg_file_size.max(1.0)) as usize;
+
+    let reuse = args.requests_per_connection.max(1) as usize;
+    let aggregate_mb = aggregate_transfer_mb(args, disk_config, compression_ratio);
+    let sustained = |connections| Some(sustained_qps(connections, avg_file_size, aggregate_mb));
+
+    vec![
         ResourceLimit {
             name: "网络带宽".to_string(),
             limiting_factor: false,
             max_connections: network_conn,
-            qps: Some(network_conn),
+            qps: Some(network_conn * reuse),
+            sustained_qps: sustained(network_conn),
+            detail: None,
         },
         ResourceLimit {
             name: "磁盘IO".to_string(),
             limiting_factor: false,
             max_connections: disk_conn,
-            qps: Some(disk_conn),
+            qps: Some(disk_conn * reuse),
+            sustained_qps: sustained(disk_conn),
+            detail: None,
         },
         ResourceLimit {
             name: "直接内存".to_string(),
             limiting_factor: false,
             max_connections: mem_connections,
             qps: None,
+            sustained_qps: None,
+            detail: Some(DirectMemBreakdown::for_file_size(avg_file_size)),
         },
         ResourceLimit {
             name: "CPU线程".to_string(),
             limiting_factor: false,
             max_connections: cpu_conn,
-            qps: Some(cpu_conn * (1000 / avg_file_size.max(1.0) as usize)),
+            qps: Some(cpu_conn * (1000 / avg_file_size.max(1.0) as usize) * reuse),
+            sustained_qps: sustained(cpu_conn),
+            detail: None,
         },
-    ];
+    ]
+}
+
+fn analyze_scenario(
+    name: &str,
+    avg_file_size: f64,
+    args: &Args,
+    disk_config: &DiskConfig,
+    mem_connections: usize,
+) -> ScenarioAnalysis {
+    let compression_ratio = args.assume_compression.unwrap_or(1.0);
+    let mut resources = resource_limits(
+        avg_file_size,
+        args,
+        disk_config,
+        mem_connections,
+        compression_ratio,
+    );
 
     // 确定限制因素
-    let final_cap = resources.iter()
+    let mut final_cap = resources
+        .iter()
         .filter(|r| r.qps.is_some())
         .min_by_key(|r| r.max_connections)
         .cloned()
         .unwrap();
 
+    // 持续吞吐量不能超过系统极限评估的推荐吞吐量(两者共用同一吞吐量模型)
+    let (ceiling_mb, ceiling_limit) = throughput_model(args).ceiling();
+    let scenario_mb = final_cap.sustained_qps.unwrap_or(0) as f64 * avg_file_size;
+    let throughput_mb = scenario_mb.min(ceiling_mb);
+    let throughput_capped = scenario_mb > ceiling_mb;
+    if throughput_capped {
+        final_cap.sustained_qps = Some((ceiling_mb / avg_file_size) as usize);
+    }
+
     // 标记限制因素
     for r in &mut resources {
         r.limiting_factor = r.max_connections == final_cap.max_connections;
@@ -204,6 +464,85 @@ fn analyze_scenario(
             final_cap.qps.unwrap_or(0)
         ));
     }
+    if throughput_capped {
+        key_findings.push(format!(
+            "持续吞吐量受{ceiling_limit}限制为{ceiling_mb:.1}MB/s(即推荐吞吐量)，\
+             持续QPS已按此折算(未限制时为{scenario_mb:.1}MB/s)"
+        ));
+    }
+    if compression_ratio < 1.0 {
+        let uncompressed = resource_limits(avg_file_size, args, disk_config, mem_connections, 1.0);
+        let uncompressed_limit = uncompressed
+            .iter()
+            .filter(|r| r.qps.is_some())
+            .min_by_key(|r| r.max_connections);
+        if let Some(before) = uncompressed_limit
+            && before.name == "网络带宽"
+            && final_cap.name == "CPU线程"
+        {
+            key_findings.push(format!(
+                "启用压缩(输出为原始大小的{:.0}%)后瓶颈由网络带宽转移到CPU线程",
+                compression_ratio * 100.0
+            ));
+        }
+    }
+    if args.requests_per_connection > 1 {
+        key_findings.push(format!(
+            "keep-alive复用: 每个连接处理{}个请求，QPS按连接复用放大，并发连接数不变",
+            args.requests_per_connection
+        ));
+    }
+    let efficiency = cpu_efficiency(args);
+    if args.cpu_arch == "aarch64" || efficiency != 1.0 {
+        key_findings.push(format!(
+            "CPU架构{}{}: 每核心处理能力按{:.0}%计算，压缩指针/堆寻址与x86_64一致，JVM内存估算不变",
+            if args.cpu_arch.is_empty() {
+                "x86_64"
+            } else {
+                &args.cpu_arch
+            },
+            args.cpu_ghz
+                .map_or(String::new(), |ghz| format!(" @{ghz}GHz")),
+            efficiency * 100.0
+        ));
+    }
+    if let Some(rate) = checksum_mb_per_core(args) {
+        key_findings.push(format!(
+            "文件校验({}): 单核约{rate:.0}MB/s，每MB传输额外消耗{:.0}%的CPU处理时间",
+            args.checksum,
+            TRANSFER_MB_PER_CORE / rate * 100.0
+        ));
+    } else if matches!(args.checksum.as_str(), "crc32" | "sha256") {
+        key_findings.push(format!(
+            "文件校验({})已卸载到硬件，不计入CPU开销",
+            args.checksum
+        ));
+    }
+    let offload_factor = offload_cpu_factor(&args.offload);
+    if offload_factor < 1.0 {
+        key_findings.push(format!(
+            "假设{}已卸载到硬件: 每连接CPU开销按{:.0}%计算",
+            match args.offload.as_str() {
+                "tls" => "TLS加解密",
+                "checksum" => "校验和计算",
+                _ => "TLS加解密和校验和计算",
+            },
+            offload_factor * 100.0
+        ));
+    }
+    if let Some(cache) = page_cache_model(args) {
+        key_findings.push(format!(
+            "假设页缓存命中率{:.0}%{}: 磁盘只承担{:.0}%的IO",
+            cache.hit_ratio * 100.0,
+            cache
+                .working_set_gb
+                .map_or(String::new(), |working_set| format!(
+                    "(工作集{working_set:.1}GB, 可用页缓存{:.1}GB)",
+                    cache.cache_gb
+                )),
+            cache.disk_io_factor(&args.workload) * 100.0
+        ));
+    }
     key_findings.push(format!(
         "直接内存配置: {:.1}GB满足{}级并发需求",
         args.total_ram * 0.08, mem_connections
@@ -213,7 +552,359 @@ fn analyze_scenario(
         name: name.to_string(),
         avg_file_size,
         resources,
+        transfer_secs: transfer_secs(
+            final_cap.max_connections,
+            avg_file_size,
+            aggregate_transfer_mb(args, disk_config, compression_ratio),
+        ),
         final_capacity: final_cap,
+        throughput_mb,
         key_findings,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn test_compression_shifts_bottleneck_to_cpu() {
+        let disk = DiskConfig {
+            read_speed: 1500.0,
+            write_speed: 1200.0,
+            sustained_write_speed: 1200.0,
+            iops: 500_000.0,
+        };
+        let args = Args {
+            cpu_cores: 1,
+            net_gbps: 1.0,
+            workload: "download".to_string(),
+            ..Default::default()
+        };
+        let plain = analyze_scenario("小文件为主", 5.0, &args, &disk, 100_000);
+        assert_eq!(plain.final_capacity.name, "网络带宽");
+
+        let compressed = Args {
+            assume_compression: Some(0.4),
+            ..args.clone()
+        };
+        let scenario = analyze_scenario("小文件为主", 5.0, &compressed, &disk, 100_000);
+        assert_eq!(scenario.final_capacity.name, "CPU线程");
+        assert!(
+            scenario
+                .key_findings
+                .iter()
+                .any(|f| f.contains("瓶颈由网络带宽转移到CPU线程"))
+        );
+
+        // 上传的字节不经服务端压缩: 网络和CPU上限均不受影响
+        let limits = |assume_compression| {
+            let upload = Args {
+                workload: "upload".to_string(),
+                assume_compression,
+                ..args.clone()
+            };
+            resource_limits(
+                5.0,
+                &upload,
+                &disk,
+                100_000,
+                upload.assume_compression.unwrap_or(1.0),
+            )
+            .into_iter()
+            .map(|limit| limit.max_connections)
+            .collect::<Vec<_>>()
+        };
+        assert_eq!(limits(Some(0.4)), limits(None));
+    }
+
+    #[test]
+    fn test_write_amplification_reduces_disk_limit() {
+        let disk = DiskConfig {
+            read_speed: 1500.0,
+            write_speed: 1200.0,
+            sustained_write_speed: 1200.0,
+            iops: 500_000.0,
+        };
+        let disk_limit = |args: &Args| {
+            resource_limits(5.0, args, &disk, 100_000, 1.0)
+                .into_iter()
+                .find(|limit| limit.name == "磁盘IO")
+                .unwrap()
+                .max_connections
+        };
+        let plain = Args {
+            cpu_cores: 8,
+            net_gbps: 10.0,
+            workload: "mixed".to_string(),
+            ..Default::default()
+        };
+        let amplified = Args {
+            write_amplification: Some(3.0),
+            ..plain.clone()
+        };
+        assert_eq!(disk_limit(&plain), 204);
+        // 混合负载只有一半是写入，写放大只作用于这一半
+        assert_eq!(disk_limit(&amplified), 102);
+        let upload = Args {
+            workload: "upload".to_string(),
+            ..amplified.clone()
+        };
+        assert_eq!(disk_limit(&upload), 68);
+
+        // 纯下载只读不写，写放大不影响磁盘上限
+        let download = Args {
+            workload: "download".to_string(),
+            ..amplified
+        };
+        assert_eq!(disk_limit(&download), 204);
+    }
+
+    #[test]
+    fn test_direct_memory_breakdown_per_scenario() {
+        let disk = DiskConfig {
+            read_speed: 1500.0,
+            write_speed: 1200.0,
+            sustained_write_speed: 1200.0,
+            iops: 500_000.0,
+        };
+        let args = Args {
+            cpu_cores: 8,
+            net_gbps: 10.0,
+            ..Default::default()
+        };
+        let small = analyze_scenario("小文件为主", 5.0, &args, &disk, 1000);
+        let large = analyze_scenario("大文件为主", 50.0, &args, &disk, 1000);
+        let detail = |s: &ScenarioAnalysis| {
+            s.resources
+                .iter()
+                .find(|r| r.name == "直接内存")
+                .and_then(|r| r.detail)
+                .unwrap()
+        };
+        assert_eq!(detail(&small).read_kb, 128.0);
+        assert_eq!(detail(&large).read_kb, 512.0);
+        assert_eq!(detail(&large).write_kb, 768.0);
+        assert!(
+            small
+                .resources
+                .iter()
+                .filter(|r| r.detail.is_some())
+                .count()
+                == 1
+        );
+    }
+
+    #[test]
+    fn test_offload_raises_cpu_capacity() {
+        let disk = DiskConfig {
+            read_speed: 1500.0,
+            write_speed: 1200.0,
+            sustained_write_speed: 1200.0,
+            iops: 500_000.0,
+        };
+        let args = Args {
+            cpu_cores: 4,
+            net_gbps: 100.0,
+            offload: "none".to_string(),
+            ..Default::default()
+        };
+        let cpu_limit = |s: &ScenarioAnalysis| {
+            s.resources
+                .iter()
+                .find(|r| r.name == "CPU线程")
+                .unwrap()
+                .max_connections
+        };
+        let plain = analyze_scenario("小文件为主", 5.0, &args, &disk, 100_000);
+        let offloaded = analyze_scenario(
+            "小文件为主",
+            5.0,
+            &Args {
+                offload: "both".to_string(),
+                ..args
+            },
+            &disk,
+            100_000,
+        );
+        assert_eq!(cpu_limit(&plain), 680);
+        assert_eq!(cpu_limit(&offloaded), 1360);
+        assert!(
+            offloaded
+                .key_findings
+                .iter()
+                .any(|f| f.contains("已卸载到硬件"))
+        );
+        assert!(
+            !plain
+                .key_findings
+                .iter()
+                .any(|f| f.contains("已卸载到硬件"))
+        );
+    }
+
+    #[test]
+    fn test_bucket_contributions() {
+        let args = Args::try_parse_from(["sa", "--file-size-distribution", "1:0.5,10:0.3,100:0.2"])
+            .unwrap()
+            .resolve_file_size();
+        let buckets = bucket_contributions(&args);
+        assert_eq!(buckets.len(), 3);
+        // 小文件占一半连接，但带宽主要来自大文件
+        assert_eq!(buckets[0].connection_share, 0.5);
+        assert!(buckets[0].bandwidth_share < 0.05);
+        assert!(buckets[2].bandwidth_share > 0.8);
+        let direct: f64 = buckets.iter().map(|b| b.direct_share).sum();
+        assert!((direct - 1.0).abs() < 1e-9);
+
+        let plain = Args::try_parse_from(["sa"]).unwrap();
+        assert!(bucket_contributions(&plain).is_empty());
+    }
+
+    #[test]
+    fn test_keep_alive_scales_qps_not_connections() {
+        let disk = DiskConfig {
+            read_speed: 1500.0,
+            write_speed: 1200.0,
+            sustained_write_speed: 1200.0,
+            iops: 500_000.0,
+        };
+        let args = Args {
+            cpu_cores: 8,
+            net_gbps: 10.0,
+            requests_per_connection: 1,
+            ..Default::default()
+        };
+        let single = analyze_scenario("小文件为主", 5.0, &args, &disk, 100_000);
+        let reused = analyze_scenario(
+            "小文件为主",
+            5.0,
+            &Args {
+                requests_per_connection: 10,
+                ..args
+            },
+            &disk,
+            100_000,
+        );
+        assert_eq!(
+            reused.final_capacity.max_connections,
+            single.final_capacity.max_connections
+        );
+        assert_eq!(
+            reused.final_capacity.qps.unwrap(),
+            single.final_capacity.qps.unwrap() * 10
+        );
+    }
+
+    #[test]
+    fn test_sustained_qps_accounts_for_transfer_time() {
+        let disk = DiskConfig {
+            read_speed: 1500.0,
+            write_speed: 1200.0,
+            sustained_write_speed: 1200.0,
+            iops: 500_000.0,
+        };
+        let args = Args {
+            cpu_cores: 16,
+            net_gbps: 10.0,
+            ..Default::default()
+        };
+        let large = analyze_scenario("大文件为主", 500.0, &args, &disk, 100_000);
+        // 500MB按单连接12.5MB/s上限需要40秒
+        assert!(large.transfer_secs >= 40.0);
+        let sustained = large.final_capacity.sustained_qps.unwrap();
+        assert!(sustained <= large.final_capacity.max_connections / 40);
+
+        assert_eq!(transfer_secs(1000, 1.0, 1000.0), 1.0);
+        assert_eq!(sustained_qps(1000, 1.0, 1000.0), 1000);
+    }
+
+    #[test]
+    fn test_sha256_checksum_makes_cpu_the_bottleneck() {
+        let disk = DiskConfig {
+            read_speed: 1500.0,
+            write_speed: 1200.0,
+            sustained_write_speed: 1200.0,
+            iops: 500_000.0,
+        };
+        let args = Args {
+            cpu_cores: 1,
+            net_gbps: 5.0,
+            checksum: "none".to_string(),
+            ..Default::default()
+        };
+        let plain = analyze_scenario("小文件为主", 5.0, &args, &disk, 100_000);
+        assert_eq!(plain.final_capacity.name, "网络带宽");
+
+        let sha256 = Args {
+            checksum: "sha256".to_string(),
+            ..args.clone()
+        };
+        let hashed = analyze_scenario("小文件为主", 5.0, &sha256, &disk, 100_000);
+        assert_eq!(hashed.final_capacity.name, "CPU线程");
+        assert!(
+            hashed
+                .key_findings
+                .iter()
+                .any(|f| f.starts_with("文件校验(sha256)"))
+        );
+
+        let offloaded = Args {
+            offload: "checksum".to_string(),
+            ..sha256
+        };
+        assert_eq!(checksum_mb_per_core(&offloaded), None);
+    }
+
+    #[test]
+    fn test_scenario_throughput_agrees_with_safety_ceiling() {
+        use clap::Parser;
+
+        // CPU处理能力与性能分析共用同一模型: 4核、10Gbps、NVMe时受网络带宽限制
+        let args = Args::parse_from(["sa", "-c", "4", "-w", "10", "-d", "nvme"]);
+        let result = crate::analysis::analyze(&args).unwrap();
+        let limits = &result.safety.theoretical_limits;
+        assert_eq!(limits.throughput_limit, "网络带宽");
+
+        // 单核配合100Gbps网络时受CPU限制，各场景的持续吞吐量不超过推荐吞吐量
+        let args = Args::parse_from(["sa", "-c", "1", "-w", "100", "-d", "nvme"]);
+        let result = crate::analysis::analyze(&args).unwrap();
+        let limits = &result.safety.theoretical_limits;
+        assert_eq!(limits.throughput_limit, "CPU");
+        for scenario in &result.performance.scenarios {
+            assert!(scenario.throughput_mb <= limits.max_throughput + 1e-9);
+            let sustained = scenario.final_capacity.sustained_qps.unwrap() as f64;
+            assert!(sustained * scenario.avg_file_size <= limits.max_throughput + 1e-9);
+        }
+
+        // CPU架构和主频同样作用于推荐吞吐量
+        let argv = "sa -c 1 -w 100 -d nvme --cpu-arch aarch64".split(' ');
+        let arm = crate::analysis::analyze(&Args::parse_from(argv)).unwrap();
+        let arm_limits = &arm.safety.theoretical_limits;
+        assert!((arm_limits.max_throughput - limits.max_throughput * 1.15).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_download_throughput_ignores_write_amplification() {
+        use clap::Parser;
+
+        let argv = ["sa", "-w", "100", "-d", "sata_ssd", "--workload=download"];
+        let plain = crate::analysis::analyze(&Args::parse_from(argv)).unwrap();
+        let amplified = crate::analysis::analyze(&Args::parse_from(
+            argv.iter().chain(&["--write-amplification", "4"]),
+        ))
+        .unwrap();
+        assert_eq!(
+            plain.safety.theoretical_limits.max_throughput,
+            amplified.safety.theoretical_limits.max_throughput
+        );
+        let sustained = |result: &crate::analysis::AnalysisResult| {
+            let scenarios = result.performance.scenarios.iter();
+            scenarios
+                .map(|s| s.final_capacity.sustained_qps)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(sustained(&plain), sustained(&amplified));
+    }
+}
@@ -1,5 +1,8 @@
-use crate::analysis::calculate_metaspace;
-use crate::args::Args;
+use crate::analysis::jvm::Collector;
+use crate::analysis::native;
+use crate::analysis::{calculate_metaspace, calculate_metaspace_detailed};
+use crate::args::{AnalysisError, Args};
+use crate::config;
 use colored::Colorize;
 
 /// 安全性分析结果
@@ -8,382 +11,2910 @@ use colored::Colorize;
 /// - `heap_safety`: 堆内存安全系数 (0-1), 1表示完全安全
 /// - `direct_mem_safety`: 直接内存安全系数 (0-1)
 /// - `risk_level`: 整体风险等级描述
+/// - `resource_risks`: 按资源利用率分别评估的风险等级，定位具体需要扩容的资源
 /// - `scenarios`: 模拟的不同负载场景
 /// - `recommendations`: 优化建议列表
+/// - `warnings`: 需要立即处理的告警，与常规建议分开输出
 pub struct SafetyAnalysis {
-    pub heap_safety: f64,                      // 堆内存安全系数 (0-1)
-    pub direct_mem_safety: f64,                // 直接内存安全系数 (0-1)
-    pub risk_level: String,                    // 整体风险等级
-    pub scenarios: Vec<Scenario>,              // 模拟场景
-    pub recommendations: Vec<String>,          // 优化建议
-    pub theoretical_limits: TheoreticalLimits, // 理论极限评估
+    pub heap_safety: f64,                            // 堆内存安全系数 (0-1)
+    pub direct_mem_safety: f64,                      // 直接内存安全系数 (0-1)
+    pub risk_level: String,                          // 整体风险等级
+    pub resource_risks: Vec<(&'static str, String)>, // (资源, 风险等级)
+    pub scenarios: Vec<Scenario>,                    // 模拟场景
+    pub recommendations: Vec<Recommendation>,        // 优化建议
+    pub warnings: Vec<Warning>,                      // 告警
+    pub theoretical_limits: TheoreticalLimits,       // 理论极限评估
+    pub memory_budget: MemoryBudget,                 // 整机内存预算
+    pub meets_target: bool,                          // 理论最大连接数是否满足预期连接数
+    pub shortfall_pct: f64,                          // 达到预期连接数还需增加的资源比例(%)
 }
 
-/// 理论极限评估(基于6-12个月稳定运行)
-pub struct TheoreticalLimits {
-    pub max_connections: usize,     // 在稳定运行条件下的最大连接数
-    pub max_throughput: f64,        // 可持续吞吐量(MB/s)
-    pub estimated_uptime: String,   // 预估稳定运行时长分类
-    pub limiting_factor: String,    // 主要瓶颈资源
-    pub burst_capacity: usize,      // 突发流量承载能力
-    pub resource_breakdown: String, // 各资源利用率分析
+/// 优化建议和告警的严重程度，按声明顺序由低到高
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
 }
 
-pub struct Scenario {
-    pub name: String,
-    pub connections: usize,
-    pub file_size: f64,
-    pub heap_usage: f64,       // GB
-    pub direct_mem_usage: f64, // GB
-    pub status: String,        // 安全/警告/危险
-}
-
-/// 动态计算每个连接的直接内存需求
-fn calculate_direct_mem_per_conn(file_size: f64) -> (f64, f64) {
-    // 读缓冲区大小 (动态调整)
-    let read_buffer = if file_size <= 10.0 {
-        128.0 // 128KB for small files
-    } else if file_size <= 100.0 {
-        512.0 // 512KB for medium files
-    } else {
-        // For large files, use 1MB buffer but allow chunked processing
-        // with memory mapping optimization
-        (1024.0_f64).min(file_size * 0.01) // 1MB or 1% of file size, whichever is smaller
-    };
+/// 优化建议
+///
+/// `rationale` 记录触发建议的条件和所依据的数值，仅在 `--explain` 时输出到控制台
+#[derive(Debug, Clone)]
+pub struct Recommendation {
+    pub summary: String,             // 建议内容
+    pub rationale: String,           // 触发条件及依据
+    pub severity: Severity,          // 严重程度
+    pub topic: Option<&'static str>, // 合并主题，主题相同的建议合并为一条
+}
 
-    // 写缓冲区大小 (通常比读缓冲区大)
-    let write_buffer = read_buffer * 1.5;
+impl Recommendation {
+    pub fn new(
+        severity: Severity,
+        summary: impl Into<String>,
+        rationale: impl Into<String>,
+    ) -> Recommendation {
+        Recommendation {
+            summary: summary.into(),
+            rationale: rationale.into(),
+            severity,
+            topic: None,
+        }
+    }
 
-    // 额外开销 (SSL/TLS, headers etc)
-    let overhead = 100.0; // 100KB fixed overhead
+    /// 指定合并主题，摘要以 `主题:` 开头时合并后只保留一次主题
+    pub fn with_topic(self, topic: &'static str) -> Recommendation {
+        Recommendation {
+            topic: Some(topic),
+            ..self
+        }
+    }
 
-    (
-        read_buffer / 1024.0 / 1024.0,               // convert to GB
-        (write_buffer + overhead) / 1024.0 / 1024.0, // convert to GB
-    )
+    /// 摘要去掉主题前缀后的细节
+    fn detail(&self) -> &str {
+        self.topic
+            .and_then(|topic| self.summary.strip_prefix(topic))
+            .map_or(&self.summary, |detail| {
+                detail.trim_start_matches([':', '：', ' '])
+            })
+    }
 }
 
-pub fn calculate_safety(args: &Args, direct_mem_gb: f64, heap_mem_gb: f64) -> SafetyAnalysis {
-    const HEAP_PER_CONN: f64 = 384.0 / 1024.0 / 1024.0; // 384KB -> GB (含对象开销)
+/// 去重、合并并按严重程度排序优化建议
+///
+/// 摘要完全相同的建议只保留一条；指定了相同主题的建议合并为一条，
+/// 细节以 `；` 连接，严重程度取最高者。排序稳定，同级建议保持生成顺序
+pub fn prioritize_recommendations(recommendations: Vec<Recommendation>) -> Vec<Recommendation> {
+    let mut merged: Vec<Recommendation> = Vec::new();
+    for rec in recommendations {
+        let Some(existing) = merged.iter_mut().find(|existing| {
+            existing.summary == rec.summary || (rec.topic.is_some() && existing.topic == rec.topic)
+        }) else {
+            merged.push(rec);
+            continue;
+        };
+        existing.severity = existing.severity.max(rec.severity);
+        if existing.summary != rec.summary {
+            existing.summary = format!("{}；{}", existing.summary, rec.detail());
+        }
+        if !existing
+            .rationale
+            .split('；')
+            .any(|part| part == rec.rationale)
+        {
+            existing.rationale = format!("{}；{}", existing.rationale, rec.rationale);
+        }
+    }
+    merged.sort_by_key(|rec| std::cmp::Reverse(rec.severity));
+    merged
+}
 
-    // 计算正常场景内存使用 (动态调整缓冲区大小)
-    let (read_buffer_per_conn, write_buffer_per_conn) =
-        calculate_direct_mem_per_conn(args.avg_file_size);
-    let normal_direct_usage =
-        args.expected_connections as f64 * (read_buffer_per_conn + write_buffer_per_conn);
+/// 告警: OOM、元空间截断、内存超额等需要立即处理的问题
+///
+/// 与 `Recommendation` 分开存放，便于按严重程度过滤，文本中不含颜色或标记符号
+#[derive(Debug, Clone)]
+pub struct Warning {
+    pub summary: String,    // 告警内容
+    pub rationale: String,  // 触发条件及依据
+    pub severity: Severity, // 严重程度
+}
 
-    // 如果是大文件(>100MB)且使用内存映射，可以减少直接内存需求
-    let mem_map_reduction = if args.avg_file_size > 100.0 && args.enable_memory_mapping {
-        0.5 // 内存映射可减少50%直接内存需求
-    } else {
-        1.0
-    };
-    let normal_direct_usage = normal_direct_usage * mem_map_reduction;
-    let normal_heap_usage = args.expected_connections as f64 * HEAP_PER_CONN;
+impl Warning {
+    pub fn new(
+        severity: Severity,
+        summary: impl Into<String>,
+        rationale: impl Into<String>,
+    ) -> Warning {
+        Warning {
+            summary: summary.into(),
+            rationale: rationale.into(),
+            severity,
+        }
+    }
+}
 
-    // 计算突发场景内存使用
-    let burst_connections = (args.expected_connections as f64 * args.burst_factor) as usize;
-    let (burst_read, burst_write) = calculate_direct_mem_per_conn(args.avg_file_size);
-    let burst_direct_usage = burst_connections as f64 * (burst_read + burst_write);
-    let burst_heap_usage = burst_connections as f64 * HEAP_PER_CONN;
+impl SafetyAnalysis {
+    /// 按严重程度从高到低排列的告警，同级保持生成顺序
+    pub fn sorted_warnings(&self) -> Vec<&Warning> {
+        let mut warnings: Vec<&Warning> = self.warnings.iter().collect();
+        warnings.sort_by_key(|w| std::cmp::Reverse(w.severity));
+        warnings
+    }
+}
 
-    // 计算安全系数 (0-1)，保留15%给JVM Native内存
-    const JVM_NATIVE_RATIO: f64 = 0.15;
-    let available_heap = heap_mem_gb * (1.0 - JVM_NATIVE_RATIO);
-    let available_direct = direct_mem_gb * (1.0 - JVM_NATIVE_RATIO);
+/// 整机内存预算(突发流量下, GB)
+///
+/// JVM之外的内存由Native预留、原生内存分配器开销和内核socket缓冲区组成，
+/// socket缓冲区随连接数线性增长，高并发时可达数GB
+pub struct MemoryBudget {
+    pub heap_gb: f64,            // 堆内存
+    pub direct_gb: f64,          // 直接内存
+    pub native_reserved_gb: f64, // JVM Native及系统预留
+    pub allocator_gb: f64,       // 原生内存分配器(arena/线程缓存)开销
+    pub socket_buffer_gb: f64,   // 内核socket收发缓冲区
+    pub total_gb: f64,           // 合计
+    pub over_committed: bool,    // 合计是否超过服务器内存
+}
 
-    // 使用更保守的安全阈值(0.7)
-    let heap_safety = 1.0 - (normal_heap_usage / (available_heap * 0.7)).min(1.0);
-    let direct_mem_safety = 1.0 - (normal_direct_usage / (available_direct * 0.7)).min(1.0);
+/// 理论极限评估(基于6-12个月稳定运行)
+pub struct TheoreticalLimits {
+    pub max_connections: usize,             // 在稳定运行条件下的最大连接数
+    pub steady_state_max: usize,            // 不受突发需求截断的稳态资源极限
+    pub steady_state_limit: Bottleneck,     // 决定稳态资源极限的资源
+    pub burst_bound: bool,                  // 最大连接数是否仅受突发需求本身限制
+    pub max_throughput: f64,                // 可持续吞吐量(MB/s)
+    pub throughput_limit: String,           // 限制吞吐量的资源(CPU/网络带宽/磁盘IO)
+    pub estimated_uptime: String,           // 预估稳定运行时长分类
+    pub limiting_factor: String,            // 主要瓶颈资源
+    pub max_accept_rate: f64,               // 最大建连速率(连接/秒)
+    pub burst_capacity: usize, // 短时突发承载能力(不计稳定性系数，故大于max_connections)
+    pub required_fds: usize,   // 突发负载下所需的文件描述符数(即推荐的LimitNOFILE)
+    pub disk_queue: Option<DiskQueueModel>, // 指定--io-threads时按队列深度折算的磁盘IOPS
+    pub page_cache: Option<PageCacheModel>, // 指定工作集或命中率时页缓存对读取的分担
+    pub utilization: ResourceUtilization, // 各资源利用率分析
+}
 
-    // 改进的风险等级评估
-    let risk_level = match (heap_safety, direct_mem_safety) {
-        (h, d) if h > 0.4 && d > 0.4 => "低风险".to_string(),
-        (h, d) if h > 0.2 || d > 0.2 => "中风险".to_string(),
-        _ => "高风险".to_string(),
-    };
+/// 决定稳态资源极限的资源维度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bottleneck {
+    DirectMemory,
+    Heap,
+    Metaspace,
+    Cpu,
+    Network,
+    Disk,
+    StorageLatency,
+}
 
-    // 创建模拟场景
-    let mut scenarios = Vec::new();
+impl Bottleneck {
+    pub fn label(self) -> &'static str {
+        match self {
+            Bottleneck::DirectMemory => "直接内存",
+            Bottleneck::Heap => "堆内存",
+            Bottleneck::Metaspace => "元空间",
+            Bottleneck::Cpu => "CPU资源",
+            Bottleneck::Network => "网络带宽",
+            Bottleneck::Disk => "磁盘IO",
+            Bottleneck::StorageLatency => "网络存储延迟",
+        }
+    }
+}
 
-    // 场景1: 长期运行(24小时)
-    scenarios.push(Scenario {
-        name: "长期运行(24h)".to_string(),
-        connections: args.expected_connections,
-        file_size: args.avg_file_size,
-        heap_usage: normal_heap_usage * 1.5, // 假设长期运行堆增长50%
-        direct_mem_usage: normal_direct_usage * 1.2, // 直接内存增长20%
-        status: status_label(
-            normal_heap_usage * 1.5,
-            heap_mem_gb,
-            normal_direct_usage * 1.2,
-            direct_mem_gb,
-        ),
-    });
+/// 预期负载下各资源的利用率(百分比, 0-100)
+pub struct ResourceUtilization {
+    pub heap_pct: f64,      // 堆内存(含数据库连接池)
+    pub db_pool_pct: f64,   // 数据库连接池占用的堆内存
+    pub direct_pct: f64,    // 直接内存
+    pub metaspace_pct: f64, // 元空间
+    pub cpu_pct: f64,       // CPU
+    pub network_pct: f64,   // 网络带宽
+    pub disk_pct: f64,      // 磁盘IO
+}
 
-    // 场景2: 正常负载
-    scenarios.push(Scenario {
-        name: "正常负载".to_string(),
-        connections: args.expected_connections,
-        file_size: args.avg_file_size,
-        heap_usage: normal_heap_usage,
-        direct_mem_usage: normal_direct_usage,
-        status: status_label(
-            normal_heap_usage,
-            heap_mem_gb,
-            normal_direct_usage,
-            direct_mem_gb,
-        ),
-    });
+impl ResourceUtilization {
+    /// 各资源维度的 (名称, 利用率%)，数据库连接池已计入堆内存，不单独列出
+    pub fn entries(&self) -> [(&'static str, f64); 6] {
+        [
+            ("堆内存", self.heap_pct),
+            ("直接内存", self.direct_pct),
+            ("元空间", self.metaspace_pct),
+            ("CPU", self.cpu_pct),
+            ("网络", self.network_pct),
+            ("磁盘IO", self.disk_pct),
+        ]
+    }
 
-    // 场景2: 突发流量
-    scenarios.push(Scenario {
-        name: format!("突发流量 ({}x)", args.burst_factor),
-        connections: burst_connections,
-        file_size: args.avg_file_size,
-        heap_usage: burst_heap_usage,
-        direct_mem_usage: burst_direct_usage,
-        status: status_label(
-            burst_heap_usage,
-            heap_mem_gb,
-            burst_direct_usage,
-            direct_mem_gb,
-        ),
-    });
+    /// 按利用率评估各资源维度的风险等级
+    pub fn risk_levels(&self, policy: &SafetyPolicy) -> Vec<(&'static str, String)> {
+        self.entries()
+            .into_iter()
+            .map(|(label, pct)| (label, policy.risk_for_utilization(pct).to_string()))
+            .collect()
+    }
 
-    // 场景3: 大文件处理
-    scenarios.push(Scenario {
-        name: "大文件处理".to_string(),
-        connections: (args.expected_connections as f64 * 0.5) as usize,
-        file_size: args.avg_file_size * 5.0,
-        heap_usage: normal_heap_usage * 0.5,
-        direct_mem_usage: normal_direct_usage * 0.5,
-        status: status_label(
-            normal_heap_usage * 0.5,
-            heap_mem_gb,
-            normal_direct_usage * 0.5,
-            direct_mem_gb,
-        ),
-    });
+    /// 预期负载下余量最小(利用率最高)的资源
+    pub fn tightest(&self) -> (&'static str, f64) {
+        self.entries()
+            .into_iter()
+            .fold(("堆内存", f64::MIN), |max, entry| {
+                if entry.1 > max.1 { entry } else { max }
+            })
+    }
+}
 
-    // 场景4: 小文件高并发
-    scenarios.push(Scenario {
-        name: "小文件高并发".to_string(),
-        connections: args.expected_connections * 3,
-        file_size: args.avg_file_size / 10.0,
-        heap_usage: normal_heap_usage * 1.5,
-        direct_mem_usage: normal_direct_usage * 1.5,
-        status: status_label(
-            normal_heap_usage * 1.5,
-            heap_mem_gb,
-            normal_direct_usage * 1.5,
-            direct_mem_gb,
-        ),
-    });
+/// 安全阈值策略
+///
+/// 集中定义安全系数、风险等级和场景状态使用的阈值，
+/// 默认值即为模型一直使用的取值
+#[derive(Debug, Clone, PartialEq)]
+pub struct SafetyPolicy {
+    pub usage_threshold: f64,     // 计算安全系数时可用内存的使用上限
+    pub low_risk_above: f64,      // 堆和直接内存安全系数均高于此值为低风险
+    pub medium_risk_above: f64,   // 任一安全系数高于此值为中风险
+    pub effective_max_ratio: f64, // 场景状态评估时的有效内存上限比例(扣除JVM开销和余量)
+    pub warn_at: f64,             // 占有效上限比例达到此值进入警告
+    pub danger_at: f64,           // 占有效上限比例达到此值进入危险
+}
 
-    // 生成优化建议
-    let mut recommendations = Vec::new();
+impl Default for SafetyPolicy {
+    fn default() -> Self {
+        SafetyPolicy {
+            usage_threshold: 0.7,
+            low_risk_above: 0.4,
+            medium_risk_above: 0.2,
+            effective_max_ratio: 0.7,
+            warn_at: 0.6,
+            danger_at: 0.8,
+        }
+    }
+}
 
-    if direct_mem_safety < 0.3 {
-        recommendations.push(format!(
-            "- 增加直接内存: {:.1}GB -> {:.1}GB",
-            direct_mem_gb,
-            direct_mem_gb * 1.3
-        ));
+impl SafetyPolicy {
+    /// 由命令行参数构建策略，未指定的阈值使用默认值
+    pub fn from_args(args: &Args) -> SafetyPolicy {
+        let default = SafetyPolicy::default();
+        SafetyPolicy {
+            warn_at: args.warn_at.unwrap_or(default.warn_at),
+            danger_at: args.danger_at.unwrap_or(default.danger_at),
+            ..default
+        }
     }
 
-    if heap_safety < 0.3 {
-        recommendations.push(format!(
-            "- 增加堆内存: {:.1}GB -> {:.1}GB",
-            heap_mem_gb,
-            heap_mem_gb * 1.2
-        ));
+    /// 场景进入警告和危险状态时内存占用的百分比(%)，即阈值乘以有效内存上限比例
+    pub fn status_bounds_pct(&self) -> (f64, f64) {
+        (
+            self.warn_at * self.effective_max_ratio * 100.0,
+            self.danger_at * self.effective_max_ratio * 100.0,
+        )
     }
 
-    if args.enable_memory_guard {
-        recommendations.push("- 启用内存防护系统: 当内存使用>85%时自动限流".to_string());
+    /// 单个资源的风险等级: 余量(1 - 利用率)与整体风险等级使用相同阈值
+    pub fn risk_for_utilization(&self, pct: f64) -> &'static str {
+        let headroom = 1.0 - pct / 100.0;
+        if headroom > self.low_risk_above {
+            "低风险"
+        } else if headroom > self.medium_risk_above {
+            "中风险"
+        } else {
+            "高风险"
+        }
     }
 
-    if args.avg_file_size > 50.0 {
-        recommendations.push("- 优化大文件处理: 使用分块上传和内存映射文件".to_string());
+    /// 检查阈值之间的大小关系
+    pub fn validate(&self) -> Result<(), AnalysisError> {
+        if self.warn_at >= self.danger_at {
+            return Err(AnalysisError::InvalidSafetyPolicy {
+                warn_at: self.warn_at,
+                danger_at: self.danger_at,
+            });
+        }
+        Ok(())
     }
+}
 
-    // 增强长期运行评估和建议
-    let heap_growth_rate = normal_heap_usage * 0.05; // 假设每小时堆增长5%
-    let oom_hours = ((heap_mem_gb * 0.9 - normal_heap_usage) / heap_growth_rate).max(0.0);
+pub struct Scenario {
+    pub name: String,
+    pub connections: usize,
+    pub file_size: f64,
+    pub heap_usage: f64,        // GB
+    pub direct_mem_usage: f64,  // GB
+    pub status: ScenarioStatus, // 安全/警告/危险
+}
 
-    recommendations.push(format!(
-        "- 内存泄漏评估: 当前配置可能在{oom_hours:.1}小时后发生OOM"
-    ));
-    recommendations.push("- 添加内存监控: 实时监控堆/直接内存的增长率".to_string());
-    recommendations.push("- 启用GC日志分析: 建议使用Prometheus+Grafana监控".to_string());
-    recommendations.push("- 启用堆转储: 设置-XX:+HeapDumpOnOutOfMemoryError".to_string());
+/// 模拟场景的内存状态
+///
+/// 按声明顺序由好到坏排序，合并多项评估时取最差者
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ScenarioStatus {
+    Safe,
+    Warning,
+    Danger,
+}
 
-    if oom_hours < 24.0 {
-        recommendations.push("❗ 紧急: 内存泄漏风险高，需要立即优化".red().to_string());
+impl ScenarioStatus {
+    pub const ALL: [ScenarioStatus; 3] = [
+        ScenarioStatus::Safe,
+        ScenarioStatus::Warning,
+        ScenarioStatus::Danger,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ScenarioStatus::Safe => "安全",
+            ScenarioStatus::Warning => "警告",
+            ScenarioStatus::Danger => "危险",
+        }
     }
 
-    // 计算理论极限
-    let theoretical_limits = calculate_theoretical_limits(
-        args,
-        direct_mem_gb,
-        heap_mem_gb,
-        normal_direct_usage,
-        normal_heap_usage,
-    );
+    /// 控制台使用的状态图标
+    pub fn icon(self) -> &'static str {
+        match self {
+            ScenarioStatus::Safe => "✅",
+            ScenarioStatus::Warning => "⚠️",
+            ScenarioStatus::Danger => "🔥",
+        }
+    }
 
-    SafetyAnalysis {
-        heap_safety,
-        direct_mem_safety,
-        risk_level,
-        scenarios,
-        recommendations,
-        theoretical_limits,
+    /// markdown报告使用的状态图标(部分渲染器不支持彩色emoji)
+    pub fn markdown_icon(self) -> &'static str {
+        match self {
+            ScenarioStatus::Safe => "✔️",
+            ScenarioStatus::Warning => "⚠",
+            ScenarioStatus::Danger => "✖️",
+        }
+    }
+
+    /// 按状态着色
+    pub fn paint(self, text: &str) -> String {
+        match self {
+            ScenarioStatus::Safe => text.green().to_string(),
+            ScenarioStatus::Warning => text.yellow().to_string(),
+            ScenarioStatus::Danger => text.red().to_string(),
+        }
     }
 }
 
-/// 计算理论极限值(基于JVM推荐配置和6-12个月稳定运行目标)
-fn calculate_theoretical_limits(
-    args: &Args,
-    direct_mem_gb: f64,
-    heap_mem_gb: f64,
-    normal_direct_usage: f64,
-    normal_heap_usage: f64,
-) -> TheoreticalLimits {
-    // 基于JVM推荐配置的资源消耗模型
-    const HEAP_PER_CONN: f64 = 384.0 / 1024.0 / 1024.0; // 384KB/连接(含对象开销)
-    const METASPACE_PER_CONN: f64 = 64.0 / 1024.0; // 64KB/连接
-    const CPU_PER_CONN: f64 = 0.0005; // 每个连接占用的CPU资源(核)
-    const NET_PER_CONN: f64 = 0.2; // 每个连接平均带宽(Mbps)
-    const DISK_IO_PER_CONN: f64 = 0.15; // 每个连接IOPS需求
+impl std::fmt::Display for ScenarioStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.icon(), self.label())
+    }
+}
 
-    // 长期稳定性系数
-    const STABILITY_FACTOR: f64 = 0.6; // 只使用60%资源保证长期稳定
-    const SAFE_MEM_USAGE: f64 = 0.7; // 更保守的内存使用阈值
+/// 每个传输连接占用的CPU资源(核)，即基准核心每核约2000个连接
+const CPU_PER_CONN: f64 = 0.0005;
+/// 每个活跃连接每秒触发的线程唤醒(IO就绪、定时器等)次数
+const WAKEUPS_PER_CONN_PER_SEC: f64 = 20.0;
+/// 单次上下文切换的CPU开销(微秒)，含缓存和TLB失效
+const CONTEXT_SWITCH_US: f64 = 5.0;
 
-    // 1. 计算各维度极限(考虑突发流量)
-    let burst_connections = (args.expected_connections as f64 * args.burst_factor) as usize;
+/// 模型假设的每核心连接数，`--connections-per-core` 可覆盖作为护栏阈值
+pub fn connections_per_core_ceiling(args: &Args) -> f64 {
+    args.connections_per_core
+        .unwrap_or_else(|| cpu_efficiency(args) / CPU_PER_CONN)
+}
 
-    // 动态计算每个连接的直接内存需求
-    let (read_buffer, write_buffer) = calculate_direct_mem_per_conn(args.avg_file_size);
-    let direct_mem_per_conn = read_buffer + write_buffer;
+/// 指定每核心连接数下上下文切换占用单核CPU的比例
+pub fn context_switch_cpu_ratio(connections_per_core: f64) -> f64 {
+    connections_per_core * WAKEUPS_PER_CONN_PER_SEC * CONTEXT_SWITCH_US / 1_000_000.0
+}
 
-    // 内存限制(基于动态计算)
-    let max_by_direct = if args.enable_memory_mapping && args.avg_file_size > 100.0 {
-        // 内存映射优化可支持更多连接
-        ((direct_mem_gb * SAFE_MEM_USAGE) / (direct_mem_per_conn * 0.7) * STABILITY_FACTOR) as usize
-    } else {
-        ((direct_mem_gb * SAFE_MEM_USAGE) / direct_mem_per_conn * STABILITY_FACTOR) as usize
-    };
-    let max_by_heap = ((heap_mem_gb * SAFE_MEM_USAGE) / HEAP_PER_CONN * STABILITY_FACTOR) as usize;
+/// 数据库连接池每个连接占用的堆内存(结果集、语句缓存等)(GB)
+const DB_POOL_HEAP_PER_CONN: f64 = 2.0 / 1024.0;
+/// 数据库连接池每个连接的线程占用的CPU资源(核)
+const DB_POOL_CPU_PER_CONN: f64 = 0.02;
 
-    // 元空间限制(基于动态计算结果)
-    let metaspace_size_mb = calculate_metaspace(args) as f64;
-    let max_by_metaspace = ((metaspace_size_mb * 1024.0 * 1024.0)
-        / (METASPACE_PER_CONN * args.expected_connections as f64)
-        * STABILITY_FACTOR) as usize;
+/// 数据库连接池占用的堆内存(GB)
+fn db_pool_heap_gb(args: &Args) -> f64 {
+    args.db_pool_size as f64 * DB_POOL_HEAP_PER_CONN
+}
 
-    // CPU限制(考虑上下文切换开销)
-    let max_by_cpu = ((args.cpu_cores as f64 / CPU_PER_CONN) * STABILITY_FACTOR) as usize;
+/// 空闲连接占用的堆内存(会话状态、连接对象, 无传输缓冲区)(GB)
+const IDLE_HEAP_PER_CONN: f64 = 64.0 / 1024.0 / 1024.0;
+/// 空闲连接的socket缓冲区占比(内核自动调优会收缩空闲连接的缓冲区)
+const IDLE_SOCKET_RATIO: f64 = 0.25;
 
-    // 网络限制
-    let max_by_net = ((args.net_gbps * 1000.0 / NET_PER_CONN) * STABILITY_FACTOR) as usize;
-
-    // 磁盘IO限制(基于SSD性能模型)
-    let (disk_iops, _disk_suggestion) = match args.disk_type.as_str() {
-        "nvme" => (500_000.0, None),
-        "sata_ssd" => {
-            if args.expected_connections > 50_000 {
-                (100_000.0, Some("考虑升级到NVMe SSD"))
-            } else {
-                (100_000.0, None)
-            }
-        }
-        _ => (200.0, Some("必须升级到SSD")), // HDD
-    };
-    let max_by_disk = ((disk_iops / DISK_IO_PER_CONN) * STABILITY_FACTOR) as usize;
+/// ZGC相对堆大小额外占用的Native内存比例
+pub(crate) const ZGC_NATIVE_RATIO: f64 = 0.05;
 
-    // 综合极限(取最小值，考虑JVM各维度限制)
-    let max_connections = max_by_direct
-        .min(max_by_heap)
-        .min(max_by_metaspace)
-        .min(max_by_cpu)
-        .min(max_by_net)
-        .min(max_by_disk)
-        .min(burst_connections); // 必须满足突发需求
+/// IP协议栈带来的额外单连接内核开销(KB)
+///
+/// IPv6的socket结构和报文头更大；双栈监听还需维护两套地址族的状态
+pub fn ip_stack_overhead_kb(ip_stack: &str) -> f64 {
+    match ip_stack {
+        "ipv6" => 2.0,
+        "dual" => 3.0,
+        _ => 0.0,
+    }
+}
 
-    // 2. 计算可持续吞吐量(考虑长期负载均衡)
-    let sustainable_throughput = (args.cpu_cores as f64 * STABILITY_FACTOR) / 0.15; // 0.15秒/MB处理时间
+/// 内存防护阈值至少比 `danger_at` 低的比例，保证限流先于危险状态生效
+const GUARD_DANGER_MARGIN: f64 = 0.05;
 
-    // 3. 长期运行评估(6-12个月)
-    let uptime_category = if max_connections >= burst_connections * 2 {
-        "12个月+ (弹性充足)"
-    } else if max_connections >= burst_connections {
-        "6-12个月 (满足需求)"
-    } else {
-        "<6个月 (需扩容)"
-    };
+/// 内存防护阈值(GB)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GuardThresholds {
+    pub direct_gb: f64, // 直接内存限流阈值
+    pub heap_gb: f64,   // 堆内存限流阈值
+}
 
-    // 4. 确定瓶颈资源
-    let limiting_factor = if max_connections == max_by_direct {
-        "直接内存"
-    } else if max_connections == max_by_heap {
-        "堆内存"
-    } else if max_connections == max_by_cpu {
-        "CPU资源"
-    } else if max_connections == max_by_net {
-        "网络带宽"
-    } else if max_connections == max_by_disk {
-        "磁盘IO"
-    } else {
-        "突发流量需求"
-    };
+/// 计算内存防护的限流阈值
+///
+/// 与场景状态评估使用同一个有效内存上限(扣除JVM开销和余量)。未指定比例时取
+/// `danger_at` 减 [`GUARD_DANGER_MARGIN`]，指定的比例也截断到该值，
+/// 保证防护在安全模型判定为危险之前生效
+pub fn memory_guard_thresholds(
+    args: &Args,
+    policy: &SafetyPolicy,
+    direct_mem_gb: f64,
+    heap_mem_gb: f64,
+) -> GuardThresholds {
+    let ceiling = (policy.danger_at - GUARD_DANGER_MARGIN).max(0.0);
+    let direct_pct = args
+        .guard_direct_pct
+        .map_or(ceiling, |pct| pct.min(ceiling));
+    let heap_pct = args.guard_heap_pct.map_or(ceiling, |pct| pct.min(ceiling));
+    GuardThresholds {
+        direct_gb: direct_mem_gb * policy.effective_max_ratio * direct_pct,
+        heap_gb: heap_mem_gb * policy.effective_max_ratio * heap_pct,
+    }
+}
 
-    // 5. 生成资源利用率分析(包含JVM维度)
-    let resource_breakdown = format!(
-        "    * JVM内存: {:.0}% (堆), {:.0}% (直接), {:.0}% (元空间)\n    * CPU: {:.0}%\n    * 网络: {:.0}%\n    * 磁盘IO: {:.0}%",
-        (normal_heap_usage / (heap_mem_gb * SAFE_MEM_USAGE) * 100.0).min(100.0),
-        (normal_direct_usage / (direct_mem_gb * SAFE_MEM_USAGE) * 100.0).min(100.0),
-        (args.expected_connections as f64 * METASPACE_PER_CONN * 100.0
-            / (metaspace_size_mb * 1024.0 * 1024.0))
-            .min(100.0),
-        (args.expected_connections as f64 / max_by_cpu as f64 * 100.0).min(100.0),
-        (args.expected_connections as f64 / max_by_net as f64 * 100.0).min(100.0),
-        (args.expected_connections as f64 / max_by_disk as f64 * 100.0).min(100.0)
-    );
+/// 每连接CPU开销中TLS记录加解密所占比例
+const TLS_CPU_SHARE: f64 = 0.3;
+/// 每连接CPU开销中校验和计算所占比例
+const CHECKSUM_CPU_SHARE: f64 = 0.2;
 
-    TheoreticalLimits {
-        max_connections,
-        max_throughput: sustainable_throughput,
-        estimated_uptime: uptime_category.to_string(),
-        limiting_factor: limiting_factor.to_string(),
-        burst_capacity: (max_connections as f64 / STABILITY_FACTOR) as usize,
-        resource_breakdown,
+/// 硬件卸载后每连接剩余的CPU开销比例
+///
+/// 只影响稳态传输的CPU开销；TLS握手仍由CPU完成，不影响建连速率
+pub fn offload_cpu_factor(offload: &str) -> f64 {
+    match offload {
+        "tls" => 1.0 - TLS_CPU_SHARE,
+        "checksum" => 1.0 - CHECKSUM_CPU_SHARE,
+        "both" => 1.0 - TLS_CPU_SHARE - CHECKSUM_CPU_SHARE,
+        _ => 1.0,
     }
 }
 
-fn status_label(heap_usage: f64, heap_max: f64, direct_usage: f64, direct_max: f64) -> String {
-    // 考虑JVM自身开销(15%)和长期运行余量(15%)
-    let effective_heap_max = heap_max * 0.7;
-    let effective_direct_max = direct_max * 0.7;
+/// 单核每秒可计算CRC32的数据量(MB)，现代CPU有硬件指令支持
+const CRC32_MB_PER_CORE: f64 = 8000.0;
+/// 单核每秒可计算SHA-256的数据量(MB)，未使用SHA扩展指令时的典型值
+const SHA256_MB_PER_CORE: f64 = 500.0;
+
+/// 文件完整性校验时单核每秒可处理的数据量(MB)
+///
+/// 未启用校验，或校验已通过 `--offload checksum/both` 卸载到硬件时返回 `None`
+pub fn checksum_mb_per_core(args: &Args) -> Option<f64> {
+    if matches!(args.offload.as_str(), "checksum" | "both") {
+        return None;
+    }
+    match args.checksum.as_str() {
+        "crc32" => Some(CRC32_MB_PER_CORE),
+        "sha256" => Some(SHA256_MB_PER_CORE),
+        _ => None,
+    }
+}
+
+/// 以 `mb_per_sec` 速率传输时文件校验占用的CPU核心数
+pub fn checksum_cpu_cores(args: &Args, mb_per_sec: f64) -> f64 {
+    checksum_mb_per_core(args).map_or(0.0, |rate| mb_per_sec / rate)
+}
+
+/// 同步复制: 每次写入需同时发往备机并落盘，磁盘写入和网络出口约为2倍
+const SYNC_REPLICATION_FACTOR: f64 = 2.0;
+/// 异步复制: 写入先缓冲再批量发往备机，合并后额外开销较小
+const ASYNC_REPLICATION_FACTOR: f64 = 1.3;
+
+/// 复制到备机后每连接磁盘写入和网络出口需求的倍数
+pub fn replication_factor(replication: &str) -> f64 {
+    match replication {
+        "sync" => SYNC_REPLICATION_FACTOR,
+        "async" => ASYNC_REPLICATION_FACTOR,
+        _ => 1.0,
+    }
+}
+
+/// 平均文件不超过该大小(MB)时提示写放大
+const SMALL_FILE_WRITE_AMPLIFICATION_MB: f64 = 1.0;
+/// 小文件负载建议的写放大系数(SSD上FTL垃圾回收与文件系统日志的典型值)
+const SUGGESTED_SMALL_FILE_WRITE_AMPLIFICATION: f64 = 3.0;
+
+/// 磁盘写放大系数，未指定时为1.0(按标称值计算)
+pub fn write_amplification(args: &Args) -> f64 {
+    args.write_amplification.unwrap_or(1.0)
+}
+
+/// 按队列深度折算的磁盘可达IOPS
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiskQueueModel {
+    pub queue_depth: u32,     // 服务器可驱动的并发IO数(--io-threads)
+    pub rated_iops: f64,      // 磁盘标称IOPS(满队列时测得)
+    pub achievable_iops: f64, // 该队列深度下可达的IOPS
+}
+
+impl DiskQueueModel {
+    /// 可达IOPS占标称值的比例
+    pub fn fraction(&self) -> f64 {
+        self.achievable_iops / self.rated_iops
+    }
+}
+
+/// 各磁盘类型的队列深度特性: (QD1时可达IOPS占标称值的比例, 达到标称IOPS所需的队列深度)
+///
+/// 机械盘受寻道限制，NCQ重排只能小幅提升；SSD依靠多通道并行，
+/// 标称IOPS在深队列下测得，QD1时NVMe只有标称值的几个百分点
+fn queue_depth_profile(disk_type: &str) -> (f64, f64) {
+    match disk_type {
+        "sata_hdd" => (0.7, 4.0),
+        "sata_ssd" => (0.15, 32.0),
+        "nvme" => (0.03, 128.0),
+        _ => (1.0, 1.0), // 自定义磁盘按标称值计算
+    }
+}
+
+/// 给定队列深度下可达IOPS占标称值的比例: 从QD1比例线性增长到饱和队列深度时的1.0
+fn queue_depth_fraction(disk_type: &str, queue_depth: u32) -> f64 {
+    let (qd1, saturation) = queue_depth_profile(disk_type);
+    if saturation <= 1.0 {
+        return 1.0;
+    }
+    let ramp = ((queue_depth.max(1) as f64 - 1.0) / (saturation - 1.0)).min(1.0);
+    qd1 + (1.0 - qd1) * ramp
+}
+
+/// 按 `--io-threads` 折算的磁盘可达IOPS，未指定时返回 `None`(假设队列足够深)
+///
+/// 分层存储时两层各自折算后再按命中率混合
+pub fn disk_queue_model(args: &Args) -> Option<DiskQueueModel> {
+    let queue_depth = args.io_threads?;
+    let achievable = |name: &str| {
+        config::disk_config_for(name).ok().map(|disk| {
+            (
+                disk.iops,
+                disk.iops * queue_depth_fraction(name, queue_depth),
+            )
+        })
+    };
+    let (rated_iops, achievable_iops) = match (&args.hot_disk, &args.cold_disk, args.hot_ratio) {
+        (Some(hot), Some(cold), Some(hot_ratio)) => {
+            let (hot_rated, hot_achievable) = achievable(hot)?;
+            let (cold_rated, cold_achievable) = achievable(cold)?;
+            let mix = |h: f64, c: f64| h * hot_ratio + c * (1.0 - hot_ratio);
+            (
+                mix(hot_rated, cold_rated),
+                mix(hot_achievable, cold_achievable),
+            )
+        }
+        _ => achievable(&args.disk_type)?,
+    };
+    Some(DiskQueueModel {
+        queue_depth,
+        rated_iops,
+        achievable_iops,
+    })
+}
+
+/// 页缓存全部命中时仍需访问磁盘的IO比例(元数据更新、访问时间和冷启动未命中)
+const MIN_DISK_IO_FACTOR: f64 = 0.02;
+
+/// 页缓存对读取的分担
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageCacheModel {
+    pub hit_ratio: f64,              // 读取的页缓存命中率
+    pub working_set_gb: Option<f64>, // 热点工作集(GB)，直接指定命中率时按指定值
+    pub cache_gb: f64,               // JVM各内存区域之外可用于页缓存的内存(GB)
+}
+
+impl PageCacheModel {
+    /// 仍需访问磁盘的IO比例: 只有读取能被页缓存命中，写入照常落盘
+    pub fn disk_io_factor(&self, workload: &str) -> f64 {
+        (1.0 - self.hit_ratio * read_share(workload)).max(MIN_DISK_IO_FACTOR)
+    }
+}
+
+/// 负载中读取所占的比例: 纯下载只读，纯上传只写，混合负载读写各半
+pub fn read_share(workload: &str) -> f64 {
+    match workload {
+        "download" => 1.0,
+        "upload" => 0.0,
+        _ => 0.5,
+    }
+}
+
+/// 按 `--cache-hit-ratio` 或 `--working-set-gb` 估算页缓存命中率，均未指定时返回 `None`
+///
+/// 推算时可用缓存为服务器内存扣除JVM各内存区域和Native预留后的剩余，
+/// 工作集能完全放入时命中率为1，否则按放入的比例计算
+pub fn page_cache_model(args: &Args) -> Option<PageCacheModel> {
+    if args.cache_hit_ratio.is_none() && args.working_set_gb.is_none() {
+        return None;
+    }
+    let (direct_mem_gb, heap_mem_gb) = crate::analysis::allocate_memory(args).ok()?;
+    let reconciliation = crate::analysis::reconcile_memory(
+        args,
+        direct_mem_gb,
+        heap_mem_gb,
+        calculate_metaspace(args),
+    );
+    let cache_gb = reconciliation.free_gb.max(0.0);
+    let hit_ratio = args.cache_hit_ratio.unwrap_or_else(|| {
+        args.working_set_gb
+            .map_or(0.0, |working_set| (cache_gb / working_set).min(1.0))
+    });
+    Some(PageCacheModel {
+        hit_ratio,
+        working_set_gb: args.working_set_gb,
+        cache_gb,
+    })
+}
+
+/// 每次逻辑IO实际访问磁盘的物理IO数
+///
+/// 读取扣除页缓存命中，只有写入按写放大系数放大，纯下载负载因此不受写放大影响；
+/// 未建模页缓存且未指定写放大时为1.0
+pub fn disk_io_factor(args: &Args) -> f64 {
+    let read_share = read_share(&args.workload);
+    let hit_ratio = page_cache_model(args).map_or(0.0, |cache| cache.hit_ratio);
+    (read_share * (1.0 - hit_ratio) + (1.0 - read_share) * write_amplification(args))
+        .max(MIN_DISK_IO_FACTOR)
+}
+
+/// 各资源维度的可持续吞吐量上限(MB/s)
+///
+/// 系统极限评估的推荐吞吐量和性能分析各场景的持续QPS都以此为准，
+/// 保证两个章节给出的吞吐量不会互相矛盾
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThroughputModel {
+    pub cpu_mb: f64,     // CPU处理能力
+    pub network_mb: f64, // 网卡带宽(按传输方向，扣除复制流量)
+    pub disk_mb: f64,    // 磁盘持续读写(按读写比例加权)，磁盘类型无效时为无穷大
+}
+
+impl ThroughputModel {
+    /// 三者中的最小值及其资源名称
+    pub fn ceiling(&self) -> (f64, &'static str) {
+        [
+            (self.cpu_mb, "CPU"),
+            (self.network_mb, "网络带宽"),
+            (self.disk_mb, "磁盘IO"),
+        ]
+        .into_iter()
+        .fold((f64::INFINITY, "CPU"), |min, candidate| {
+            if candidate.0 < min.0 { candidate } else { min }
+        })
+    }
+}
+
+/// 单核每秒可处理的传输数据量(MB)
+pub const TRANSFER_MB_PER_CORE: f64 = 850.0;
+
+/// 单核每秒可压缩的数据量(MB)，按gzip低压缩级别估算
+const COMPRESSION_MB_PER_CORE: f64 = 200.0;
+
+/// CPU每秒可处理的传输数据量(MB)，性能分析的CPU并发上限与推荐吞吐量共用
+///
+/// 卸载到硬件的TLS/校验和工作不再占用CPU，每核心处理能力按CPU架构和主频折算，
+/// 文件校验按每MB输入额外消耗CPU，短连接周转的GC开销从可用核心中扣除。
+/// `compression_ratio` 小于1.0时只压缩响应(读取)的字节，读取部分还需按
+/// `COMPRESSION_MB_PER_CORE` 的速度压缩
+pub fn cpu_transfer_mb(args: &Args, compression_ratio: f64) -> f64 {
+    let compression_cpu_factor = if compression_ratio < 1.0 {
+        1.0 + TRANSFER_MB_PER_CORE * read_share(&args.workload) / COMPRESSION_MB_PER_CORE
+    } else {
+        1.0
+    };
+    let gc_free_cores = (args.cpu_cores as f64 - churn_gc_cores(args)).max(0.0);
+    gc_free_cores * cpu_efficiency(args) * TRANSFER_MB_PER_CORE
+        / (compression_cpu_factor * offload_cpu_factor(&args.offload)
+            + checksum_cpu_cores(args, TRANSFER_MB_PER_CORE))
+}
+
+/// 计算可持续吞吐量模型(考虑长期负载均衡，只使用60%的CPU)
+///
+/// 磁盘按读写比例加权: 读取按读取速度计算并扣除页缓存命中，写入按SLC缓存写满后的
+/// 持续速度计算并计入复制和写放大，纯下载负载因此不受写放大影响
+pub fn throughput_model(args: &Args) -> ThroughputModel {
+    const STABILITY_FACTOR: f64 = 0.6; // 与理论极限一致的长期稳定性系数
+
+    let replication = replication_factor(&args.replication);
+    let read_share = read_share(&args.workload);
+    let hit_ratio = page_cache_model(args).map_or(0.0, |cache| cache.hit_ratio);
+    let model = ThroughputModel {
+        cpu_mb: cpu_transfer_mb(args, args.assume_compression.unwrap_or(1.0)) * STABILITY_FACTOR,
+        network_mb: directional_net_gbps(args) * 125.0 / replication, // Gbps -> MB/s
+        disk_mb: config::effective_disk(args).map_or(f64::INFINITY, |disk| {
+            // 每MB传输占用的磁盘时间(秒)，全部命中页缓存的纯下载不占用磁盘
+            let secs_per_mb = read_share * (1.0 - hit_ratio) / disk.read_speed
+                + (1.0 - read_share) * replication * write_amplification(args)
+                    / disk.sustained_write_speed;
+            1.0 / secs_per_mb
+        }),
+    };
+    log::debug!(
+        "吞吐量上限: CPU={:.1}MB/s, 网络={:.1}MB/s, 磁盘={:.1}MB/s",
+        model.cpu_mb,
+        model.network_mb,
+        model.disk_mb
+    );
+    model
+}
+
+/// 网络极限使用的带宽(Gbps)
+///
+/// 下载服务受出口带宽限制，上传服务受入口带宽限制，mixed取两者较小值；
+/// 未单独指定方向时均为 `--net-gbps`
+pub fn directional_net_gbps(args: &Args) -> f64 {
+    let egress = args.net_egress_gbps.unwrap_or(args.net_gbps);
+    let ingress = args.net_ingress_gbps.unwrap_or(args.net_gbps);
+    match args.workload.as_str() {
+        "download" => egress,
+        "upload" => ingress,
+        _ => egress.min(ingress),
+    }
+}
+
+/// x86_64每核心效率系数(基准)
+const X86_64_CPU_EFFICIENCY: f64 = 1.0;
+/// aarch64每核心效率系数: Graviton/Ampere的vCPU是独占物理核心，
+/// 而x86的vCPU通常是超线程，IO密集型负载下单vCPU吞吐略高
+const AARCH64_CPU_EFFICIENCY: f64 = 1.15;
+
+/// 每核心处理能力常量(`CPU_PER_CONN` 等)对应的基准主频(GHz)
+const REFERENCE_CPU_GHZ: f64 = 2.5;
+/// 主频缩放系数的上下限: 内存带宽和缓存未命中不随主频线性提升
+const CPU_CLOCK_FACTOR_RANGE: (f64, f64) = (0.5, 1.5);
+
+/// 主频相对基准的缩放系数，未指定 `--cpu-ghz` 时为1.0
+pub fn cpu_clock_factor(args: &Args) -> f64 {
+    let (min, max) = CPU_CLOCK_FACTOR_RANGE;
+    args.cpu_ghz
+        .map_or(1.0, |ghz| (ghz / REFERENCE_CPU_GHZ).clamp(min, max))
+}
+
+/// 每核心处理能力相对2.5GHz x86_64的效率系数
+///
+/// `--cpu-efficiency` 优先，否则按 `--cpu-arch` 取默认值，再乘以主频缩放系数
+pub fn cpu_efficiency(args: &Args) -> f64 {
+    let arch = args.cpu_efficiency.unwrap_or(match args.cpu_arch.as_str() {
+        "aarch64" => AARCH64_CPU_EFFICIENCY,
+        _ => X86_64_CPU_EFFICIENCY,
+    });
+    arch * cpu_clock_factor(args)
+}
+
+/// 每个连接从建立到关闭产生的堆垃圾(MB): 请求解析、会话对象、缓冲区包装等
+const CHURN_GARBAGE_PER_CONN_MB: f64 = 0.25;
+/// 回收每GB/s短命对象分配速率所需的CPU核数(young GC的复制和卡表扫描)
+const GC_CORES_PER_GB_PER_SEC: f64 = 0.1;
+/// 年轻代占堆的比例
+const YOUNG_GEN_RATIO: f64 = 1.0 / 3.0;
+/// 连接周转垃圾中过早晋升到老年代的比例
+const PREMATURE_PROMOTION_RATIO: f64 = 0.0005;
+
+/// 短连接周转带来的GC压力
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GcPressure {
+    pub churn_per_sec: f64,        // 每秒新建并关闭的连接数
+    pub alloc_mb_per_sec: f64,     // 连接周转产生的分配速率(MB/s)
+    pub young_gc_per_min: f64,     // 预计每分钟young GC次数
+    pub gc_cores: f64,             // young GC占用的CPU核数
+    pub promoted_gb_per_hour: f64, // 过早晋升导致的老年代增长(GB/h)
+}
+
+/// 连接周转占用的GC CPU核数，长连接(未指定 `--connection-duration`)时为0
+pub fn churn_gc_cores(args: &Args) -> f64 {
+    args.connection_duration.map_or(0.0, |secs| {
+        let alloc_mb = args.expected_connections as f64 / secs * CHURN_GARBAGE_PER_CONN_MB;
+        alloc_mb / 1024.0 * GC_CORES_PER_GB_PER_SEC
+    })
+}
+
+/// 按连接存活时间估算分配速率、young GC频率和晋升速率
+///
+/// 稳态下每秒周转 `expected_connections / connection_duration` 个连接，
+/// 年轻代按堆的1/3估算，填满一次即触发一次young GC
+pub fn gc_pressure(args: &Args, heap_mem_gb: f64) -> Option<GcPressure> {
+    let secs = args.connection_duration?;
+    let churn_per_sec = args.expected_connections as f64 / secs;
+    let alloc_mb_per_sec = churn_per_sec * CHURN_GARBAGE_PER_CONN_MB;
+    let young_gen_mb = (heap_mem_gb * 1024.0 * YOUNG_GEN_RATIO).max(1.0);
+    Some(GcPressure {
+        churn_per_sec,
+        alloc_mb_per_sec,
+        young_gc_per_min: alloc_mb_per_sec / young_gen_mb * 60.0,
+        gc_cores: churn_gc_cores(args),
+        promoted_gb_per_hour: alloc_mb_per_sec * 3600.0 * PREMATURE_PROMOTION_RATIO / 1024.0,
+    })
+}
+
+/// 每个连接占用的文件描述符数(socket + 正在传输的文件)
+const FDS_PER_CONNECTION: usize = 2;
+/// 与连接无关的文件描述符余量(jar、日志、epoll等)
+const BASE_FDS: usize = 1024;
+
+/// 突发负载下进程所需的文件描述符数
+///
+/// 突发连接和空闲连接各占socket与文件两个描述符，数据库连接池同样计入，
+/// 另加与连接无关的固定余量。结果即推荐的 `LimitNOFILE`/`ulimit -n`
+pub fn required_fds(args: &Args) -> usize {
+    let connections = args.burst_connections() + args.idle_connections + args.db_pool_size;
+    connections * FDS_PER_CONNECTION + BASE_FDS
+}
+
+/// 单核每秒可完成的TCP建连数(accept、socket初始化、注册事件循环)
+const ACCEPTS_PER_CORE: f64 = 20_000.0;
+/// 单核每秒可完成的TLS完整握手数(ECDHE + RSA-2048签名为主要开销)
+const TLS_HANDSHAKES_PER_CORE: f64 = 1_000.0;
+
+/// 单核每秒可建立的连接数
+fn accepts_per_core(tls: bool) -> f64 {
+    if tls {
+        TLS_HANDSHAKES_PER_CORE
+    } else {
+        ACCEPTS_PER_CORE
+    }
+}
+
+/// glibc每个CPU核心默认最多创建的malloc arena数
+const GLIBC_ARENAS_PER_CORE: f64 = 8.0;
+/// 每个glibc arena因碎片常驻的内存(MB)
+const GLIBC_ARENA_RSS_MB: f64 = 2.0;
+/// 推荐的 `MALLOC_ARENA_MAX`
+const RECOMMENDED_ARENA_MAX: usize = 4;
+
+/// 原生内存分配器的额外开销(GB)
+///
+/// glibc malloc按CPU核心数创建大量arena，碎片随核心数线性增长；
+/// jemalloc的arena更少且碎片更低；tcmalloc主要是每线程缓存
+pub fn allocator_overhead_gb(args: &Args) -> f64 {
+    let cores = args.cpu_cores as f64;
+    let overhead_mb = match args.native_allocator.as_str() {
+        "jemalloc" => cores * 4.0 * 0.5,
+        "tcmalloc" => cores * 2.0,
+        _ => cores * GLIBC_ARENAS_PER_CORE * GLIBC_ARENA_RSS_MB,
+    };
+    overhead_mb / 1024.0
+}
+
+/// 指定连接数下内核socket收发缓冲区占用的内存(GB)
+fn socket_buffer_gb(args: &Args, connections: usize) -> f64 {
+    let per_conn_kb = args.socket_buffer_kb as f64 + ip_stack_overhead_kb(&args.ip_stack);
+    connections as f64 * per_conn_kb / 1024.0 / 1024.0
+}
+
+/// JVM Native及系统预留(GB)，包含ZGC额外的常驻内存
+pub(crate) fn jvm_native_reserved_gb(args: &Args, heap_mem_gb: f64) -> f64 {
+    // ZGC的多重映射和转发表使常驻内存明显高于G1/Shenandoah；NMT估算已按收集器计入GC开销
+    let gc_native_gb = if crate::analysis::jvm::selected_collector(args) == Collector::Zgc
+        && !native::uses_nmt_model(args)
+    {
+        heap_mem_gb * ZGC_NATIVE_RATIO
+    } else {
+        0.0
+    };
+    native::native_reserved_gb(args, heap_mem_gb) + gc_native_gb
+}
+
+/// 计算突发流量下的整机内存预算
+fn calculate_memory_budget(args: &Args, direct_mem_gb: f64, heap_mem_gb: f64) -> MemoryBudget {
+    let burst_connections = args.burst_connections();
+    let native_reserved_gb = jvm_native_reserved_gb(args, heap_mem_gb);
+    let socket_buffer_gb = socket_buffer_gb(args, burst_connections)
+        + socket_buffer_gb(args, args.idle_connections) * IDLE_SOCKET_RATIO;
+    let allocator_gb = allocator_overhead_gb(args);
+    let total_gb =
+        heap_mem_gb + direct_mem_gb + native_reserved_gb + allocator_gb + socket_buffer_gb;
+
+    log::debug!(
+        "内存预算: 堆={heap_mem_gb:.1}GB, 直接内存={direct_mem_gb:.1}GB, Native预留={native_reserved_gb:.1}GB, 分配器={allocator_gb:.2}GB, socket缓冲区={socket_buffer_gb:.2}GB, 合计={total_gb:.1}GB"
+    );
+
+    MemoryBudget {
+        heap_gb: heap_mem_gb,
+        direct_gb: direct_mem_gb,
+        native_reserved_gb,
+        allocator_gb,
+        socket_buffer_gb,
+        total_gb,
+        over_committed: total_gb > args.effective_total_ram(),
+    }
+}
+
+/// 单连接直接内存的组成(KB)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DirectMemBreakdown {
+    pub read_kb: f64,  // 读缓冲区
+    pub write_kb: f64, // 写缓冲区
+    pub pool_kb: f64,  // 池化分配器及SSL/TLS、协议头等固定开销
+}
+
+impl DirectMemBreakdown {
+    /// 按平均文件大小动态计算缓冲区大小
+    pub fn for_file_size(file_size: f64) -> Self {
+        // 读缓冲区大小 (动态调整)
+        let read_kb = if file_size <= 10.0 {
+            128.0 // 128KB for small files
+        } else if file_size <= 100.0 {
+            512.0 // 512KB for medium files
+        } else {
+            // For large files, use 1MB buffer but allow chunked processing
+            // with memory mapping optimization
+            (1024.0_f64).min(file_size * 0.01) // 1MB or 1% of file size, whichever is smaller
+        };
+
+        DirectMemBreakdown {
+            read_kb,
+            // 写缓冲区大小 (通常比读缓冲区大)
+            write_kb: read_kb * 1.5,
+            // 额外开销 (SSL/TLS, headers etc)
+            pool_kb: 100.0, // 100KB fixed overhead
+        }
+    }
+
+    /// 按文件大小分布加权的单连接组成，未指定分布时等同于按平均文件大小计算
+    pub fn weighted(args: &Args) -> Self {
+        args.file_size_buckets().into_iter().fold(
+            DirectMemBreakdown {
+                read_kb: 0.0,
+                write_kb: 0.0,
+                pool_kb: 0.0,
+            },
+            |sum, (size, weight)| {
+                let bucket = DirectMemBreakdown::for_file_size(size);
+                DirectMemBreakdown {
+                    read_kb: sum.read_kb + bucket.read_kb * weight,
+                    write_kb: sum.write_kb + bucket.write_kb * weight,
+                    pool_kb: sum.pool_kb + bucket.pool_kb * weight,
+                }
+            },
+        )
+    }
+
+    /// 单连接合计(KB)
+    pub fn total_kb(&self) -> f64 {
+        self.read_kb + self.write_kb + self.pool_kb
+    }
+}
+
+impl std::fmt::Display for DirectMemBreakdown {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "读缓冲 {:.0}KB, 写缓冲 {:.0}KB, 池 {:.0}KB (每连接)",
+            self.read_kb, self.write_kb, self.pool_kb
+        )
+    }
+}
+
+/// 零拷贝是否生效: 仅非TLS的下载由内核直接从页缓存发送到socket
+///
+/// TLS需要在用户态加密，上传需要把数据读入用户态后写盘，两者仍使用读缓冲区
+pub fn zero_copy_applies(args: &Args) -> bool {
+    args.zero_copy && args.workload == "download" && !args.tls
+}
+
+/// 动态计算每个连接的直接内存需求 (读缓冲GB, 写缓冲及额外开销GB)
+///
+/// 指定文件大小分布时按各区间的连接占比加权；零拷贝生效时文件内容不经过读缓冲区
+fn calculate_direct_mem_per_conn(args: &Args) -> (f64, f64) {
+    let mut breakdown = DirectMemBreakdown::weighted(args);
+    if zero_copy_applies(args) {
+        breakdown.read_kb = 0.0;
+    }
+
+    log::trace!(
+        "单连接直接内存: 文件={}MB, 读缓冲={:.1}KB, 写缓冲={:.1}KB, 额外开销={:.1}KB",
+        args.avg_file_size,
+        breakdown.read_kb,
+        breakdown.write_kb,
+        breakdown.pool_kb
+    );
+
+    (
+        breakdown.read_kb / 1024.0 / 1024.0, // convert to GB
+        (breakdown.write_kb + breakdown.pool_kb) / 1024.0 / 1024.0, // convert to GB
+    )
+}
+
+/// 每个传输连接的基础堆内存(GB)，384KB含对象开销
+const HEAP_PER_CONN: f64 = 384.0 / 1024.0 / 1024.0;
+
+/// 每个传输连接占用的堆内存(GB): 基础开销加上在途请求的请求头和元数据开销
+fn heap_per_conn(args: &Args) -> f64 {
+    HEAP_PER_CONN + args.avg_request_heap_kb / 1024.0 / 1024.0
+}
+
+/// 指定连接数下正常负载的 (堆内存GB, 直接内存GB) 使用量
+fn normal_usage(args: &Args, connections: usize) -> (f64, f64) {
+    // 计算正常场景内存使用 (动态调整缓冲区大小)
+    let (read_buffer_per_conn, write_buffer_per_conn) = calculate_direct_mem_per_conn(args);
+    let direct_usage = connections as f64 * (read_buffer_per_conn + write_buffer_per_conn);
+
+    // 如果是大文件(>100MB)且使用内存映射，可以减少直接内存需求
+    let mem_map_reduction = if args.avg_file_size > 100.0 && args.enable_memory_mapping {
+        0.5 // 内存映射可减少50%直接内存需求
+    } else {
+        1.0
+    };
+    let direct_usage = direct_usage * mem_map_reduction;
+    // 空闲连接(keep-alive、预热连接池)只占用堆和socket内存，不占用传输缓冲区
+    let idle_heap_usage = args.idle_connections as f64 * IDLE_HEAP_PER_CONN;
+    let heap_usage = connections as f64 * heap_per_conn(args) + idle_heap_usage;
+
+    (heap_usage, direct_usage)
+}
+
+/// 计算任意连接数下的 (堆安全系数, 直接内存安全系数, 风险等级)
+///
+/// 与 [`calculate_safety`] 使用相同的模型，只是连接数不取自 `args.expected_connections`，
+/// 便于采样绘制负载-风险曲线
+pub fn safety_at(
+    args: &Args,
+    direct_mem_gb: f64,
+    heap_mem_gb: f64,
+    connections: usize,
+) -> (f64, f64, String) {
+    safety_at_with_policy(
+        args,
+        direct_mem_gb,
+        heap_mem_gb,
+        connections,
+        &SafetyPolicy::from_args(args),
+    )
+}
+
+fn safety_at_with_policy(
+    args: &Args,
+    direct_mem_gb: f64,
+    heap_mem_gb: f64,
+    connections: usize,
+    policy: &SafetyPolicy,
+) -> (f64, f64, String) {
+    let (heap_usage, direct_usage) = normal_usage(args, connections);
+
+    // 数据库连接池与传输连接共享堆内存，先从可用堆中扣除
+    let transfer_heap_gb = (heap_mem_gb - db_pool_heap_gb(args)).max(0.0);
+
+    // 计算安全系数 (0-1)，保留15%给JVM Native内存
+    const JVM_NATIVE_RATIO: f64 = 0.15;
+    let available_heap = transfer_heap_gb * (1.0 - JVM_NATIVE_RATIO);
+    let available_direct = direct_mem_gb * (1.0 - JVM_NATIVE_RATIO);
+
+    // 使用更保守的安全阈值
+    let heap_safety = 1.0 - (heap_usage / (available_heap * policy.usage_threshold)).min(1.0);
+    let direct_mem_safety =
+        1.0 - (direct_usage / (available_direct * policy.usage_threshold)).min(1.0);
+
+    // 改进的风险等级评估
+    let risk_level = match (heap_safety, direct_mem_safety) {
+        (h, d) if h > policy.low_risk_above && d > policy.low_risk_above => "低风险".to_string(),
+        (h, d) if h > policy.medium_risk_above || d > policy.medium_risk_above => {
+            "中风险".to_string()
+        }
+        _ => "高风险".to_string(),
+    };
+
+    (heap_safety, direct_mem_safety, risk_level)
+}
+
+pub fn calculate_safety(args: &Args, direct_mem_gb: f64, heap_mem_gb: f64) -> SafetyAnalysis {
+    calculate_safety_with_policy(
+        args,
+        direct_mem_gb,
+        heap_mem_gb,
+        &SafetyPolicy::from_args(args),
+    )
+}
+
+/// 指定突发倍数下的内存占用和状态
+pub struct BurstPoint {
+    pub burst_factor: f64,             // 突发倍数
+    pub connections: usize,            // 突发连接数
+    pub heap_usage: f64,               // 堆内存使用(GB)
+    pub direct_mem_usage: f64,         // 直接内存使用(GB)
+    pub heap_status: ScenarioStatus,   // 堆内存状态
+    pub direct_status: ScenarioStatus, // 直接内存状态
+}
+
+impl BurstPoint {
+    /// 堆内存和直接内存都未进入危险区
+    pub fn survives(&self) -> bool {
+        self.heap_status != ScenarioStatus::Danger && self.direct_status != ScenarioStatus::Danger
+    }
+}
+
+/// 按突发倍数计算突发场景的内存占用，堆和直接内存分别评估状态
+pub fn burst_point(
+    args: &Args,
+    direct_mem_gb: f64,
+    heap_mem_gb: f64,
+    burst_factor: f64,
+    policy: &SafetyPolicy,
+) -> BurstPoint {
+    let connections = (args.expected_connections as f64 * burst_factor.max(1.0)) as usize;
+    let (read, write) = calculate_direct_mem_per_conn(args);
+    let direct_mem_usage = connections as f64 * (read + write);
+    let idle_heap_usage = args.idle_connections as f64 * IDLE_HEAP_PER_CONN;
+    let heap_usage = connections as f64 * heap_per_conn(args) + idle_heap_usage;
+    let transfer_heap_gb = (heap_mem_gb - db_pool_heap_gb(args)).max(0.0);
+    BurstPoint {
+        burst_factor,
+        connections,
+        heap_usage,
+        direct_mem_usage,
+        heap_status: usage_status(heap_usage, transfer_heap_gb, policy),
+        direct_status: usage_status(direct_mem_usage, direct_mem_gb, policy),
+    }
+}
+
+/// 持续高峰场景
+///
+/// 内存按突发连接数评估，另以稳态资源极限(CPU、网络、磁盘，含GC开销)衡量:
+/// 负载比例按资源风险阈值(与CPU、磁盘等资源利用率相同)判定状态，与内存状态取较差者
+fn sustained_peak_scenario(
+    args: &Args,
+    direct_mem_gb: f64,
+    heap_mem_gb: f64,
+    factor: f64,
+    steady_state_max: usize,
+    policy: &SafetyPolicy,
+) -> Scenario {
+    let peak = burst_point(args, direct_mem_gb, heap_mem_gb, factor, policy);
+    let load_pct = peak.connections as f64 / steady_state_max.max(1) as f64 * 100.0;
+    let resource_status = match policy.risk_for_utilization(load_pct) {
+        "低风险" => ScenarioStatus::Safe,
+        "中风险" => ScenarioStatus::Warning,
+        _ => ScenarioStatus::Danger,
+    };
+    Scenario {
+        name: format!("持续高峰 ({factor}x)"),
+        connections: peak.connections,
+        file_size: args.avg_file_size,
+        heap_usage: peak.heap_usage,
+        direct_mem_usage: peak.direct_mem_usage,
+        status: peak
+            .heap_status
+            .max(peak.direct_status)
+            .max(resource_status),
+    }
+}
+
+/// SSD读写混合负载下读写相互干扰带来的额外IO开销
+const MIXED_RW_INTERFERENCE: f64 = 1.25;
+
+/// 读写混合场景(如双向同步服务)
+///
+/// 每个连接同时上传和下载: 读写缓冲区均按满额计入，不享受内存映射的减免；
+/// 磁盘同时承担读和写，IO需求翻倍并因读写干扰进一步放大。
+/// 内存状态沿用 `status_label`，与磁盘争用状态取较差者
+fn mixed_read_write_scenario(
+    args: &Args,
+    direct_mem_gb: f64,
+    transfer_heap_gb: f64,
+    heap_usage: f64,
+    disk_pct: f64,
+    policy: &SafetyPolicy,
+) -> Scenario {
+    // 混合流量中的上传仍需读缓冲区，不适用零拷贝的豁免
+    let per_conn_gb = DirectMemBreakdown::weighted(args).total_kb() / 1024.0 / 1024.0;
+    let direct_mem_usage = args.expected_connections as f64 * per_conn_gb;
+    let disk_load = disk_pct / 100.0 * 2.0 * MIXED_RW_INTERFERENCE;
+    let disk_status = if disk_load > 1.0 {
+        ScenarioStatus::Danger
+    } else if disk_load > policy.danger_at {
+        ScenarioStatus::Warning
+    } else {
+        ScenarioStatus::Safe
+    };
+    Scenario {
+        name: "读写混合".to_string(),
+        connections: args.expected_connections,
+        file_size: args.avg_file_size,
+        heap_usage,
+        direct_mem_usage,
+        status: status_label(
+            heap_usage,
+            transfer_heap_gb,
+            direct_mem_usage,
+            direct_mem_gb,
+            policy,
+        )
+        .max(disk_status),
+    }
+}
+
+/// 单一内存区域的状态，按该区域的占用比例套用 `warn_at`/`danger_at`
+///
+/// 与 `status_label` 不同: 后者在堆和直接内存都超过 `danger_at` 时才判定危险
+fn usage_status(usage: f64, max: f64, policy: &SafetyPolicy) -> ScenarioStatus {
+    let ratio = usage / (max * policy.effective_max_ratio);
+    if ratio < policy.warn_at {
+        ScenarioStatus::Safe
+    } else if ratio < policy.danger_at {
+        ScenarioStatus::Warning
+    } else {
+        ScenarioStatus::Danger
+    }
+}
+
+/// 使用指定的阈值策略计算安全性
+pub fn calculate_safety_with_policy(
+    args: &Args,
+    direct_mem_gb: f64,
+    heap_mem_gb: f64,
+    policy: &SafetyPolicy,
+) -> SafetyAnalysis {
+    let (normal_heap_usage, normal_direct_usage) = normal_usage(args, args.expected_connections);
+
+    // 数据库连接池与传输连接共享堆内存，先从可用堆中扣除
+    let transfer_heap_gb = (heap_mem_gb - db_pool_heap_gb(args)).max(0.0);
+
+    let (heap_safety, direct_mem_safety, risk_level) = safety_at_with_policy(
+        args,
+        direct_mem_gb,
+        heap_mem_gb,
+        args.expected_connections,
+        policy,
+    );
+
+    // 创建模拟场景
+    let mut scenarios = Vec::new();
+
+    // 场景1: 长期运行(24小时)
+    scenarios.push(Scenario {
+        name: "长期运行(24h)".to_string(),
+        connections: args.expected_connections,
+        file_size: args.avg_file_size,
+        heap_usage: normal_heap_usage * 1.5, // 假设长期运行堆增长50%
+        direct_mem_usage: normal_direct_usage * 1.2, // 直接内存增长20%
+        status: status_label(
+            normal_heap_usage * 1.5,
+            transfer_heap_gb,
+            normal_direct_usage * 1.2,
+            direct_mem_gb,
+            policy,
+        ),
+    });
+
+    // 场景2: 正常负载
+    scenarios.push(Scenario {
+        name: "正常负载".to_string(),
+        connections: args.expected_connections,
+        file_size: args.avg_file_size,
+        heap_usage: normal_heap_usage,
+        direct_mem_usage: normal_direct_usage,
+        status: status_label(
+            normal_heap_usage,
+            transfer_heap_gb,
+            normal_direct_usage,
+            direct_mem_gb,
+            policy,
+        ),
+    });
+
+    // 场景2: 突发流量，区分持续高峰时只考验内存缓冲
+    let burst = burst_point(args, direct_mem_gb, heap_mem_gb, args.burst_factor, policy);
+    let burst_index = scenarios.len();
+    scenarios.push(Scenario {
+        name: if args.sustained_burst_factor.is_some() {
+            format!("瞬时突发 ({}x)", args.burst_factor)
+        } else {
+            format!("突发流量 ({}x)", args.burst_factor)
+        },
+        connections: burst.connections,
+        file_size: args.avg_file_size,
+        heap_usage: burst.heap_usage,
+        direct_mem_usage: burst.direct_mem_usage,
+        status: status_label(
+            burst.heap_usage,
+            transfer_heap_gb,
+            burst.direct_mem_usage,
+            direct_mem_gb,
+            policy,
+        ),
+    });
+
+    // 场景3: 大文件处理
+    scenarios.push(Scenario {
+        name: "大文件处理".to_string(),
+        connections: (args.expected_connections as f64 * 0.5) as usize,
+        file_size: args.avg_file_size * 5.0,
+        heap_usage: normal_heap_usage * 0.5,
+        direct_mem_usage: normal_direct_usage * 0.5,
+        status: status_label(
+            normal_heap_usage * 0.5,
+            transfer_heap_gb,
+            normal_direct_usage * 0.5,
+            direct_mem_gb,
+            policy,
+        ),
+    });
+
+    // 场景4: 小文件高并发
+    scenarios.push(Scenario {
+        name: "小文件高并发".to_string(),
+        connections: args.expected_connections * 3,
+        file_size: args.avg_file_size / 10.0,
+        heap_usage: normal_heap_usage * 1.5,
+        direct_mem_usage: normal_direct_usage * 1.5,
+        status: status_label(
+            normal_heap_usage * 1.5,
+            transfer_heap_gb,
+            normal_direct_usage * 1.5,
+            direct_mem_gb,
+            policy,
+        ),
+    });
+
+    // 生成优化建议
+    let mut recommendations = Vec::new();
+    let mut warnings = Vec::new();
+
+    if direct_mem_safety < 0.3 {
+        recommendations.push(Recommendation::new(
+            Severity::Warning,
+            format!(
+                "增加直接内存: {:.1}GB -> {:.1}GB",
+                direct_mem_gb,
+                direct_mem_gb * 1.3
+            ),
+            format!("direct_mem_safety={direct_mem_safety:.2} < 0.3，按1.3x扩容"),
+        ));
+    }
+
+    if heap_safety < 0.3 {
+        recommendations.push(Recommendation::new(
+            Severity::Warning,
+            format!(
+                "增加堆内存: {:.1}GB -> {:.1}GB",
+                heap_mem_gb,
+                heap_mem_gb * 1.2
+            ),
+            format!("heap_safety={heap_safety:.2} < 0.3，按1.2x扩容"),
+        ));
+    }
+
+    if args.enable_memory_guard {
+        let guard = memory_guard_thresholds(args, policy, direct_mem_gb, heap_mem_gb);
+        recommendations.push(Recommendation::new(
+            Severity::Info,
+            format!(
+                "启用内存防护系统: 直接内存超过{:.1}GB或堆内存超过{:.1}GB时自动限流",
+                guard.direct_gb, guard.heap_gb
+            ),
+            format!(
+                "指定了 --enable-memory-guard，阈值 = 内存 × 有效上限{:.0}% × 防护比例(不超过danger_at)",
+                policy.effective_max_ratio * 100.0
+            ),
+        ));
+    }
+
+    // 内存划分与负载类型明显不符: 大文件主要消耗直接内存，小文件主要消耗堆
+    let direct_heap_ratio = direct_mem_gb / heap_mem_gb;
+    if args.avg_file_size > 100.0 && direct_heap_ratio < 0.3 {
+        recommendations.push(Recommendation::new(
+            Severity::Warning,
+            "内存划分不匹配: 大文件负载的直接内存占比过低，建议提高 --direct-ratio",
+            format!(
+                "avg_file_size={:.0}MB > 100MB，直接内存/堆={direct_heap_ratio:.2} < 0.3",
+                args.avg_file_size
+            ),
+        ));
+    } else if args.avg_file_size <= 1.0 && direct_heap_ratio > 1.0 {
+        recommendations.push(Recommendation::new(
+            Severity::Warning,
+            "内存划分不匹配: 小文件负载的直接内存多于堆，建议提高 --heap-ratio",
+            format!(
+                "avg_file_size={:.1}MB <= 1MB，直接内存/堆={direct_heap_ratio:.2} > 1.0",
+                args.avg_file_size
+            ),
+        ));
+    }
+
+    // 经验公式: 连接池大小 ≈ CPU核心数 × 2 + 磁盘数
+    let suggested_pool = args.cpu_cores * 2 + 1;
+    if args.db_pool_size > suggested_pool * 2 {
+        recommendations.push(Recommendation::new(
+            Severity::Warning,
+            format!(
+                "数据库连接池过大: {}个连接远超{}核CPU可有效利用的数量，建议约{}个",
+                args.db_pool_size, args.cpu_cores, suggested_pool
+            ),
+            format!(
+                "db_pool_size={} > 2 × (cpu_cores×2+1) = {}",
+                args.db_pool_size,
+                suggested_pool * 2
+            ),
+        ));
+    }
+
+    if args.network_storage {
+        recommendations.push(Recommendation::new(
+            Severity::Warning,
+            "网络存储: 单连接IOPS估算基于本地磁盘模型，实际延迟更高，估算偏乐观",
+            "指定了 --network-storage",
+        ));
+    }
+
+    // 小文件写入的元数据和日志开销占比高，标称磁盘性能明显高估实际容量
+    if args.avg_file_size <= SMALL_FILE_WRITE_AMPLIFICATION_MB && args.write_amplification.is_none()
+    {
+        recommendations.push(Recommendation::new(
+            Severity::Info,
+            format!(
+                "小文件写放大: 建议以 --write-amplification {SUGGESTED_SMALL_FILE_WRITE_AMPLIFICATION} 重新评估磁盘容量"
+            ),
+            format!(
+                "avg_file_size={:.1}MB <= {SMALL_FILE_WRITE_AMPLIFICATION_MB}MB，未指定 --write-amplification",
+                args.avg_file_size
+            ),
+        ));
+    }
+
+    if args.avg_file_size > 50.0 {
+        recommendations.push(Recommendation::new(
+            Severity::Info,
+            "优化大文件处理: 使用分块上传和内存映射文件",
+            format!("avg_file_size={:.1}MB > 50MB", args.avg_file_size),
+        ));
+    }
+
+    // 增强长期运行评估和建议
+    // 短连接周转时过早晋升的对象使老年代额外增长
+    let gc = gc_pressure(args, heap_mem_gb);
+    let promoted_gb = gc.map_or(0.0, |gc| gc.promoted_gb_per_hour);
+    let heap_growth_rate = normal_heap_usage * 0.05 + promoted_gb; // 假设每小时堆增长5%
+    let oom_hours = ((heap_mem_gb * 0.9 - normal_heap_usage) / heap_growth_rate).max(0.0);
+
+    recommendations.push(Recommendation::new(
+        Severity::Info,
+        format!("内存泄漏评估: 当前配置可能在{oom_hours:.1}小时后发生OOM"),
+        format!(
+            "(堆{heap_mem_gb:.1}GB×0.9 - 正常堆使用{normal_heap_usage:.2}GB) / 每小时增长{heap_growth_rate:.3}GB(按5%/h估算{})",
+            if promoted_gb > 0.0 {
+                format!("，含连接周转晋升{promoted_gb:.3}GB")
+            } else {
+                String::new()
+            }
+        ),
+    ));
+    if let Some(gc) = gc {
+        recommendations.push(Recommendation::new(
+            if gc.young_gc_per_min > 60.0 { Severity::Warning } else { Severity::Info },
+            format!(
+                "预计GC频率: 每分钟约{:.1}次young GC，占用{:.2}核CPU",
+                gc.young_gc_per_min, gc.gc_cores
+            ),
+            format!(
+                "每秒周转{:.0}个连接 × {CHURN_GARBAGE_PER_CONN_MB}MB = {:.0}MB/s分配速率，年轻代按堆的1/3估算",
+                gc.churn_per_sec, gc.alloc_mb_per_sec
+            ),
+        ));
+    }
+    for summary in [
+        "长期运行监控: 实时监控堆/直接内存的增长率",
+        "长期运行监控: 启用GC日志分析，建议使用Prometheus+Grafana监控",
+        "长期运行监控: 设置-XX:+HeapDumpOnOutOfMemoryError启用堆转储",
+    ] {
+        recommendations.push(
+            Recommendation::new(Severity::Info, summary, "长期运行服务的通用建议")
+                .with_topic("长期运行监控"),
+        );
+    }
+
+    if oom_hours < 24.0 {
+        warnings.push(Warning::new(
+            Severity::Critical,
+            "紧急: 内存泄漏风险高，需要立即优化",
+            format!("预计{oom_hours:.1}小时后OOM < 24小时"),
+        ));
+    }
+
+    // 未显式限制直接内存时上限跟随-Xmx，运维按"堆 + 少量直接内存"规划的内存会被低估
+    if args.no_explicit_direct {
+        warnings.push(Warning::new(
+            Severity::Warning,
+            format!(
+                "直接内存未显式限制: 默认上限约等于堆({heap_mem_gb:.1}GB)，建议显式设置 -XX:MaxDirectMemorySize"
+            ),
+            format!(
+                "--no-explicit-direct: 堆{heap_mem_gb:.1}GB + 直接内存{direct_mem_gb:.1}GB，直接内存按HotSpot默认值计算"
+            ),
+        ));
+    }
+
+    // 元空间被上限截断时，报告中的推荐值会低于实际需求
+    let metaspace = calculate_metaspace_detailed(args);
+    if metaspace.ceiling_hit {
+        warnings.push(Warning::new(
+            Severity::Critical,
+            format!(
+                "元空间不足: 计算需求{:.0}MB超过上限{}MB，建议拆分服务或用 --max-metaspace-mb 提高上限",
+                metaspace.uncapped_mb, metaspace.final_mb
+            ),
+            format!(
+                "基础{:.0}MB + 连接{:.0}MB + 文件{:.0}MB，乘以安全边际{:.1}",
+                metaspace.base,
+                metaspace.connection_factor,
+                metaspace.file_size_factor,
+                metaspace.safety_margin
+            ),
+        ));
+    }
+
+    // glibc的arena数随核心数增长，多核机器上限制arena数可显著降低Native内存
+    let uses_glibc = !matches!(args.native_allocator.as_str(), "jemalloc" | "tcmalloc");
+    if uses_glibc && args.cpu_cores > RECOMMENDED_ARENA_MAX {
+        recommendations.push(Recommendation::new(
+            Severity::Info,
+            format!(
+                "限制glibc malloc arena: 设置环境变量 MALLOC_ARENA_MAX={RECOMMENDED_ARENA_MAX}，或改用jemalloc"
+            ),
+            format!(
+                "glibc默认最多{}个arena({}核×8)，按每个{GLIBC_ARENA_RSS_MB}MB碎片估算约{:.0}MB",
+                args.cpu_cores * GLIBC_ARENAS_PER_CORE as usize,
+                args.cpu_cores,
+                allocator_overhead_gb(args) * 1024.0
+            ),
+        ));
+    }
+
+    // 内核socket缓冲区在JVM之外，可能使整机内存超出物理内存
+    let memory_budget = calculate_memory_budget(args, direct_mem_gb, heap_mem_gb);
+    if memory_budget.over_committed {
+        warnings.push(Warning::new(
+            Severity::Critical,
+            format!(
+                "内存超额: 堆+直接内存+Native预留+分配器+socket缓冲区合计{:.1}GB超过服务器内存{:.1}GB，突发时可能触发swap",
+                memory_budget.total_gb,
+                args.effective_total_ram()
+            ),
+            format!(
+                "{:.1} + {:.1} + {:.1} + {:.1} + {:.1} = {:.1}GB > total_ram={:.1}GB",
+                memory_budget.heap_gb,
+                memory_budget.direct_gb,
+                memory_budget.native_reserved_gb,
+                memory_budget.allocator_gb,
+                memory_budget.socket_buffer_gb,
+                memory_budget.total_gb,
+                args.effective_total_ram()
+            ),
+        ));
+    }
+
+    // 保留内存是硬性下限，比例分配的最小值下限挤占它时已缩减堆和直接内存
+    if let Ok(Some((requested_gb, allowed_gb))) = crate::analysis::free_memory_floor_excess(args) {
+        warnings.push(Warning::new(
+            Severity::Warning,
+            format!(
+                "保留内存不足: 堆+直接内存{requested_gb:.1}GB超出保留{}GB空闲内存后的可用{allowed_gb:.1}GB，已按比例缩减",
+                args.min_free_memory_gb.unwrap_or(0.0)
+            ),
+            format!(
+                "{:.1}GB - 保留{}GB - 元空间、代码缓存及Native预留{:.1}GB = {allowed_gb:.1}GB",
+                args.effective_total_ram(),
+                args.min_free_memory_gb.unwrap_or(0.0),
+                crate::analysis::non_heap_reserved_gb(args).unwrap_or(0.0)
+            ),
+        ));
+    }
+
+    // 突发型实例的物理内存可能被宿主机超售，预先触碰堆页面可在启动时暴露不足
+    if let Some(ratio) = args.overcommit_ratio.filter(|&r| r < 1.0) {
+        warnings.push(Warning::new(
+            Severity::Warning,
+            format!(
+                "内存超售: 仅按标称内存的{:.0}%({:.1}GB)分配，建议设置 -XX:+AlwaysPreTouch 在启动时预先分配物理页",
+                ratio * 100.0,
+                args.effective_total_ram()
+            ),
+            format!(
+                "total_ram={:.1}GB × overcommit_ratio={ratio}，首次访问时缺页可能因宿主机内存不足而卡顿或被OOM",
+                args.total_ram
+            ),
+        ));
+    }
+
+    // 文件描述符上限往往先于内存和CPU耗尽，表现为 Too many open files
+    let fds = required_fds(args);
+    if let Some(limit) = args.ulimit_nofile.filter(|&limit| limit < fds) {
+        warnings.push(Warning::new(
+            Severity::Critical,
+            format!(
+                "文件描述符不足: 突发时约需{fds}个，超过ulimit -n={limit}，建议设置 LimitNOFILE={fds} 或 ulimit -n {fds}"
+            ),
+            format!(
+                "(突发连接{} + 空闲连接{} + 连接池{}) × {FDS_PER_CONNECTION} + {BASE_FDS} = {fds} > {limit}",
+                args.burst_connections(),
+                args.idle_connections,
+                args.db_pool_size
+            ),
+        ));
+    }
+
+    // 每核心连接数远超模型假设时，CPU不足应在瓶颈计算之前醒目提示
+    let per_core = args.expected_connections as f64 / args.cpu_cores.max(1) as f64;
+    let ceiling = connections_per_core_ceiling(args);
+    if per_core > ceiling {
+        let switch_pct = context_switch_cpu_ratio(per_core) * 100.0;
+        warnings.push(Warning::new(
+            if per_core > ceiling * 2.0 { Severity::Critical } else { Severity::Warning },
+            format!(
+                "CPU核心数不足: 每核{per_core:.0}个连接，超过模型假设的每核{ceiling:.0}个，仅上下文切换就约占每核{switch_pct:.0}%的CPU"
+            ),
+            format!(
+                "expected_connections={} / cpu_cores={}；按每连接每秒{WAKEUPS_PER_CONN_PER_SEC}次唤醒 × {CONTEXT_SWITCH_US}µs估算上下文切换开销",
+                args.expected_connections, args.cpu_cores
+            ),
+        ));
+    }
+
+    if args.zero_copy && !zero_copy_applies(args) {
+        warnings.push(Warning::new(
+            Severity::Info,
+            "零拷贝未计入内存模型: 仅非TLS的下载负载可绕过用户态读缓冲区",
+            format!(
+                "zero_copy=true, workload={}, tls={}",
+                args.workload, args.tls
+            ),
+        ));
+    }
+
+    // 计算理论极限
+    let theoretical_limits = calculate_theoretical_limits(
+        args,
+        direct_mem_gb,
+        heap_mem_gb,
+        normal_direct_usage,
+        normal_heap_usage,
+    );
+
+    // 持续高峰: 内存之外还需CPU、磁盘、GC等稳态资源长时间承载，紧随瞬时突发之后列出
+    if let Some(factor) = args.sustained_burst_factor {
+        scenarios.insert(
+            burst_index + 1,
+            sustained_peak_scenario(
+                args,
+                direct_mem_gb,
+                heap_mem_gb,
+                factor,
+                theoretical_limits.steady_state_max,
+                policy,
+            ),
+        );
+    }
+
+    // 场景5: 读写混合，双向同步服务的最坏情况
+    scenarios.push(mixed_read_write_scenario(
+        args,
+        direct_mem_gb,
+        transfer_heap_gb,
+        normal_heap_usage,
+        theoretical_limits.utilization.disk_pct,
+        policy,
+    ));
+
+    // 复制开销可能使瓶颈转移到磁盘或网络
+    if replication_factor(&args.replication) > 1.0 {
+        let without = calculate_theoretical_limits(
+            &Args {
+                replication: "none".to_string(),
+                ..args.clone()
+            },
+            direct_mem_gb,
+            heap_mem_gb,
+            normal_direct_usage,
+            normal_heap_usage,
+        );
+        let limiting = &theoretical_limits.limiting_factor;
+        let summary = if *limiting != without.limiting_factor {
+            format!(
+                "已计入{}复制开销: 瓶颈由{}转移到{limiting}，最大连接数 {} -> {}",
+                args.replication,
+                without.limiting_factor,
+                without.max_connections,
+                theoretical_limits.max_connections
+            )
+        } else {
+            format!(
+                "已计入{}复制开销: 瓶颈仍为{limiting}，最大连接数 {} -> {}",
+                args.replication, without.max_connections, theoretical_limits.max_connections
+            )
+        };
+        recommendations.push(Recommendation::new(
+            Severity::Info,
+            summary,
+            format!(
+                "--replication {}: 每连接磁盘写入和网络出口按{:.1}倍计算",
+                args.replication,
+                replication_factor(&args.replication)
+            ),
+        ));
+    }
+
+    // 建连速率与稳态连接数无关: 连接集中到达时握手开销可能先耗尽CPU
+    if let Some(arrival_rate) = args.arrival_rate {
+        let max_rate = theoretical_limits.max_accept_rate;
+        let burst_rate = arrival_rate * args.burst_factor.max(1.0);
+        let rationale = format!(
+            "arrival_rate={arrival_rate:.0}/s × burst_factor={:.1} = {burst_rate:.0}/s，{}单核{:.0}次/秒估算上限{max_rate:.0}/s",
+            args.burst_factor.max(1.0),
+            if args.tls { "TLS握手" } else { "TCP建连" },
+            accepts_per_core(args.tls)
+        );
+        if arrival_rate > max_rate {
+            warnings.push(Warning::new(
+                Severity::Critical,
+                format!(
+                    "建连速率超限: 正常负载每秒{arrival_rate:.0}个新连接已超过CPU可承受的{max_rate:.0}个/秒"
+                ),
+                rationale,
+            ));
+        } else if burst_rate > max_rate {
+            recommendations.push(Recommendation::new(
+                Severity::Warning,
+                format!(
+                    "突发建连速率超限: 突发时每秒约{burst_rate:.0}个新连接，超过CPU可承受的{max_rate:.0}个/秒，建议启用连接复用{}或在负载均衡层限流",
+                    if args.tls { "/TLS会话恢复" } else { "" }
+                ),
+                rationale,
+            ));
+        }
+    }
+
+    let (meets_target, shortfall_pct) = capacity_verdict(
+        args.expected_connections,
+        theoretical_limits.max_connections,
+    );
+
+    let resource_risks = theoretical_limits.utilization.risk_levels(policy);
+
+    SafetyAnalysis {
+        heap_safety,
+        direct_mem_safety,
+        risk_level,
+        resource_risks,
+        scenarios,
+        recommendations: prioritize_recommendations(recommendations),
+        warnings,
+        theoretical_limits,
+        memory_budget,
+        meets_target,
+        shortfall_pct,
+    }
+}
+
+/// 计算理论极限值(基于JVM推荐配置和6-12个月稳定运行目标)
+fn calculate_theoretical_limits(
+    args: &Args,
+    direct_mem_gb: f64,
+    heap_mem_gb: f64,
+    normal_direct_usage: f64,
+    normal_heap_usage: f64,
+) -> TheoreticalLimits {
+    // 基于JVM推荐配置的资源消耗模型
+    const METASPACE_PER_CONN: f64 = 64.0 / 1024.0; // 64KB/连接
+    const NET_PER_CONN: f64 = 0.2; // 每个连接平均带宽(Mbps)
+    const DISK_IO_PER_CONN: f64 = 0.15; // 每个连接IOPS需求
+    const NETWORK_STORAGE_RTT_MS: f64 = 1.0; // 网络存储单次IO往返延迟(ms)
+    const NETWORK_STORAGE_IO_DEPTH: f64 = 64.0; // 网络存储并发IO深度
+
+    // 长期稳定性系数
+    const STABILITY_FACTOR: f64 = 0.6; // 只使用60%资源保证长期稳定
+    const SAFE_MEM_USAGE: f64 = 0.7; // 更保守的内存使用阈值
+
+    // 1. 计算各维度极限(考虑突发流量)
+    let burst_connections = args.burst_connections();
+
+    // 动态计算每个连接的直接内存需求
+    let (read_buffer, write_buffer) = calculate_direct_mem_per_conn(args);
+    let direct_mem_per_conn = read_buffer + write_buffer;
+
+    // 内存限制(基于动态计算)
+    let max_by_direct = if args.enable_memory_mapping && args.avg_file_size > 100.0 {
+        // 内存映射优化可支持更多连接
+        ((direct_mem_gb * SAFE_MEM_USAGE) / (direct_mem_per_conn * 0.7) * STABILITY_FACTOR) as usize
+    } else {
+        ((direct_mem_gb * SAFE_MEM_USAGE) / direct_mem_per_conn * STABILITY_FACTOR) as usize
+    };
+    let transfer_heap_gb = (heap_mem_gb - db_pool_heap_gb(args)).max(0.0);
+    let max_by_heap =
+        ((transfer_heap_gb * SAFE_MEM_USAGE) / heap_per_conn(args) * STABILITY_FACTOR) as usize;
+
+    // 元空间限制(基于动态计算结果)
+    let metaspace_size_mb = calculate_metaspace(args) as f64;
+    let max_by_metaspace = ((metaspace_size_mb * 1024.0 * 1024.0)
+        / (METASPACE_PER_CONN * args.expected_connections as f64)
+        * STABILITY_FACTOR) as usize;
+
+    // CPU限制(考虑上下文切换开销)
+    // 数据库连接池线程占用的CPU从可用核心中扣除
+    // 短连接周转引发的young GC同样从可用核心中扣除
+    let transfer_cores = (args.cpu_cores as f64
+        - args.db_pool_size as f64 * DB_POOL_CPU_PER_CONN
+        - churn_gc_cores(args))
+    .max(0.0);
+    // 文件校验按每连接的传输字节额外消耗CPU
+    let cpu_per_conn = CPU_PER_CONN * offload_cpu_factor(&args.offload)
+        + checksum_cpu_cores(args, NET_PER_CONN / 8.0);
+    let max_by_cpu =
+        ((transfer_cores * cpu_efficiency(args) / cpu_per_conn) * STABILITY_FACTOR) as usize;
+
+    // 建连速率: 与稳态连接数正交，只取决于accept/握手的CPU开销
+    let max_accept_rate = transfer_cores * accepts_per_core(args.tls) * STABILITY_FACTOR;
+
+    // 复制到备机时每连接的网络出口和磁盘写入需求按倍数放大
+    let replication = replication_factor(&args.replication);
+
+    // 网络限制
+    let max_by_net = ((directional_net_gbps(args) * 1000.0 / (NET_PER_CONN * replication))
+        * STABILITY_FACTOR) as usize;
+
+    // 磁盘IO限制(基于SSD性能模型，分层存储时按命中率混合)
+    let effective_disk = config::effective_disk(args).ok();
+    // IO线程数有限时磁盘达不到标称IOPS
+    let disk_queue = disk_queue_model(args);
+    let disk_iops = disk_queue.map_or_else(
+        || effective_disk.map_or(200.0, |disk| disk.iops),
+        |queue| queue.achievable_iops,
+    );
+    // 页缓存命中的读取不访问磁盘，写放大使每次逻辑写入消耗多次物理IO
+    let page_cache = page_cache_model(args);
+    let disk_io_per_conn = DISK_IO_PER_CONN * disk_io_factor(args);
+    let max_by_disk = ((disk_iops / (disk_io_per_conn * replication)) * STABILITY_FACTOR) as usize;
+
+    // 网络存储(NFS等)每次IO在后端服务时间之外还需要一次网络往返:
+    // 并发IO深度不变时，可达IOPS = 深度 / (深度 / 后端IOPS + 往返延迟)，
+    // 即后端磁盘的IOPS按往返延迟折减
+    let max_by_storage_latency = if args.network_storage {
+        let service_secs = NETWORK_STORAGE_IO_DEPTH / disk_iops;
+        let latency_iops =
+            NETWORK_STORAGE_IO_DEPTH / (service_secs + NETWORK_STORAGE_RTT_MS / 1000.0);
+        ((latency_iops / (disk_io_per_conn * replication)) * STABILITY_FACTOR) as usize
+    } else {
+        usize::MAX
+    };
+
+    log::debug!(
+        "各维度连接上限: 直接内存={max_by_direct}, 堆={max_by_heap}, 元空间={max_by_metaspace}, CPU={max_by_cpu}, 网络={max_by_net}, 磁盘={max_by_disk}, 突发需求={burst_connections}"
+    );
+    if args.network_storage {
+        log::debug!("网络存储延迟上限: {max_by_storage_latency}");
+    }
+
+    // 稳态极限(取各资源维度最小值，考虑JVM各维度限制)
+    let steady_state_max = max_by_direct
+        .min(max_by_heap)
+        .min(max_by_metaspace)
+        .min(max_by_cpu)
+        .min(max_by_net)
+        .min(max_by_disk)
+        .min(max_by_storage_latency);
+
+    // 综合极限: 只需评估到突发需求为止，超出部分视为余量
+    let max_connections = steady_state_max.min(burst_connections);
+    let burst_bound = burst_connections < steady_state_max;
+
+    // 2. 计算可持续吞吐量(考虑长期负载均衡)，与性能分析共用同一吞吐量模型
+    let (sustainable_throughput, throughput_limit) = throughput_model(args).ceiling();
+
+    // 3. 长期运行评估(6-12个月)
+    let uptime_category = if max_connections >= burst_connections * 2 {
+        "12个月+ (弹性充足)"
+    } else if max_connections >= burst_connections {
+        "6-12个月 (满足需求)"
+    } else {
+        "<6个月 (需扩容)"
+    };
+
+    // 4. 确定瓶颈资源
+    let steady_state_limit = if steady_state_max == max_by_direct {
+        Bottleneck::DirectMemory
+    } else if steady_state_max == max_by_heap {
+        Bottleneck::Heap
+    } else if steady_state_max == max_by_metaspace {
+        Bottleneck::Metaspace
+    } else if steady_state_max == max_by_cpu {
+        Bottleneck::Cpu
+    } else if steady_state_max == max_by_net {
+        Bottleneck::Network
+    } else if steady_state_max == max_by_disk {
+        Bottleneck::Disk
+    } else {
+        Bottleneck::StorageLatency
+    };
+    // 受限于突发需求本身时，各资源均有余量，单独标注
+    let limiting_factor = if burst_bound {
+        "突发流量需求"
+    } else {
+        steady_state_limit.label()
+    };
+
+    // 5. 生成资源利用率分析(包含JVM维度)
+    let utilization = ResourceUtilization {
+        heap_pct: ((normal_heap_usage + db_pool_heap_gb(args)) / (heap_mem_gb * SAFE_MEM_USAGE)
+            * 100.0)
+            .min(100.0),
+        db_pool_pct: (db_pool_heap_gb(args) / (heap_mem_gb * SAFE_MEM_USAGE) * 100.0).min(100.0),
+        direct_pct: (normal_direct_usage / (direct_mem_gb * SAFE_MEM_USAGE) * 100.0).min(100.0),
+        metaspace_pct: (args.expected_connections as f64 * METASPACE_PER_CONN * 100.0
+            / (metaspace_size_mb * 1024.0 * 1024.0))
+            .min(100.0),
+        cpu_pct: (args.expected_connections as f64 / max_by_cpu as f64 * 100.0).min(100.0),
+        network_pct: (args.expected_connections as f64 / max_by_net as f64 * 100.0).min(100.0),
+        disk_pct: (args.expected_connections as f64
+            / max_by_disk.min(max_by_storage_latency) as f64
+            * 100.0)
+            .min(100.0),
+    };
+
+    TheoreticalLimits {
+        max_connections,
+        steady_state_max,
+        steady_state_limit,
+        burst_bound,
+        max_throughput: sustainable_throughput,
+        throughput_limit: throughput_limit.to_string(),
+        estimated_uptime: uptime_category.to_string(),
+        limiting_factor: limiting_factor.to_string(),
+        // 突发只持续很短时间，不需要为长期稳定预留40%余量，
+        // 故去掉稳定性系数: 该值是短时峰值上限，而非可持续的连接数
+        burst_capacity: (max_connections as f64 / STABILITY_FACTOR) as usize,
+        required_fds: required_fds(args),
+        disk_queue,
+        page_cache,
+        max_accept_rate,
+        utilization,
+    }
+}
+
+/// 比较预期连接数与理论最大连接数，返回 (是否满足, 资源缺口百分比)
+///
+/// 缺口按 `目标 / 最大值 - 1` 计算，满足目标时为0
+fn capacity_verdict(target: usize, max_connections: usize) -> (bool, f64) {
+    if target <= max_connections {
+        (true, 0.0)
+    } else {
+        let shortfall = (target as f64 / max_connections.max(1) as f64 - 1.0) * 100.0;
+        (false, shortfall)
+    }
+}
+
+/// 内存达到危险阈值的模拟场景
+pub struct MemoryFitFailure {
+    pub scenario: String,   // 场景名称
+    pub pool: &'static str, // 达到危险阈值的内存区域
+    pub ratio: f64,         // 该区域占有效上限的比例，超过1即超出有效上限
+}
+
+/// 找出堆或直接内存达到 `danger_at` 的模拟场景(含突发、小文件高并发等)
+///
+/// 两个内存区域分别判定: 场景状态要求两者都达到危险才标记为危险，
+/// 而任一区域超出上限都会导致OOM，因此比场景状态和整体风险等级更严格
+pub fn memory_fit_failures(
+    args: &Args,
+    direct_mem_gb: f64,
+    heap_mem_gb: f64,
+    scenarios: &[Scenario],
+) -> Vec<MemoryFitFailure> {
+    let policy = SafetyPolicy::from_args(args);
+    let transfer_heap_gb = (heap_mem_gb - db_pool_heap_gb(args)).max(0.0);
+    scenarios
+        .iter()
+        .flat_map(|scenario| {
+            [
+                ("堆内存", scenario.heap_usage, transfer_heap_gb),
+                ("直接内存", scenario.direct_mem_usage, direct_mem_gb),
+            ]
+            .into_iter()
+            .filter(|&(_, usage, max)| usage_status(usage, max, &policy) == ScenarioStatus::Danger)
+            .map(|(pool, usage, max)| MemoryFitFailure {
+                scenario: scenario.name.clone(),
+                pool,
+                ratio: usage / (max * policy.effective_max_ratio),
+            })
+        })
+        .collect()
+}
+
+fn status_label(
+    heap_usage: f64,
+    heap_max: f64,
+    direct_usage: f64,
+    direct_max: f64,
+    policy: &SafetyPolicy,
+) -> ScenarioStatus {
+    // 考虑JVM自身开销(15%)和长期运行余量(15%)
+    let effective_heap_max = heap_max * policy.effective_max_ratio;
+    let effective_direct_max = direct_max * policy.effective_max_ratio;
 
     let heap_ratio = heap_usage / effective_heap_max;
     let direct_ratio = direct_usage / effective_direct_max;
 
-    match (heap_ratio, direct_ratio) {
-        (h, d) if h < 0.6 && d < 0.6 => "✅ 安全".green().to_string(),
-        (h, d) if h < 0.8 || d < 0.8 => "⚠️ 警告".yellow().to_string(),
-        _ => "🔥 危险".red().to_string(),
+    match (heap_ratio, direct_ratio) {
+        (h, d) if h < policy.warn_at && d < policy.warn_at => ScenarioStatus::Safe,
+        (h, d) if h < policy.danger_at || d < policy.danger_at => ScenarioStatus::Warning,
+        _ => ScenarioStatus::Danger,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::args::Args;
+
+    #[test]
+    fn test_calculate_safety() {
+        let args = Args {
+            total_ram: 16.0,
+            cpu_cores: 8,
+            net_gbps: 1.0,
+            disk_type: "sata_ssd".to_string(),
+            expected_connections: 1000,
+            burst_factor: 2.0,
+            avg_file_size: 5.0,
+            enable_memory_guard: true,
+            enable_memory_mapping: false,
+            complexity: "medium".to_string(),
+            generate_markdown: false,
+            ..Default::default()
+        };
+        let safety = calculate_safety(&args, 4.0, 12.0);
+        assert!(safety.heap_safety > 0.0, "Heap safety should be positive");
+        assert!(
+            safety.direct_mem_safety > 0.0,
+            "Direct memory safety should be positive"
+        );
+        assert!(!safety.scenarios.is_empty(), "Should generate scenarios");
+        assert!(
+            !safety.recommendations.is_empty(),
+            "Should generate recommendations"
+        );
+    }
+
+    #[test]
+    fn test_socket_buffers_can_overcommit_memory() {
+        let args = Args {
+            total_ram: 16.0,
+            cpu_cores: 16,
+            net_gbps: 10.0,
+            disk_type: "nvme".to_string(),
+            expected_connections: 100_000,
+            burst_factor: 3.0,
+            avg_file_size: 1.0,
+            complexity: "medium".to_string(),
+            ..Default::default()
+        };
+        let without_sockets = calculate_safety(&args, 2.0, 8.0).memory_budget;
+        assert_eq!(without_sockets.socket_buffer_gb, 0.0);
+        assert!(!without_sockets.over_committed);
+
+        let with_sockets = calculate_safety(
+            &Args {
+                socket_buffer_kb: 128,
+                ..args
+            },
+            2.0,
+            8.0,
+        );
+        // 300k connections * 128KB ≈ 36.6GB
+        assert!(with_sockets.memory_budget.socket_buffer_gb > 36.0);
+        assert!(with_sockets.memory_budget.over_committed);
+        assert!(
+            with_sockets
+                .warnings
+                .iter()
+                .any(|w| w.summary.contains("内存超额") && w.severity == Severity::Critical)
+        );
+    }
+
+    #[test]
+    fn test_throughput_bound_by_network() {
+        let args = Args {
+            total_ram: 64.0,
+            cpu_cores: 64,
+            net_gbps: 1.0,
+            disk_type: "nvme".to_string(),
+            expected_connections: 1000,
+            burst_factor: 2.0,
+            avg_file_size: 5.0,
+            complexity: "medium".to_string(),
+            ..Default::default()
+        };
+        let limits = calculate_safety(&args, 8.0, 20.0).theoretical_limits;
+        assert_eq!(limits.max_throughput, 125.0);
+        assert_eq!(limits.throughput_limit, "网络带宽");
+
+        let limits = calculate_safety(
+            &Args {
+                net_gbps: 100.0,
+                disk_type: "sata_hdd".to_string(),
+                workload: "upload".to_string(),
+                ..args.clone()
+            },
+            8.0,
+            20.0,
+        )
+        .theoretical_limits;
+        assert_eq!(limits.max_throughput, 100.0);
+        assert_eq!(limits.throughput_limit, "磁盘IO");
+
+        // SLC缓存写满后的持续写入速度决定长期吞吐量
+        let limits = calculate_safety(
+            &Args {
+                net_gbps: 100.0,
+                sustained_write_speed: Some(200.0),
+                workload: "upload".to_string(),
+                ..args.clone()
+            },
+            8.0,
+            20.0,
+        )
+        .theoretical_limits;
+        assert_eq!(limits.max_throughput, 200.0);
+        assert_eq!(limits.throughput_limit, "磁盘IO");
+
+        // 读写按比例加权: 读取按读取速度，只有写入按写放大折算
+        let amplified = |workload: &str| {
+            calculate_safety(
+                &Args {
+                    net_gbps: 100.0,
+                    disk_type: "sata_hdd".to_string(),
+                    workload: workload.to_string(),
+                    write_amplification: Some(4.0),
+                    ..args.clone()
+                },
+                8.0,
+                20.0,
+            )
+            .theoretical_limits
+            .max_throughput
+        };
+        assert_eq!(amplified("download"), 120.0);
+        assert_eq!(amplified("upload"), 25.0);
+        assert!((amplified("mixed") - 1.0 / (0.5 / 120.0 + 0.5 * 4.0 / 100.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_tiered_disk_blends_iops() {
+        let args = Args {
+            total_ram: 64.0,
+            cpu_cores: 1000,
+            net_gbps: 100.0,
+            disk_type: "nvme".to_string(),
+            expected_connections: 1000,
+            burst_factor: 2.0,
+            avg_file_size: 5.0,
+            complexity: "medium".to_string(),
+            workload: "upload".to_string(),
+            ..Default::default()
+        };
+        let tiered = Args {
+            hot_disk: Some("nvme".to_string()),
+            cold_disk: Some("sata_hdd".to_string()),
+            hot_ratio: Some(0.5),
+            ..args.clone()
+        };
+        let single = calculate_safety(&args, 8.0, 20.0).theoretical_limits;
+        let blended = calculate_safety(&tiered, 8.0, 20.0).theoretical_limits;
+        assert_eq!(single.max_throughput, 1200.0);
+        assert_eq!(blended.max_throughput, 1200.0 * 0.5 + 100.0 * 0.5);
+        assert!(blended.utilization.disk_pct > single.utilization.disk_pct);
+    }
+
+    #[test]
+    fn test_prioritize_recommendations_merges_and_sorts() {
+        let recs = prioritize_recommendations(vec![
+            Recommendation::new(Severity::Info, "长期运行监控: 监控堆增长", "通用")
+                .with_topic("长期运行监控"),
+            Recommendation::new(
+                Severity::Info,
+                "网络存储: 估算偏乐观",
+                "指定了 --network-storage",
+            ),
+            Recommendation::new(
+                Severity::Info,
+                "网络存储: 估算偏乐观",
+                "指定了 --network-storage",
+            ),
+            Recommendation::new(Severity::Info, "长期运行监控: 启用GC日志", "通用")
+                .with_topic("长期运行监控"),
+            Recommendation::new(
+                Severity::Warning,
+                "增加堆内存: 8GB -> 9.6GB",
+                "heap_safety低",
+            ),
+            // 冒号前文字相同但未指定主题的建议不合并
+            Recommendation::new(Severity::Warning, "内存划分不匹配: 直接内存过低", "大文件"),
+            Recommendation::new(Severity::Warning, "内存划分不匹配: 直接内存过高", "小文件"),
+            // 主题与摘要前缀不一致时不截取摘要
+            Recommendation::new(Severity::Info, " 调优", "通用").with_topic("长期运行监控"),
+        ]);
+        let summaries: Vec<&str> = recs.iter().map(|r| r.summary.as_str()).collect();
+        assert_eq!(
+            summaries,
+            [
+                "增加堆内存: 8GB -> 9.6GB",
+                "内存划分不匹配: 直接内存过低",
+                "内存划分不匹配: 直接内存过高",
+                "长期运行监控: 监控堆增长；启用GC日志； 调优",
+                "网络存储: 估算偏乐观",
+            ]
+        );
+        assert_eq!(recs[3].rationale, "通用");
+    }
+
+    #[test]
+    fn test_io_threads_limit_achievable_iops() {
+        let args = |disk: &str, io_threads| Args {
+            disk_type: disk.to_string(),
+            io_threads,
+            ..Default::default()
+        };
+        assert_eq!(disk_queue_model(&args("nvme", None)), None);
+
+        // QD1时NVMe只有标称的3%，机械盘仍有70%
+        let nvme = disk_queue_model(&args("nvme", Some(1))).unwrap();
+        assert!((nvme.fraction() - 0.03).abs() < 1e-9);
+        let hdd = disk_queue_model(&args("sata_hdd", Some(1))).unwrap();
+        assert!((hdd.fraction() - 0.7).abs() < 1e-9);
+
+        // 队列足够深时达到标称值
+        let deep = disk_queue_model(&args("nvme", Some(256))).unwrap();
+        assert_eq!(deep.achievable_iops, 500_000.0);
+        let hdd_deep = disk_queue_model(&args("sata_hdd", Some(4))).unwrap();
+        assert_eq!(hdd_deep.achievable_iops, 200.0);
+
+        let limits = |io_threads| {
+            let args = Args {
+                total_ram: 64.0,
+                cpu_cores: 1000,
+                net_gbps: 100.0,
+                expected_connections: 1000,
+                burst_factor: 2.0,
+                avg_file_size: 5.0,
+                ..args("nvme", io_threads)
+            };
+            calculate_safety(&args, 8.0, 20.0).theoretical_limits
+        };
+        let unbounded = limits(None);
+        let shallow = limits(Some(4));
+        assert!(shallow.disk_queue.is_some());
+        assert!(shallow.utilization.disk_pct > unbounded.utilization.disk_pct);
+    }
+
+    #[test]
+    fn test_page_cache_relieves_disk_for_hot_working_set() {
+        let args = |working_set_gb, workload: &str| Args {
+            total_ram: 64.0,
+            cpu_cores: 16,
+            net_gbps: 10.0,
+            expected_connections: 1000,
+            burst_factor: 2.0,
+            avg_file_size: 5.0,
+            disk_type: "sata_hdd".to_string(),
+            workload: workload.to_string(),
+            working_set_gb,
+            ..Default::default()
+        };
+        assert_eq!(page_cache_model(&args(None, "download")), None);
+
+        // 工作集能放入空闲内存时全部命中，超出时按放入的比例命中
+        let hot = page_cache_model(&args(Some(1.0), "download")).unwrap();
+        assert_eq!(hot.hit_ratio, 1.0);
+        let cold = page_cache_model(&args(Some(hot.cache_gb * 4.0), "download")).unwrap();
+        assert!((cold.hit_ratio - 0.25).abs() < 1e-9);
+
+        // 只有读取被缓存，上传仍按全部IO落盘
+        assert!((hot.disk_io_factor("mixed") - 0.5).abs() < 1e-9);
+        assert_eq!(hot.disk_io_factor("upload"), 1.0);
+
+        let uncached = calculate_safety(&args(None, "download"), 8.0, 20.0).theoretical_limits;
+        let cached = calculate_safety(&args(Some(1.0), "download"), 8.0, 20.0).theoretical_limits;
+        assert_eq!(uncached.limiting_factor, "磁盘IO");
+        assert_ne!(cached.limiting_factor, "磁盘IO");
+        assert!(cached.utilization.disk_pct < uncached.utilization.disk_pct);
+        assert!(cached.page_cache.is_some());
+    }
+
+    #[test]
+    fn test_ip_stack_adds_socket_overhead() {
+        let args = Args {
+            total_ram: 64.0,
+            cpu_cores: 16,
+            expected_connections: 10_000,
+            burst_factor: 2.0,
+            socket_buffer_kb: 128,
+            ip_stack: "ipv4".to_string(),
+            ..Default::default()
+        };
+        let ipv4 = calculate_memory_budget(&args, 4.0, 16.0);
+        let dual = calculate_memory_budget(
+            &Args {
+                ip_stack: "dual".to_string(),
+                ..args.clone()
+            },
+            4.0,
+            16.0,
+        );
+        assert_eq!(ipv4.socket_buffer_gb, 20_000.0 * 128.0 / 1024.0 / 1024.0);
+        assert!(dual.socket_buffer_gb > ipv4.socket_buffer_gb);
+    }
+
+    #[test]
+    fn test_idle_connections_cost_heap_but_not_direct() {
+        let args = Args {
+            total_ram: 32.0,
+            cpu_cores: 16,
+            net_gbps: 1.0,
+            disk_type: "sata_ssd".to_string(),
+            expected_connections: 1000,
+            burst_factor: 3.0,
+            avg_file_size: 10.0,
+            socket_buffer_kb: 128,
+            complexity: "medium".to_string(),
+            ..Default::default()
+        };
+        let active_only = calculate_safety(&args, 2.56, 11.2);
+        let with_idle = calculate_safety(
+            &Args {
+                idle_connections: 50_000,
+                ..args.clone()
+            },
+            2.56,
+            11.2,
+        );
+        assert!(with_idle.heap_safety < active_only.heap_safety);
+        assert_eq!(with_idle.direct_mem_safety, active_only.direct_mem_safety);
+        assert!(
+            with_idle.memory_budget.socket_buffer_gb > active_only.memory_budget.socket_buffer_gb
+        );
+    }
+
+    #[test]
+    fn test_request_heap_lowers_heap_bound() {
+        let args = Args {
+            total_ram: 32.0,
+            cpu_cores: 64,
+            net_gbps: 100.0,
+            disk_type: "nvme".to_string(),
+            expected_connections: 1000,
+            burst_factor: 3.0,
+            avg_file_size: 10.0,
+            complexity: "medium".to_string(),
+            ..Default::default()
+        };
+        let base = calculate_safety(&args, 2.56, 1.0);
+        let header_heavy = calculate_safety(
+            &Args {
+                avg_request_heap_kb: 384.0,
+                ..args.clone()
+            },
+            2.56,
+            1.0,
+        );
+        assert!(header_heavy.heap_safety < base.heap_safety);
+        assert_eq!(header_heavy.direct_mem_safety, base.direct_mem_safety);
+        assert!(
+            header_heavy.theoretical_limits.utilization.heap_pct
+                > base.theoretical_limits.utilization.heap_pct
+        );
+        // 每连接堆开销翻倍，受堆内存限制的稳态极限随之减半
+        let heap_bound = &header_heavy.theoretical_limits;
+        assert_eq!(heap_bound.steady_state_limit, Bottleneck::Heap);
+        assert_eq!(
+            heap_bound.steady_state_max,
+            base.theoretical_limits.steady_state_max / 2
+        );
+    }
+
+    #[test]
+    fn test_memory_fit_failures_list_danger_scenarios() {
+        let args = Args {
+            total_ram: 32.0,
+            cpu_cores: 16,
+            net_gbps: 1.0,
+            disk_type: "sata_ssd".to_string(),
+            expected_connections: 1000,
+            burst_factor: 3.0,
+            avg_file_size: 10.0,
+            complexity: "medium".to_string(),
+            ..Default::default()
+        };
+        let roomy = calculate_safety(&args, 16.0, 11.2);
+        assert!(memory_fit_failures(&args, 16.0, 11.2, &roomy.scenarios).is_empty());
+
+        let tight = calculate_safety(&args, 0.2, 0.2);
+        let failures = memory_fit_failures(&args, 0.2, 0.2, &tight.scenarios);
+        let burst: Vec<&MemoryFitFailure> = failures
+            .iter()
+            .filter(|f| f.scenario == "突发流量 (3x)")
+            .collect();
+        assert_eq!(
+            burst.iter().map(|f| f.pool).collect::<Vec<_>>(),
+            ["堆内存", "直接内存"]
+        );
+        let scenario = tight
+            .scenarios
+            .iter()
+            .find(|s| s.name == "突发流量 (3x)")
+            .unwrap();
+        assert!((burst[1].ratio - scenario.direct_mem_usage / (0.2 * 0.7)).abs() < 1e-9);
+        assert!(burst[0].ratio > 1.0);
+
+        // 堆内存充足时场景只标记为警告，直接内存超出上限仍应失败
+        let direct_only = calculate_safety(&args, 0.2, 11.2);
+        let scenario = direct_only
+            .scenarios
+            .iter()
+            .find(|s| s.name == "突发流量 (3x)")
+            .unwrap();
+        assert_eq!(scenario.status, ScenarioStatus::Warning);
+        let failures = memory_fit_failures(&args, 0.2, 11.2, &direct_only.scenarios);
+        let burst = failures
+            .iter()
+            .find(|f| f.scenario == "突发流量 (3x)")
+            .unwrap();
+        assert_eq!(burst.pool, "直接内存");
+        assert!(burst.ratio > 1.0);
+        assert!(failures.iter().all(|f| f.pool == "直接内存"));
+    }
+
+    #[test]
+    fn test_tls_burst_arrival_rate_warns() {
+        let args = Args {
+            total_ram: 32.0,
+            cpu_cores: 16,
+            net_gbps: 1.0,
+            disk_type: "sata_ssd".to_string(),
+            expected_connections: 1000,
+            burst_factor: 3.0,
+            avg_file_size: 10.0,
+            complexity: "medium".to_string(),
+            arrival_rate: Some(5000.0),
+            ..Default::default()
+        };
+        let has_rate_warning = |safety: &SafetyAnalysis| {
+            safety
+                .recommendations
+                .iter()
+                .any(|r| r.summary.contains("建连速率超限"))
+        };
+
+        let plain = calculate_safety(&args, 2.56, 11.2);
+        assert_eq!(
+            plain.theoretical_limits.max_accept_rate,
+            16.0 * 20_000.0 * 0.6
+        );
+        assert!(!has_rate_warning(&plain));
+
+        let tls = calculate_safety(&Args { tls: true, ..args }, 2.56, 11.2);
+        assert_eq!(tls.theoretical_limits.max_accept_rate, 16.0 * 1_000.0 * 0.6);
+        let warning = tls
+            .recommendations
+            .iter()
+            .find(|r| r.summary.contains("建连速率超限"))
+            .unwrap();
+        assert_eq!(warning.severity, Severity::Warning);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::args::Args;
+    #[test]
+    fn test_memory_guard_fires_before_danger() {
+        use assert_approx_eq::assert_approx_eq;
+
+        let policy = SafetyPolicy::default();
+        let guard = memory_guard_thresholds(&Args::default(), &policy, 10.0, 10.0);
+        // 默认比例比danger_at(0.8)低0.05
+        assert_approx_eq!(guard.direct_gb, 10.0 * 0.7 * 0.75);
+        assert_approx_eq!(guard.heap_gb, 10.0 * 0.7 * 0.75);
+
+        let custom = Args {
+            guard_direct_pct: Some(0.5),
+            guard_heap_pct: Some(0.6),
+            ..Default::default()
+        };
+        let guard = memory_guard_thresholds(&custom, &policy, 10.0, 10.0);
+        assert_approx_eq!(guard.direct_gb, 3.5);
+        assert_approx_eq!(guard.heap_gb, 4.2);
+
+        // 指定的比例不低于danger_at时同样截断到danger_at之下
+        let at_danger = Args {
+            guard_direct_pct: Some(0.9),
+            guard_heap_pct: Some(0.8),
+            ..Default::default()
+        };
+        let guard = memory_guard_thresholds(&at_danger, &policy, 10.0, 10.0);
+        assert!(guard.direct_gb < 10.0 * 0.7 * policy.danger_at);
+        assert!(guard.heap_gb < 10.0 * 0.7 * policy.danger_at);
+    }
 
     #[test]
-    fn test_calculate_safety() {
+    fn test_capacity_verdict_boundary() {
+        assert_eq!(capacity_verdict(1000, 1000), (true, 0.0));
+        assert_eq!(capacity_verdict(999, 1000), (true, 0.0));
+        assert_eq!(capacity_verdict(1500, 1000), (false, 50.0));
+        assert!(!capacity_verdict(1, 0).0);
+    }
+
+    #[test]
+    fn test_tightest_resource() {
+        let utilization = ResourceUtilization {
+            heap_pct: 40.0,
+            db_pool_pct: 0.0,
+            direct_pct: 82.0,
+            metaspace_pct: 10.0,
+            cpu_pct: 82.0,
+            network_pct: 60.0,
+            disk_pct: 5.0,
+        };
+        // ties keep the first listed resource
+        assert_eq!(utilization.tightest(), ("直接内存", 82.0));
+    }
+
+    #[test]
+    fn test_glibc_arena_overhead_scales_with_cores() {
+        let glibc = Args {
+            cpu_cores: 32,
+            native_allocator: "glibc".to_string(),
+            ..Default::default()
+        };
+        let jemalloc = Args {
+            native_allocator: "jemalloc".to_string(),
+            ..glibc.clone()
+        };
+        assert_eq!(allocator_overhead_gb(&glibc), 0.5);
+        assert!(allocator_overhead_gb(&jemalloc) < allocator_overhead_gb(&glibc));
+    }
+
+    #[test]
+    fn test_safety_at_matches_calculate_safety() {
+        let args = Args {
+            total_ram: 16.0,
+            cpu_cores: 8,
+            net_gbps: 1.0,
+            disk_type: "sata_ssd".to_string(),
+            expected_connections: 3000,
+            burst_factor: 2.0,
+            avg_file_size: 10.0,
+            complexity: "medium".to_string(),
+            ..Default::default()
+        };
+        let full = calculate_safety(&args, 1.5, 5.0);
+        let (heap, direct, risk) = safety_at(&args, 1.5, 5.0, args.expected_connections);
+        assert_eq!(heap, full.heap_safety);
+        assert_eq!(direct, full.direct_mem_safety);
+        assert_eq!(risk, full.risk_level);
+
+        let (light_heap, _, _) = safety_at(&args, 1.5, 5.0, 100);
+        assert!(light_heap > heap);
+    }
+
+    #[test]
+    fn test_network_storage_latency_limit() {
+        let local = Args {
+            total_ram: 1024.0,
+            cpu_cores: 1000,
+            net_gbps: 100.0,
+            disk_type: "nvme".to_string(),
+            expected_connections: 100_000,
+            burst_factor: 3.0,
+            avg_file_size: 5.0,
+            complexity: "medium".to_string(),
+            ..Default::default()
+        };
+        let safety = calculate_safety(&local, 300.0, 400.0);
+        assert_ne!(safety.theoretical_limits.limiting_factor, "网络存储延迟");
+
+        let nfs = Args {
+            network_storage: true,
+            ..local
+        };
+        let safety = calculate_safety(&nfs, 300.0, 400.0);
+        assert_eq!(safety.theoretical_limits.limiting_factor, "网络存储延迟");
+
+        // 往返延迟折减后端磁盘的IOPS: 后端越快上限越高，但低于本地磁盘
+        let max = |disk_type: &str, network_storage| {
+            calculate_safety(
+                &Args {
+                    disk_type: disk_type.to_string(),
+                    network_storage,
+                    ..nfs.clone()
+                },
+                300.0,
+                400.0,
+            )
+            .theoretical_limits
+            .steady_state_max
+        };
+        assert!(max("sata_hdd", true) < max("nvme", true));
+        assert!(max("sata_hdd", true) < max("sata_hdd", false));
+    }
+
+    #[test]
+    fn test_default_policy_preserves_classification() {
+        let policy = SafetyPolicy::default();
+        let samples = [0.0, 0.3, 0.599, 0.601, 0.799, 0.801, 1.2];
+        for &h in &samples {
+            for &d in &samples {
+                // Reference: the thresholds hardcoded before SafetyPolicy existed
+                let expected = match (h, d) {
+                    (h, d) if h < 0.6 && d < 0.6 => ScenarioStatus::Safe,
+                    (h, d) if h < 0.8 || d < 0.8 => ScenarioStatus::Warning,
+                    _ => ScenarioStatus::Danger,
+                };
+                let label = status_label(h * 0.7, 1.0, d * 0.7, 1.0, &policy);
+                assert_eq!(label, expected, "heap ratio {h}, direct ratio {d}");
+            }
+        }
+
+        let args = Args {
+            total_ram: 16.0,
+            cpu_cores: 8,
+            net_gbps: 1.0,
+            disk_type: "sata_ssd".to_string(),
+            expected_connections: 8000,
+            burst_factor: 2.0,
+            avg_file_size: 50.0,
+            complexity: "medium".to_string(),
+            ..Default::default()
+        };
+        let with_default = calculate_safety_with_policy(&args, 1.5, 4.0, &policy);
+        let from_args = calculate_safety(&args, 1.5, 4.0);
+        assert_eq!(with_default.risk_level, from_args.risk_level);
+        assert_eq!(with_default.heap_safety, from_args.heap_safety);
+    }
+
+    #[test]
+    fn test_policy_rejects_inverted_thresholds() {
+        let policy = SafetyPolicy {
+            warn_at: 0.9,
+            danger_at: 0.8,
+            ..Default::default()
+        };
+        assert!(policy.validate().is_err());
+        assert!(SafetyPolicy::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_burst_bound_limit_reports_steady_state() {
+        let args = Args {
+            total_ram: 64.0,
+            cpu_cores: 16,
+            net_gbps: 10.0,
+            disk_type: "nvme".to_string(),
+            expected_connections: 100,
+            burst_factor: 2.0,
+            avg_file_size: 5.0,
+            complexity: "medium".to_string(),
+            ..Default::default()
+        };
+        let limits = calculate_safety(&args, 8.0, 20.0).theoretical_limits;
+        assert!(limits.burst_bound);
+        assert_eq!(limits.limiting_factor, "突发流量需求");
+        assert_eq!(limits.max_connections, 200);
+        assert!(limits.steady_state_max > limits.max_connections);
+    }
+
+    #[test]
+    fn test_db_pool_reduces_capacity() {
+        let args = Args {
+            total_ram: 16.0,
+            cpu_cores: 8,
+            net_gbps: 10.0,
+            disk_type: "nvme".to_string(),
+            expected_connections: 5000,
+            burst_factor: 3.0,
+            avg_file_size: 1.0,
+            complexity: "medium".to_string(),
+            ..Default::default()
+        };
+        let without_pool = calculate_safety(&args, 8.0, 4.0);
+        let with_pool = calculate_safety(
+            &Args {
+                db_pool_size: 200,
+                ..args.clone()
+            },
+            8.0,
+            4.0,
+        );
+        assert!(with_pool.heap_safety < without_pool.heap_safety);
+        assert!(with_pool.theoretical_limits.utilization.db_pool_pct > 0.0);
+        assert_eq!(without_pool.theoretical_limits.utilization.db_pool_pct, 0.0);
+        assert!(
+            with_pool
+                .recommendations
+                .iter()
+                .any(|r| r.summary.contains("数据库连接池过大"))
+        );
+    }
+
+    #[test]
+    fn test_warnings_sorted_by_severity() {
+        let mut safety = calculate_safety(&Args::default(), 2.0, 8.0);
+        safety.warnings = vec![
+            Warning::new(Severity::Warning, "w1", ""),
+            Warning::new(Severity::Critical, "c1", ""),
+            Warning::new(Severity::Warning, "w2", ""),
+            Warning::new(Severity::Critical, "c2", ""),
+        ];
+        let order: Vec<&str> = safety
+            .sorted_warnings()
+            .iter()
+            .map(|w| w.summary.as_str())
+            .collect();
+        assert_eq!(order, ["c1", "c2", "w1", "w2"]);
+    }
+
+    #[test]
+    fn test_cpu_arch_efficiency_scales_cpu_limit() {
+        let args = Args {
+            total_ram: 64.0,
+            cpu_cores: 8,
+            net_gbps: 10.0,
+            expected_connections: 1000,
+            ..Default::default()
+        };
+        assert_eq!(cpu_efficiency(&args), 1.0);
+        let arm = Args {
+            cpu_arch: "aarch64".to_string(),
+            ..args.clone()
+        };
+        assert_eq!(cpu_efficiency(&arm), AARCH64_CPU_EFFICIENCY);
+        let tuned = Args {
+            cpu_efficiency: Some(0.9),
+            ..arm.clone()
+        };
+        assert_eq!(cpu_efficiency(&tuned), 0.9);
+
+        // 主频按2.5GHz基准线性缩放，超出范围时截断
+        let fast = Args {
+            cpu_ghz: Some(3.5),
+            ..args.clone()
+        };
+        assert!((cpu_efficiency(&fast) - 1.4).abs() < 1e-9);
+        let extreme = Args {
+            cpu_ghz: Some(10.0),
+            ..args.clone()
+        };
+        assert_eq!(cpu_efficiency(&extreme), 1.5);
+
+        let cpu_pct = |args: &Args| {
+            calculate_safety(args, 4.0, 16.0)
+                .theoretical_limits
+                .utilization
+                .cpu_pct
+        };
+        assert!(cpu_pct(&arm) < cpu_pct(&args));
+        assert!(cpu_pct(&tuned) > cpu_pct(&args));
+    }
+
+    #[test]
+    fn test_sync_replication_shifts_bottleneck_to_io() {
+        let args = Args {
+            total_ram: 256.0,
+            cpu_cores: 64,
+            net_gbps: 10.0,
+            disk_type: "sata_hdd".to_string(),
+            expected_connections: 1000,
+            burst_factor: 1.0,
+            ..Default::default()
+        };
+        let plain = calculate_safety(&args, 20.0, 80.0);
+        let sync = calculate_safety(
+            &Args {
+                replication: "sync".to_string(),
+                ..args.clone()
+            },
+            20.0,
+            80.0,
+        );
+        assert_eq!(
+            sync.theoretical_limits.utilization.disk_pct,
+            (plain.theoretical_limits.utilization.disk_pct * 2.0).min(100.0)
+        );
+        assert!(sync.theoretical_limits.max_throughput < plain.theoretical_limits.max_throughput);
+        assert!(
+            sync.recommendations
+                .iter()
+                .any(|r| r.summary.starts_with("已计入sync复制开销"))
+        );
+        assert!(
+            !plain
+                .recommendations
+                .iter()
+                .any(|r| r.summary.contains("复制开销"))
+        );
+    }
+
+    #[test]
+    fn test_overcommit_ratio_shrinks_trusted_memory() {
+        let args = Args {
+            total_ram: 32.0,
+            cpu_cores: 16,
+            net_gbps: 1.0,
+            disk_type: "nvme".to_string(),
+            expected_connections: 1000,
+            burst_factor: 2.0,
+            avg_file_size: 10.0,
+            complexity: "medium".to_string(),
+            ..Default::default()
+        };
+        let overcommitted = Args {
+            overcommit_ratio: Some(0.8),
+            ..args.clone()
+        };
+        assert_eq!(overcommitted.effective_total_ram(), 25.6);
+
+        let (plain_direct, plain_heap) = crate::analysis::allocate_memory(&args).unwrap();
+        let (direct, heap) = crate::analysis::allocate_memory(&overcommitted).unwrap();
+        assert!(direct < plain_direct && heap < plain_heap);
+
+        let safety = calculate_safety(&overcommitted, direct, heap);
+        assert!(
+            safety
+                .warnings
+                .iter()
+                .any(|w| w.summary.contains("AlwaysPreTouch") && w.severity == Severity::Warning)
+        );
+        let plain = calculate_safety(&args, plain_direct, plain_heap);
+        assert!(
+            !plain
+                .warnings
+                .iter()
+                .any(|w| w.summary.contains("内存超售"))
+        );
+    }
+
+    #[test]
+    fn test_short_connections_raise_gc_pressure() {
+        let args = Args {
+            total_ram: 32.0,
+            cpu_cores: 8,
+            net_gbps: 10.0,
+            disk_type: "nvme".to_string(),
+            expected_connections: 20_000,
+            burst_factor: 2.0,
+            avg_file_size: 1.0,
+            complexity: "medium".to_string(),
+            ..Default::default()
+        };
+        let long_lived = calculate_safety(&args, 4.0, 12.0);
+        assert!(gc_pressure(&args, 12.0).is_none());
+        assert!(
+            !long_lived
+                .recommendations
+                .iter()
+                .any(|r| r.summary.starts_with("预计GC频率"))
+        );
+
+        let churning = Args {
+            connection_duration: Some(0.1),
+            ..args.clone()
+        };
+        let gc = gc_pressure(&churning, 12.0).unwrap();
+        // 200k连接/s × 0.25MB = 50GB/s
+        assert_eq!(gc.churn_per_sec, 200_000.0);
+        assert!(gc.young_gc_per_min > 60.0);
+        let safety = calculate_safety(&churning, 4.0, 12.0);
+        assert!(
+            safety.theoretical_limits.max_connections
+                <= long_lived.theoretical_limits.max_connections
+        );
+        assert!(
+            safety
+                .recommendations
+                .iter()
+                .any(|r| r.summary.starts_with("预计GC频率"))
+        );
+    }
+
+    #[test]
+    fn test_resource_risks_follow_utilization() {
+        let policy = SafetyPolicy::default();
+        assert_eq!(policy.risk_for_utilization(30.0), "低风险");
+        assert_eq!(policy.risk_for_utilization(70.0), "中风险");
+        assert_eq!(policy.risk_for_utilization(95.0), "高风险");
+
         let args = Args {
             total_ram: 16.0,
             cpu_cores: 8,
@@ -392,21 +2923,368 @@ mod tests {
             expected_connections: 1000,
             burst_factor: 2.0,
             avg_file_size: 5.0,
-            enable_memory_guard: true,
-            enable_memory_mapping: false,
             complexity: "medium".to_string(),
-            generate_markdown: false,
+            ..Default::default()
         };
         let safety = calculate_safety(&args, 4.0, 12.0);
-        assert!(safety.heap_safety > 0.0, "Heap safety should be positive");
+        let utilization = &safety.theoretical_limits.utilization;
+        assert_eq!(safety.resource_risks.len(), 6);
+        assert_eq!(safety.resource_risks[0].0, "堆内存");
+        assert_eq!(
+            safety.resource_risks[0].1,
+            policy.risk_for_utilization(utilization.heap_pct)
+        );
+        assert_eq!(
+            safety.resource_risks[5],
+            (
+                "磁盘IO",
+                policy
+                    .risk_for_utilization(utilization.disk_pct)
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_ulimit_nofile_below_requirement_warns() {
+        let args = Args {
+            total_ram: 32.0,
+            cpu_cores: 16,
+            net_gbps: 10.0,
+            disk_type: "nvme".to_string(),
+            expected_connections: 2000,
+            burst_factor: 3.0,
+            avg_file_size: 1.0,
+            complexity: "medium".to_string(),
+            ..Default::default()
+        };
+        // 6000突发连接 × 2 + 1024
+        assert_eq!(required_fds(&args), 13_024);
+        let unchecked = calculate_safety(&args, 4.0, 12.0);
+        assert_eq!(unchecked.theoretical_limits.required_fds, 13_024);
         assert!(
-            safety.direct_mem_safety > 0.0,
-            "Direct memory safety should be positive"
+            !unchecked
+                .warnings
+                .iter()
+                .any(|w| w.summary.starts_with("文件描述符不足"))
+        );
+
+        let limited = calculate_safety(
+            &Args {
+                ulimit_nofile: Some(4096),
+                ..args.clone()
+            },
+            4.0,
+            12.0,
+        );
+        assert!(limited.warnings.iter().any(|w| {
+            w.severity == Severity::Critical && w.summary.contains("LimitNOFILE=13024")
+        }));
+
+        let raised = calculate_safety(
+            &Args {
+                ulimit_nofile: Some(65_536),
+                ..args
+            },
+            4.0,
+            12.0,
         );
-        assert!(!safety.scenarios.is_empty(), "Should generate scenarios");
         assert!(
-            !safety.recommendations.is_empty(),
-            "Should generate recommendations"
+            !raised
+                .warnings
+                .iter()
+                .any(|w| w.summary.starts_with("文件描述符不足"))
+        );
+    }
+
+    #[test]
+    fn test_mixed_scenario_keeps_read_buffer_under_zero_copy() {
+        let args = Args {
+            total_ram: 16.0,
+            cpu_cores: 8,
+            net_gbps: 10.0,
+            disk_type: "nvme".to_string(),
+            avg_file_size: 5.0,
+            expected_connections: 1000,
+            burst_factor: 1.0,
+            complexity: "medium".to_string(),
+            workload: "download".to_string(),
+            ..Default::default()
+        };
+        let mixed = |args: &Args| {
+            let safety = calculate_safety(args, 4.0, 4.0);
+            let scenario = safety.scenarios.into_iter().find(|s| s.name == "读写混合");
+            scenario.unwrap().direct_mem_usage
+        };
+        let buffered = mixed(&args);
+        assert!((buffered - 1000.0 * 420.0 / 1024.0 / 1024.0).abs() < 1e-9);
+        let zero_copy = Args {
+            zero_copy: true,
+            ..args
+        };
+        assert_eq!(mixed(&zero_copy), buffered);
+    }
+
+    #[test]
+    fn test_zero_copy_drops_read_buffer_for_plain_downloads() {
+        let args = Args {
+            total_ram: 64.0,
+            cpu_cores: 16,
+            net_gbps: 10.0,
+            disk_type: "nvme".to_string(),
+            avg_file_size: 50.0,
+            expected_connections: 5000,
+            burst_factor: 2.0,
+            complexity: "medium".to_string(),
+            workload: "download".to_string(),
+            ..Default::default()
+        };
+        let zero_copy = Args {
+            zero_copy: true,
+            ..args.clone()
+        };
+        let (read, write) = calculate_direct_mem_per_conn(&args);
+        assert!(read > 0.0);
+        assert_eq!(calculate_direct_mem_per_conn(&zero_copy), (0.0, write));
+
+        let buffered = calculate_safety(&args, 16.0, 12.0);
+        let copied = calculate_safety(&zero_copy, 16.0, 12.0);
+        assert!(copied.direct_mem_safety > buffered.direct_mem_safety);
+
+        // TLS需要用户态加密，零拷贝不生效
+        let tls = Args {
+            tls: true,
+            ..zero_copy
+        };
+        assert!(!zero_copy_applies(&tls));
+        assert_eq!(calculate_direct_mem_per_conn(&tls).0, read);
+        assert!(calculate_safety(&tls, 4.0, 12.0).warnings.iter().any(|w| {
+            w.severity == Severity::Info && w.summary.starts_with("零拷贝未计入内存模型")
+        }));
+    }
+
+    #[test]
+    fn test_connections_per_core_guardrail() {
+        let args = Args {
+            total_ram: 64.0,
+            cpu_cores: 2,
+            net_gbps: 10.0,
+            disk_type: "nvme".to_string(),
+            expected_connections: 50_000,
+            burst_factor: 1.0,
+            complexity: "medium".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(connections_per_core_ceiling(&args), 2000.0);
+        // 每核2000个连接时上下文切换约占20%
+        assert!((context_switch_cpu_ratio(2000.0) - 0.2).abs() < 1e-9);
+
+        let undersized = calculate_safety(&args, 8.0, 24.0);
+        let warning = undersized
+            .warnings
+            .iter()
+            .find(|w| w.summary.starts_with("CPU核心数不足"))
+            .unwrap();
+        assert_eq!(warning.severity, Severity::Critical);
+        assert!(warning.summary.contains("每核25000个连接"));
+
+        let sized = calculate_safety(
+            &Args {
+                cpu_cores: 32,
+                ..args.clone()
+            },
+            8.0,
+            24.0,
+        );
+        assert!(
+            !sized
+                .warnings
+                .iter()
+                .any(|w| w.summary.starts_with("CPU核心数不足"))
+        );
+
+        // 显式放宽护栏阈值
+        let relaxed = calculate_safety(
+            &Args {
+                connections_per_core: Some(30_000.0),
+                ..args
+            },
+            8.0,
+            24.0,
+        );
+        assert!(
+            !relaxed
+                .warnings
+                .iter()
+                .any(|w| w.summary.starts_with("CPU核心数不足"))
+        );
+    }
+
+    #[test]
+    fn test_mixed_read_write_scenario_contends_disk() {
+        let args = Args {
+            total_ram: 64.0,
+            cpu_cores: 16,
+            net_gbps: 10.0,
+            disk_type: "nvme".to_string(),
+            expected_connections: 500,
+            burst_factor: 2.0,
+            avg_file_size: 200.0,
+            enable_memory_mapping: true,
+            complexity: "medium".to_string(),
+            ..Default::default()
+        };
+        let safety = calculate_safety(&args, 8.0, 24.0);
+        let normal = safety
+            .scenarios
+            .iter()
+            .find(|s| s.name == "正常负载")
+            .unwrap();
+        let mixed = safety
+            .scenarios
+            .iter()
+            .find(|s| s.name == "读写混合")
+            .unwrap();
+        // 内存映射的减免不适用于同时读写，直接内存按满额计入
+        assert!((mixed.direct_mem_usage - normal.direct_mem_usage * 2.0).abs() < 1e-9);
+        assert_eq!(mixed.status, ScenarioStatus::Safe);
+
+        // 机械盘同时承担读写时磁盘争用进入危险区
+        let hdd = calculate_safety(
+            &Args {
+                disk_type: "sata_hdd".to_string(),
+                ..args
+            },
+            8.0,
+            24.0,
+        );
+        let mixed = hdd.scenarios.iter().find(|s| s.name == "读写混合").unwrap();
+        assert_eq!(mixed.status, ScenarioStatus::Danger);
+    }
+
+    #[test]
+    fn test_sustained_burst_checks_steady_state_resources() {
+        let args = Args {
+            total_ram: 64.0,
+            cpu_cores: 4,
+            net_gbps: 10.0,
+            disk_type: "nvme".to_string(),
+            expected_connections: 1000,
+            burst_factor: 3.0,
+            avg_file_size: 1.0,
+            complexity: "medium".to_string(),
+            ..Default::default()
+        };
+        let single = calculate_safety(&args, 8.0, 24.0);
+        assert_eq!(single.scenarios.len(), 6);
+        assert!(single.scenarios[2].name.starts_with("突发流量"));
+
+        let tiered = calculate_safety(
+            &Args {
+                sustained_burst_factor: Some(20.0),
+                ..args
+            },
+            8.0,
+            24.0,
         );
+        assert_eq!(tiered.scenarios.len(), 7);
+        assert_eq!(tiered.scenarios[2].name, "瞬时突发 (3x)");
+        assert_eq!(tiered.scenarios[2].status, single.scenarios[2].status);
+        let sustained = &tiered.scenarios[3];
+        assert_eq!(sustained.name, "持续高峰 (20x)");
+        assert_eq!(sustained.connections, 20_000);
+        // 4核CPU无法长时间承载20000连接
+        assert!(sustained.connections > tiered.theoretical_limits.steady_state_max);
+        assert_eq!(sustained.status, ScenarioStatus::Danger);
+    }
+
+    #[test]
+    fn test_sustained_peak_load_uses_resource_thresholds() {
+        let args = Args {
+            total_ram: 32.0,
+            cpu_cores: 4,
+            net_gbps: 10.0,
+            disk_type: "nvme".to_string(),
+            expected_connections: 1000,
+            burst_factor: 2.0,
+            avg_file_size: 1.0,
+            complexity: "medium".to_string(),
+            ..Default::default()
+        };
+        let policy = SafetyPolicy::default();
+        let status = |steady_state_max| {
+            sustained_peak_scenario(&args, 8.0, 24.0, 2.0, steady_state_max, &policy).status
+        };
+        // 2000连接分别占稳态极限的50%、70%、91%
+        assert_eq!(status(4000), ScenarioStatus::Safe);
+        assert_eq!(status(2857), ScenarioStatus::Warning);
+        assert_eq!(status(2200), ScenarioStatus::Danger);
+    }
+
+    #[test]
+    fn test_egress_cap_bounds_download_workload() {
+        let args = Args {
+            total_ram: 64.0,
+            cpu_cores: 32,
+            net_gbps: 10.0,
+            disk_type: "nvme".to_string(),
+            expected_connections: 1000,
+            burst_factor: 1.0,
+            avg_file_size: 10.0,
+            complexity: "medium".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(directional_net_gbps(&args), 10.0);
+
+        let capped = Args {
+            net_egress_gbps: Some(1.0),
+            ..args.clone()
+        };
+        assert_eq!(directional_net_gbps(&capped), 1.0); // mixed取较小值
+        let upload = Args {
+            workload: "upload".to_string(),
+            ..capped.clone()
+        };
+        assert_eq!(directional_net_gbps(&upload), 10.0);
+        let download = Args {
+            workload: "download".to_string(),
+            ..capped
+        };
+        assert_eq!(directional_net_gbps(&download), 1.0);
+
+        let up = calculate_safety(&upload, 8.0, 24.0).theoretical_limits;
+        let down = calculate_safety(&download, 8.0, 24.0).theoretical_limits;
+        assert!(down.utilization.network_pct > up.utilization.network_pct);
+        assert!(down.max_throughput < up.max_throughput);
+    }
+
+    #[test]
+    fn test_write_amplification_only_applies_to_writes() {
+        let args = |workload: &str, write_amplification| Args {
+            total_ram: 32.0,
+            cpu_cores: 16,
+            net_gbps: 10.0,
+            disk_type: "sata_hdd".to_string(),
+            expected_connections: 100,
+            avg_file_size: 10.0,
+            complexity: "medium".to_string(),
+            workload: workload.to_string(),
+            write_amplification,
+            ..Default::default()
+        };
+        assert_eq!(disk_io_factor(&args("download", Some(3.0))), 1.0);
+        assert_eq!(disk_io_factor(&args("mixed", Some(3.0))), 2.0);
+        assert_eq!(disk_io_factor(&args("upload", Some(3.0))), 3.0);
+
+        let disk_pct = |args: &Args| {
+            calculate_safety(args, 4.0, 12.0)
+                .theoretical_limits
+                .utilization
+                .disk_pct
+        };
+        let plain = disk_pct(&args("download", None));
+        assert!((disk_pct(&args("download", Some(3.0))) - plain).abs() < 1e-9);
+        // 连接上限取整，按比例比较
+        assert!((disk_pct(&args("upload", Some(3.0))) / plain - 3.0).abs() < 0.01);
     }
 }
@@ -1,39 +1,52 @@
-use crate::analysis::SafetyAnalysis;
-use crate::utils::Repeated;
+use crate::analysis::{SafetyAnalysis, SafetyPolicy};
+use crate::utils::{Repeated, fmt_num, print_table, status_ranges, status_summary};
 use colored::Colorize;
+use std::io::Write;
 
-pub fn print_scenarios(safety: &SafetyAnalysis) {
-    println!(
+pub fn print_scenarios(
+    out: &mut impl Write,
+    safety: &SafetyAnalysis,
+    policy: &SafetyPolicy,
+) -> std::io::Result<()> {
+    writeln!(
+        out,
         "\n{}{}",
         "▬".magenta().bold().reversed(),
         " 负载场景模拟 ".magenta().bold().reversed()
-    );
-    println!("{}", "▬".blue().bold().repeated(50));
+    )?;
+    writeln!(out, "{}", "▬".blue().bold().repeated(50))?;
 
-    println!(
-        "  {:<18} {:<12} {:<12} {:<12} {:<12} {:<10}",
-        "场景".cyan(),
-        "连接数".cyan(),
-        "文件大小".cyan(),
-        "堆内存".cyan(),
-        "直接内存".cyan(),
-        "状态".cyan()
-    );
+    let rows: Vec<Vec<String>> = safety
+        .scenarios
+        .iter()
+        .map(|scenario| {
+            vec![
+                scenario.name.clone(),
+                scenario.connections.to_string(),
+                fmt_num(scenario.file_size, 1),
+                fmt_num(scenario.heap_usage, 2),
+                fmt_num(scenario.direct_mem_usage, 2),
+                scenario.status.paint(&scenario.status.to_string()),
+            ]
+        })
+        .collect();
+    print_table(
+        out,
+        &["场景", "连接数", "文件大小", "堆内存", "直接内存", "状态"],
+        &rows,
+        &[18, 12, 12, 12, 12, 10],
+    )?;
+    writeln!(
+        out,
+        "\n  {}: {}",
+        "场景状态".cyan(),
+        status_summary(&safety.scenarios, |status, text| status.paint(text))
+    )?;
 
-    for scenario in &safety.scenarios {
-        println!(
-            "  {:<18} {:<12} {:<12.1} {:<12.2} {:<12.2} {}",
-            scenario.name,
-            scenario.connections,
-            scenario.file_size,
-            scenario.heap_usage,
-            scenario.direct_mem_usage,
-            scenario.status
-        );
-    }
-
-    // 解释状态标识
-    println!("\n  {}: <70% 内存使用", "✅ 安全".green());
-    println!("  {}: 70-85% 内存使用", "⚠️ 警告".yellow());
-    println!("  {}: >85% 内存使用", "🔥 危险".red());
+    // 解释状态标识，范围按警告/危险阈值和有效内存上限比例计算
+    let [safe, warning, danger] = status_ranges(policy);
+    writeln!(out, "\n  {}: {safe} 内存使用", "✅ 安全".green())?;
+    writeln!(out, "  {}: {warning} 内存使用", "⚠️ 警告".yellow())?;
+    writeln!(out, "  {}: {danger} 内存使用", "🔥 危险".red())?;
+    Ok(())
 }
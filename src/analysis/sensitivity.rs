@@ -0,0 +1,129 @@
+use crate::analysis::{allocate_memory, calculate_safety};
+use crate::args::{AnalysisError, Args};
+
+/// 敏感性分析结果
+///
+/// 对 `total_ram`、`avg_file_size`、`expected_connections` 随机扰动后
+/// 重复计算安全性，汇总风险等级分布和最大连接数的范围
+pub struct SensitivityReport {
+    pub samples: usize,                    // 采样次数
+    pub variation_pct: f64,                // 扰动幅度(±%)
+    pub seed: u64,                         // 随机种子
+    pub risk_counts: Vec<(String, usize)>, // 各风险等级出现次数
+    pub min_connections: usize,            // 最大连接数的最小值
+    pub median_connections: usize,         // 最大连接数的中位数
+    pub max_connections: usize,            // 最大连接数的最大值
+}
+
+/// 可复现的伪随机数生成器(SplitMix64)
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// 返回 [-1.0, 1.0) 区间内的均匀分布随机数
+    fn next_signed_unit(&mut self) -> f64 {
+        let unit = (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        unit * 2.0 - 1.0
+    }
+}
+
+/// 执行敏感性分析
+///
+/// 每次采样独立地将三个输入在 ±`variation_pct`% 范围内均匀扰动，
+/// 相同的种子总是产生相同的结果
+pub fn analyze_sensitivity(
+    args: &Args,
+    variation_pct: f64,
+    samples: usize,
+    seed: u64,
+) -> Result<SensitivityReport, AnalysisError> {
+    let mut rng = SplitMix64(seed);
+    let spread = variation_pct / 100.0;
+    let mut risk_counts: Vec<(String, usize)> = Vec::new();
+    let mut connections = Vec::with_capacity(samples);
+
+    for _ in 0..samples {
+        let mut perturbed = args.clone();
+        perturbed.total_ram = args.total_ram * (1.0 + spread * rng.next_signed_unit());
+        perturbed.avg_file_size = args.avg_file_size * (1.0 + spread * rng.next_signed_unit());
+        perturbed.expected_connections =
+            ((args.expected_connections as f64 * (1.0 + spread * rng.next_signed_unit())) as usize)
+                .max(1);
+
+        let (direct_mem_gb, heap_mem_gb) = allocate_memory(&perturbed)?;
+        let safety = calculate_safety(&perturbed, direct_mem_gb, heap_mem_gb);
+
+        match risk_counts
+            .iter_mut()
+            .find(|(level, _)| *level == safety.risk_level)
+        {
+            Some((_, count)) => *count += 1,
+            None => risk_counts.push((safety.risk_level.clone(), 1)),
+        }
+        connections.push(safety.theoretical_limits.max_connections);
+    }
+
+    connections.sort_unstable();
+    Ok(SensitivityReport {
+        samples,
+        variation_pct,
+        seed,
+        risk_counts,
+        min_connections: connections.first().copied().unwrap_or(0),
+        median_connections: connections.get(connections.len() / 2).copied().unwrap_or(0),
+        max_connections: connections.last().copied().unwrap_or(0),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_args() -> Args {
+        Args {
+            total_ram: 32.0,
+            cpu_cores: 16,
+            net_gbps: 1.0,
+            disk_type: "sata_ssd".to_string(),
+            avg_file_size: 10.0,
+            expected_connections: 1000,
+            burst_factor: 3.0,
+            complexity: "medium".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_sensitivity_is_reproducible() {
+        let a = analyze_sensitivity(&test_args(), 20.0, 50, 7).unwrap();
+        let b = analyze_sensitivity(&test_args(), 20.0, 50, 7).unwrap();
+        assert_eq!(a.risk_counts, b.risk_counts);
+        assert_eq!(a.median_connections, b.median_connections);
+        assert_eq!(a.risk_counts.iter().map(|(_, n)| n).sum::<usize>(), 50);
+        assert!(a.min_connections <= a.median_connections);
+        assert!(a.median_connections <= a.max_connections);
+    }
+
+    #[test]
+    fn test_zero_variation_matches_baseline() {
+        let args = test_args();
+        let report = analyze_sensitivity(&args, 0.0, 5, 1).unwrap();
+        let (direct, heap) = allocate_memory(&args).unwrap();
+        let baseline = calculate_safety(&args, direct, heap);
+        assert_eq!(
+            report.min_connections,
+            baseline.theoretical_limits.max_connections
+        );
+        assert_eq!(
+            report.max_connections,
+            baseline.theoretical_limits.max_connections
+        );
+    }
+}
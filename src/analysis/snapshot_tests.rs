@@ -0,0 +1,128 @@
+//! 计算结果快照测试
+//!
+//! 对一组有代表性的参数组合固定元空间、安全系数、理论极限和各场景最终能力，
+//! 防止修改模型时无意中改变报告中的关键数值。
+//! 有意调整模型后使用 `cargo insta test --review` 审阅并接受新快照，随改动一起提交。
+
+use super::performance::ResourceLimit;
+use super::{analyze, calculate_metaspace};
+use crate::args::Args;
+
+/// 快照覆盖的参数矩阵: 小/中/大规模 × 不同磁盘、复杂度和文件大小
+fn case_matrix() -> Vec<(&'static str, Args)> {
+    let base = Args {
+        total_ram: 32.0,
+        cpu_cores: 16,
+        net_gbps: 1.0,
+        disk_type: "sata_ssd".to_string(),
+        avg_file_size: 10.0,
+        expected_connections: 1000,
+        burst_factor: 3.0,
+        enable_memory_guard: true,
+        complexity: "medium".to_string(),
+        workload_lifetime: "long".to_string(),
+        socket_buffer_kb: 128,
+        ip_stack: "ipv4".to_string(),
+        ..Default::default()
+    };
+
+    vec![
+        ("default", base.clone()),
+        (
+            "small_hdd_low",
+            Args {
+                total_ram: 8.0,
+                cpu_cores: 4,
+                disk_type: "sata_hdd".to_string(),
+                avg_file_size: 2.0,
+                expected_connections: 200,
+                complexity: "low".to_string(),
+                ..base.clone()
+            },
+        ),
+        (
+            "large_files_mmap",
+            Args {
+                total_ram: 64.0,
+                cpu_cores: 32,
+                net_gbps: 10.0,
+                disk_type: "nvme".to_string(),
+                avg_file_size: 500.0,
+                expected_connections: 2000,
+                enable_memory_mapping: true,
+                ..base.clone()
+            },
+        ),
+        (
+            "high_concurrency_high",
+            Args {
+                total_ram: 128.0,
+                cpu_cores: 64,
+                net_gbps: 25.0,
+                disk_type: "nvme".to_string(),
+                avg_file_size: 1.0,
+                expected_connections: 50_000,
+                burst_factor: 2.0,
+                complexity: "high".to_string(),
+                db_pool_size: 100,
+                ..base.clone()
+            },
+        ),
+        (
+            "overloaded",
+            Args {
+                total_ram: 16.0,
+                cpu_cores: 8,
+                avg_file_size: 50.0,
+                expected_connections: 20_000,
+                burst_factor: 5.0,
+                ..base
+            },
+        ),
+    ]
+}
+
+/// 快照固定的计算结果
+#[derive(Debug)]
+#[allow(dead_code)] // 字段仅通过Debug输出到快照
+struct Pinned<'a> {
+    metaspace_mb: i32,
+    heap_mem_gb: f64,
+    direct_mem_gb: f64,
+    heap_safety: f64,
+    direct_mem_safety: f64,
+    risk_level: &'a str,
+    max_connections: usize,
+    limiting_factor: &'a str,
+    max_throughput: f64,
+    throughput_limit: &'a str,
+    final_capacity: Vec<(&'a str, &'a ResourceLimit)>,
+}
+
+#[test]
+fn test_calculations_match_snapshot() {
+    for (name, args) in case_matrix() {
+        let result = analyze(&args).unwrap();
+        let safety = &result.safety;
+        let limits = &safety.theoretical_limits;
+        let pinned = Pinned {
+            metaspace_mb: calculate_metaspace(&args),
+            heap_mem_gb: result.heap_mem_gb,
+            direct_mem_gb: result.direct_mem_gb,
+            heap_safety: safety.heap_safety,
+            direct_mem_safety: safety.direct_mem_safety,
+            risk_level: &safety.risk_level,
+            max_connections: limits.max_connections,
+            limiting_factor: &limits.limiting_factor,
+            max_throughput: limits.max_throughput,
+            throughput_limit: &limits.throughput_limit,
+            final_capacity: result
+                .performance
+                .scenarios
+                .iter()
+                .map(|scenario| (scenario.name.as_str(), &scenario.final_capacity))
+                .collect(),
+        };
+        insta::assert_debug_snapshot!(name, pinned);
+    }
+}
@@ -0,0 +1,341 @@
+use crate::analysis::analyze;
+use crate::analysis::cost::{CostRates, monthly_cost};
+use crate::analysis::safety::{Bottleneck, TheoreticalLimits};
+use crate::args::Args;
+use crate::config;
+
+/// 扩容路径的最大步数，避免无法收敛的模型无限循环
+const MAX_STEPS: usize = 10;
+
+/// 可以通过增加硬件扩容的资源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Resource {
+    Ram,
+    Cpu,
+    Network,
+    Disk,
+}
+
+impl Resource {
+    const ALL: [Resource; 4] = [
+        Resource::Ram,
+        Resource::Cpu,
+        Resource::Network,
+        Resource::Disk,
+    ];
+
+    /// 能解除该瓶颈的资源；元空间上限随预期连接数计算，网络存储延迟取决于存储架构，
+    /// 均与硬件规格无关
+    fn unbinding(bottleneck: Bottleneck) -> Option<Resource> {
+        match bottleneck {
+            Bottleneck::DirectMemory | Bottleneck::Heap => Some(Resource::Ram),
+            Bottleneck::Cpu => Some(Resource::Cpu),
+            Bottleneck::Network => Some(Resource::Network),
+            Bottleneck::Disk => Some(Resource::Disk),
+            Bottleneck::Metaspace | Bottleneck::StorageLatency => None,
+        }
+    }
+}
+
+/// 扩容路径中的一步: 只增加一种资源
+pub struct UpgradeStep {
+    pub bottleneck: Bottleneck,              // 本步解除的瓶颈资源
+    pub change: String,                      // 资源增量描述，如 "+2 CPU核心"
+    pub max_connections: usize,              // 扩容后的稳态最大连接数
+    pub next_bottleneck: Option<Bottleneck>, // 下一瓶颈，达到目标时为 `None`
+    pub monthly_cost: Option<f64>,           // 本步的每月增量成本，未指定单价时为 `None`
+}
+
+/// 达到目标连接数的逐步扩容路径
+pub struct UpgradePath {
+    pub target: usize,            // 目标连接数
+    pub start_connections: usize, // 当前配置的稳态最大连接数
+    pub steps: Vec<UpgradeStep>,
+    pub blocked: Option<String>, // 无法通过增加硬件解除的瓶颈及原因
+}
+
+/// 从当前配置出发，每次只增加一种资源，重新分析后找到下一个瓶颈，
+/// 直到稳态最大连接数达到目标
+///
+/// 各维度的连接上限与资源量近似成正比，增量按 目标 / 当前上限 折算，
+/// 折算后仍受同一资源限制(如固定开销使上限不完全线性)时在下一步继续增加。
+/// 未指定单价时增加瓶颈资源；指定单价时逐个试算各资源，选择每新增一个连接成本最低的
+/// (如增加内存扩大页缓存，可能比换用更快的磁盘更划算)
+pub fn upgrade_path(args: &Args, target: usize) -> anyhow::Result<UpgradePath> {
+    let rates = CostRates::from_args(args);
+    let mut current = args.clone();
+    let mut limits = analyze(&current)?.safety.theoretical_limits;
+    let start_connections = limits.steady_state_max;
+    let mut steps = Vec::new();
+    let mut blocked = None;
+
+    while limits.steady_state_max < target {
+        if steps.len() == MAX_STEPS {
+            blocked = Some(format!("{MAX_STEPS}步内未能达到目标连接数"));
+            break;
+        }
+        let bottleneck = limits.steady_state_limit;
+        let candidate = match &rates {
+            Some(rates) => cheapest_upgrade(&current, &limits, target, rates)?,
+            None => match Resource::unbinding(bottleneck)
+                .and_then(|resource| upgrade_resource(&current, resource, &limits, target))
+            {
+                Some((next, change)) => {
+                    let next_limits = analyze(&next)?.safety.theoretical_limits;
+                    Some((next, change, next_limits))
+                }
+                None => None,
+            },
+        };
+        let Some((next, change, next_limits)) = candidate else {
+            blocked = Some(format!("{}无法通过增加硬件解除", bottleneck.label()));
+            break;
+        };
+        steps.push(UpgradeStep {
+            bottleneck,
+            change,
+            max_connections: next_limits.steady_state_max,
+            next_bottleneck: (next_limits.steady_state_max < target)
+                .then_some(next_limits.steady_state_limit),
+            monthly_cost: rates
+                .as_ref()
+                .map(|rates| monthly_cost(&next, rates) - monthly_cost(&current, rates)),
+        });
+        current = next;
+        limits = next_limits;
+    }
+
+    Ok(UpgradePath {
+        target,
+        start_connections,
+        steps,
+        blocked,
+    })
+}
+
+/// 逐个试算各资源的扩容，返回每新增一个连接成本最低的一种
+///
+/// 不能增加连接数的资源不参与比较；成本相同时优先瓶颈资源
+fn cheapest_upgrade(
+    args: &Args,
+    limits: &TheoreticalLimits,
+    target: usize,
+    rates: &CostRates,
+) -> anyhow::Result<Option<(Args, String, TheoreticalLimits)>> {
+    let binding = Resource::unbinding(limits.steady_state_limit);
+    let mut best: Option<(f64, (Args, String, TheoreticalLimits))> = None;
+    for resource in binding
+        .into_iter()
+        .chain(Resource::ALL.into_iter().filter(|r| Some(*r) != binding))
+    {
+        let Some((next, change)) = upgrade_resource(args, resource, limits, target) else {
+            continue;
+        };
+        let next_limits = analyze(&next)?.safety.theoretical_limits;
+        let gained = next_limits
+            .steady_state_max
+            .saturating_sub(limits.steady_state_max);
+        if gained == 0 {
+            continue;
+        }
+        let cost_per_conn =
+            (monthly_cost(&next, rates) - monthly_cost(args, rates)) / gained as f64;
+        if best
+            .as_ref()
+            .is_none_or(|(best_cost, _)| cost_per_conn < *best_cost)
+        {
+            best = Some((cost_per_conn, (next, change, next_limits)));
+        }
+    }
+    Ok(best.map(|(_, upgrade)| upgrade))
+}
+
+/// 增加指定资源后的配置及增量描述，该资源无法继续增加时返回 `None`
+fn upgrade_resource(
+    args: &Args,
+    resource: Resource,
+    limits: &TheoreticalLimits,
+    target: usize,
+) -> Option<(Args, String)> {
+    let scale = target as f64 / limits.steady_state_max.max(1) as f64;
+    match resource {
+        Resource::Ram => {
+            let total_ram = (args.total_ram * scale).ceil().max(args.total_ram + 1.0);
+            Some((
+                Args {
+                    total_ram,
+                    ..args.clone()
+                },
+                format!("+{}GB 内存", total_ram - args.total_ram),
+            ))
+        }
+        Resource::Cpu => {
+            let cpu_cores =
+                ((args.cpu_cores as f64 * scale).ceil() as usize).max(args.cpu_cores + 1);
+            Some((
+                Args {
+                    cpu_cores,
+                    ..args.clone()
+                },
+                format!("+{} CPU核心", cpu_cores - args.cpu_cores),
+            ))
+        }
+        Resource::Network => {
+            let upgrade = |gbps: f64| (gbps * scale).ceil().max(gbps + 1.0);
+            let net_gbps = upgrade(args.net_gbps);
+            Some((
+                Args {
+                    net_gbps,
+                    net_egress_gbps: args.net_egress_gbps.map(upgrade),
+                    net_ingress_gbps: args.net_ingress_gbps.map(upgrade),
+                    ..args.clone()
+                },
+                format!("+{}Gbps 网络带宽", net_gbps - args.net_gbps),
+            ))
+        }
+        Resource::Disk => {
+            // 换用IOPS更高的磁盘类型中最接近当前的一种，分层存储按单一磁盘评估
+            let current_iops = config::effective_disk(args).ok()?.iops;
+            let disk_type = config::disk_type_names()
+                .into_iter()
+                .filter_map(|name| Some((name, config::disk_config_for(name).ok()?.iops)))
+                .filter(|(_, iops)| *iops > current_iops)
+                .min_by(|a, b| a.1.total_cmp(&b.1))?
+                .0;
+            Some((
+                Args {
+                    disk_type: disk_type.to_string(),
+                    hot_disk: None,
+                    cold_disk: None,
+                    hot_ratio: None,
+                    sustained_write_speed: None,
+                    ..args.clone()
+                },
+                format!("磁盘换用 {disk_type}"),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cpu_bound() -> Args {
+        Args {
+            total_ram: 64.0,
+            cpu_cores: 2,
+            net_gbps: 10.0,
+            disk_type: "nvme".to_string(),
+            avg_file_size: 10.0,
+            expected_connections: 5000,
+            burst_factor: 1.0,
+            complexity: "medium".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_upgrade_path_unbinds_one_resource_per_step() {
+        let args = cpu_bound();
+        let start = analyze(&args).unwrap().safety.theoretical_limits;
+        assert_eq!(start.steady_state_limit, Bottleneck::Cpu);
+
+        let target = start.steady_state_max * 3;
+        let path = upgrade_path(&args, target).unwrap();
+        assert_eq!(path.start_connections, start.steady_state_max);
+        assert!(path.blocked.is_none());
+        assert_eq!(path.steps[0].bottleneck, Bottleneck::Cpu);
+        assert!(path.steps[0].change.ends_with("CPU核心"));
+        let last = path.steps.last().unwrap();
+        assert!(last.max_connections >= target);
+        assert!(last.next_bottleneck.is_none());
+        // 每一步的下一瓶颈即为下一步要解除的瓶颈
+        for pair in path.steps.windows(2) {
+            assert_eq!(pair[0].next_bottleneck, Some(pair[1].bottleneck));
+        }
+    }
+
+    #[test]
+    fn test_upgrade_path_prices_each_step() {
+        let args = Args {
+            cost_per_core: Some(50.0),
+            ..cpu_bound()
+        };
+        let start = analyze(&args)
+            .unwrap()
+            .safety
+            .theoretical_limits
+            .steady_state_max;
+        let path = upgrade_path(&args, start + 1).unwrap();
+        assert_eq!(path.steps.len(), 1);
+        assert_eq!(path.steps[0].change, "+1 CPU核心");
+        assert_eq!(path.steps[0].monthly_cost, Some(50.0));
+    }
+
+    #[test]
+    fn test_upgrade_path_picks_cheapest_resource_per_connection() {
+        // 磁盘受限但工作集略大于页缓存: 加内存即可全部命中，比换用昂贵的磁盘更划算
+        let base = Args {
+            total_ram: 64.0,
+            cpu_cores: 16,
+            net_gbps: 10.0,
+            expected_connections: 1000,
+            burst_factor: 1.0,
+            avg_file_size: 5.0,
+            disk_type: "sata_hdd".to_string(),
+            workload: "download".to_string(),
+            complexity: "medium".to_string(),
+            ..Default::default()
+        };
+        let cache_gb = crate::analysis::safety::page_cache_model(&Args {
+            working_set_gb: Some(1.0),
+            ..base.clone()
+        })
+        .unwrap()
+        .cache_gb;
+        let args = Args {
+            working_set_gb: Some(cache_gb * 1.5),
+            cost_per_gb_ram: Some(1.0),
+            disk_cost: config::disk_type_names()
+                .into_iter()
+                .map(|name| {
+                    (
+                        name.to_string(),
+                        if name == "sata_hdd" { 0.0 } else { 100000.0 },
+                    )
+                })
+                .collect(),
+            ..base
+        };
+        let start = analyze(&args).unwrap().safety.theoretical_limits;
+        assert_eq!(start.steady_state_limit, Bottleneck::Disk);
+
+        let path = upgrade_path(&args, start.steady_state_max + 1).unwrap();
+        assert_eq!(path.steps[0].bottleneck, Bottleneck::Disk);
+        assert!(
+            path.steps[0].change.ends_with("内存"),
+            "{}",
+            path.steps[0].change
+        );
+
+        // 不指定单价时仍增加瓶颈资源
+        let unpriced = upgrade_path(
+            &Args {
+                cost_per_gb_ram: None,
+                disk_cost: Vec::new(),
+                ..args
+            },
+            start.steady_state_max + 1,
+        )
+        .unwrap();
+        assert!(unpriced.steps[0].change.starts_with("磁盘换用"));
+    }
+
+    #[test]
+    fn test_upgrade_path_is_empty_when_target_met() {
+        let path = upgrade_path(&cpu_bound(), 1).unwrap();
+        assert!(path.steps.is_empty());
+        assert!(path.blocked.is_none());
+    }
+}
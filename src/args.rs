@@ -1,19 +1,122 @@
+use anyhow::Context;
 use clap::Parser;
+use std::path::PathBuf;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum AnalysisError {
-    #[error("不支持的磁盘类型: {0}")]
-    InvalidDiskType(String),
+    #[error("不支持的磁盘类型: {name}. 可用选项: {}", .available.join(", "))]
+    InvalidDiskType {
+        name: String,
+        available: Vec<String>,
+    },
     #[error("无效的内存值: {0}")]
     InvalidMemoryValue(f64),
     #[error("无效的连接数: {0}")]
     InvalidConnectionCount(usize),
+    #[error("无效的CPU核心数: {0} (至少为1)")]
+    InvalidCpuCores(usize),
+    #[error("突发流量倍数不能小于1(突发流量不会低于正常负载), 但得到 {0}")]
+    InvalidBurstFactor(f64),
+    #[error("内存比例之和超过100%: 直接内存{direct} + 堆内存{heap} + Native预留{native}")]
+    InvalidMemoryRatio { direct: f64, heap: f64, native: f64 },
+    #[error("警告阈值({warn_at})必须小于危险阈值({danger_at})")]
+    InvalidSafetyPolicy { warn_at: f64, danger_at: f64 },
+    #[error("元空间下限({min}MB)不能大于上限({max}MB)")]
+    InvalidMetaspaceBounds { min: f64, max: f64 },
+    #[error("无法识别的{flag}取值: `{value}` (--strict模式下不回退到默认值)")]
+    UnrecognizedValue { flag: &'static str, value: String },
+}
+
+/// 文件大小分布
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileSizeDistribution {
+    pub buckets: Vec<(f64, f64)>, // (文件大小MB, 权重)，权重之和为1
+}
+
+impl FileSizeDistribution {
+    /// 加权平均文件大小(MB)
+    pub fn mean(&self) -> f64 {
+        self.buckets
+            .iter()
+            .map(|(size, weight)| size * weight)
+            .sum()
+    }
+}
+
+impl std::fmt::Display for FileSizeDistribution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pairs: Vec<String> = self
+            .buckets
+            .iter()
+            .map(|(size, weight)| format!("{size}:{weight}"))
+            .collect();
+        write!(f, "{}", pairs.join(","))
+    }
+}
+
+/// 生效配置中单个字段的取值
+///
+/// 列表类参数按命令行写法以逗号连接，空列表与未指定的可选参数均为 `Null`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    Text(String),
+}
+
+impl From<bool> for ConfigValue {
+    fn from(value: bool) -> Self {
+        ConfigValue::Bool(value)
+    }
+}
+
+macro_rules! number_config_value {
+    ($($ty:ty),*) => {
+        $(impl From<$ty> for ConfigValue {
+            fn from(value: $ty) -> Self {
+                ConfigValue::Number(value as f64)
+            }
+        })*
+    };
+}
+number_config_value!(f64, usize, u64, u32, u8);
+
+impl From<String> for ConfigValue {
+    fn from(value: String) -> Self {
+        ConfigValue::Text(value)
+    }
+}
+
+impl From<PathBuf> for ConfigValue {
+    fn from(value: PathBuf) -> Self {
+        ConfigValue::Text(value.display().to_string())
+    }
+}
+
+impl<T: Into<ConfigValue>> From<Option<T>> for ConfigValue {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(ConfigValue::Null, Into::into)
+    }
+}
+
+fn join_list(items: impl Iterator<Item = String>) -> ConfigValue {
+    let items: Vec<String> = items.collect();
+    if items.is_empty() {
+        ConfigValue::Null
+    } else {
+        ConfigValue::Text(items.join(","))
+    }
 }
 
 /// 文件上传下载系统性能与安全性分析工具
-#[derive(Parser, Debug, Default)]
-#[clap(version = "3.2", author = "System Safety Analyst")]
+#[derive(Parser, Debug, Default, Clone)]
+#[clap(
+    version = "3.2",
+    author = "System Safety Analyst",
+    args_override_self = true
+)]
 pub struct Args {
     /// 服务器总内存(GB) [必须大于0]
     #[clap(short= 'r', long, default_value = "32", value_parser = validate_positive_float)]
@@ -27,6 +130,18 @@ pub struct Args {
     #[clap(short = 'w', long, default_value = "1")]
     pub net_gbps: f64,
 
+    /// 出口(下行到客户端)带宽(Gbps)，未指定时同 --net-gbps；云厂商出口限速通常低于网卡带宽
+    #[clap(long, value_parser = validate_positive_float)]
+    pub net_egress_gbps: Option<f64>,
+
+    /// 入口(客户端上传)带宽(Gbps)，未指定时同 --net-gbps
+    #[clap(long, value_parser = validate_positive_float)]
+    pub net_ingress_gbps: Option<f64>,
+
+    /// 传输方向 [mixed, download, upload]: 决定网络极限取出口还是入口带宽，mixed取两者较小值
+    #[clap(long, default_value = "mixed", value_parser = ["mixed", "download", "upload"])]
+    pub workload: String,
+
     /// 磁盘类型 [sata_hdd, sata_ssd, nvme]
     #[clap(short = 'd', long, default_value = "sata_ssd", value_parser = validate_disk_type)]
     pub disk_type: String,
@@ -35,14 +150,38 @@ pub struct Args {
     #[clap(short = 'f', long, default_value = "10")]
     pub avg_file_size: f64,
 
+    /// 文件大小分布，格式为 `大小MB:权重` 列表，如 "1:0.5,10:0.3,100:0.2"，
+    /// 指定后缓冲区和容量按分布加权计算，平均文件大小取分布的加权平均
+    #[clap(long, value_parser = parse_file_size_distribution)]
+    pub file_size_distribution: Option<FileSizeDistribution>,
+
+    /// 样本目录: 统计目录下普通文件(跳过子目录、符号链接和空文件)的实际大小，
+    /// 作为文件大小分布和平均文件大小；显式指定 `-f` 或 `--file-size-distribution` 时以其为准
+    #[clap(long, alias = "seed-files-from")]
+    pub sample_dir: Option<PathBuf>,
+
     /// 预期最大并发连接数
     #[clap(short = 'n', long, default_value = "1000")]
     pub expected_connections: usize,
 
-    /// 最大突发流量倍数 [必须大于1]
+    /// 最大突发流量倍数 [不小于1, 1表示无突发]
     #[clap(short = 'b', long, default_value = "3", value_parser = validate_burst_factor)]
     pub burst_factor: f64,
 
+    /// 持续高峰倍数(持续数分钟): 按CPU、磁盘和GC等稳态资源评估，
+    /// 与 `--burst-factor` 描述的瞬时突发(数秒，只考验内存缓冲)分开建模
+    #[clap(long, value_parser = validate_burst_factor)]
+    pub sustained_burst_factor: Option<f64>,
+
+    /// 集群节点数: 大于1时在单节点分析之外汇总集群容量并检查单节点故障(N-1)冗余，
+    /// 预期连接数和突发倍数均为每个节点的负载
+    #[clap(long, default_value = "1", value_parser = clap::value_parser!(u32).range(1..))]
+    pub nodes: u32,
+
+    /// 集群负载均衡效率 (0, 1]: 节点间负载不均时集群容量的折扣
+    #[clap(long, default_value = "0.9", value_parser = validate_lb_efficiency)]
+    pub lb_efficiency: f64,
+
     /// 是否启用内存防护 [true, false]
     #[clap(short = 'p', long, default_value = "true")]
     pub enable_memory_guard: bool,
@@ -51,14 +190,1002 @@ pub struct Args {
     #[clap(short = 'm', long, default_value = "false")]
     pub enable_memory_mapping: bool,
 
-    /// 应用复杂度级别 [low, medium, high]
+    /// 分层存储的热数据磁盘类型 [sata_hdd, sata_ssd, nvme]，需同时指定冷数据磁盘和命中率
+    #[clap(long, value_parser = validate_disk_type, requires_all = ["cold_disk", "hot_ratio"])]
+    pub hot_disk: Option<String>,
+
+    /// 分层存储的冷数据磁盘类型 [sata_hdd, sata_ssd, nvme]
+    #[clap(long, value_parser = validate_disk_type, requires = "hot_disk")]
+    pub cold_disk: Option<String>,
+
+    /// 热数据磁盘的命中率 (0-1)
+    #[clap(long, value_parser = validate_ratio, requires = "hot_disk")]
+    pub hot_ratio: Option<f64>,
+
+    /// 磁盘为网络存储(NFS等)，IO需额外计入网络往返延迟
+    #[clap(long, action)]
+    pub network_storage: bool,
+
+    /// 正常负载下每秒新建的连接数，用于评估建连(accept/握手)的CPU开销
+    #[clap(long, value_parser = validate_positive_float)]
+    pub arrival_rate: Option<f64>,
+
+    /// 连接使用TLS，建连时需计入握手的加密开销
+    #[clap(long, action)]
+    pub tls: bool,
+
+    /// 下载使用零拷贝(sendfile/FileChannel.transferTo)，文件内容不经过用户态读缓冲区；
+    /// 仅对非TLS的download负载生效
+    #[clap(long, action)]
+    pub zero_copy: bool,
+
+    /// 垃圾收集器 [auto, g1, zgc, shenandoah]: auto按应用复杂度选择
+    #[clap(long, default_value = "auto", value_parser = ["auto", "g1", "zgc", "shenandoah"])]
+    pub gc: String,
+
+    /// 目标JDK主版本号，用于判断收集器和参数的兼容性
+    #[clap(long, default_value = "17", value_parser = clap::value_parser!(u32).range(8..))]
+    pub jdk_version: u32,
+
+    /// 逐条检查推荐的JVM参数在目标JDK上是否可用，输出不兼容参数及替代写法
+    #[clap(long, action)]
+    pub compat_report: bool,
+
+    /// JIT代码缓存大小(MB)，覆盖按复杂度和CPU核心数计算的值
+    #[clap(long, value_parser = clap::value_parser!(u32).range(48..=2048))]
+    pub code_cache_mb: Option<u32>,
+
+    /// Native内存预留模型 [flat, nmt]: flat按内存固定比例预留，
+    /// nmt按NMT分类(线程栈、代码缓存、GC数据结构等)逐项估算
+    #[clap(long, default_value = "flat", value_parser = ["flat", "nmt"])]
+    pub native_model: String,
+
+    /// 线程栈大小(KB)，即 `-Xss`，用于估算线程栈占用的Native内存
+    #[clap(long, default_value = "1024", value_parser = clap::value_parser!(u32).range(128..))]
+    pub thread_stack_kb: u32,
+
+    /// 响应实时压缩的输出/输入比例 (0-1]，如0.4表示压缩后为原始大小的40%，上传不压缩
+    #[clap(long, value_parser = validate_compression_ratio)]
+    pub assume_compression: Option<f64>,
+
+    /// 每个连接(HTTP keep-alive)依次处理的请求数，QPS随之提升而并发连接数不变
+    #[clap(long, default_value = "1", value_parser = clap::value_parser!(u64).range(1..))]
+    pub requests_per_connection: u64,
+
+    /// 每个在途请求额外占用的堆内存(KB): 请求/响应头、JWT、解析后的元数据和临时对象，
+    /// 叠加在每连接384KB的基础堆开销之上；HTTP/2等单连接并发多个请求时按并发数累计到此值
+    #[clap(long, default_value = "0", value_parser = validate_non_negative_float)]
+    pub avg_request_heap_kb: f64,
+
+    /// 长期保持的空闲连接数(keep-alive、预热连接池)，只占用堆和socket内存
+    #[clap(long, default_value = "0")]
+    pub idle_connections: usize,
+
+    /// 每个连接的平均存活时间(秒)，短连接频繁周转会产生大量年轻代垃圾；未指定时视为长连接
+    #[clap(long, value_parser = validate_positive_float)]
+    pub connection_duration: Option<f64>,
+
+    /// 延迟SLO: 单次传输p99延迟目标(ms)，据此求出满足SLO的最大连接数
+    #[clap(long, value_parser = validate_positive_float)]
+    pub target_p99_ms: Option<f64>,
+
+    /// 进程的文件描述符上限(ulimit -n)，低于突发负载所需时告警
+    #[clap(long)]
+    pub ulimit_nofile: Option<usize>,
+
+    /// 后端数据库连接池大小(与传输连接共享堆内存和CPU)
+    #[clap(long, default_value = "0")]
+    pub db_pool_size: usize,
+
+    /// 应用复杂度级别 [low, medium, high]: 其他取值按medium处理(--strict时报错)
     #[clap(short = 'l', long, default_value = "medium")]
     pub complexity: String,
 
-    /// 是否生成markdown报告
+    /// 直接内存占总内存比例(覆盖按复杂度选择的默认值) (0-1)
+    #[clap(long, value_parser = validate_ratio)]
+    pub direct_ratio: Option<f64>,
+
+    /// 模拟未设置-XX:MaxDirectMemorySize的JVM: 直接内存上限取HotSpot默认值(约等于-Xmx)
+    #[clap(long, action)]
+    pub no_explicit_direct: bool,
+
+    /// 堆内存占总内存比例(覆盖按复杂度选择的默认值) (0-1)
+    #[clap(long, value_parser = validate_ratio)]
+    pub heap_ratio: Option<f64>,
+
+    /// 进程生命周期 [short, long]: short适用于短时任务，侧重启动预热优化
+    #[clap(long, default_value = "long", value_parser = ["short", "long"])]
+    pub workload_lifetime: String,
+
+    /// 敏感性分析: 随机扰动内存、文件大小和连接数，评估结论的稳定性
+    #[clap(long, action)]
+    pub sensitivity: bool,
+
+    /// 敏感性分析的扰动幅度(±%)
+    #[clap(long, default_value = "20", value_parser = validate_sensitivity_pct, requires = "sensitivity")]
+    pub sensitivity_pct: f64,
+
+    /// 敏感性分析的采样次数
+    #[clap(long, default_value = "100", requires = "sensitivity")]
+    pub sensitivity_samples: usize,
+
+    /// 敏感性分析的随机种子(相同种子结果可复现)
+    #[clap(long, default_value = "42", requires = "sensitivity")]
+    pub seed: u64,
+
+    /// 场景状态进入警告的阈值(占有效内存上限比例) (0-1)
+    #[clap(long, value_parser = validate_ratio)]
+    pub warn_at: Option<f64>,
+
+    /// 场景状态进入危险的阈值(占有效内存上限比例) (0-1)
+    #[clap(long, value_parser = validate_ratio)]
+    pub danger_at: Option<f64>,
+
+    /// 内存防护对直接内存限流的阈值(占有效内存上限比例, 默认及上限为 danger_at - 0.05) (0-1)
+    #[clap(long, value_parser = validate_ratio)]
+    pub guard_direct_pct: Option<f64>,
+
+    /// 内存防护对堆内存限流的阈值(占有效内存上限比例, 默认及上限为 danger_at - 0.05) (0-1)
+    #[clap(long, value_parser = validate_ratio)]
+    pub guard_heap_pct: Option<f64>,
+
+    /// 与之前保存的基线(JSON)对比关键指标，文件缺失或格式不兼容时仅告警
+    #[clap(long)]
+    pub baseline: Option<PathBuf>,
+
+    /// 将本次分析的关键指标保存为基线(JSON)，供之后 `--baseline` 对比
+    #[clap(long)]
+    pub save_baseline: Option<PathBuf>,
+
+    /// 写出生成全部测试文件的bash脚本到指定路径
+    #[clap(long)]
+    pub emit_testfiles_script: Option<PathBuf>,
+
+    /// 输出中间计算过程 (-v 调试信息, -vv 详细跟踪)
+    #[clap(short = 'v', long, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// 每个连接的内核socket收发缓冲区合计(KB)，对应SO_SNDBUF+SO_RCVBUF
+    /// (默认值接近Linux自动调优下的典型占用)
+    #[clap(long, default_value = "128")]
+    pub socket_buffer_kb: usize,
+
+    /// 原生内存分配器 [glibc, jemalloc, tcmalloc]: glibc的arena开销随CPU核心数增长
+    #[clap(long, default_value = "glibc", value_parser = ["glibc", "jemalloc", "tcmalloc"])]
+    pub native_allocator: String,
+
+    /// 连接使用的IP协议栈 [ipv4, ipv6, dual]: IPv6和双栈的内核单连接开销略高
+    #[clap(long, default_value = "ipv4", value_parser = ["ipv4", "ipv6", "dual"])]
+    pub ip_stack: String,
+
+    /// 卸载到SmartNIC/加密卡的工作 [none, tls, checksum, both]: 卸载部分不再计入CPU开销
+    #[clap(long, default_value = "none", value_parser = ["none", "tls", "checksum", "both"])]
+    pub offload: String,
+
+    /// 每个文件的完整性校验算法 [none, crc32, sha256]: 按传输字节计入CPU开销
+    #[clap(long, default_value = "none", value_parser = ["none", "crc32", "sha256"])]
+    pub checksum: String,
+
+    /// 向备机复制传输数据的方式 [none, sync, async]: 复制会增加磁盘写入和网络出口流量
+    #[clap(long, default_value = "none", value_parser = ["none", "sync", "async"])]
+    pub replication: String,
+
+    /// 磁盘写放大系数(>=1，默认1.0): 小文件/元数据密集负载下SSD的FTL和日志写入
+    /// 使实际可用的写入带宽和IOPS只有标称值的 1/系数
+    #[clap(long, value_parser = validate_write_amplification)]
+    pub write_amplification: Option<f64>,
+
+    /// 磁盘持续写入速度(MB/s): 消费级和部分数据中心SSD在SLC缓存写满后写入速度骤降，
+    /// 所有吞吐量计算均按此值，标称(峰值)写入速度仅作展示；未指定时同标称写入速度，
+    /// 超过标称写入速度时按标称值计
+    #[clap(long, value_parser = validate_positive_float)]
+    pub sustained_write_speed: Option<f64>,
+
+    /// 服务器可同时发出的磁盘IO数(IO线程数，即队列深度)，按队列深度折算磁盘可达IOPS
+    #[clap(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub io_threads: Option<u32>,
+
+    /// 热点文件工作集大小(GB)，与JVM之外的空闲内存比较推算页缓存命中率
+    #[clap(long, value_parser = validate_positive_float)]
+    pub working_set_gb: Option<f64>,
+
+    /// 页缓存命中率 [0-1]，命中的读取不再消耗磁盘IO(优先于 `--working-set-gb` 的推算值)
+    #[clap(long, value_parser = validate_hit_ratio)]
+    pub cache_hit_ratio: Option<f64>,
+
+    /// CPU架构 [x86_64, aarch64]: 决定每核心处理能力的效率系数
+    #[clap(long, default_value = "x86_64", value_parser = ["x86_64", "aarch64"])]
+    pub cpu_arch: String,
+
+    /// 每核心效率系数，覆盖 --cpu-arch 的默认值(x86_64为1.0)
+    #[clap(long, value_parser = validate_positive_float)]
+    pub cpu_efficiency: Option<f64>,
+
+    /// 每核心可承载连接数的护栏阈值，预期连接数/核心数超过时告警(默认按CPU模型约2000)
+    #[clap(long, value_parser = validate_positive_float)]
+    pub connections_per_core: Option<f64>,
+
+    /// CPU主频(GHz)，每核心处理能力相对2.5GHz基准线性缩放(限制在0.5x-1.5x)
+    #[clap(long, value_parser = validate_positive_float)]
+    pub cpu_ghz: Option<f64>,
+
+    /// 内存超售比例 (0-1]: 云上突发型实例只按标称内存的该比例计算可用内存(如0.8)
+    #[clap(long, value_parser = validate_overcommit_ratio)]
+    pub overcommit_ratio: Option<f64>,
+
+    /// 必须保持空闲的内存(GB)，留给操作系统页缓存和监控agent，不参与按比例分配
+    #[clap(long, value_parser = validate_non_negative_float)]
+    pub min_free_memory_gb: Option<f64>,
+
+    /// 每GB内存每月成本(指定任一单价后，扩容建议附带增量成本估算)
+    #[clap(long)]
+    pub cost_per_gb_ram: Option<f64>,
+
+    /// 每个CPU核心每月成本
+    #[clap(long)]
+    pub cost_per_core: Option<f64>,
+
+    /// 每Gbps带宽每月成本
+    #[clap(long)]
+    pub cost_per_gbps: Option<f64>,
+
+    /// 各磁盘类型每月成本(名称=成本，逗号分隔，如sata_ssd=120,nvme=300)，供 --rank-disks 使用
+    #[clap(long, value_delimiter = ',', value_parser = parse_disk_cost)]
+    pub disk_cost: Vec<(String, f64)>,
+
+    /// 磁盘排名模式: 固定其他参数，逐个评估所有磁盘类型，按每千连接月成本排序
+    #[clap(long, action)]
+    pub rank_disks: bool,
+
+    /// 扩容路径模式: 输出达到指定连接数的逐步扩容路径，每步只增加当前的瓶颈资源
+    #[clap(long)]
+    pub upgrade_path: Option<usize>,
+
+    /// 成本估算使用的货币单位
+    #[clap(long, default_value = "CNY")]
+    pub currency: String,
+
+    /// 输出预期负载下各资源的占用比例和剩余余量
+    #[clap(long, action)]
+    pub percent_headroom: bool,
+
+    /// 在每条优化建议下输出触发条件和依据的数值
+    #[clap(long, action)]
+    pub explain: bool,
+
+    /// 元空间推荐值的下限(MB)，默认128
+    #[clap(long, value_parser = validate_positive_float)]
+    pub min_metaspace_mb: Option<f64>,
+
+    /// 元空间推荐值的上限(MB)，默认3072；大量动态生成类的应用(Groovy/Scala等)可能需要更大
+    #[clap(long, value_parser = validate_positive_float)]
+    pub max_metaspace_mb: Option<f64>,
+
+    /// 报告中数值的小数位数(默认按各字段自身的精度)
+    #[clap(long)]
+    pub precision: Option<usize>,
+
+    /// 输出元空间推荐值的逐项推导过程
+    #[clap(long, action)]
+    pub explain_metaspace: bool,
+
+    /// 严格模式: 参数取值无法识别时报错，而不是按默认值继续分析
+    #[clap(long, action)]
+    pub strict: bool,
+
+    /// 任一模拟场景(含突发流量、小文件高并发)的堆或直接内存达到危险阈值时列出这些场景
+    /// 并以退出码2返回，比整体风险等级更严格
+    #[clap(long, action)]
+    pub strict_memory_fit: bool,
+
+    /// 对分析结果的断言(可重复)，如 `max_connections>=10000`、`risk_level=低风险`，
+    /// 支持 =、>=、<=、>、<；任一断言不成立时列出失败项并以退出码2返回
+    #[clap(long = "assert", value_parser = crate::assertion::Assertion::parse)]
+    pub assertions: Vec<crate::assertion::Assertion>,
+
+    /// 按 `格式[:路径]` 输出，可重复指定，一次分析同时写出多种格式(路径省略或为 `-` 时写到标准输出):
+    /// text为控制台报告，markdown为markdown报告，json为与基线相同的指标JSON；
+    /// env为shell的export语句，dockerfile为ENV指令，
+    /// systemd为带MemoryMax/LimitNOFILE/TasksMax的 `[Service]` 段，
+    /// k8s为容器 `resources` 段和HPA清单
+    #[clap(long, value_parser = parse_emit)]
+    pub emit: Vec<(String, Option<PathBuf>)>,
+
+    /// systemd单元 `ExecStart` 使用的java可执行文件，默认 /usr/bin/java
+    #[clap(long)]
+    pub java_bin: Option<PathBuf>,
+
+    /// systemd单元 `ExecStart` 启动的应用jar，默认 /opt/app/app.jar
+    #[clap(long)]
+    pub app_jar: Option<PathBuf>,
+
+    /// 静默模式: 不输出各报告章节，仅在风险等级不为低风险时输出风险和告警，
+    /// 并以退出码1返回；`--format jsonl` 的结果行照常输出
+    #[clap(short = 'q', long, action)]
+    pub quiet: bool,
+
+    /// 关闭彩色输出(也可设置NO_COLOR环境变量，输出重定向时自动关闭)
+    #[clap(long, action)]
+    pub no_color: bool,
+
+    /// 控制台表格使用固定列宽(不按内容和中文宽度自动对齐)
+    #[clap(long, action)]
+    pub no_auto_width: bool,
+
+    /// 是否生成markdown报告文件(写入 --output-dir 下的sa_report.md)
     #[clap(short = 'g', long, action)]
     pub generate_markdown: bool,
+
+    /// 报告包含的章节(逗号分隔，默认全部)
+    /// [config, safety, scenarios, performance, jvm, recommendations]
+    #[clap(
+        long,
+        value_delimiter = ',',
+        value_parser = ["config", "safety", "scenarios", "performance", "jvm", "recommendations"]
+    )]
+    pub report_sections: Vec<String>,
+
+    /// 主报告格式 [console, markdown, jsonl]: markdown时以markdown代替控制台报告输出，
+    /// jsonl时每个分析结果输出一行JSON(配合 `--batch` 逐个流式输出)
+    #[clap(long, default_value = "console", value_parser = ["console", "markdown", "jsonl"])]
+    pub format: String,
+
+    /// `--format markdown` 的输出文件，`-` 或不指定时写到标准输出
+    #[clap(short = 'o', long)]
+    pub output: Option<PathBuf>,
+
+    /// 报告输出目录(不存在时自动创建)
+    #[clap(long, default_value = ".")]
+    pub output_dir: PathBuf,
+
+    /// 报告文件名带生成时间(sa_report_YYYYMMDD_HHMMSS.md)，多次运行不互相覆盖
+    #[clap(long, action)]
+    pub timestamped: bool,
+
+    /// 固定报告中的生成时间(RFC 3339格式，如2024-01-02T03:04:05+08:00)，便于生成可复现的报告
+    #[clap(long, value_parser = parse_report_time)]
+    pub report_time: Option<chrono::DateTime<chrono::FixedOffset>>,
+
+    /// 确定性输出: 固定生成时间(未指定 --report-time 时为1970-01-01T00:00:00Z)并关闭颜色，
+    /// 相同输入的多次运行输出逐字节一致，用于golden文件测试
+    #[clap(long, action)]
+    pub deterministic: bool,
+
+    /// 从配置文件读取参数(`key = value`格式，命令行参数优先)
+    #[clap(long)]
+    pub config: Option<PathBuf>,
+
+    /// 监视配置文件，保存后自动重新分析(需配合--config)
+    #[clap(long, action, requires = "config")]
+    pub watch: bool,
+
+    /// 与配置文件在指定git版本(如HEAD、main)中的内容对比关键指标(需配合--config)，
+    /// 该版本中没有此文件时只输出本次分析
+    #[clap(long, requires = "config")]
+    pub since_git: Option<String>,
+
+    /// 批量分析: 文件中每行一组命令行参数，输出汇总表
+    #[clap(long)]
+    pub batch: Option<PathBuf>,
+
+    /// 扫描模式: 在0到指定连接数之间采样，以CSV输出各连接数下的安全系数和风险等级
+    #[clap(long)]
+    pub sweep: Option<usize>,
+
+    /// 扫描模式的采样点数
+    #[clap(long, default_value = "20", requires = "sweep")]
+    pub sweep_points: usize,
+
+    /// 依次评估多个突发倍数(逗号分隔，如2,3,5,10)，在场景模拟后输出各倍数下的内存状态
+    #[clap(long, value_delimiter = ',', value_parser = validate_burst_factor)]
+    pub burst_sweep: Vec<f64>,
+
+    /// 交互模式: 终端进入原始模式，按键即时调整参数并重新分析
+    #[clap(long, action)]
+    pub tui: bool,
 }
+
+impl Args {
+    /// 解析命令行参数，并合并 `--config` 指定的配置文件
+    ///
+    /// 配置文件中的参数排在命令行参数之前，因此命令行中显式给出的值优先
+    pub fn parse_with_config(argv: &[String]) -> anyhow::Result<Args> {
+        Args::parse_resolved(&Args::config_argv(argv)?)
+    }
+
+    /// 与 [`Args::parse_with_config`] 相同，但配置文件内容由调用方给出(如git历史版本)，
+    /// `source` 用于错误信息
+    pub fn parse_with_config_contents(
+        argv: &[String],
+        contents: &str,
+        source: &str,
+    ) -> anyhow::Result<Args> {
+        Args::parse_resolved(&merge_config_contents(argv, contents, source)?)
+    }
+
+    /// 只合并配置文件、不统计样本目录的参数，用于在分析前确定日志级别和颜色等输出设置
+    pub fn parse_settings(argv: &[String]) -> anyhow::Result<Args> {
+        Ok(Args::try_parse_from(Args::config_argv(argv)?)?)
+    }
+
+    /// 把 `--config` 配置文件中的参数插入到命令行参数之前，未指定配置文件时原样返回
+    fn config_argv(argv: &[String]) -> anyhow::Result<Vec<String>> {
+        let args = Args::try_parse_from(argv)?;
+        let Some(path) = &args.config else {
+            return Ok(argv.to_vec());
+        };
+
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("无法读取配置文件: {}", path.display()))?;
+        merge_config_contents(argv, &contents, &path.display().to_string())
+    }
+
+    /// 解析参数并确定文件大小: 未显式指定文件大小时统计 `--sample-dir` 样本目录
+    fn parse_resolved(argv: &[String]) -> anyhow::Result<Args> {
+        use clap::{CommandFactory, FromArgMatches, parser::ValueSource};
+
+        let matches = Args::command().try_get_matches_from(argv)?;
+        let mut args = Args::from_arg_matches(&matches)?;
+        if let Some(dir) = &args.sample_dir {
+            let explicit = |id| matches.value_source(id) == Some(ValueSource::CommandLine);
+            if explicit("avg_file_size") || explicit("file_size_distribution") {
+                log::info!("已显式指定文件大小，忽略样本目录: {}", dir.display());
+            } else {
+                let sample = crate::sample::sample_dir(dir)?;
+                log::info!(
+                    "样本目录 {}: 统计{}个文件(跳过{}项)，平均文件大小{:.2}MB",
+                    dir.display(),
+                    sample.files,
+                    sample.skipped,
+                    sample.mean_mb
+                );
+                args.file_size_distribution = Some(sample.distribution);
+            }
+        }
+        Ok(args.resolve_file_size())
+    }
+
+    /// 用文件大小分布的加权平均覆盖 `avg_file_size`
+    ///
+    /// 解析命令行后调用，保证只依赖平均值的计算(元空间、内存比例等)与分布一致
+    pub fn resolve_file_size(mut self) -> Args {
+        if let Some(distribution) = &self.file_size_distribution {
+            self.avg_file_size = distribution.mean();
+        }
+        self
+    }
+
+    /// 参与加权计算的 (文件大小MB, 权重)，未指定分布时为平均文件大小单桶
+    pub fn file_size_buckets(&self) -> Vec<(f64, f64)> {
+        match &self.file_size_distribution {
+            Some(distribution) => distribution.buckets.clone(),
+            None => vec![(self.avg_file_size, 1.0)],
+        }
+    }
+
+    /// 可信任的服务器内存(GB): 标称内存 × 超售比例，未指定 `--overcommit-ratio` 时即标称内存
+    pub fn effective_total_ram(&self) -> f64 {
+        self.total_ram * self.overcommit_ratio.unwrap_or(1.0)
+    }
+
+    /// 参与按比例分配的内存(GB): 可信内存扣除 `--min-free-memory-gb` 保留值
+    pub fn allocatable_ram(&self) -> f64 {
+        (self.effective_total_ram() - self.min_free_memory_gb.unwrap_or(0.0)).max(0.0)
+    }
+
+    /// 报告和基线中记录的生成时间
+    ///
+    /// `--report-time` 优先；`--deterministic` 时固定为Unix纪元，否则为当前时间
+    pub fn generated_at(&self) -> chrono::DateTime<chrono::FixedOffset> {
+        match self.report_time {
+            Some(time) => time,
+            None if self.deterministic => chrono::DateTime::UNIX_EPOCH.fixed_offset(),
+            None => chrono::Local::now().fixed_offset(),
+        }
+    }
+
+    /// 控制台和markdown报告是否包含指定章节，未指定 `--report-sections` 时包含全部
+    pub fn includes_section(&self, section: &str) -> bool {
+        self.report_sections.is_empty() || self.report_sections.iter().any(|s| s == section)
+    }
+
+    /// 校验不经过clap直接构造的 `Args`(如 `Default` 后逐个赋值)
+    ///
+    /// 与命令行的 `value_parser` 检查一致: 内存必须大于0、磁盘类型有效、
+    /// CPU核心数至少为1、突发倍数不小于1；无法识别的枚举取值(如 `complexity`)
+    /// 在 `--strict` 模式下报错，否则记录警告后按默认值处理
+    pub fn validate(&self) -> Result<(), AnalysisError> {
+        if !(self.total_ram.is_finite() && self.total_ram > 0.0) {
+            return Err(AnalysisError::InvalidMemoryValue(self.total_ram));
+        }
+        crate::config::disk_config_for(&self.disk_type)?;
+        if self.cpu_cores == 0 {
+            return Err(AnalysisError::InvalidCpuCores(self.cpu_cores));
+        }
+        if !(self.burst_factor.is_finite() && self.burst_factor >= 1.0) {
+            return Err(AnalysisError::InvalidBurstFactor(self.burst_factor));
+        }
+        for (flag, value) in self.unrecognized_values() {
+            if self.strict {
+                return Err(AnalysisError::UnrecognizedValue { flag, value });
+            }
+            log::warn!("无法识别的{flag}取值 `{value}`，按默认值处理");
+        }
+        Ok(())
+    }
+
+    /// 突发时的连接数(预期连接数 × 突发倍数)
+    ///
+    /// 命令行和 `validate` 已校验倍数不小于1；未经校验直接调用时同样按1截断，
+    /// 保证突发连接数不会低于预期连接数
+    pub fn burst_connections(&self) -> usize {
+        (self.expected_connections as f64 * self.burst_factor.max(1.0)) as usize
+    }
+
+    /// 取值无法识别、分析时会回退到默认值的参数，返回 (参数名, 取值)
+    ///
+    /// 空字符串是直接构造 `Args` 时表示默认值的约定，不视为无法识别
+    pub fn unrecognized_values(&self) -> Vec<(&'static str, String)> {
+        let known: [(&'static str, &str, &[&str]); 13] = [
+            ("--complexity", &self.complexity, &["low", "medium", "high"]),
+            (
+                "--workload-lifetime",
+                &self.workload_lifetime,
+                &["short", "long"],
+            ),
+            ("--gc", &self.gc, &["auto", "g1", "zgc", "shenandoah"]),
+            (
+                "--native-allocator",
+                &self.native_allocator,
+                &["glibc", "jemalloc", "tcmalloc"],
+            ),
+            ("--ip-stack", &self.ip_stack, &["ipv4", "ipv6", "dual"]),
+            (
+                "--offload",
+                &self.offload,
+                &["none", "tls", "checksum", "both"],
+            ),
+            ("--cpu-arch", &self.cpu_arch, &["x86_64", "aarch64"]),
+            (
+                "--replication",
+                &self.replication,
+                &["none", "sync", "async"],
+            ),
+            ("--checksum", &self.checksum, &["none", "crc32", "sha256"]),
+            (
+                "--disk-type",
+                &self.disk_type,
+                &["sata_hdd", "sata_ssd", "nvme"],
+            ),
+            (
+                "--workload",
+                &self.workload,
+                &["mixed", "download", "upload"],
+            ),
+            ("--format", &self.format, &["console", "markdown", "jsonl"]),
+            ("--native-model", &self.native_model, &["flat", "nmt"]),
+        ];
+        known
+            .into_iter()
+            .filter(|(_, value, allowed)| !value.is_empty() && !allowed.contains(value))
+            .map(|(flag, value, _)| (flag, value.to_string()))
+            .collect()
+    }
+
+    /// 生效的完整配置: 解析命令行、合并 `--config` 文件、应用默认值和文件大小分布之后
+    /// 每个字段的取值，写入机器可读输出，便于他人复现分析结果
+    pub fn resolved_config(&self) -> Vec<(&'static str, ConfigValue)> {
+        vec![
+            ("total_ram", self.total_ram.into()),
+            ("cpu_cores", self.cpu_cores.into()),
+            ("net_gbps", self.net_gbps.into()),
+            ("net_egress_gbps", self.net_egress_gbps.into()),
+            ("net_ingress_gbps", self.net_ingress_gbps.into()),
+            ("workload", self.workload.clone().into()),
+            ("disk_type", self.disk_type.clone().into()),
+            ("avg_file_size", self.avg_file_size.into()),
+            (
+                "file_size_distribution",
+                self.file_size_distribution
+                    .as_ref()
+                    .map(ToString::to_string)
+                    .into(),
+            ),
+            ("sample_dir", self.sample_dir.clone().into()),
+            ("expected_connections", self.expected_connections.into()),
+            ("burst_factor", self.burst_factor.into()),
+            ("sustained_burst_factor", self.sustained_burst_factor.into()),
+            ("nodes", self.nodes.into()),
+            ("lb_efficiency", self.lb_efficiency.into()),
+            ("enable_memory_guard", self.enable_memory_guard.into()),
+            ("enable_memory_mapping", self.enable_memory_mapping.into()),
+            ("hot_disk", self.hot_disk.clone().into()),
+            ("cold_disk", self.cold_disk.clone().into()),
+            ("hot_ratio", self.hot_ratio.into()),
+            ("network_storage", self.network_storage.into()),
+            ("arrival_rate", self.arrival_rate.into()),
+            ("tls", self.tls.into()),
+            ("zero_copy", self.zero_copy.into()),
+            ("gc", self.gc.clone().into()),
+            ("jdk_version", self.jdk_version.into()),
+            ("compat_report", self.compat_report.into()),
+            ("code_cache_mb", self.code_cache_mb.into()),
+            ("native_model", self.native_model.clone().into()),
+            ("thread_stack_kb", self.thread_stack_kb.into()),
+            ("assume_compression", self.assume_compression.into()),
+            (
+                "requests_per_connection",
+                self.requests_per_connection.into(),
+            ),
+            ("avg_request_heap_kb", self.avg_request_heap_kb.into()),
+            ("idle_connections", self.idle_connections.into()),
+            ("connection_duration", self.connection_duration.into()),
+            ("target_p99_ms", self.target_p99_ms.into()),
+            ("ulimit_nofile", self.ulimit_nofile.into()),
+            ("db_pool_size", self.db_pool_size.into()),
+            ("complexity", self.complexity.clone().into()),
+            ("direct_ratio", self.direct_ratio.into()),
+            ("no_explicit_direct", self.no_explicit_direct.into()),
+            ("heap_ratio", self.heap_ratio.into()),
+            ("workload_lifetime", self.workload_lifetime.clone().into()),
+            ("sensitivity", self.sensitivity.into()),
+            ("sensitivity_pct", self.sensitivity_pct.into()),
+            ("sensitivity_samples", self.sensitivity_samples.into()),
+            ("seed", self.seed.into()),
+            ("warn_at", self.warn_at.into()),
+            ("danger_at", self.danger_at.into()),
+            ("guard_direct_pct", self.guard_direct_pct.into()),
+            ("guard_heap_pct", self.guard_heap_pct.into()),
+            ("baseline", self.baseline.clone().into()),
+            ("save_baseline", self.save_baseline.clone().into()),
+            (
+                "emit_testfiles_script",
+                self.emit_testfiles_script.clone().into(),
+            ),
+            ("verbose", self.verbose.into()),
+            ("socket_buffer_kb", self.socket_buffer_kb.into()),
+            ("native_allocator", self.native_allocator.clone().into()),
+            ("ip_stack", self.ip_stack.clone().into()),
+            ("offload", self.offload.clone().into()),
+            ("checksum", self.checksum.clone().into()),
+            ("replication", self.replication.clone().into()),
+            ("write_amplification", self.write_amplification.into()),
+            ("sustained_write_speed", self.sustained_write_speed.into()),
+            ("io_threads", self.io_threads.into()),
+            ("working_set_gb", self.working_set_gb.into()),
+            ("cache_hit_ratio", self.cache_hit_ratio.into()),
+            ("cpu_arch", self.cpu_arch.clone().into()),
+            ("cpu_efficiency", self.cpu_efficiency.into()),
+            ("cpu_ghz", self.cpu_ghz.into()),
+            ("connections_per_core", self.connections_per_core.into()),
+            ("overcommit_ratio", self.overcommit_ratio.into()),
+            ("min_free_memory_gb", self.min_free_memory_gb.into()),
+            ("cost_per_gb_ram", self.cost_per_gb_ram.into()),
+            ("cost_per_core", self.cost_per_core.into()),
+            ("cost_per_gbps", self.cost_per_gbps.into()),
+            (
+                "disk_cost",
+                join_list(
+                    self.disk_cost
+                        .iter()
+                        .map(|(name, cost)| format!("{name}={cost}")),
+                ),
+            ),
+            ("rank_disks", self.rank_disks.into()),
+            ("upgrade_path", self.upgrade_path.into()),
+            ("currency", self.currency.clone().into()),
+            ("percent_headroom", self.percent_headroom.into()),
+            ("explain", self.explain.into()),
+            ("min_metaspace_mb", self.min_metaspace_mb.into()),
+            ("max_metaspace_mb", self.max_metaspace_mb.into()),
+            ("precision", self.precision.into()),
+            ("explain_metaspace", self.explain_metaspace.into()),
+            ("strict", self.strict.into()),
+            ("strict_memory_fit", self.strict_memory_fit.into()),
+            (
+                "assertions",
+                join_list(self.assertions.iter().map(ToString::to_string)),
+            ),
+            (
+                "emit",
+                join_list(self.emit.iter().map(|(format, path)| match path {
+                    Some(path) => format!("{format}:{}", path.display()),
+                    None => format.clone(),
+                })),
+            ),
+            ("java_bin", self.java_bin.clone().into()),
+            ("app_jar", self.app_jar.clone().into()),
+            ("quiet", self.quiet.into()),
+            ("no_color", self.no_color.into()),
+            ("no_auto_width", self.no_auto_width.into()),
+            ("generate_markdown", self.generate_markdown.into()),
+            (
+                "report_sections",
+                join_list(self.report_sections.iter().cloned()),
+            ),
+            ("format", self.format.clone().into()),
+            ("output", self.output.clone().into()),
+            ("output_dir", self.output_dir.clone().into()),
+            ("timestamped", self.timestamped.into()),
+            (
+                "report_time",
+                self.report_time.map(|time| time.to_rfc3339()).into(),
+            ),
+            ("deterministic", self.deterministic.into()),
+            ("config", self.config.clone().into()),
+            ("watch", self.watch.into()),
+            ("since_git", self.since_git.clone().into()),
+            ("batch", self.batch.clone().into()),
+            ("sweep", self.sweep.into()),
+            ("sweep_points", self.sweep_points.into()),
+            (
+                "burst_sweep",
+                join_list(self.burst_sweep.iter().map(ToString::to_string)),
+            ),
+            ("tui", self.tui.into()),
+        ]
+    }
+
+    /// 将当前参数还原为等价的命令行参数，便于复现分析结果
+    pub fn to_cli_flags(&self) -> String {
+        let mut flags = format!(
+            "sa --total-ram {} --cpu-cores {} --net-gbps {} --disk-type {} --avg-file-size {} \
+             --expected-connections {} --burst-factor {} --complexity {}",
+            self.total_ram,
+            self.cpu_cores,
+            self.net_gbps,
+            self.disk_type,
+            self.avg_file_size,
+            self.expected_connections,
+            self.burst_factor,
+            self.complexity
+        );
+        if let Some(distribution) = &self.file_size_distribution {
+            flags.push_str(&format!(" --file-size-distribution {distribution}"));
+        }
+        if let Some(gbps) = self.net_egress_gbps {
+            flags.push_str(&format!(" --net-egress-gbps {gbps}"));
+        }
+        if let Some(gbps) = self.net_ingress_gbps {
+            flags.push_str(&format!(" --net-ingress-gbps {gbps}"));
+        }
+        if matches!(self.workload.as_str(), "download" | "upload") {
+            flags.push_str(&format!(" --workload {}", self.workload));
+        }
+        if let Some(factor) = self.sustained_burst_factor {
+            flags.push_str(&format!(" --sustained-burst-factor {factor}"));
+        }
+        if self.nodes > 1 {
+            flags.push_str(&format!(
+                " --nodes {} --lb-efficiency {}",
+                self.nodes, self.lb_efficiency
+            ));
+        }
+        if self.workload_lifetime == "short" {
+            flags.push_str(" --workload-lifetime short");
+        }
+        if let Some(ratio) = self.direct_ratio {
+            flags.push_str(&format!(" --direct-ratio {ratio}"));
+        }
+        if let Some(ratio) = self.heap_ratio {
+            flags.push_str(&format!(" --heap-ratio {ratio}"));
+        }
+        if let Some(warn_at) = self.warn_at {
+            flags.push_str(&format!(" --warn-at {warn_at}"));
+        }
+        if let Some(danger_at) = self.danger_at {
+            flags.push_str(&format!(" --danger-at {danger_at}"));
+        }
+        if let Some(pct) = self.guard_direct_pct {
+            flags.push_str(&format!(" --guard-direct-pct {pct}"));
+        }
+        if let Some(pct) = self.guard_heap_pct {
+            flags.push_str(&format!(" --guard-heap-pct {pct}"));
+        }
+        if let (Some(hot), Some(cold), Some(ratio)) =
+            (&self.hot_disk, &self.cold_disk, self.hot_ratio)
+        {
+            flags.push_str(&format!(
+                " --hot-disk {hot} --cold-disk {cold} --hot-ratio {ratio}"
+            ));
+        }
+        if matches!(self.gc.as_str(), "g1" | "zgc" | "shenandoah") {
+            flags.push_str(&format!(" --gc {}", self.gc));
+        }
+        if self.jdk_version > 0 && self.jdk_version != 17 {
+            flags.push_str(&format!(" --jdk-version {}", self.jdk_version));
+        }
+        if let Some(mb) = self.code_cache_mb {
+            flags.push_str(&format!(" --code-cache-mb {mb}"));
+        }
+        if self.native_model == "nmt" {
+            flags.push_str(" --native-model nmt");
+        }
+        if self.thread_stack_kb > 0 && self.thread_stack_kb != 1024 {
+            flags.push_str(&format!(" --thread-stack-kb {}", self.thread_stack_kb));
+        }
+        if let Some(ratio) = self.assume_compression {
+            flags.push_str(&format!(" --assume-compression {ratio}"));
+        }
+        if self.avg_request_heap_kb > 0.0 {
+            flags.push_str(&format!(
+                " --avg-request-heap-kb {}",
+                self.avg_request_heap_kb
+            ));
+        }
+        if self.idle_connections > 0 {
+            flags.push_str(&format!(" --idle-connections {}", self.idle_connections));
+        }
+        if self.requests_per_connection > 1 {
+            flags.push_str(&format!(
+                " --requests-per-connection {}",
+                self.requests_per_connection
+            ));
+        }
+        if let Some(secs) = self.connection_duration {
+            flags.push_str(&format!(" --connection-duration {secs}"));
+        }
+        if let Some(ms) = self.target_p99_ms {
+            flags.push_str(&format!(" --target-p99-ms {ms}"));
+        }
+        if let Some(limit) = self.ulimit_nofile {
+            flags.push_str(&format!(" --ulimit-nofile {limit}"));
+        }
+        if self.db_pool_size > 0 {
+            flags.push_str(&format!(" --db-pool-size {}", self.db_pool_size));
+        }
+        flags.push_str(&format!(" --socket-buffer-kb {}", self.socket_buffer_kb));
+        if matches!(self.native_allocator.as_str(), "jemalloc" | "tcmalloc") {
+            flags.push_str(&format!(" --native-allocator {}", self.native_allocator));
+        }
+        if matches!(self.ip_stack.as_str(), "ipv6" | "dual") {
+            flags.push_str(&format!(" --ip-stack {}", self.ip_stack));
+        }
+        if matches!(self.offload.as_str(), "tls" | "checksum" | "both") {
+            flags.push_str(&format!(" --offload {}", self.offload));
+        }
+        if let Some(min) = self.min_metaspace_mb {
+            flags.push_str(&format!(" --min-metaspace-mb {min}"));
+        }
+        if let Some(max) = self.max_metaspace_mb {
+            flags.push_str(&format!(" --max-metaspace-mb {max}"));
+        }
+        if matches!(self.checksum.as_str(), "crc32" | "sha256") {
+            flags.push_str(&format!(" --checksum {}", self.checksum));
+        }
+        if matches!(self.replication.as_str(), "sync" | "async") {
+            flags.push_str(&format!(" --replication {}", self.replication));
+        }
+        if let Some(threads) = self.io_threads {
+            flags.push_str(&format!(" --io-threads {threads}"));
+        }
+        if let Some(factor) = self.write_amplification {
+            flags.push_str(&format!(" --write-amplification {factor}"));
+        }
+        if let Some(speed) = self.sustained_write_speed {
+            flags.push_str(&format!(" --sustained-write-speed {speed}"));
+        }
+        if let Some(gb) = self.working_set_gb {
+            flags.push_str(&format!(" --working-set-gb {gb}"));
+        }
+        if let Some(ratio) = self.cache_hit_ratio {
+            flags.push_str(&format!(" --cache-hit-ratio {ratio}"));
+        }
+        if self.cpu_arch == "aarch64" {
+            flags.push_str(" --cpu-arch aarch64");
+        }
+        if let Some(efficiency) = self.cpu_efficiency {
+            flags.push_str(&format!(" --cpu-efficiency {efficiency}"));
+        }
+        if let Some(ghz) = self.cpu_ghz {
+            flags.push_str(&format!(" --cpu-ghz {ghz}"));
+        }
+        if let Some(ceiling) = self.connections_per_core {
+            flags.push_str(&format!(" --connections-per-core {ceiling}"));
+        }
+        if let Some(ratio) = self.overcommit_ratio {
+            flags.push_str(&format!(" --overcommit-ratio {ratio}"));
+        }
+        if let Some(gb) = self.min_free_memory_gb {
+            flags.push_str(&format!(" --min-free-memory-gb {gb}"));
+        }
+        if self.no_explicit_direct {
+            flags.push_str(" --no-explicit-direct");
+        }
+        if self.enable_memory_guard {
+            flags.push_str(" --enable-memory-guard");
+        }
+        if self.enable_memory_mapping {
+            flags.push_str(" --enable-memory-mapping");
+        }
+        if self.network_storage {
+            flags.push_str(" --network-storage");
+        }
+        if let Some(rate) = self.arrival_rate {
+            flags.push_str(&format!(" --arrival-rate {rate}"));
+        }
+        if self.tls {
+            flags.push_str(" --tls");
+        }
+        if self.zero_copy {
+            flags.push_str(" --zero-copy");
+        }
+        if self.strict {
+            flags.push_str(" --strict");
+        }
+        if self.generate_markdown {
+            flags.push_str(" --generate-markdown");
+        }
+        if self.deterministic {
+            flags.push_str(" --deterministic");
+        }
+        if !self.report_sections.is_empty() {
+            flags.push_str(&format!(
+                " --report-sections {}",
+                self.report_sections.join(",")
+            ));
+        }
+        if matches!(self.format.as_str(), "markdown" | "jsonl") {
+            flags.push_str(&format!(" --format {}", self.format));
+        }
+        if let Some(path) = &self.output {
+            flags.push_str(&format!(" --output {}", path.display()));
+        }
+        flags
+    }
+}
+/// 将 `key = value` 格式的配置文件转换为等价的命令行参数
+///
+/// 支持TOML的简单子集: `#` 注释、`[section]` 标题(忽略)、字符串、数字和布尔值。
+/// 键名中的下划线等价于命令行参数中的连字符，布尔值为false时不生成参数
+pub fn parse_config_file(contents: &str) -> Result<Vec<String>, String> {
+    let mut flags = Vec::new();
+
+    for (lineno, line) in contents.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() || line.starts_with('[') {
+            continue;
+        }
+
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("第{}行缺少`=`: {line}", lineno + 1))?;
+        let key = key.trim().replace('_', "-");
+        let value = value.trim().trim_matches('"');
+        if key.is_empty() {
+            return Err(format!("第{}行缺少参数名", lineno + 1));
+        }
+
+        match value {
+            "true" => flags.push(format!("--{key}")),
+            "false" => {}
+            _ => {
+                flags.push(format!("--{key}"));
+                flags.push(value.to_string());
+            }
+        }
+    }
+
+    Ok(flags)
+}
+
+/// 把配置文件转换的参数插入到命令行参数之前，因此命令行中显式给出的值优先
+fn merge_config_contents(
+    argv: &[String],
+    contents: &str,
+    source: &str,
+) -> anyhow::Result<Vec<String>> {
+    let file_flags = parse_config_file(contents)
+        .map_err(|e| anyhow::anyhow!("配置文件格式错误: {source}: {e}"))?;
+    Ok(argv
+        .iter()
+        .take(1)
+        .cloned()
+        .chain(file_flags)
+        .chain(argv.iter().skip(1).cloned())
+        .collect())
+}
+
 fn validate_positive_float(s: &str) -> Result<f64, String> {
     let val: f64 = s.parse().map_err(|_| format!("`{s}` 不是有效的浮点数"))?;
     if val > 0.0 {
@@ -68,15 +1195,126 @@ fn validate_positive_float(s: &str) -> Result<f64, String> {
     }
 }
 
+fn validate_non_negative_float(s: &str) -> Result<f64, String> {
+    let val: f64 = s.parse().map_err(|_| format!("`{s}` 不是有效的浮点数"))?;
+    if val >= 0.0 {
+        Ok(val)
+    } else {
+        Err(format!("值不能为负数, 但得到 {val}"))
+    }
+}
+
+fn validate_ratio(s: &str) -> Result<f64, String> {
+    let val: f64 = s.parse().map_err(|_| format!("`{s}` 不是有效的浮点数"))?;
+    if val > 0.0 && val < 1.0 {
+        Ok(val)
+    } else {
+        Err(format!("比例必须在0到1之间, 但得到 {val}"))
+    }
+}
+
+fn validate_hit_ratio(s: &str) -> Result<f64, String> {
+    let val: f64 = s.parse().map_err(|_| format!("`{s}` 不是有效的浮点数"))?;
+    if (0.0..=1.0).contains(&val) {
+        Ok(val)
+    } else {
+        Err(format!("命中率必须在0到1之间(含), 但得到 {val}"))
+    }
+}
+
+fn validate_compression_ratio(s: &str) -> Result<f64, String> {
+    let val: f64 = s.parse().map_err(|_| format!("`{s}` 不是有效的浮点数"))?;
+    if val > 0.0 && val <= 1.0 {
+        Ok(val)
+    } else {
+        Err(format!("压缩比例必须大于0且不超过1, 但得到 {val}"))
+    }
+}
+
+fn validate_lb_efficiency(s: &str) -> Result<f64, String> {
+    let val: f64 = s.parse().map_err(|_| format!("`{s}` 不是有效的浮点数"))?;
+    if val > 0.0 && val <= 1.0 {
+        Ok(val)
+    } else {
+        Err(format!("负载均衡效率必须大于0且不超过1, 但得到 {val}"))
+    }
+}
+
+fn validate_overcommit_ratio(s: &str) -> Result<f64, String> {
+    let val: f64 = s.parse().map_err(|_| format!("`{s}` 不是有效的浮点数"))?;
+    if val > 0.0 && val <= 1.0 {
+        Ok(val)
+    } else {
+        Err(format!("超售比例必须大于0且不超过1, 但得到 {val}"))
+    }
+}
+
+fn validate_sensitivity_pct(s: &str) -> Result<f64, String> {
+    let val: f64 = s.parse().map_err(|_| format!("`{s}` 不是有效的浮点数"))?;
+    if val > 0.0 && val < 100.0 {
+        Ok(val)
+    } else {
+        Err(format!("扰动幅度必须在0到100之间(不含), 但得到 {val}"))
+    }
+}
+
+fn parse_file_size_distribution(s: &str) -> Result<FileSizeDistribution, String> {
+    let mut buckets = Vec::new();
+    for pair in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (size, weight) = pair
+            .split_once(':')
+            .ok_or_else(|| format!("`{pair}` 应为 大小:权重 格式"))?;
+        let size: f64 = size
+            .trim()
+            .parse()
+            .map_err(|_| format!("`{size}` 不是有效的文件大小"))?;
+        let weight: f64 = weight
+            .trim()
+            .parse()
+            .map_err(|_| format!("`{weight}` 不是有效的权重"))?;
+        if !(size > 0.0 && size.is_finite() && weight > 0.0 && weight.is_finite()) {
+            return Err(format!("`{pair}` 的文件大小和权重都必须大于0"));
+        }
+        buckets.push((size, weight));
+    }
+    if buckets.is_empty() {
+        return Err("文件大小分布不能为空".to_string());
+    }
+    // 权重按比例归一化，允许直接填写文件数量
+    let total: f64 = buckets.iter().map(|(_, weight)| weight).sum();
+    for (_, weight) in &mut buckets {
+        *weight /= total;
+    }
+    Ok(FileSizeDistribution { buckets })
+}
+
 fn validate_burst_factor(s: &str) -> Result<f64, String> {
     let val: f64 = s.parse().map_err(|_| format!("`{s}` 不是有效的浮点数"))?;
-    if val > 1.0 {
+    if val.is_finite() && val >= 1.0 {
+        Ok(val)
+    } else {
+        Err(format!(
+            "突发流量倍数不能小于1(突发流量不会低于正常负载), 但得到 {val}"
+        ))
+    }
+}
+
+fn validate_write_amplification(s: &str) -> Result<f64, String> {
+    let val: f64 = s.parse().map_err(|_| format!("`{s}` 不是有效的浮点数"))?;
+    if val.is_finite() && val >= 1.0 {
         Ok(val)
     } else {
-        Err(format!("突发流量倍数必须大于1, 但得到 {val}"))
+        Err(format!(
+            "写放大系数不能小于1(实际写入不会少于逻辑写入), 但得到 {val}"
+        ))
     }
 }
 
+fn parse_report_time(s: &str) -> Result<chrono::DateTime<chrono::FixedOffset>, String> {
+    chrono::DateTime::parse_from_rfc3339(s)
+        .map_err(|e| format!("`{s}` 不是有效的RFC 3339时间: {e}"))
+}
+
 fn validate_disk_type(s: &str) -> Result<String, String> {
     match s {
         "sata_hdd" | "sata_ssd" | "nvme" => Ok(s.to_string()),
@@ -85,3 +1323,358 @@ fn validate_disk_type(s: &str) -> Result<String, String> {
         )),
     }
 }
+
+/// `--emit` 支持的输出格式
+const EMIT_FORMATS: [&str; 7] = [
+    "text",
+    "markdown",
+    "json",
+    "env",
+    "dockerfile",
+    "systemd",
+    "k8s",
+];
+
+/// 解析 `格式[:路径]`，路径省略或为 `-` 时为 `None`(标准输出)
+fn parse_emit(s: &str) -> Result<(String, Option<PathBuf>), String> {
+    let (format, path) = match s.split_once(':') {
+        Some((format, path)) => (format.trim(), Some(path.trim())),
+        None => (s.trim(), None),
+    };
+    if !EMIT_FORMATS.contains(&format) {
+        return Err(format!(
+            "不支持的输出格式: {format}. 可用选项: {}",
+            EMIT_FORMATS.join(", ")
+        ));
+    }
+    match path {
+        Some("") => Err(format!("`{s}` 缺少输出路径")),
+        Some("-") | None => Ok((format.to_string(), None)),
+        Some(path) => Ok((format.to_string(), Some(PathBuf::from(path)))),
+    }
+}
+
+fn parse_disk_cost(s: &str) -> Result<(String, f64), String> {
+    let (name, cost) = s
+        .split_once('=')
+        .ok_or_else(|| format!("`{s}` 缺少`=`，格式应为 磁盘类型=每月成本"))?;
+    let name = validate_disk_type(name.trim())?;
+    let cost: f64 = cost
+        .trim()
+        .parse()
+        .map_err(|_| format!("`{cost}` 不是有效的成本"))?;
+    if cost < 0.0 {
+        return Err(format!("磁盘成本不能为负数, 但得到 {cost}"));
+    }
+    Ok((name, cost))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_rejects_invalid_fields() {
+        let valid = Args::parse_from(["sa"]);
+        assert!(valid.validate().is_ok());
+
+        let invalid = |args: Args| args.validate().unwrap_err();
+        assert!(matches!(
+            invalid(Args {
+                total_ram: 0.0,
+                ..valid.clone()
+            }),
+            AnalysisError::InvalidMemoryValue(_)
+        ));
+        assert!(matches!(
+            invalid(Args {
+                total_ram: f64::NAN,
+                ..valid.clone()
+            }),
+            AnalysisError::InvalidMemoryValue(_)
+        ));
+        assert!(matches!(
+            invalid(Args {
+                disk_type: "floppy".to_string(),
+                ..valid.clone()
+            }),
+            AnalysisError::InvalidDiskType { .. }
+        ));
+        assert!(matches!(
+            invalid(Args {
+                cpu_cores: 0,
+                ..valid.clone()
+            }),
+            AnalysisError::InvalidCpuCores(0)
+        ));
+        assert!(matches!(
+            invalid(Args {
+                burst_factor: 0.5,
+                ..valid.clone()
+            }),
+            AnalysisError::InvalidBurstFactor(_)
+        ));
+
+        // 无法识别的复杂度只在严格模式下报错，否则按medium处理
+        let unknown_complexity = Args {
+            complexity: "extreme".to_string(),
+            ..valid.clone()
+        };
+        assert!(unknown_complexity.validate().is_ok());
+        assert!(matches!(
+            invalid(Args {
+                strict: true,
+                ..unknown_complexity
+            }),
+            AnalysisError::UnrecognizedValue {
+                flag: "--complexity",
+                ..
+            }
+        ));
+
+        // 库调用方直接构造的Args在analyze入口即被拒绝
+        let Err(err) = crate::analysis::analyze(&Args {
+            cpu_cores: 0,
+            ..valid
+        }) else {
+            panic!("cpu_cores=0 应被拒绝");
+        };
+        assert!(err.to_string().contains("CPU核心数"));
+    }
+
+    #[test]
+    fn test_cache_hit_ratio_accepts_bounds() {
+        assert_eq!(validate_hit_ratio("0"), Ok(0.0));
+        assert_eq!(validate_hit_ratio("1"), Ok(1.0));
+        assert!(validate_hit_ratio("1.1").is_err());
+        assert!(validate_hit_ratio("-0.1").is_err());
+        assert!(validate_hit_ratio("NaN").is_err());
+        assert_eq!(
+            Args::parse_from(["sa", "--cache-hit-ratio", "1"]).cache_hit_ratio,
+            Some(1.0)
+        );
+    }
+
+    #[test]
+    fn test_sensitivity_pct_range() {
+        assert_eq!(validate_sensitivity_pct("20"), Ok(20.0));
+        assert!(validate_sensitivity_pct("0").is_err());
+        assert!(validate_sensitivity_pct("100").is_err());
+        assert!(validate_sensitivity_pct("NaN").is_err());
+    }
+
+    #[test]
+    fn test_burst_factor_lower_bound() {
+        assert_eq!(validate_burst_factor("1"), Ok(1.0));
+        assert!(validate_burst_factor("0.5").is_err());
+        assert!(validate_burst_factor("NaN").is_err());
+
+        let args = Args {
+            expected_connections: 1000,
+            burst_factor: 0.5,
+            ..Default::default()
+        };
+        assert_eq!(args.burst_connections(), 1000);
+    }
+
+    #[test]
+    fn test_unrecognized_values() {
+        let args = Args::try_parse_from(["sa", "--complexity", "hgih"]).unwrap();
+        assert_eq!(
+            args.unrecognized_values(),
+            vec![("--complexity", "hgih".to_string())]
+        );
+        assert!(Args::default().unrecognized_values().is_empty());
+        assert!(
+            Args::try_parse_from(["sa"])
+                .unwrap()
+                .unrecognized_values()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_file_size_distribution() {
+        let args = Args::try_parse_from(["sa", "--file-size-distribution", "1:5, 10:3, 100:2"])
+            .unwrap()
+            .resolve_file_size();
+        let distribution = args.file_size_distribution.as_ref().unwrap();
+        assert_eq!(
+            distribution.buckets,
+            vec![(1.0, 0.5), (10.0, 0.3), (100.0, 0.2)]
+        );
+        assert!((args.avg_file_size - 23.5).abs() < 1e-9);
+        assert_eq!(distribution.to_string(), "1:0.5,10:0.3,100:0.2");
+
+        assert!(parse_file_size_distribution("").is_err());
+        assert!(parse_file_size_distribution("10").is_err());
+        assert!(parse_file_size_distribution("10:0").is_err());
+    }
+
+    #[test]
+    fn test_parse_config_file() {
+        let contents = r#"
+# 容量规划
+[server]
+total_ram = 64
+disk_type = "nvme"   # 本地盘
+enable_memory_mapping = true
+network_storage = false
+"#;
+        let flags = parse_config_file(contents).unwrap();
+        assert_eq!(
+            flags,
+            vec![
+                "--total-ram",
+                "64",
+                "--disk-type",
+                "nvme",
+                "--enable-memory-mapping"
+            ]
+        );
+
+        assert!(parse_config_file("total_ram 64").is_err());
+    }
+
+    #[test]
+    fn test_command_line_overrides_config() {
+        let contents = "total_ram = 64\ncpu_cores = 8\n";
+        let file_flags = parse_config_file(contents).unwrap();
+        let argv: Vec<String> = ["sa"]
+            .into_iter()
+            .map(String::from)
+            .chain(file_flags)
+            .chain(["--total-ram", "128"].into_iter().map(String::from))
+            .collect();
+        let args = Args::try_parse_from(argv).unwrap();
+        assert_eq!(args.total_ram, 128.0);
+        assert_eq!(args.cpu_cores, 8);
+    }
+
+    #[test]
+    fn test_resolved_config_covers_every_argument() {
+        use clap::CommandFactory;
+
+        let args =
+            Args::try_parse_from(["sa", "--total-ram", "32", "--burst-sweep", "2,5"]).unwrap();
+        let config = args.resolved_config();
+        let keys: Vec<&str> = config.iter().map(|(key, _)| *key).collect();
+        for arg in Args::command().get_arguments() {
+            let id = arg.get_id().as_str();
+            if id != "help" && id != "version" {
+                assert!(keys.contains(&id), "resolved_config缺少字段 `{id}`");
+            }
+        }
+
+        let value = |key: &str| &config.iter().find(|(k, _)| *k == key).unwrap().1;
+        assert_eq!(value("total_ram"), &ConfigValue::Number(32.0));
+        assert_eq!(
+            value("disk_type"),
+            &ConfigValue::Text("sata_ssd".to_string())
+        );
+        assert_eq!(value("tls"), &ConfigValue::Bool(false));
+        assert_eq!(value("hot_ratio"), &ConfigValue::Null);
+        assert_eq!(value("burst_sweep"), &ConfigValue::Text("2,5".to_string()));
+    }
+
+    #[test]
+    fn test_sample_dir_yields_to_explicit_file_size() {
+        let dir = std::env::temp_dir().join(format!("sa_args_sample_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.bin"), vec![0u8; 2 * 1024 * 1024]).unwrap();
+        let argv = |extra: &[&str]| -> Vec<String> {
+            ["sa", "--sample-dir", dir.to_str().unwrap()]
+                .iter()
+                .chain(extra)
+                .map(|s| s.to_string())
+                .collect()
+        };
+
+        let sampled = Args::parse_with_config(&argv(&[])).unwrap();
+        let explicit = Args::parse_with_config(&argv(&["-f", "5"])).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(sampled.avg_file_size, 2.0);
+        assert_eq!(
+            sampled.file_size_distribution.unwrap().buckets,
+            vec![(2.0, 1.0)]
+        );
+        assert_eq!(explicit.avg_file_size, 5.0);
+        assert!(explicit.file_size_distribution.is_none());
+    }
+
+    #[test]
+    fn test_settings_read_output_flags_from_config() {
+        let path =
+            std::env::temp_dir().join(format!("sa_args_settings_{}.toml", std::process::id()));
+        std::fs::write(
+            &path,
+            "no_color = true\nquiet = true\ndeterministic = true\n",
+        )
+        .unwrap();
+        let argv: Vec<String> = ["sa", "--config", path.to_str().unwrap()]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let settings = Args::parse_settings(&argv);
+        std::fs::remove_file(&path).unwrap();
+        let settings = settings.unwrap();
+        assert!(settings.no_color && settings.quiet && settings.deterministic);
+    }
+
+    #[test]
+    fn test_deterministic_pins_generated_at() {
+        let args = Args::try_parse_from(["sa", "--deterministic"]).unwrap();
+        assert_eq!(
+            args.generated_at().to_rfc3339(),
+            "1970-01-01T00:00:00+00:00"
+        );
+        assert_eq!(args.generated_at(), args.generated_at());
+
+        // 显式指定的生成时间优先
+        let pinned = Args::try_parse_from([
+            "sa",
+            "--deterministic",
+            "--report-time",
+            "2024-01-02T03:04:05+08:00",
+        ])
+        .unwrap();
+        assert_eq!(
+            pinned.generated_at().to_rfc3339(),
+            "2024-01-02T03:04:05+08:00"
+        );
+    }
+
+    #[test]
+    fn test_emit_accepts_repeated_format_and_path() {
+        let args = Args::try_parse_from([
+            "sa",
+            "--emit",
+            "json:out.json",
+            "--emit",
+            "markdown:report.md",
+            "--emit",
+            "text:-",
+            "--emit",
+            "env",
+        ])
+        .unwrap();
+        assert_eq!(
+            args.emit,
+            vec![
+                ("json".to_string(), Some(PathBuf::from("out.json"))),
+                ("markdown".to_string(), Some(PathBuf::from("report.md"))),
+                ("text".to_string(), None),
+                ("env".to_string(), None),
+            ]
+        );
+        let config = args.resolved_config();
+        let emit = &config.iter().find(|(key, _)| *key == "emit").unwrap().1;
+        assert_eq!(
+            emit,
+            &ConfigValue::Text("json:out.json,markdown:report.md,text,env".to_string())
+        );
+
+        assert!(parse_emit("yaml:out.yaml").is_err());
+        assert!(parse_emit("json:").is_err());
+    }
+}
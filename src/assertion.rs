@@ -0,0 +1,220 @@
+use crate::args::ConfigValue;
+use crate::baseline::Baseline;
+
+/// 断言的比较运算符
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    Eq,
+    Ge,
+    Le,
+    Gt,
+    Lt,
+}
+
+impl Comparison {
+    /// 按解析顺序排列: 双字符运算符在前，避免 `>=` 被识别为 `>`
+    const SYMBOLS: [(&'static str, Comparison); 5] = [
+        (">=", Comparison::Ge),
+        ("<=", Comparison::Le),
+        (">", Comparison::Gt),
+        ("<", Comparison::Lt),
+        ("=", Comparison::Eq),
+    ];
+
+    fn symbol(self) -> &'static str {
+        Self::SYMBOLS
+            .iter()
+            .find(|(_, comparison)| *comparison == self)
+            .map_or("=", |(symbol, _)| symbol)
+    }
+}
+
+/// 对分析结果的断言，如 `max_connections>=10000`、`risk_level=低风险`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assertion {
+    pub field: String,          // 结果字段名，与基线JSON中的字段名相同
+    pub comparison: Comparison, // 比较运算符
+    pub expected: String,       // 期望值
+}
+
+impl std::fmt::Display for Assertion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}{}{}",
+            self.field,
+            self.comparison.symbol(),
+            self.expected
+        )
+    }
+}
+
+impl Assertion {
+    /// 解析 `字段<运算符>期望值`，支持 `=`、`>=`、`<=`、`>`、`<`
+    pub fn parse(s: &str) -> Result<Assertion, String> {
+        let start = s
+            .find(['<', '>', '='])
+            .ok_or_else(|| format!("`{s}` 缺少比较运算符 (=, >=, <=, >, <)"))?;
+        let (field, rest) = s.split_at(start);
+        let (comparison, expected) = Comparison::SYMBOLS
+            .into_iter()
+            .find_map(|(symbol, comparison)| {
+                rest.strip_prefix(symbol)
+                    .map(|expected| (comparison, expected))
+            })
+            .ok_or_else(|| format!("`{s}` 缺少比较运算符 (=, >=, <=, >, <)"))?;
+        let (field, expected) = (field.trim(), expected.trim());
+        if field.is_empty() || expected.is_empty() {
+            return Err(format!("`{s}` 应为 字段<运算符>期望值 格式"));
+        }
+        Ok(Assertion {
+            field: field.to_string(),
+            comparison,
+            expected: expected.to_string(),
+        })
+    }
+
+    /// 对照分析结果检查断言，不成立时返回原因
+    ///
+    /// 数值字段支持全部运算符，风险等级等文本字段和 `meets_target` 等布尔字段只支持 `=`
+    pub fn check(&self, result: &Baseline) -> Result<(), String> {
+        let actual = result
+            .field(&self.field)
+            .ok_or_else(|| format!("未知字段，可用字段: {}", result.field_names().join(", ")))?;
+        let holds = match (&actual, self.comparison) {
+            (ConfigValue::Number(actual), comparison) => {
+                let expected: f64 = self
+                    .expected
+                    .parse()
+                    .map_err(|_| format!("期望值 `{}` 不是有效的数字", self.expected))?;
+                match comparison {
+                    Comparison::Eq => (actual - expected).abs() < 1e-9,
+                    Comparison::Ge => *actual >= expected,
+                    Comparison::Le => *actual <= expected,
+                    Comparison::Gt => *actual > expected,
+                    Comparison::Lt => *actual < expected,
+                }
+            }
+            (ConfigValue::Text(actual), Comparison::Eq) => *actual == self.expected,
+            (ConfigValue::Bool(actual), Comparison::Eq) => {
+                let expected: bool = self
+                    .expected
+                    .parse()
+                    .map_err(|_| format!("期望值 `{}` 不是 true 或 false", self.expected))?;
+                *actual == expected
+            }
+            (ConfigValue::Bool(_), _) => {
+                return Err(format!("字段 `{}` 为布尔值，只支持 =", self.field));
+            }
+            _ => return Err(format!("字段 `{}` 为文本，只支持 =", self.field)),
+        };
+        match (holds, actual) {
+            (true, _) => Ok(()),
+            (false, ConfigValue::Text(actual)) => Err(format!("实际值 {actual}")),
+            (false, ConfigValue::Number(actual)) => Err(format!("实际值 {actual}")),
+            (false, ConfigValue::Bool(actual)) => Err(format!("实际值 {actual}")),
+            (false, _) => Err("实际值为空".to_string()),
+        }
+    }
+}
+
+/// 逐个检查断言，返回不成立的断言及原因
+pub fn failed_assertions<'a>(
+    assertions: &'a [Assertion],
+    result: &Baseline,
+) -> Vec<(&'a Assertion, String)> {
+    assertions
+        .iter()
+        .filter_map(|assertion| {
+            assertion
+                .check(result)
+                .err()
+                .map(|reason| (assertion, reason))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result() -> Baseline {
+        Baseline {
+            generated_at: "2024-01-02T03:04:05+08:00".to_string(),
+            max_connections: 12000.0,
+            burst_capacity: 20000.0,
+            max_throughput: 800.0,
+            heap_safety: 0.6,
+            direct_mem_safety: 0.5,
+            heap_mem_gb: 16.0,
+            direct_mem_gb: 8.0,
+            metaspace_size_mb: 256.0,
+            risk_level: "低风险".to_string(),
+            limiting_factor: "CPU资源".to_string(),
+            meets_target: false,
+            shortfall_pct: 25.0,
+            resolved_config: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_picks_longest_operator() {
+        let assertion = Assertion::parse("max_connections>=10000").unwrap();
+        assert_eq!(assertion.field, "max_connections");
+        assert_eq!(assertion.comparison, Comparison::Ge);
+        assert_eq!(assertion.expected, "10000");
+        assert_eq!(assertion.to_string(), "max_connections>=10000");
+        assert_eq!(
+            Assertion::parse("heap_safety < 0.5").unwrap().comparison,
+            Comparison::Lt
+        );
+        assert!(Assertion::parse("max_connections").is_err());
+        assert!(Assertion::parse(">=10").is_err());
+        assert!(Assertion::parse("risk_level=").is_err());
+    }
+
+    #[test]
+    fn test_failed_assertions_report_actual_values() {
+        let assertions: Vec<Assertion> = [
+            "max_connections>=10000",
+            "risk_level=低风险",
+            "heap_safety>0.7",
+            "limiting_factor>CPU",
+            "max_qps=1",
+            "direct_mem_gb<=abc",
+        ]
+        .into_iter()
+        .map(|s| Assertion::parse(s).unwrap())
+        .collect();
+        let failures = failed_assertions(&assertions, &result());
+        let reasons: Vec<(String, &str)> = failures
+            .iter()
+            .map(|(assertion, reason)| (assertion.to_string(), reason.as_str()))
+            .collect();
+        assert_eq!(reasons.len(), 4);
+        assert_eq!(reasons[0], ("heap_safety>0.7".to_string(), "实际值 0.6"));
+        assert_eq!(reasons[1].1, "字段 `limiting_factor` 为文本，只支持 =");
+        assert!(
+            reasons[2]
+                .1
+                .starts_with("未知字段，可用字段: max_connections")
+        );
+        assert_eq!(reasons[3].1, "期望值 `abc` 不是有效的数字");
+    }
+
+    #[test]
+    fn test_boolean_field_supports_equality() {
+        let check = |s: &str| Assertion::parse(s).unwrap().check(&result());
+        assert_eq!(check("meets_target=false"), Ok(()));
+        assert_eq!(check("meets_target=true"), Err("实际值 false".to_string()));
+        assert_eq!(check("shortfall_pct<=25"), Ok(()));
+        assert_eq!(
+            check("meets_target>=true"),
+            Err("字段 `meets_target` 为布尔值，只支持 =".to_string())
+        );
+        assert_eq!(
+            check("meets_target=yes"),
+            Err("期望值 `yes` 不是 true 或 false".to_string())
+        );
+    }
+}
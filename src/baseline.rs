@@ -0,0 +1,517 @@
+use crate::analysis::AnalysisResult;
+use crate::args::{Args, ConfigValue};
+use anyhow::{Context, bail};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// 基线文件格式版本，关键指标的含义变化时递增
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// 保存到基线文件的关键指标
+///
+/// 只保留用于回归对比的汇总数值，不包含完整分析结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct Baseline {
+    pub generated_at: String,                        // 生成时间(RFC 3339)
+    pub max_connections: f64,                        // 理论最大连接数
+    pub burst_capacity: f64,                         // 突发容量
+    pub max_throughput: f64,                         // 推荐吞吐量(MB/s)
+    pub heap_safety: f64,                            // 堆内存安全系数
+    pub direct_mem_safety: f64,                      // 直接内存安全系数
+    pub heap_mem_gb: f64,                            // 堆内存(GB)
+    pub direct_mem_gb: f64,                          // 直接内存(GB)
+    pub metaspace_size_mb: f64,                      // 元空间(MB)
+    pub risk_level: String,                          // 整体风险等级
+    pub limiting_factor: String,                     // 主要限制因素
+    pub meets_target: bool,                          // 是否满足预期连接数
+    pub shortfall_pct: f64,                          // 达到预期连接数还需增加的资源比例(%)
+    pub resolved_config: Vec<(String, ConfigValue)>, // 生成时的生效配置(旧基线文件中为空)
+}
+
+/// 单个指标相对基线的变化
+pub struct MetricDelta {
+    pub label: &'static str,
+    pub baseline: f64,
+    pub current: f64,
+}
+
+impl MetricDelta {
+    /// 变化百分比，基线为0时按0处理
+    pub fn change_pct(&self) -> f64 {
+        if self.baseline == 0.0 {
+            0.0
+        } else {
+            (self.current - self.baseline) / self.baseline * 100.0
+        }
+    }
+}
+
+impl Baseline {
+    pub fn from_result(result: &AnalysisResult, args: &Args, generated_at: &str) -> Self {
+        let limits = &result.safety.theoretical_limits;
+        Baseline {
+            generated_at: generated_at.to_string(),
+            max_connections: limits.max_connections as f64,
+            burst_capacity: limits.burst_capacity as f64,
+            max_throughput: limits.max_throughput,
+            heap_safety: result.safety.heap_safety,
+            direct_mem_safety: result.safety.direct_mem_safety,
+            heap_mem_gb: result.heap_mem_gb,
+            direct_mem_gb: result.direct_mem_gb,
+            metaspace_size_mb: result.metaspace_size_mb as f64,
+            risk_level: result.safety.risk_level.clone(),
+            limiting_factor: limits.limiting_factor.clone(),
+            meets_target: result.safety.meets_target,
+            shortfall_pct: result.safety.shortfall_pct,
+            resolved_config: args
+                .resolved_config()
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        }
+    }
+
+    /// 参与对比的数值指标
+    fn metrics(&self) -> [(&'static str, &'static str, f64); 9] {
+        [
+            ("max_connections", "理论最大连接数", self.max_connections),
+            ("burst_capacity", "突发容量", self.burst_capacity),
+            ("max_throughput", "推荐吞吐量(MB/s)", self.max_throughput),
+            ("heap_safety", "堆内存安全系数", self.heap_safety),
+            (
+                "direct_mem_safety",
+                "直接内存安全系数",
+                self.direct_mem_safety,
+            ),
+            ("heap_mem_gb", "堆内存(GB)", self.heap_mem_gb),
+            ("direct_mem_gb", "直接内存(GB)", self.direct_mem_gb),
+            ("metaspace_size_mb", "元空间(MB)", self.metaspace_size_mb),
+            ("shortfall_pct", "资源缺口(%)", self.shortfall_pct),
+        ]
+    }
+
+    /// 按字段名取值: 数值指标、整体风险等级、主要限制因素或是否满足预期连接数
+    pub fn field(&self, key: &str) -> Option<ConfigValue> {
+        match key {
+            "risk_level" => Some(ConfigValue::Text(self.risk_level.clone())),
+            "limiting_factor" => Some(ConfigValue::Text(self.limiting_factor.clone())),
+            "meets_target" => Some(ConfigValue::Bool(self.meets_target)),
+            _ => self
+                .metrics()
+                .into_iter()
+                .find(|(name, _, _)| *name == key)
+                .map(|(_, _, value)| ConfigValue::Number(value)),
+        }
+    }
+
+    /// [`Baseline::field`] 支持的字段名
+    pub fn field_names(&self) -> Vec<&'static str> {
+        self.metrics()
+            .into_iter()
+            .map(|(name, _, _)| name)
+            .chain(["risk_level", "limiting_factor", "meets_target"])
+            .collect()
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut json = format!("{{\n  \"schema_version\": {SCHEMA_VERSION},\n");
+        for (key, value) in [
+            ("generated_at", &self.generated_at),
+            ("risk_level", &self.risk_level),
+            ("limiting_factor", &self.limiting_factor),
+        ] {
+            json.push_str(&format!("  \"{key}\": \"{}\",\n", escape_json(value)));
+        }
+        json.push_str(&format!("  \"meets_target\": {},\n", self.meets_target));
+        let metrics = self.metrics();
+        for (index, (key, _, value)) in metrics.iter().enumerate() {
+            let last = index + 1 == metrics.len() && self.resolved_config.is_empty();
+            let separator = if last { "" } else { "," };
+            json.push_str(&format!("  \"{key}\": {value}{separator}\n"));
+        }
+        if !self.resolved_config.is_empty() {
+            json.push_str("  \"resolved_config\": {\n");
+            for (index, (key, value)) in self.resolved_config.iter().enumerate() {
+                let separator = if index + 1 < self.resolved_config.len() {
+                    ","
+                } else {
+                    ""
+                };
+                json.push_str(&format!(
+                    "    \"{key}\": {}{separator}\n",
+                    config_value_json(value)
+                ));
+            }
+            json.push_str("  }\n");
+        }
+        json.push('}');
+        json
+    }
+
+    /// 单行JSON(JSON Lines的一行)，字段与 [`Baseline::to_json`] 相同，
+    /// `leading` 中的字段排在最前
+    pub fn to_json_line(&self, leading: &[(&str, ConfigValue)]) -> String {
+        let mut fields: Vec<(&str, String)> = leading
+            .iter()
+            .map(|(key, value)| (*key, config_value_json(value)))
+            .collect();
+        fields.push(("schema_version", SCHEMA_VERSION.to_string()));
+        for (key, value) in [
+            ("generated_at", &self.generated_at),
+            ("risk_level", &self.risk_level),
+            ("limiting_factor", &self.limiting_factor),
+        ] {
+            fields.push((key, format!("\"{}\"", escape_json(value))));
+        }
+        fields.push(("meets_target", self.meets_target.to_string()));
+        for (key, _, value) in self.metrics() {
+            fields.push((key, config_value_json(&value.into())));
+        }
+        let config: Vec<(&str, String)> = self
+            .resolved_config
+            .iter()
+            .map(|(key, value)| (key.as_str(), config_value_json(value)))
+            .collect();
+        if !config.is_empty() {
+            fields.push(("resolved_config", compact_object(&config)));
+        }
+        compact_object(&fields)
+    }
+
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        let mut chars = json.trim().chars().peekable();
+        let fields = parse_object(&mut chars)?;
+        let number = |key: &str| match fields.get(key) {
+            Some(JsonValue::Number(n)) => Ok(*n),
+            _ => Err(anyhow::anyhow!("缺少数值字段 `{key}`")),
+        };
+        let string = |key: &str| match fields.get(key) {
+            Some(JsonValue::String(s)) => Ok(s.clone()),
+            _ => Err(anyhow::anyhow!("缺少字符串字段 `{key}`")),
+        };
+
+        let version = number("schema_version")?;
+        if version != SCHEMA_VERSION as f64 {
+            bail!("基线格式版本为 {version}，当前仅支持 {SCHEMA_VERSION}");
+        }
+        Ok(Baseline {
+            generated_at: string("generated_at")?,
+            max_connections: number("max_connections")?,
+            burst_capacity: number("burst_capacity")?,
+            max_throughput: number("max_throughput")?,
+            heap_safety: number("heap_safety")?,
+            direct_mem_safety: number("direct_mem_safety")?,
+            heap_mem_gb: number("heap_mem_gb")?,
+            direct_mem_gb: number("direct_mem_gb")?,
+            metaspace_size_mb: number("metaspace_size_mb")?,
+            risk_level: string("risk_level")?,
+            limiting_factor: string("limiting_factor")?,
+            // 旧基线文件中没有容量判定，按满足预期连接数处理
+            meets_target: match fields.get("meets_target") {
+                Some(JsonValue::Bool(value)) => *value,
+                Some(_) => bail!("字段 `meets_target` 不是布尔值"),
+                None => true,
+            },
+            shortfall_pct: match fields.get("shortfall_pct") {
+                Some(JsonValue::Number(value)) => *value,
+                Some(_) => bail!("字段 `shortfall_pct` 不是数值"),
+                None => 0.0,
+            },
+            resolved_config: match fields.get("resolved_config") {
+                Some(JsonValue::Object(config)) => config
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.to_config_value()))
+                    .collect(),
+                Some(_) => bail!("字段 `resolved_config` 不是对象"),
+                None => Vec::new(),
+            },
+        })
+    }
+
+    /// 当前结果相对基线的各指标变化
+    pub fn compare(&self, current: &Baseline) -> Vec<MetricDelta> {
+        self.metrics()
+            .iter()
+            .zip(current.metrics())
+            .map(|((_, label, baseline), (_, _, current))| MetricDelta {
+                label,
+                baseline: *baseline,
+                current,
+            })
+            .collect()
+    }
+}
+
+/// 读取基线文件
+pub fn load(path: &Path) -> anyhow::Result<Baseline> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("无法读取基线文件: {}", path.display()))?;
+    Baseline::from_json(&contents)
+        .with_context(|| format!("基线文件格式不兼容: {}", path.display()))
+}
+
+/// 写出基线文件
+pub fn save(path: &Path, baseline: &Baseline) -> anyhow::Result<()> {
+    std::fs::write(path, baseline.to_json() + "\n")
+        .with_context(|| format!("无法写入基线文件: {}", path.display()))
+}
+
+enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Object(BTreeMap<String, JsonValue>),
+}
+
+impl JsonValue {
+    fn to_config_value(&self) -> ConfigValue {
+        match self {
+            JsonValue::Bool(value) => ConfigValue::Bool(*value),
+            JsonValue::Number(value) => ConfigValue::Number(*value),
+            JsonValue::String(value) => ConfigValue::Text(value.clone()),
+            JsonValue::Null | JsonValue::Object(_) => ConfigValue::Null,
+        }
+    }
+}
+
+/// 单行JSON对象
+pub fn json_line(fields: &[(&str, ConfigValue)]) -> String {
+    let fields: Vec<(&str, String)> = fields
+        .iter()
+        .map(|(key, value)| (*key, config_value_json(value)))
+        .collect();
+    compact_object(&fields)
+}
+
+/// 由已编码的字段值组成不含换行的JSON对象
+fn compact_object(fields: &[(&str, String)]) -> String {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|(key, value)| format!("\"{}\":{value}", escape_json(key)))
+        .collect();
+    format!("{{{}}}", fields.join(","))
+}
+
+fn config_value_json(value: &ConfigValue) -> String {
+    match value {
+        ConfigValue::Bool(value) => value.to_string(),
+        ConfigValue::Number(value) if value.is_finite() => value.to_string(),
+        ConfigValue::Text(value) => format!("\"{}\"", escape_json(value)),
+        ConfigValue::Number(_) | ConfigValue::Null => "null".to_string(),
+    }
+}
+
+fn escape_json(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// 解析JSON对象，`{` 尚未读取
+///
+/// 基线文件由本工具生成，只需支持字符串、数值、布尔、null和嵌套对象，不支持数组
+fn parse_object(
+    chars: &mut std::iter::Peekable<std::str::Chars>,
+) -> anyhow::Result<BTreeMap<String, JsonValue>> {
+    let mut fields = BTreeMap::new();
+
+    if chars.next() != Some('{') {
+        bail!("基线文件不是JSON对象");
+    }
+    loop {
+        skip_whitespace(chars);
+        match chars.next() {
+            Some('}') if fields.is_empty() => break,
+            Some('"') => {}
+            _ => bail!("期望字段名"),
+        }
+        let key = parse_string(chars)?;
+        skip_whitespace(chars);
+        if chars.next() != Some(':') {
+            bail!("字段 `{key}` 后缺少 `:`");
+        }
+        skip_whitespace(chars);
+        let value =
+            match chars.peek() {
+                Some('"') => {
+                    chars.next();
+                    JsonValue::String(parse_string(chars)?)
+                }
+                Some('{') => JsonValue::Object(parse_object(chars)?),
+                _ => {
+                    let mut literal = String::new();
+                    while let Some(&c) = chars.peek() {
+                        if c == ',' || c == '}' || c.is_whitespace() {
+                            break;
+                        }
+                        literal.push(c);
+                        chars.next();
+                    }
+                    match literal.as_str() {
+                        "null" => JsonValue::Null,
+                        "true" => JsonValue::Bool(true),
+                        "false" => JsonValue::Bool(false),
+                        _ => JsonValue::Number(literal.parse().map_err(|_| {
+                            anyhow::anyhow!("字段 `{key}` 的值 `{literal}` 不是数值")
+                        })?),
+                    }
+                }
+            };
+        fields.insert(key, value);
+        skip_whitespace(chars);
+        match chars.next() {
+            Some(',') => continue,
+            Some('}') => break,
+            _ => bail!("期望 `,` 或 `}}`"),
+        }
+    }
+    Ok(fields)
+}
+
+fn skip_whitespace(chars: &mut std::iter::Peekable<std::str::Chars>) {
+    while chars.peek().is_some_and(|c| c.is_whitespace()) {
+        chars.next();
+    }
+}
+
+/// 解析开头引号之后的字符串内容
+fn parse_string(chars: &mut std::iter::Peekable<std::str::Chars>) -> anyhow::Result<String> {
+    let mut value = String::new();
+    loop {
+        match chars.next() {
+            Some('"') => return Ok(value),
+            Some('\\') => match chars.next() {
+                Some('n') => value.push('\n'),
+                Some('t') => value.push('\t'),
+                Some('u') => {
+                    let code: String = chars.by_ref().take(4).collect();
+                    let c = u32::from_str_radix(&code, 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .ok_or_else(|| anyhow::anyhow!("无效的转义 \\u{code}"))?;
+                    value.push(c);
+                }
+                Some(c) => value.push(c),
+                None => bail!("字符串未结束"),
+            },
+            Some(c) => value.push(c),
+            None => bail!("字符串未结束"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Baseline {
+        Baseline {
+            generated_at: "2026-09-15T10:00:00+08:00".to_string(),
+            max_connections: 12000.0,
+            burst_capacity: 20000.0,
+            max_throughput: 750.5,
+            heap_safety: 0.82,
+            direct_mem_safety: 0.64,
+            heap_mem_gb: 11.2,
+            direct_mem_gb: 2.56,
+            metaspace_size_mb: 440.0,
+            risk_level: "低风险".to_string(),
+            limiting_factor: "网络带宽 \"主\"".to_string(),
+            meets_target: false,
+            shortfall_pct: 12.5,
+            resolved_config: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_baseline_round_trip() {
+        let baseline = sample();
+        assert_eq!(Baseline::from_json(&baseline.to_json()).unwrap(), baseline);
+    }
+
+    #[test]
+    fn test_baseline_round_trip_with_resolved_config() {
+        let baseline = Baseline {
+            resolved_config: vec![
+                ("burst_factor".to_string(), ConfigValue::Number(3.0)),
+                (
+                    "config".to_string(),
+                    ConfigValue::Text("prod.conf".to_string()),
+                ),
+                ("hot_ratio".to_string(), ConfigValue::Null),
+                ("tls".to_string(), ConfigValue::Bool(true)),
+            ],
+            ..sample()
+        };
+        let json = baseline.to_json();
+        assert!(json.contains("  \"resolved_config\": {\n    \"burst_factor\": 3,"));
+        assert_eq!(Baseline::from_json(&json).unwrap(), baseline);
+    }
+
+    #[test]
+    fn test_json_line_is_single_line_and_round_trips() {
+        let baseline = Baseline {
+            resolved_config: vec![("tls".to_string(), ConfigValue::Bool(true))],
+            ..sample()
+        };
+        let line = baseline.to_json_line(&[("line", ConfigValue::Number(3.0))]);
+        assert!(!line.contains('\n'));
+        assert!(line.starts_with("{\"line\":3,\"schema_version\":1,"));
+        assert!(line.ends_with(",\"resolved_config\":{\"tls\":true}}"));
+        assert_eq!(Baseline::from_json(&line).unwrap(), baseline);
+    }
+
+    #[test]
+    fn test_baseline_includes_capacity_verdict() {
+        let baseline = sample();
+        assert!(baseline.to_json().contains("  \"meets_target\": false,\n"));
+        let line = baseline.to_json_line(&[]);
+        assert!(line.contains("\"meets_target\":false,"));
+        assert!(line.contains("\"shortfall_pct\":12.5"));
+        assert_eq!(
+            baseline.field("meets_target"),
+            Some(ConfigValue::Bool(false))
+        );
+        assert!(baseline.field_names().contains(&"shortfall_pct"));
+
+        // 旧基线文件没有这两个字段时仍可读取
+        let legacy = baseline
+            .to_json()
+            .replace("  \"meets_target\": false,\n", "")
+            .replace(",\n  \"shortfall_pct\": 12.5", "");
+        let legacy = Baseline::from_json(&legacy).unwrap();
+        assert!(legacy.meets_target);
+        assert_eq!(legacy.shortfall_pct, 0.0);
+    }
+
+    #[test]
+    fn test_baseline_schema_mismatch_is_error() {
+        let json = sample()
+            .to_json()
+            .replace("\"schema_version\": 1", "\"schema_version\": 2");
+        assert!(Baseline::from_json(&json).is_err());
+        assert!(Baseline::from_json("{\"schema_version\": 1}").is_err());
+        assert!(Baseline::from_json("not json").is_err());
+    }
+
+    #[test]
+    fn test_compare_reports_drop() {
+        let old = sample();
+        let new = Baseline {
+            max_connections: 10200.0,
+            ..sample()
+        };
+        let deltas = old.compare(&new);
+        assert_eq!(deltas[0].label, "理论最大连接数");
+        assert!((deltas[0].change_pct() + 15.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,147 @@
+use crate::analysis::{AnalysisResult, analyze};
+use crate::args::{Args, ConfigValue};
+use crate::baseline::{Baseline, json_line};
+use std::io::Write;
+
+/// 批量分析中单个配置的摘要
+pub struct BatchSummary {
+    pub disk_type: String,
+    pub total_ram: f64,
+    pub max_connections: usize,
+    pub limiting_factor: String,
+    pub risk_level: String,
+}
+
+/// 批量分析中的一行配置及其结果
+pub struct BatchEntry {
+    pub line: usize,                           // 在批量文件中的行号
+    pub flags: String,                         // 原始参数
+    pub outcome: Result<BatchSummary, String>, // 分析摘要或失败原因
+}
+
+/// 依次分析批量文件中的每个配置
+///
+/// 每个非空、非 `#` 注释行是一组命令行参数(如 `--total-ram 64 --disk-type nvme`)，
+/// 未指定的参数使用默认值。单个配置出错时记录失败原因并继续处理后续配置
+pub fn run_batch(contents: &str) -> Vec<BatchEntry> {
+    config_lines(contents)
+        .map(|(line, flags)| BatchEntry {
+            line,
+            flags: flags.to_string(),
+            outcome: analyze_line(flags).map(|(args, result)| summarize(args, result)),
+        })
+        .collect()
+}
+
+/// 以JSON Lines逐个输出批量分析结果，每个配置分析完成后立即写出并刷新
+///
+/// 成功的配置输出与基线文件相同的字段，失败的配置输出 `error` 字段；
+/// 每行都带有 `line` 和 `flags`，可以独立解析
+pub fn stream_batch(
+    contents: &str,
+    out: &mut impl Write,
+    generated_at: &str,
+) -> std::io::Result<()> {
+    for (line, flags) in config_lines(contents) {
+        let leading = [
+            ("line", ConfigValue::from(line)),
+            ("flags", ConfigValue::from(flags.to_string())),
+        ];
+        let json = match analyze_line(flags) {
+            Ok((args, result)) => {
+                Baseline::from_result(&result, &args, generated_at).to_json_line(&leading)
+            }
+            Err(reason) => {
+                let [line, flags] = leading;
+                json_line(&[line, flags, ("error", ConfigValue::from(reason))])
+            }
+        };
+        writeln!(out, "{json}")?;
+        out.flush()?;
+    }
+    Ok(())
+}
+
+/// 批量文件中的 (行号, 参数)，跳过空行和 `#` 注释行
+fn config_lines(contents: &str) -> impl Iterator<Item = (usize, &str)> {
+    contents
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+}
+
+/// 按命令行的方式解析一行参数(含 `--config` 和 `--sample-dir`)并分析
+fn analyze_line(line: &str) -> Result<(Args, AnalysisResult), String> {
+    let argv: Vec<String> = std::iter::once("sa")
+        .chain(line.split_whitespace())
+        .map(String::from)
+        .collect();
+    let args = Args::parse_with_config(&argv).map_err(|e| first_line(&format!("{e:#}")))?;
+    let result = analyze(&args).map_err(|e| format!("{e:#}"))?;
+    Ok((args, result))
+}
+
+fn summarize(args: Args, result: AnalysisResult) -> BatchSummary {
+    BatchSummary {
+        disk_type: args.disk_type,
+        total_ram: args.total_ram,
+        max_connections: result.safety.theoretical_limits.max_connections,
+        limiting_factor: result.safety.theoretical_limits.limiting_factor,
+        risk_level: result.safety.risk_level,
+    }
+}
+
+/// clap的错误信息包含用法提示，摘要表中只保留首行
+fn first_line(message: &str) -> String {
+    message.lines().next().unwrap_or_default().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_batch_continues_after_failure() {
+        let contents = "\
+# SKU列表
+--total-ram 64 --disk-type nvme
+
+--total-ram -1
+--total-ram 16 --cpu-cores 8
+";
+        let entries = run_batch(contents);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].line, 2);
+        assert_eq!(entries[0].outcome.as_ref().unwrap().disk_type, "nvme");
+        assert!(entries[1].outcome.is_err());
+        assert_eq!(entries[2].outcome.as_ref().unwrap().total_ram, 16.0);
+    }
+
+    #[test]
+    fn test_run_batch_reads_sample_dir() {
+        let dir = std::env::temp_dir().join(format!("sa_batch_sample_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.bin"), vec![0u8; 2 * 1024 * 1024]).unwrap();
+        let line = format!("--sample-dir {}", dir.display());
+        let sampled = analyze_line(&line);
+        std::fs::remove_dir_all(&dir).unwrap();
+        let (args, _) = sampled.unwrap();
+        assert_eq!(args.avg_file_size, 2.0);
+        assert!(args.file_size_distribution.is_some());
+    }
+
+    #[test]
+    fn test_stream_batch_emits_one_line_per_config() {
+        let contents = "--total-ram 64 --disk-type nvme\n--total-ram -1\n";
+        let mut out = Vec::new();
+        stream_batch(contents, &mut out, "2026-01-01T00:00:00+00:00").unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("{\"line\":1,\"flags\":\"--total-ram 64 --disk-type nvme\","));
+        let baseline = Baseline::from_json(lines[0]).unwrap();
+        assert!(baseline.max_connections > 0.0);
+        assert!(lines[1].starts_with("{\"line\":2,\"flags\":\"--total-ram -1\",\"error\":\""));
+    }
+}
@@ -1,10 +1,14 @@
+use crate::args::AnalysisError;
 use lazy_static::lazy_static;
 use std::collections::HashMap;
 use std::sync::RwLock;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct DiskConfig {
-    pub read_speed: f64,  // MB/s
-    pub write_speed: f64, // MB/s
+    pub read_speed: f64,            // MB/s
+    pub write_speed: f64,           // MB/s，短时峰值(SSD的SLC缓存未写满时)，仅用于展示
+    pub sustained_write_speed: f64, // MB/s，SLC缓存写满后的持续写入速度
+    pub iops: f64,                  // 随机IO能力
 }
 
 lazy_static! {
@@ -15,6 +19,8 @@ lazy_static! {
             DiskConfig {
                 read_speed: 120.0,
                 write_speed: 100.0,
+                sustained_write_speed: 100.0,
+                iops: 200.0,
             },
         );
         configs.insert(
@@ -22,6 +28,8 @@ lazy_static! {
             DiskConfig {
                 read_speed: 300.0,
                 write_speed: 250.0,
+                sustained_write_speed: 250.0,
+                iops: 100_000.0,
             },
         );
         configs.insert(
@@ -29,6 +37,8 @@ lazy_static! {
             DiskConfig {
                 read_speed: 1500.0,
                 write_speed: 1200.0,
+                sustained_write_speed: 1200.0,
+                iops: 500_000.0,
             },
         );
         configs
@@ -38,3 +48,137 @@ lazy_static! {
 pub fn get_disk_configs() -> &'static RwLock<HashMap<&'static str, DiskConfig>> {
     &DISK_CONFIGS
 }
+
+/// 已注册的磁盘类型名称，按字母排序
+///
+/// 配置表是 `HashMap`，遍历顺序每次运行都可能不同，所有需要列出磁盘类型的地方都应经由此函数
+pub fn disk_type_names() -> Vec<&'static str> {
+    let mut names: Vec<&'static str> = get_disk_configs().read().unwrap().keys().copied().collect();
+    names.sort();
+    names
+}
+
+/// 按名称查找磁盘配置
+///
+/// 名称未注册时返回 `InvalidDiskType`，并列出当前可用的磁盘类型
+pub fn disk_config_for(name: &str) -> Result<DiskConfig, AnalysisError> {
+    let config = get_disk_configs().read().unwrap().get(name).copied();
+    config.ok_or_else(|| AnalysisError::InvalidDiskType {
+        name: name.to_string(),
+        available: disk_type_names().into_iter().map(str::to_string).collect(),
+    })
+}
+
+/// 按热数据命中率混合热/冷两层磁盘的性能
+pub fn blend_disks(hot: &DiskConfig, cold: &DiskConfig, hot_ratio: f64) -> DiskConfig {
+    let mix = |h: f64, c: f64| h * hot_ratio + c * (1.0 - hot_ratio);
+    DiskConfig {
+        read_speed: mix(hot.read_speed, cold.read_speed),
+        write_speed: mix(hot.write_speed, cold.write_speed),
+        sustained_write_speed: mix(hot.sustained_write_speed, cold.sustained_write_speed),
+        iops: mix(hot.iops, cold.iops),
+    }
+}
+
+/// 分析使用的有效磁盘性能
+///
+/// 指定了 `--hot-disk`/`--cold-disk` 时返回两层按命中率混合的结果，
+/// 否则返回 `--disk-type` 对应的配置；`--sustained-write-speed` 覆盖持续写入速度，
+/// 持续写入不会快于峰值写入，超过峰值时按峰值计
+pub fn effective_disk(args: &crate::args::Args) -> Result<DiskConfig, AnalysisError> {
+    let disk = match (&args.hot_disk, &args.cold_disk, args.hot_ratio) {
+        (Some(hot), Some(cold), Some(hot_ratio)) => {
+            blend_disks(&disk_config_for(hot)?, &disk_config_for(cold)?, hot_ratio)
+        }
+        _ => disk_config_for(&args.disk_type)?,
+    };
+    Ok(DiskConfig {
+        sustained_write_speed: args
+            .sustained_write_speed
+            .map_or(disk.sustained_write_speed, |speed| {
+                speed.min(disk.write_speed)
+            }),
+        ..disk
+    })
+}
+
+/// 预留给JVM Native内存(线程栈等)的总内存比例
+pub const NATIVE_MEM_RATIO: f64 = 0.1;
+
+/// 根据应用复杂度和平均文件大小返回默认的 (直接内存比例, 堆内存比例)
+///
+/// 小文件以堆上的对象开销为主，大文件以直接内存中的传输缓冲区为主。
+/// 文件超过10MB后按文件大小的对数将部分堆比例转给直接内存，
+/// 最多转移堆比例的一半，两者之和保持不变
+pub fn memory_ratios(complexity: &str, avg_file_size: f64) -> (f64, f64) {
+    let (direct, heap) = match complexity {
+        "low" => (0.06, 0.4),  // 低复杂度应用需要更多堆
+        "high" => (0.12, 0.3), // 高IO应用需要更多直接内存
+        _ => (0.08, 0.35),     // 默认比例
+    };
+    let shift = (0.15 * (avg_file_size / 10.0).log10()).clamp(0.0, heap * 0.5);
+    (direct + shift, heap - shift)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_ratios_shift_to_direct_for_large_files() {
+        assert_eq!(memory_ratios("low", 10.0), (0.06, 0.4));
+        assert_eq!(memory_ratios("low", 1.0), (0.06, 0.4));
+
+        let (direct, heap) = memory_ratios("low", 500.0);
+        assert!(direct > heap, "500MB files should be direct-memory heavy");
+        assert!((direct + heap - 0.46).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_blend_disks() {
+        let configs = get_disk_configs().read().unwrap();
+        let nvme = configs["nvme"];
+        let hdd = configs["sata_hdd"];
+        assert_eq!(blend_disks(&nvme, &hdd, 1.0), nvme);
+
+        let blended = blend_disks(&nvme, &hdd, 0.8);
+        assert_eq!(blended.read_speed, 1500.0 * 0.8 + 120.0 * 0.2);
+        assert!(blended.iops > hdd.iops && blended.iops < nvme.iops);
+    }
+
+    #[test]
+    fn test_sustained_write_speed_overrides_nominal() {
+        let args = crate::args::Args {
+            disk_type: "nvme".to_string(),
+            ..Default::default()
+        };
+        let nominal = effective_disk(&args).unwrap();
+        assert_eq!(nominal.sustained_write_speed, nominal.write_speed);
+
+        let cliff = effective_disk(&crate::args::Args {
+            sustained_write_speed: Some(400.0),
+            ..args
+        })
+        .unwrap();
+        assert_eq!(cliff.write_speed, 1200.0);
+        assert_eq!(cliff.sustained_write_speed, 400.0);
+
+        let above_peak = effective_disk(&crate::args::Args {
+            disk_type: "sata_ssd".to_string(),
+            sustained_write_speed: Some(5000.0),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(above_peak.sustained_write_speed, above_peak.write_speed);
+    }
+
+    #[test]
+    fn test_unknown_disk_type_lists_available() {
+        assert_eq!(disk_config_for("nvme").unwrap().iops, 500_000.0);
+        let err = disk_config_for("tape").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "不支持的磁盘类型: tape. 可用选项: nvme, sata_hdd, sata_ssd"
+        );
+    }
+}
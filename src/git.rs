@@ -0,0 +1,67 @@
+use anyhow::{Context, bail};
+use std::path::Path;
+use std::process::Command;
+
+/// 读取文件在指定git版本中的内容
+///
+/// 在文件所在目录执行 `git show <ref>:./<文件名>`，因此与当前工作目录无关
+pub fn show_file(git_ref: &str, path: &Path) -> anyhow::Result<String> {
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let name = path
+        .file_name()
+        .with_context(|| format!("不是文件路径: {}", path.display()))?;
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .arg("show")
+        .arg(format!("{git_ref}:./{}", name.to_string_lossy()))
+        .output()
+        .context("无法执行git")?;
+    if !output.status.success() {
+        bail!(
+            "{git_ref}中没有{}: {}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    String::from_utf8(output.stdout)
+        .with_context(|| format!("{git_ref}中的{}不是UTF-8文本", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["-c", "user.name=sa", "-c", "user.email=sa@example.com"])
+            .args(args)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {args:?}");
+    }
+
+    #[test]
+    fn test_show_file_reads_previous_revision() {
+        let dir = std::env::temp_dir().join(format!("sa_git_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("plan.toml");
+        git(&dir, &["init", "-q"]);
+        std::fs::write(&path, "total_ram = 64\n").unwrap();
+        git(&dir, &["add", "plan.toml"]);
+        git(&dir, &["commit", "-q", "-m", "plan"]);
+        std::fs::write(&path, "total_ram = 128\n").unwrap();
+
+        let previous = show_file("HEAD", &path);
+        let missing = show_file("HEAD", &dir.join("other.toml"));
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(previous.unwrap(), "total_ram = 64\n");
+        assert!(missing.unwrap_err().to_string().starts_with("HEAD中没有"));
+    }
+}
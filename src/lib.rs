@@ -1,7 +1,14 @@
 pub mod analysis;
 pub mod args;
+pub mod assertion;
+pub mod baseline;
+pub mod batch;
 pub mod config;
+pub mod git;
+pub mod sample;
+pub mod tui;
 pub mod utils;
+pub mod watch;
 
 pub use analysis::{performance::PerformanceReport, SafetyAnalysis, Scenario};
 pub use args::Args;
@@ -1,101 +1,500 @@
 use anyhow::Context;
 use clap::Parser;
 use sa::Args;
-use sa::analysis::{calculate_metaspace, calculate_safety};
-use sa::config;
-use sa::utils::{print_configuration, print_safety_report, print_system_limits};
+use sa::analysis::{AnalysisResult, analyze};
+use sa::utils::{
+    print_backlog_recommendations, print_configuration, print_safety_report, print_system_limits,
+};
+use std::io::Write;
 
 fn main() -> anyhow::Result<()> {
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let argv: Vec<String> = std::env::args().collect();
+    let cli = Args::try_parse_from(&argv).unwrap_or_else(|e| e.exit());
+    // 日志和颜色设置同样可以写在配置文件中；配置文件有误时先按命令行设置，错误在后面报告
+    let settings = Args::parse_settings(&argv).unwrap_or_else(|_| cli.clone());
+
+    let log_level = match settings.verbose {
+        0 if settings.quiet => "warn",
+        0 => "info",
+        1 => "debug",
+        _ => "trace",
+    };
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(log_level)).init();
     log::info!("启动文件传输系统分析工具");
-    let args = Args::parse();
-
-    // 获取磁盘配置
-    let configs = config::get_disk_configs().read().unwrap();
-    let disk_config = configs
-        .get(args.disk_type.as_str())
-        .context("无效的磁盘类型")?;
-    let disk_read_speed = disk_config.read_speed;
-    let disk_write_speed = disk_config.write_speed;
-
-    // 1. 计算内存分配
-    // 根据应用类型动态调整内存分配
-    let (direct_ratio, heap_ratio) = match args.complexity.as_str() {
-        "low" => (0.06, 0.4),    // 低复杂度应用需要更多堆
-        "high" => (0.12, 0.3),   // 高IO应用需要更多直接内存
-        _ => (0.08, 0.35)        // 默认比例
+
+    sa::utils::configure_color(settings.no_color || settings.deterministic);
+
+    // 监视模式: 配置文件每次保存后重新加载并分析，文件错误不会退出
+    if cli.watch {
+        let path = cli.config.clone().expect("--watch requires --config");
+        return sa::watch::run(&path, || {
+            let args = Args::parse_with_config(&argv)?;
+            sa::utils::set_auto_width(!args.no_auto_width);
+            sa::utils::set_precision(args.precision);
+            run_analysis(&args, &argv).map(|_| ())
+        });
+    }
+
+    let args = match Args::parse_with_config(&argv) {
+        Ok(args) => args,
+        Err(e) => match e.downcast::<clap::Error>() {
+            Ok(clap_err) => clap_err.exit(),
+            Err(e) => return Err(e),
+        },
     };
-    // 保证最小可用内存
-    let direct_mem_gb = (args.total_ram * direct_ratio).max(1.0);
-    let heap_mem_gb = (args.total_ram * heap_ratio).max(4.0);
-    // 保留10%给JVM Native内存(线程栈等)
-    let _native_mem_gb = args.total_ram * 0.1;
-    log::debug!(
-        "内存分配计算: 总内存={}GB, 直接内存={:.1}GB, 堆内存={:.1}GB",
-        args.total_ram,
-        direct_mem_gb,
-        heap_mem_gb
-    );
-
-    // 2. 动态计算元空间大小
-    let metaspace_size_mb = calculate_metaspace(&args);
-
-    // 3. 计算安全系数
-    let safety = calculate_safety(&args, direct_mem_gb, heap_mem_gb);
+
+    sa::utils::set_auto_width(!args.no_auto_width);
+    sa::utils::set_precision(args.precision);
+
+    // 批量模式: 逐行分析配置文件并输出汇总表，jsonl格式时逐行流式输出
+    if let Some(path) = &args.batch {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("无法读取批量文件: {}", path.display()))?;
+        let mut stdout = std::io::stdout().lock();
+        if args.format == "jsonl" {
+            sa::batch::stream_batch(&contents, &mut stdout, &baseline_time(&args))?;
+        } else {
+            sa::utils::print_batch_summary(&mut stdout, &sa::batch::run_batch(&contents))?;
+        }
+        return Ok(());
+    }
+
+    // 扫描模式: 输出连接数与风险的CSV，供绘制容量曲线
+    if let Some(max_connections) = args.sweep {
+        let points = sa::analysis::safety_sweep(&args, max_connections, args.sweep_points)?;
+        println!("connections,heap_safety,direct_mem_safety,risk_level");
+        for point in points {
+            println!(
+                "{},{:.4},{:.4},{}",
+                point.connections, point.heap_safety, point.direct_mem_safety, point.risk_level
+            );
+        }
+        return Ok(());
+    }
+
+    // 磁盘排名模式: 逐个评估磁盘类型，输出性价比排名表
+    if args.rank_disks {
+        let rankings = sa::analysis::cost::rank_disks(&args)?;
+        sa::utils::print_disk_ranking(&mut std::io::stdout().lock(), &rankings, &args.currency)?;
+        return Ok(());
+    }
+
+    // 扩容路径模式: 逐个解除瓶颈，输出达到目标连接数的扩容步骤
+    if let Some(target) = args.upgrade_path {
+        let path = sa::analysis::upgrade::upgrade_path(&args, target)?;
+        sa::utils::print_upgrade_path(&mut std::io::stdout().lock(), &path, &args.currency)?;
+        return Ok(());
+    }
+
+    // 输出模式: 只分析一次，依次交给各 `--emit` 目标的渲染器
+    if !args.emit.is_empty() {
+        let result = analyze(&args)?;
+        for (format, path) in &args.emit {
+            match path {
+                Some(path) => {
+                    let mut file = std::fs::File::create(path)
+                        .with_context(|| format!("无法写入输出文件: {}", path.display()))?;
+                    sa::utils::without_color(|| emit(&mut file, format, &args, &result))?;
+                    log::info!("{format}输出已写入: {}", path.display());
+                }
+                None => emit(&mut std::io::stdout().lock(), format, &args, &result)?,
+            }
+        }
+        check_gates(&args, &result)?;
+        return Ok(());
+    }
+
+    // 交互模式: 由TUI循环接管输出
+    if args.tui {
+        return sa::tui::run(args);
+    }
+
+    let result = run_analysis(&args, &argv)?;
+    check_gates(&args, &result)?;
+    // 静默模式通过退出码返回结论
+    if args.quiet && result.safety.risk_level != "低风险" {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// 执行分析并输出完整报告，静默模式下只输出告警
+///
+/// `argv` 为原始命令行参数，与git历史版本的配置对比时用于重新解析
+fn run_analysis(args: &Args, argv: &[String]) -> anyhow::Result<AnalysisResult> {
+    let result = analyze(args)?;
+    let mut stdout = std::io::stdout().lock();
+    let markdown = args.format == "markdown";
+    // markdown写到标准输出时不再混入控制台章节，保证输出可直接交给pandoc等工具
+    let markdown_to_stdout = markdown
+        && args
+            .output
+            .as_ref()
+            .is_none_or(|path| path.as_os_str() == "-");
+    // jsonl的标准输出只包含结果行
+    let jsonl = args.format == "jsonl";
+    // 报告文件不受静默模式影响，静默只抑制标准输出
+    if let Some(path) = args
+        .output
+        .as_ref()
+        .filter(|_| markdown && !markdown_to_stdout)
+    {
+        let mut file = std::fs::File::create(path)
+            .with_context(|| format!("无法写入报告: {}", path.display()))?;
+        sa::utils::write_markdown_report(
+            &mut file,
+            &report_context(args, &result),
+            args.generated_at(),
+        )?;
+        log::info!("Markdown报告已生成: {}", path.display());
+    }
+    // jsonl结果行是机器可读的输出而非报告章节，静默模式下同样输出
+    if jsonl {
+        let baseline = sa::baseline::Baseline::from_result(&result, args, &baseline_time(args));
+        writeln!(stdout, "{}", baseline.to_json_line(&[]))?;
+        stdout.flush()?;
+    }
+    if args.quiet {
+        sa::utils::print_quiet_summary(&mut std::io::stderr().lock(), &result.safety)?;
+    } else if markdown_to_stdout {
+        sa::utils::write_markdown_report(
+            &mut stdout,
+            &report_context(args, &result),
+            args.generated_at(),
+        )?;
+    } else if !markdown && !jsonl {
+        print_report(&mut stdout, args, &result)?;
+    }
+
+    // 基线对比: 基线不可用时不影响本次分析
+    if let Some(path) = &args.baseline
+        && !args.quiet
+        && !markdown_to_stdout
+        && !jsonl
+    {
+        match sa::baseline::load(path) {
+            Ok(baseline) => sa::utils::print_baseline_comparison(
+                &mut stdout,
+                &baseline,
+                &sa::baseline::Baseline::from_result(&result, args, &baseline_time(args)),
+            )?,
+            Err(e) => log::warn!("{e:#}，跳过基线对比"),
+        }
+    }
+
+    // 与git历史版本的配置对比: 该版本中没有配置文件时只输出本次分析
+    if let Some(git_ref) = &args.since_git
+        && let Some(config) = &args.config
+        && !args.quiet
+        && !markdown_to_stdout
+        && !jsonl
+    {
+        match previous_baseline(args, argv, git_ref, config) {
+            Ok(previous) => sa::utils::print_git_comparison(
+                &mut stdout,
+                git_ref,
+                config,
+                &previous,
+                &sa::baseline::Baseline::from_result(&result, args, &baseline_time(args)),
+            )?,
+            Err(e) => log::warn!("{e:#}，跳过与{git_ref}的对比"),
+        }
+    }
+    write_outputs(&mut stdout, args, &result)?;
+    Ok(result)
+}
+
+/// 按 `--emit` 的格式渲染分析结果: 报告、指标JSON，或可直接用于部署的
+/// JAVA_TOOL_OPTIONS、systemd单元和k8s清单
+fn emit(
+    out: &mut impl Write,
+    format: &str,
+    args: &Args,
+    result: &AnalysisResult,
+) -> anyhow::Result<()> {
+    match format {
+        "text" => print_report(out, args, result)?,
+        "markdown" => sa::utils::write_markdown_report(
+            out,
+            &report_context(args, result),
+            args.generated_at(),
+        )?,
+        "json" => {
+            let baseline = sa::baseline::Baseline::from_result(result, args, &baseline_time(args));
+            writeln!(out, "{}", baseline.to_json())?;
+        }
+        _ => {
+            let flags = sa::analysis::jvm::JvmFlags::recommended(
+                args,
+                result.direct_mem_gb,
+                result.heap_mem_gb,
+                result.metaspace_size_mb,
+                &result.safety,
+            );
+            let reconciliation = sa::analysis::reconcile_memory(
+                args,
+                result.direct_mem_gb,
+                result.heap_mem_gb,
+                result.metaspace_size_mb,
+            );
+            match format {
+                "dockerfile" => writeln!(out, "{}", flags.dockerfile_env())?,
+                "systemd" => {
+                    let limits = sa::analysis::jvm::SystemdLimits::new(
+                        args,
+                        reconciliation.allocated_gb,
+                        &result.safety,
+                    );
+                    writeln!(out, "{}", flags.systemd_service(args, &limits))?;
+                }
+                "k8s" => {
+                    let resources = sa::analysis::jvm::K8sResources::new(
+                        args,
+                        reconciliation.allocated_gb,
+                        sa::analysis::native::native_reserved_gb(args, result.heap_mem_gb),
+                        &result.safety,
+                    );
+                    writeln!(out, "{}", resources.manifest())?;
+                }
+                _ => writeln!(out, "{}", flags.shell_export())?,
+            }
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// 检查 `--assert` 断言和 `--strict-memory-fit`，任一不通过时输出失败项并以退出码2退出
+fn check_gates(args: &Args, result: &AnalysisResult) -> anyhow::Result<()> {
+    let mut stderr = std::io::stderr().lock();
+    let mut passed = true;
+
+    if !args.assertions.is_empty() {
+        let baseline = sa::baseline::Baseline::from_result(result, args, &baseline_time(args));
+        let failures = sa::assertion::failed_assertions(&args.assertions, &baseline);
+        if failures.is_empty() {
+            log::info!("{}个断言全部成立", args.assertions.len());
+        } else {
+            sa::utils::print_assertion_failures(&mut stderr, &failures, args.assertions.len())?;
+            passed = false;
+        }
+    }
+
+    if args.strict_memory_fit {
+        let failures = sa::analysis::safety::memory_fit_failures(
+            args,
+            result.direct_mem_gb,
+            result.heap_mem_gb,
+            &result.safety.scenarios,
+        );
+        if !failures.is_empty() {
+            sa::utils::print_memory_fit_failures(&mut stderr, &failures)?;
+            passed = false;
+        }
+    }
+
+    if !passed {
+        std::process::exit(2);
+    }
+    Ok(())
+}
+
+/// 按顺序输出各控制台报告章节
+fn print_report(out: &mut impl Write, args: &Args, result: &AnalysisResult) -> anyhow::Result<()> {
+    let safety = &result.safety;
+    let performance = &result.performance;
 
     // 1. 打印系统配置和基础分析
-    print_configuration(
-        &args,
-        direct_mem_gb,
-        heap_mem_gb,
-        metaspace_size_mb,
-        disk_read_speed,
-        disk_write_speed,
-    );
+    if args.includes_section("config") {
+        write_config_section(out, args, result)?;
+    }
+
+    // 余量模式: 优先回答预期负载下还剩多少余量
+    if args.percent_headroom && args.includes_section("safety") {
+        sa::utils::print_headroom_report(out, safety)?;
+    }
 
     // 2. 打印系统极限评估
-    print_system_limits(&safety);
+    if args.includes_section("safety") {
+        write_limits_section(out, result)?;
+    }
 
     // 3. 打印场景模拟分析
-    sa::analysis::print_scenarios(&safety);
+    if args.includes_section("scenarios") {
+        write_scenarios_section(out, args, result)?;
+    }
 
-    // 4. 打印安全性报告
-    print_safety_report(&safety);
+    // 4. 打印安全性报告，告警和优化建议属于recommendations章节
+    match (
+        args.includes_section("safety"),
+        args.includes_section("recommendations"),
+    ) {
+        (true, true) => print_safety_report(out, safety, args.explain)?,
+        (true, false) => sa::utils::print_risk_assessment(out, safety)?,
+        (false, true) => sa::utils::print_safety_advice(out, safety, args.explain)?,
+        (false, false) => {}
+    }
 
-    // 5. 计算并打印性能报告
-    let performance = sa::analysis::performance::calculate_performance(
-        &args,
-        disk_config,
-        direct_mem_gb,
-        heap_mem_gb,
-    );
-    sa::utils::print_performance_report(&performance);
+    // 5. 打印性能报告
+    if args.includes_section("performance") {
+        sa::utils::print_performance_report(out, performance)?;
+    }
 
     // 6. 打印JVM配置建议
+    if args.includes_section("jvm") {
+        write_jvm_section(out, args, result)?;
+    }
+    Ok(())
+}
+
+/// 控制台报告: 系统配置和基础分析
+fn write_config_section(
+    out: &mut impl Write,
+    args: &Args,
+    result: &AnalysisResult,
+) -> std::io::Result<()> {
+    print_configuration(
+        out,
+        args,
+        result.direct_mem_gb,
+        result.heap_mem_gb,
+        result.metaspace_size_mb,
+        result.disk_read_speed,
+        result.disk_write_speed,
+    )
+}
+
+/// 控制台报告: 系统极限、连接队列和集群容量
+fn write_limits_section(out: &mut impl Write, result: &AnalysisResult) -> std::io::Result<()> {
+    print_system_limits(out, &result.safety)?;
+    print_backlog_recommendations(out, &result.backlog)?;
+    if let Some(cluster) = &result.cluster {
+        sa::utils::print_cluster_summary(out, cluster)?;
+    }
+    Ok(())
+}
+
+/// 控制台报告: 场景模拟及 `--burst-sweep` 的突发倍数扫描
+fn write_scenarios_section(
+    out: &mut impl Write,
+    args: &Args,
+    result: &AnalysisResult,
+) -> anyhow::Result<()> {
+    let policy = sa::analysis::SafetyPolicy::from_args(args);
+    sa::analysis::print_scenarios(out, &result.safety, &policy)?;
+    if !args.burst_sweep.is_empty() {
+        let points = sa::analysis::burst_sweep(args, &args.burst_sweep)?;
+        sa::utils::print_burst_sweep(out, &points)?;
+    }
+    Ok(())
+}
+
+/// 控制台报告: JVM配置建议及 `--explain-metaspace` 的元空间推导
+fn write_jvm_section(
+    out: &mut impl Write,
+    args: &Args,
+    result: &AnalysisResult,
+) -> std::io::Result<()> {
     sa::analysis::print_jvm_recommendations(
-        &args,
-        direct_mem_gb,
-        heap_mem_gb,
-        metaspace_size_mb,
-        &safety,
-        &performance,
-    );
+        out,
+        args,
+        result.direct_mem_gb,
+        result.heap_mem_gb,
+        result.metaspace_size_mb,
+        &result.safety,
+        &result.performance,
+    )?;
+    if args.explain_metaspace {
+        sa::analysis::print_metaspace_explanation(out, args)?;
+    }
+    Ok(())
+}
+
+/// 敏感性分析和各类文件输出，静默模式下仍写出文件
+fn write_outputs(out: &mut impl Write, args: &Args, result: &AnalysisResult) -> anyhow::Result<()> {
+    // 7. 敏感性分析
+    if args.sensitivity && !args.quiet && args.format == "console" {
+        let report = sa::analysis::sensitivity::analyze_sensitivity(
+            args,
+            args.sensitivity_pct,
+            args.sensitivity_samples,
+            args.seed,
+        )?;
+        sa::utils::print_sensitivity_report(out, &report)?;
+    }
+
+    if let Some(path) = &args.save_baseline {
+        let baseline = sa::baseline::Baseline::from_result(result, args, &baseline_time(args));
+        sa::baseline::save(path, &baseline)?;
+        log::info!("基线已保存: {}", path.display());
+    }
+
+    // 8. 写出测试文件生成脚本
+    if let Some(path) = &args.emit_testfiles_script {
+        write_testfiles_script(path)?;
+        log::info!("测试文件生成脚本已写出: {}", path.display());
+    }
 
     // 9. 生成markdown报告
     if args.generate_markdown {
-        let report_ctx = sa::utils::ReportContext {
-            args: &args,
-            direct_mem_gb,
-            heap_mem_gb,
-            metaspace_size_mb,
-            disk_read_speed,
-            disk_write_speed,
-            safety: &safety,
-            performance: &performance,
-        };
-        sa::utils::generate_markdown_report(&report_ctx)?;
-        log::info!("Markdown报告已生成: sa_report.md");
+        let report_ctx = report_context(args, result);
+        let path = sa::utils::generate_markdown_report(&report_ctx, args.generated_at())?;
+        log::info!("Markdown报告已生成: {}", path.display());
+    }
+
+    Ok(())
+}
+
+fn report_context<'a>(args: &'a Args, result: &'a AnalysisResult) -> sa::utils::ReportContext<'a> {
+    sa::utils::ReportContext {
+        args,
+        direct_mem_gb: result.direct_mem_gb,
+        heap_mem_gb: result.heap_mem_gb,
+        metaspace_size_mb: result.metaspace_size_mb,
+        disk_read_speed: result.disk_read_speed,
+        disk_write_speed: result.disk_write_speed,
+        safety: &result.safety,
+        performance: &result.performance,
+        backlog: &result.backlog,
+        cluster: result.cluster.as_ref(),
     }
+}
 
+/// 用配置文件在git历史版本中的内容(命令行参数同样优先)重新分析，得到对比用的关键指标
+fn previous_baseline(
+    args: &Args,
+    argv: &[String],
+    git_ref: &str,
+    config: &std::path::Path,
+) -> anyhow::Result<sa::baseline::Baseline> {
+    let contents = sa::git::show_file(git_ref, config)?;
+    let previous_args = Args::parse_with_config_contents(
+        argv,
+        &contents,
+        &format!("{git_ref}:{}", config.display()),
+    )?;
+    let previous = analyze(&previous_args)?;
+    Ok(sa::baseline::Baseline::from_result(
+        &previous,
+        &previous_args,
+        &baseline_time(args),
+    ))
+}
+
+/// 基线中记录的生成时间(精确到秒)
+fn baseline_time(args: &Args) -> String {
+    args.generated_at()
+        .to_rfc3339_opts(chrono::SecondsFormat::Secs, false)
+}
+
+fn write_testfiles_script(path: &std::path::Path) -> anyhow::Result<()> {
+    std::fs::write(path, sa::analysis::performance::generate_testfiles_script())
+        .with_context(|| format!("无法写入脚本: {}", path.display()))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))?;
+    }
     Ok(())
 }
@@ -0,0 +1,105 @@
+use crate::args::FileSizeDistribution;
+use anyhow::Context;
+use std::path::Path;
+
+const BYTES_PER_MB: f64 = 1024.0 * 1024.0;
+
+/// 样本目录的文件大小统计
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileSample {
+    pub files: usize,                       // 参与统计的文件数
+    pub skipped: usize,                     // 跳过的子目录、符号链接和空文件
+    pub mean_mb: f64,                       // 平均文件大小(MB)
+    pub distribution: FileSizeDistribution, // 按2的幂分桶的文件大小分布
+}
+
+/// 统计目录下普通文件的大小(不递归子目录，不跟随符号链接)
+///
+/// 分布按1MB、2MB、4MB…分桶，每个桶的大小取桶内文件的平均值，
+/// 因此分布的加权平均与样本平均值一致。空文件不参与统计
+pub fn sample_dir(path: &Path) -> anyhow::Result<FileSample> {
+    let entries =
+        std::fs::read_dir(path).with_context(|| format!("无法读取样本目录: {}", path.display()))?;
+
+    let mut sizes = Vec::new();
+    let mut skipped = 0;
+    for entry in entries {
+        let entry = entry.with_context(|| format!("无法读取样本目录: {}", path.display()))?;
+        // DirEntry::file_type不跟随符号链接
+        let file_type = entry.file_type()?;
+        let len = if file_type.is_file() {
+            entry.metadata()?.len()
+        } else {
+            0
+        };
+        if len == 0 {
+            skipped += 1;
+            continue;
+        }
+        sizes.push(len as f64 / BYTES_PER_MB);
+    }
+    if sizes.is_empty() {
+        anyhow::bail!("样本目录中没有非空的普通文件: {}", path.display());
+    }
+
+    Ok(FileSample {
+        files: sizes.len(),
+        skipped,
+        mean_mb: sizes.iter().sum::<f64>() / sizes.len() as f64,
+        distribution: histogram(&sizes),
+    })
+}
+
+/// 按2的幂分桶: 第0桶为不超过1MB，第n桶为(2^(n-1), 2^n]MB
+fn histogram(sizes: &[f64]) -> FileSizeDistribution {
+    let mut buckets: Vec<(f64, usize)> = Vec::new(); // (桶内总大小MB, 文件数)
+    for &size in sizes {
+        let index = size.log2().ceil().max(0.0) as usize;
+        if buckets.len() <= index {
+            buckets.resize(index + 1, (0.0, 0));
+        }
+        buckets[index].0 += size;
+        buckets[index].1 += 1;
+    }
+    FileSizeDistribution {
+        buckets: buckets
+            .into_iter()
+            .filter(|(_, count)| *count > 0)
+            .map(|(total, count)| (total / count as f64, count as f64 / sizes.len() as f64))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_preserves_mean() {
+        let sizes = [0.5, 0.25, 3.0, 3.5, 100.0];
+        let distribution = histogram(&sizes);
+        assert_eq!(distribution.buckets.len(), 3);
+        assert_eq!(distribution.buckets[0], (0.375, 0.4));
+        assert_eq!(distribution.buckets[1], (3.25, 0.4));
+        assert_eq!(distribution.buckets[2], (100.0, 0.2));
+        let mean = sizes.iter().sum::<f64>() / sizes.len() as f64;
+        assert!((distribution.mean() - mean).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_sample_dir_skips_directories_and_empty_files() {
+        let dir = std::env::temp_dir().join(format!("sa_sample_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        std::fs::write(dir.join("a.bin"), vec![0u8; 1024 * 1024]).unwrap();
+        std::fs::write(dir.join("b.bin"), vec![0u8; 3 * 1024 * 1024]).unwrap();
+        std::fs::write(dir.join("empty.bin"), []).unwrap();
+        std::fs::write(dir.join("nested").join("c.bin"), vec![0u8; 1024]).unwrap();
+
+        let sample = sample_dir(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(sample.files, 2);
+        assert_eq!(sample.skipped, 2);
+        assert_eq!(sample.mean_mb, 2.0);
+        assert_eq!(sample.distribution.buckets, vec![(1.0, 0.5), (3.0, 0.5)]);
+    }
+}
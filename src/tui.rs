@@ -0,0 +1,254 @@
+use crate::analysis::analyze;
+use crate::args::Args;
+use crate::utils::{Repeated, print_safety_bar};
+use colored::Colorize;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::{cursor, execute, terminal};
+use std::io::{self, Write};
+
+/// 按键调整步长
+const RAM_STEP_GB: f64 = 4.0;
+const CORE_STEP: usize = 1;
+const CONNECTION_STEP: usize = 500;
+const FILE_SIZE_STEP_MB: f64 = 5.0;
+
+/// 一次按键的处理结果
+#[derive(Debug, PartialEq)]
+enum KeyAction {
+    Quit,          // 退出交互模式
+    PrintFlags,    // 显示当前配置对应的命令行参数
+    Adjusted,      // 参数已调整，需要重新分析
+    Ignored,       // 按键释放等无需处理的事件
+    Unknown(char), // 未识别的按键
+}
+
+/// 终端原始模式和备用屏幕的守卫，离开作用域(含出错返回)时恢复终端
+struct RawTerminal;
+
+impl RawTerminal {
+    fn enter() -> anyhow::Result<Self> {
+        terminal::enable_raw_mode()
+            .map_err(|e| anyhow::anyhow!("无法进入终端原始模式(交互模式需要在终端中运行): {e}"))?;
+        let guard = RawTerminal;
+        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(guard)
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// 交互模式主循环
+///
+/// 终端切换到原始模式，每次按键立即调整参数并重新调用 [`analyze`] 刷新显示；
+/// 按 `q`、Esc 或 Ctrl+C 退出，按 `p` 输出当前配置对应的命令行参数
+pub fn run(mut args: Args) -> anyhow::Result<()> {
+    let _terminal = RawTerminal::enter()?;
+    let mut message = String::new();
+
+    loop {
+        let mut frame = Vec::new();
+        render(&mut frame, &args, &message)?;
+        // 原始模式下换行不会回到行首
+        let frame = String::from_utf8_lossy(&frame).replace('\n', "\r\n");
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(frame.as_bytes())?;
+        stdout.flush()?;
+        drop(stdout);
+
+        let key = match event::read()? {
+            Event::Key(key) => key,
+            // 窗口大小变化等事件只需重新绘制
+            _ => continue,
+        };
+        match handle_key(&mut args, &key) {
+            KeyAction::Quit => return Ok(()),
+            KeyAction::PrintFlags => message = args.to_cli_flags(),
+            KeyAction::Adjusted => message.clear(),
+            KeyAction::Ignored => {}
+            KeyAction::Unknown(key) => message = format!("未知按键: {key}"),
+        }
+    }
+}
+
+/// 将按键事件映射为参数调整或控制操作
+fn handle_key(args: &mut Args, key: &KeyEvent) -> KeyAction {
+    if key.kind == KeyEventKind::Release {
+        return KeyAction::Ignored;
+    }
+    match key.code {
+        KeyCode::Esc => KeyAction::Quit,
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => KeyAction::Quit,
+        KeyCode::Char('q' | 'Q') => KeyAction::Quit,
+        KeyCode::Char('p' | 'P') => KeyAction::PrintFlags,
+        KeyCode::Char(c) if apply_key(args, c) => KeyAction::Adjusted,
+        KeyCode::Char(c) => KeyAction::Unknown(c),
+        _ => KeyAction::Ignored,
+    }
+}
+
+/// 根据按键调整参数，返回按键是否被识别
+fn apply_key(args: &mut Args, key: char) -> bool {
+    match key {
+        'R' => args.total_ram += RAM_STEP_GB,
+        'r' => args.total_ram = (args.total_ram - RAM_STEP_GB).max(1.0),
+        'C' => args.cpu_cores += CORE_STEP,
+        'c' => args.cpu_cores = args.cpu_cores.saturating_sub(CORE_STEP).max(1),
+        'N' => args.expected_connections += CONNECTION_STEP,
+        'n' => {
+            args.expected_connections = args
+                .expected_connections
+                .saturating_sub(CONNECTION_STEP)
+                .max(1)
+        }
+        'F' => args.avg_file_size += FILE_SIZE_STEP_MB,
+        'f' => args.avg_file_size = (args.avg_file_size - FILE_SIZE_STEP_MB).max(1.0),
+        _ => return false,
+    }
+    true
+}
+
+fn render(out: &mut impl Write, args: &Args, message: &str) -> std::io::Result<()> {
+    // 清屏并将光标移到左上角
+    write!(out, "\x1b[2J\x1b[H")?;
+
+    writeln!(
+        out,
+        "{}{}",
+        "▬".cyan().bold().reversed(),
+        " 交互分析模式 ".cyan().bold().reversed()
+    )?;
+    writeln!(out, "{}", "▬".cyan().bold().repeated(50))?;
+
+    writeln!(
+        out,
+        "  {:>20}: {:.1} GB",
+        "服务器内存".cyan(),
+        args.total_ram
+    )?;
+    writeln!(out, "  {:>20}: {}", "CPU核心数".cyan(), args.cpu_cores)?;
+    writeln!(
+        out,
+        "  {:>20}: {}",
+        "预期并发连接".cyan(),
+        args.expected_connections
+    )?;
+    writeln!(
+        out,
+        "  {:>20}: {:.1} MB",
+        "平均文件大小".cyan(),
+        args.avg_file_size
+    )?;
+
+    match analyze(args) {
+        Ok(result) => {
+            let safety = &result.safety;
+            let risk_color = match safety.risk_level.as_str() {
+                "低风险" => "green",
+                "中风险" => "yellow",
+                _ => "red",
+            };
+
+            writeln!(out)?;
+            writeln!(
+                out,
+                "  {:>20}: {}",
+                "整体风险等级".cyan(),
+                safety.risk_level.color(risk_color).bold()
+            )?;
+            print_safety_bar(out, "堆内存安全", safety.heap_safety)?;
+            print_safety_bar(out, "直接内存安全", safety.direct_mem_safety)?;
+            writeln!(
+                out,
+                "  {:>20}: {}",
+                "理论最大连接数".cyan(),
+                safety.theoretical_limits.max_connections
+            )?;
+            writeln!(
+                out,
+                "  {:>20}: {}",
+                "主要限制因素".cyan(),
+                safety.theoretical_limits.limiting_factor
+            )?;
+        }
+        Err(e) => writeln!(out, "\n  {}: {e:#}", "分析失败".red().bold())?,
+    }
+
+    writeln!(
+        out,
+        "\n  {}: R/r 内存±{RAM_STEP_GB}GB  C/c 核心±{CORE_STEP}  N/n 连接±{CONNECTION_STEP}  F/f 文件±{FILE_SIZE_STEP_MB}MB  p 输出参数  q/Esc 退出",
+        "按键".cyan()
+    )?;
+
+    if !message.is_empty() {
+        writeln!(out, "\n  {message}")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_key_respects_lower_bounds() {
+        let mut args = Args {
+            total_ram: 2.0,
+            cpu_cores: 1,
+            expected_connections: 100,
+            avg_file_size: 2.0,
+            ..Default::default()
+        };
+        for key in ['r', 'c', 'n', 'f'] {
+            assert!(apply_key(&mut args, key));
+        }
+        assert_eq!(args.total_ram, 1.0);
+        assert_eq!(args.cpu_cores, 1);
+        assert_eq!(args.expected_connections, 1);
+        assert_eq!(args.avg_file_size, 1.0);
+        assert!(!apply_key(&mut args, 'x'));
+    }
+
+    #[test]
+    fn test_handle_key_maps_raw_key_events() {
+        let mut args = Args {
+            cpu_cores: 4,
+            ..Default::default()
+        };
+        let press = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        assert_eq!(
+            handle_key(&mut args, &press(KeyCode::Char('C'))),
+            KeyAction::Adjusted
+        );
+        assert_eq!(args.cpu_cores, 5);
+        assert_eq!(
+            handle_key(&mut args, &press(KeyCode::Char('p'))),
+            KeyAction::PrintFlags
+        );
+        assert_eq!(
+            handle_key(&mut args, &press(KeyCode::Char('x'))),
+            KeyAction::Unknown('x')
+        );
+        assert_eq!(
+            handle_key(&mut args, &press(KeyCode::Up)),
+            KeyAction::Ignored
+        );
+        assert_eq!(handle_key(&mut args, &press(KeyCode::Esc)), KeyAction::Quit);
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert_eq!(handle_key(&mut args, &ctrl_c), KeyAction::Quit);
+        assert_eq!(args.cpu_cores, 5);
+
+        // 按键释放事件不重复调整
+        let release = KeyEvent::new_with_kind(
+            KeyCode::Char('C'),
+            KeyModifiers::NONE,
+            KeyEventKind::Release,
+        );
+        assert_eq!(handle_key(&mut args, &release), KeyAction::Ignored);
+        assert_eq!(args.cpu_cores, 5);
+    }
+}
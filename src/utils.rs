@@ -1,6 +1,100 @@
+use crate::analysis::Severity;
+use crate::analysis::cost::{CostRates, ScalingSuggestion, estimate_scaling_cost, format_cost};
 use colored::Colorize;
 use std::fs::File;
-use std::io::Write;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use unicode_width::UnicodeWidthStr;
+
+/// 统一决定控制台输出是否着色
+///
+/// `--no-color`、非空的 `NO_COLOR` 环境变量或标准输出不是终端(重定向到文件/管道)
+/// 时关闭颜色。所有 `.color()` 调用都遵循 `colored` 的全局开关，无需逐处判断
+pub fn configure_color(no_color: bool) {
+    let env_disabled = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    if no_color || env_disabled || !std::io::stdout().is_terminal() {
+        colored::control::set_override(false);
+    }
+}
+
+/// 临时关闭彩色输出，用于把控制台报告写入文件
+pub fn without_color<T>(f: impl FnOnce() -> T) -> T {
+    let colorize = colored::control::SHOULD_COLORIZE.should_colorize();
+    colored::control::set_override(false);
+    let result = f();
+    colored::control::set_override(colorize);
+    result
+}
+
+/// 控制台表格是否按内容自动计算列宽
+static AUTO_WIDTH: AtomicBool = AtomicBool::new(true);
+
+/// 设置控制台表格列宽模式，关闭后使用固定列宽
+pub fn set_auto_width(enabled: bool) {
+    AUTO_WIDTH.store(enabled, Ordering::Relaxed);
+}
+
+/// 未指定 `--precision` 时为 `usize::MAX`，各字段使用自身的默认小数位数
+static PRECISION: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+/// 设置控制台和markdown报告中数值的统一小数位数
+pub fn set_precision(places: Option<usize>) {
+    PRECISION.store(places.unwrap_or(usize::MAX), Ordering::Relaxed);
+}
+
+/// 按 `--precision` 格式化报告中的数值，未指定时使用该字段的默认小数位数
+pub fn fmt_num(value: f64, default_places: usize) -> String {
+    let places = match PRECISION.load(Ordering::Relaxed) {
+        usize::MAX => default_places,
+        places => places,
+    };
+    format!("{value:.places$}")
+}
+
+/// 预期连接数下的p99延迟，排队饱和时显示为 `∞ (饱和)` 而非 `inf`
+fn p99_label(slo: &crate::analysis::performance::SloCheck, unit: &str) -> String {
+    if slo.saturated() {
+        "∞ (饱和)".to_string()
+    } else {
+        format!("{}{unit}", fmt_num(slo.expected_p99_ms, 0))
+    }
+}
+
+/// 各状态的场景数量，按 安全/警告/危险 的顺序
+pub fn status_counts(
+    scenarios: &[crate::Scenario],
+) -> [(crate::analysis::ScenarioStatus, usize); 3] {
+    crate::analysis::ScenarioStatus::ALL.map(|status| {
+        (
+            status,
+            scenarios.iter().filter(|s| s.status == status).count(),
+        )
+    })
+}
+
+/// 场景状态汇总，如 "2 安全, 2 警告, 1 危险"，`paint` 决定每一项的着色方式
+pub fn status_summary(
+    scenarios: &[crate::Scenario],
+    paint: impl Fn(crate::analysis::ScenarioStatus, &str) -> String,
+) -> String {
+    status_counts(scenarios)
+        .iter()
+        .map(|(status, count)| paint(*status, &format!("{count} {}", status.label())))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// 安全、警告、危险三种场景状态对应的内存占用范围，如 "<42%"、"42-56%"、"≥56%"
+pub fn status_ranges(policy: &crate::analysis::SafetyPolicy) -> [String; 3] {
+    let (warn, danger) = policy.status_bounds_pct();
+    let (warn, danger) = (fmt_num(warn, 0), fmt_num(danger, 0));
+    [
+        format!("<{warn}%"),
+        format!("{warn}-{danger}%"),
+        format!("≥{danger}%"),
+    ]
+}
 
 /// 报告生成上下文
 pub struct ReportContext<'a> {
@@ -12,44 +106,210 @@ pub struct ReportContext<'a> {
     pub disk_write_speed: f64,
     pub safety: &'a crate::analysis::SafetyAnalysis,
     pub performance: &'a crate::analysis::performance::PerformanceReport,
+    pub backlog: &'a crate::analysis::backlog::BacklogRecommendation,
+    pub cluster: Option<&'a crate::analysis::cluster::ClusterAnalysis>,
+}
+
+/// 生成markdown报告并写入输出目录，返回报告文件路径
+///
+/// `generated_at` 写入报告头部(以及带时间戳的文件名)，固定该时间即可得到逐字节可复现的报告。
+/// 输出目录不存在时自动创建
+pub fn generate_markdown_report(
+    ctx: &ReportContext,
+    generated_at: chrono::DateTime<chrono::FixedOffset>,
+) -> anyhow::Result<PathBuf> {
+    let path = report_path(&ctx.args.output_dir, ctx.args.timestamped, generated_at);
+    if !ctx.args.output_dir.as_os_str().is_empty() {
+        std::fs::create_dir_all(&ctx.args.output_dir)?;
+    }
+    write_markdown_report(&mut File::create(&path)?, ctx, generated_at)?;
+    Ok(path)
+}
+
+/// 将markdown报告写入任意输出(文件、标准输出等)，内容与报告文件一致
+pub fn write_markdown_report(
+    out: &mut impl Write,
+    ctx: &ReportContext,
+    generated_at: chrono::DateTime<chrono::FixedOffset>,
+) -> anyhow::Result<()> {
+    out.write_all(render_markdown_report(ctx, generated_at)?.as_bytes())?;
+    Ok(())
+}
+
+/// 报告文件路径: `--timestamped` 时为 `sa_report_YYYYMMDD_HHMMSS.md`，否则为 `sa_report.md`
+fn report_path(
+    output_dir: &Path,
+    timestamped: bool,
+    generated_at: chrono::DateTime<chrono::FixedOffset>,
+) -> PathBuf {
+    let file_name = if timestamped {
+        format!("sa_report_{}.md", generated_at.format("%Y%m%d_%H%M%S"))
+    } else {
+        "sa_report.md".to_string()
+    };
+    output_dir.join(file_name)
 }
 
-/// 生成markdown报告
-pub fn generate_markdown_report(ctx: &ReportContext) -> anyhow::Result<()> {
-    let mut file = File::create("sa_report.md")?;
+/// 渲染完整的markdown报告内容
+pub fn render_markdown_report(
+    ctx: &ReportContext,
+    generated_at: chrono::DateTime<chrono::FixedOffset>,
+) -> anyhow::Result<String> {
+    // 正文先写入缓冲区，以便根据实际写出的标题生成目录
+    let mut file: Vec<u8> = Vec::new();
+
+    // 2-3. 系统配置和内存配置建议
+    if ctx.args.includes_section("config") {
+        write_config_section(&mut file, ctx)?;
+    }
+
+    // 4. 系统极限评估
+    if ctx.args.includes_section("safety") {
+        write_limits_section(&mut file, ctx)?;
+    }
+
+    // 5. 负载场景模拟
+    if ctx.args.includes_section("scenarios") {
+        write_scenarios_section(&mut file, ctx)?;
+    }
+
+    // 6. 内存安全分析
+    if ctx.args.includes_section("safety") {
+        write_memory_safety_section(&mut file, ctx)?;
+    }
+
+    // 7. JVM配置建议
+    if ctx.args.includes_section("jvm") {
+        write_jvm_section(&mut file, ctx)?;
+    }
+
+    // 8. 性能分析
+    if ctx.args.includes_section("performance") {
+        write_performance_section(&mut file, ctx)?;
+    }
+
+    // 9. 服务器扩容建议
+    if ctx.args.includes_section("safety") {
+        write_scaling_section(&mut file, ctx)?;
+    }
+
+    // 10. 延迟SLO和性能测试建议
+    if ctx.args.includes_section("performance") {
+        write_performance_test_section(&mut file, ctx)?;
+    }
+
+    // 11. 告警和优化建议
+    if ctx.args.includes_section("recommendations") {
+        write_recommendations_section(&mut file, ctx)?;
+    }
 
-    // 1. 标题和基本信息
-    writeln!(file, "# 文件传输系统分析报告")?;
+    // 1. 标题、基本信息和目录
+    let body = String::from_utf8(file)?;
+    let mut out: Vec<u8> = Vec::new();
+    writeln!(out, "# 文件传输系统分析报告")?;
     writeln!(
-        file,
+        out,
         "> 生成时间: {}\n",
-        chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+        generated_at.format("%Y-%m-%d %H:%M:%S")
     )?;
+    write_table_of_contents(&mut out, &body)?;
+    out.write_all(body.as_bytes())?;
+
+    Ok(String::from_utf8(out)?)
+}
 
-    // 2. 系统配置
+/// markdown报告: 系统配置、内存配置建议及内存分配对账
+fn write_config_section(file: &mut impl Write, ctx: &ReportContext) -> std::io::Result<()> {
     writeln!(file, "## 系统配置")?;
     writeln!(file, "| 配置项 | 值 |")?;
     writeln!(file, "|--------|----|")?;
-    writeln!(file, "| 服务器内存 | {:.1} GB |", ctx.args.total_ram)?;
+    writeln!(
+        file,
+        "| 服务器内存 | {} GB |",
+        fmt_num(ctx.args.total_ram, 1)
+    )?;
     writeln!(file, "| CPU核心数 | {} |", ctx.args.cpu_cores)?;
-    writeln!(file, "| 网络带宽 | {:.1} Gbps |", ctx.args.net_gbps)?;
+    writeln!(file, "| 网络带宽 | {} |", net_bandwidth_label(ctx.args))?;
     writeln!(
         file,
-        "| 磁盘类型 | {} (读: {:.0} MB/s, 写: {:.0} MB/s) |",
-        ctx.args.disk_type, ctx.disk_read_speed, ctx.disk_write_speed
+        "| 磁盘类型 | {}{} (读: {} MB/s, 写: {} MB/s{}) |",
+        disk_label(ctx.args),
+        if ctx.args.network_storage {
+            " [网络存储]"
+        } else {
+            ""
+        },
+        fmt_num(ctx.disk_read_speed, 0),
+        fmt_num(ctx.disk_write_speed, 0),
+        sustained_write_note(ctx.args)
+    )?;
+    writeln!(
+        file,
+        "| 平均文件大小 | {} MB |",
+        fmt_num(ctx.args.avg_file_size, 1)
     )?;
-    writeln!(file, "| 平均文件大小 | {:.1} MB |", ctx.args.avg_file_size)?;
     writeln!(file, "| 预期并发连接 | {} |", ctx.args.expected_connections)?;
-    writeln!(file, "| 突发流量倍数 | {}x |", ctx.args.burst_factor)?;
+    if ctx.args.idle_connections > 0 {
+        writeln!(file, "| 空闲连接 | {} |", ctx.args.idle_connections)?;
+    }
+    writeln!(file, "| IP协议栈 | {} |", ip_stack_label(ctx.args))?;
+    writeln!(file, "| 硬件卸载 | {} |", offload_label(ctx.args))?;
+    writeln!(file, "| 备机复制 | {} |", replication_label(ctx.args))?;
+    writeln!(file, "| CPU架构 | {} |", cpu_arch_label(ctx.args))?;
+    writeln!(file, "| 突发流量倍数 | {} |", burst_factor_label(ctx.args))?;
     writeln!(file, "| 应用复杂度 | {} |\n", ctx.args.complexity)?;
 
     // 3. 内存配置建议
     writeln!(file, "## 内存配置建议")?;
-    writeln!(file, "- 推荐堆内存: {:.1} GB", ctx.heap_mem_gb)?;
-    writeln!(file, "- 推荐直接内存: {:.1} GB", ctx.direct_mem_gb)?;
+    writeln!(file, "- 推荐堆内存: {} GB", fmt_num(ctx.heap_mem_gb, 1))?;
+    writeln!(file, "- 推荐直接内存: {} GB", fmt_num(ctx.direct_mem_gb, 1))?;
     writeln!(file, "- 元空间大小: {} MB\n", ctx.metaspace_size_mb)?;
 
-    // 4. 系统极限评估
+    let reconciliation = crate::analysis::reconcile_memory(
+        ctx.args,
+        ctx.direct_mem_gb,
+        ctx.heap_mem_gb,
+        ctx.metaspace_size_mb,
+    );
+    writeln!(file, "### 内存分配对账")?;
+    writeln!(file, "| 内存区域 | 容量(GB) |")?;
+    writeln!(file, "|----------|----------|")?;
+    for (label, gb) in &reconciliation.items {
+        writeln!(file, "| {label} | {} |", fmt_num(*gb, 2))?;
+    }
+    writeln!(
+        file,
+        "| 合计 | {} |",
+        fmt_num(reconciliation.allocated_gb, 2)
+    )?;
+    writeln!(
+        file,
+        "| 剩余 | {}{} |\n",
+        fmt_num(reconciliation.free_gb, 2),
+        if reconciliation.free_gb < 0.0 {
+            " ❗ 超过服务器内存".to_string()
+        } else if reconciliation.over_subscribed() {
+            format!(" ❗ 低于保留值{}GB", fmt_num(reconciliation.min_free_gb, 1))
+        } else {
+            String::new()
+        }
+    )?;
+
+    if crate::analysis::native::uses_nmt_model(ctx.args) {
+        let estimate = crate::analysis::native::estimate_native_memory(ctx.args, ctx.heap_mem_gb);
+        writeln!(file, "### Native内存(NMT分类)")?;
+        writeln!(file, "| 分类 | 容量(MB) |")?;
+        writeln!(file, "|------|----------|")?;
+        for (category, mb) in &estimate.categories {
+            writeln!(file, "| {category} | {} |", fmt_num(*mb, 0))?;
+        }
+        writeln!(file)?;
+    }
+    Ok(())
+}
+
+/// markdown报告: 系统极限评估: 容量、瓶颈、内存预算、连接队列和集群容量
+fn write_limits_section(file: &mut impl Write, ctx: &ReportContext) -> std::io::Result<()> {
     writeln!(file, "## 系统极限评估")?;
     writeln!(file, "### 容量评估")?;
     writeln!(
@@ -64,8 +324,20 @@ pub fn generate_markdown_report(ctx: &ReportContext) -> anyhow::Result<()> {
     )?;
     writeln!(
         file,
-        "- 推荐吞吐量: {:.1} MB/s",
-        ctx.safety.theoretical_limits.max_throughput
+        "- 推荐吞吐量: {} MB/s (受限于{})",
+        fmt_num(ctx.safety.theoretical_limits.max_throughput, 1),
+        ctx.safety.theoretical_limits.throughput_limit
+    )?;
+    writeln!(
+        file,
+        "- 最大建连速率: {} 连接/秒{}",
+        fmt_num(ctx.safety.theoretical_limits.max_accept_rate, 0),
+        if ctx.args.tls { " (TLS)" } else { "" }
+    )?;
+    writeln!(
+        file,
+        "- 文件描述符需求: {} (LimitNOFILE / ulimit -n)",
+        ctx.safety.theoretical_limits.required_fds
     )?;
     writeln!(
         file,
@@ -79,11 +351,144 @@ pub fn generate_markdown_report(ctx: &ReportContext) -> anyhow::Result<()> {
         "- 主要限制因素: {}",
         ctx.safety.theoretical_limits.limiting_factor
     )?;
+    if ctx.safety.theoretical_limits.burst_bound {
+        writeln!(
+            file,
+            "- 说明: 当前配置的瓶颈是突发需求本身，稳态容量更高 ({})",
+            ctx.safety.theoretical_limits.steady_state_max
+        )?;
+    }
     writeln!(file, "```")?;
-    writeln!(file, "{}", ctx.safety.theoretical_limits.resource_breakdown)?;
+    let utilization = &ctx.safety.theoretical_limits.utilization;
+    writeln!(
+        file,
+        "    * JVM内存: {}% (堆), {}% (直接), {}% (元空间)",
+        fmt_num(utilization.heap_pct, 0),
+        fmt_num(utilization.direct_pct, 0),
+        fmt_num(utilization.metaspace_pct, 0)
+    )?;
+    if utilization.db_pool_pct > 0.0 {
+        writeln!(
+            file,
+            "    * 数据库连接池: {}% (堆)",
+            fmt_num(utilization.db_pool_pct, 0)
+        )?;
+    }
+    writeln!(file, "    * CPU: {}%", fmt_num(utilization.cpu_pct, 0))?;
+    writeln!(file, "    * 网络: {}%", fmt_num(utilization.network_pct, 0))?;
+    writeln!(file, "    * 磁盘IO: {}%", fmt_num(utilization.disk_pct, 0))?;
+    if let Some(queue) = &ctx.safety.theoretical_limits.disk_queue {
+        writeln!(
+            file,
+            "      (队列深度{}: 可达{} IOPS / 标称{} IOPS，按QD1比例线性增长到饱和队列深度估算)",
+            queue.queue_depth,
+            fmt_num(queue.achievable_iops, 0),
+            fmt_num(queue.rated_iops, 0)
+        )?;
+    }
+    if let Some(cache) = &ctx.safety.theoretical_limits.page_cache {
+        writeln!(
+            file,
+            "      (页缓存命中率{}%: 磁盘只承担{}%的IO，可用页缓存{} GB)",
+            fmt_num(cache.hit_ratio * 100.0, 0),
+            fmt_num(cache.disk_io_factor(&ctx.args.workload) * 100.0, 0),
+            fmt_num(cache.cache_gb, 1)
+        )?;
+    }
     writeln!(file, "```\n")?;
 
-    // 5. 负载场景模拟
+    let budget = &ctx.safety.memory_budget;
+    writeln!(file, "### 内存预算(突发流量)")?;
+    writeln!(file, "- 堆内存: {} GB", fmt_num(budget.heap_gb, 1))?;
+    writeln!(file, "- 直接内存: {} GB", fmt_num(budget.direct_gb, 1))?;
+    writeln!(
+        file,
+        "- Native预留: {} GB",
+        fmt_num(budget.native_reserved_gb, 1)
+    )?;
+    writeln!(file, "- 分配器开销: {} GB", fmt_num(budget.allocator_gb, 1))?;
+    writeln!(
+        file,
+        "- socket缓冲区: {} GB",
+        fmt_num(budget.socket_buffer_gb, 1)
+    )?;
+    writeln!(
+        file,
+        "- 合计: {} GB{}\n",
+        fmt_num(budget.total_gb, 1),
+        if budget.over_committed {
+            " ❗ 超过服务器内存，突发时可能触发swap"
+        } else {
+            ""
+        }
+    )?;
+
+    if ctx.args.percent_headroom {
+        writeln!(file, "### 预期负载余量")?;
+        writeln!(file, "| 资源 | 占用 | 余量 |")?;
+        writeln!(file, "|------|------|------|")?;
+        for (label, pct) in utilization.entries() {
+            writeln!(
+                file,
+                "| {label} | {}% | {}% |",
+                fmt_num(pct, 0),
+                fmt_num(100.0 - pct, 0)
+            )?;
+        }
+        let (tightest, pct) = utilization.tightest();
+        writeln!(
+            file,
+            "\n**最紧张资源: {tightest} 占用{}% (余量{}%)**\n",
+            fmt_num(pct, 0),
+            fmt_num(100.0 - pct, 0)
+        )?;
+    }
+
+    writeln!(file, "### 连接队列调优")?;
+    writeln!(file, "- 突发连接数: {}", ctx.backlog.burst_connections)?;
+    writeln!(file, "- 推荐accept backlog: {}", ctx.backlog.backlog)?;
+    writeln!(file, "- 推荐accept线程数: {}", ctx.backlog.accept_threads)?;
+    writeln!(file, "```ini")?;
+    writeln!(file, "net.core.somaxconn = {}", ctx.backlog.somaxconn)?;
+    writeln!(
+        file,
+        "net.ipv4.tcp_max_syn_backlog = {}",
+        ctx.backlog.tcp_max_syn_backlog
+    )?;
+    writeln!(file, "```\n")?;
+
+    if let Some(cluster) = ctx.cluster {
+        writeln!(file, "### 集群容量 ({}节点)", cluster.nodes)?;
+        writeln!(
+            file,
+            "- 集群稳态最大连接数: {} (单节点{} × {}节点 × 负载均衡效率{})",
+            cluster.max_connections,
+            cluster.node_max_connections,
+            cluster.nodes,
+            cluster.lb_efficiency
+        )?;
+        writeln!(
+            file,
+            "- 集群可持续吞吐量: {} MB/s",
+            fmt_num(cluster.max_throughput, 1)
+        )?;
+        writeln!(file, "- 集群突发连接数: {}", cluster.burst_connections)?;
+        writeln!(
+            file,
+            "- 单节点故障(N-1): 每节点需承载{}个突发连接，{}\n",
+            cluster.failover_connections,
+            if cluster.survives_node_failure {
+                "可以承受"
+            } else {
+                "**超过单节点极限**"
+            }
+        )?;
+    }
+    Ok(())
+}
+
+/// markdown报告: 负载场景模拟
+fn write_scenarios_section(file: &mut impl Write, ctx: &ReportContext) -> std::io::Result<()> {
     writeln!(file, "## 负载场景模拟")?;
     writeln!(
         file,
@@ -96,37 +501,50 @@ pub fn generate_markdown_report(ctx: &ReportContext) -> anyhow::Result<()> {
     for scenario in &ctx.safety.scenarios {
         writeln!(
             file,
-            "| {} | {} | {:.1} | {:.2} | {:.2} | {} |",
+            "| {} | {} | {} | {} | {} | {} {} |",
             scenario.name,
             scenario.connections,
-            scenario.file_size,
-            scenario.heap_usage,
-            scenario.direct_mem_usage,
-            String::from_utf8_lossy(&strip_ansi_escapes::strip(&scenario.status))
-                .replace("✅", "✔️")
-                .replace("⚠️", "⚠")
-                .replace("🔥", "✖️")
+            fmt_num(scenario.file_size, 1),
+            fmt_num(scenario.heap_usage, 2),
+            fmt_num(scenario.direct_mem_usage, 2),
+            scenario.status.markdown_icon(),
+            scenario.status.label()
         )?;
     }
+    writeln!(
+        file,
+        "\n**场景状态:** {}",
+        status_summary(&ctx.safety.scenarios, |_, text| text.to_string())
+    )?;
 
     // 状态说明
+    let [safe, warning, danger] =
+        status_ranges(&crate::analysis::SafetyPolicy::from_args(ctx.args));
     writeln!(file, "\n**状态说明:**")?;
-    writeln!(file, "- ✔️ 安全: <70% 内存使用")?;
-    writeln!(file, "- ⚠ 警告: 70-85% 内存使用")?;
-    writeln!(file, "- ✖️ 危险: >85% 内存使用\n")?;
+    writeln!(file, "- ✔️ 安全: {safe} 内存使用")?;
+    writeln!(file, "- ⚠ 警告: {warning} 内存使用")?;
+    writeln!(file, "- ✖️ 危险: {danger} 内存使用\n")?;
+    Ok(())
+}
 
-    // 6. 内存安全分析
+/// markdown报告: 内存安全分析
+fn write_memory_safety_section(file: &mut impl Write, ctx: &ReportContext) -> std::io::Result<()> {
     writeln!(file, "## 内存安全分析")?;
     writeln!(file, "- 整体风险等级: **{}**", ctx.safety.risk_level)?;
     writeln!(
         file,
-        "- 堆内存安全系数: {:.0}%",
-        ctx.safety.heap_safety * 100.0
+        "- 分项风险等级: {}",
+        resource_risk_summary(ctx.safety)
     )?;
     writeln!(
         file,
-        "- 直接内存安全系数: {:.0}%",
-        ctx.safety.direct_mem_safety * 100.0
+        "- 堆内存安全系数: {}%",
+        fmt_num(ctx.safety.heap_safety * 100.0, 0)
+    )?;
+    writeln!(
+        file,
+        "- 直接内存安全系数: {}%",
+        fmt_num(ctx.safety.direct_mem_safety * 100.0, 0)
     )?;
 
     // 安全系数图表
@@ -139,77 +557,29 @@ pub fn generate_markdown_report(ctx: &ReportContext) -> anyhow::Result<()> {
         safety_bar(ctx.safety.direct_mem_safety)
     )?;
     writeln!(file, "```\n")?;
+    Ok(())
+}
 
-    // 7. JVM配置建议
+/// markdown报告: JVM配置建议及参数兼容性
+fn write_jvm_section(file: &mut impl Write, ctx: &ReportContext) -> std::io::Result<()> {
     writeln!(file, "## JVM配置建议")?;
     writeln!(file, "```ini")?;
-    
-    // 基础配置
-    writeln!(file, "# 基础配置")?;
-    writeln!(file, "-Xms{}g -Xmx{}g", ctx.heap_mem_gb as i32, ctx.heap_mem_gb as i32)?;
-    writeln!(file, "-XX:MaxDirectMemorySize={}g", ctx.direct_mem_gb as i32)?;
-    writeln!(file, "-XX:MaxMetaspaceSize={}m", ctx.metaspace_size_mb)?;
-    writeln!(file, "-XX:ReservedCodeCacheSize=256m")?;
-    writeln!(file)?;
-
-    // GC配置
-    writeln!(file, "# GC配置")?;
-    match ctx.args.complexity.as_str() {
-        "high" => {
-            writeln!(file, "-XX:+UseZGC  # 低延迟GC，适合高复杂度应用")?;
-            writeln!(file, "-XX:ZCollectionInterval=5  # 每5秒一次ZGC")?;
-        }
-        "low" => {
-            writeln!(file, "-XX:+UseG1GC  # 平衡型GC")?;
-            writeln!(file, "-XX:MaxGCPauseMillis=200")?;
-        }
-        _ => {
-            writeln!(file, "-XX:+UseShenandoahGC  # 并发GC")?;
-            writeln!(file, "-XX:ShenandoahGCHeuristics=adaptive")?;
-        }
-    }
-    writeln!(file, "-XX:ParallelGCThreads={}", (ctx.args.cpu_cores as f64 * 0.5).ceil() as i32)?;
-    writeln!(file, "-XX:ConcGCThreads={}", (ctx.args.cpu_cores as f64 * 0.25).ceil() as i32)?;
-    writeln!(file)?;
-
-    // 内存优化
-    writeln!(file, "# 内存优化")?;
-    if ctx.safety.direct_mem_safety < 0.4 {
-        writeln!(file, "-Djdk.nio.maxCachedBufferSize=131072  # 降低缓存阈值至128KB")?;
-    } else {
-        writeln!(file, "-Djdk.nio.maxCachedBufferSize=262144  # 256KB缓存阈值")?;
-    }
-
-    if ctx.args.enable_memory_guard {
-        writeln!(file, "-Dapp.memory.guard.enabled=true")?;
-        writeln!(file, "-Dapp.memory.guard.direct.threshold={:.1}g", ctx.direct_mem_gb * 0.85)?;
-        writeln!(file, "-Dapp.memory.guard.heap.threshold={:.1}g", ctx.heap_mem_gb * 0.8)?;
-    }
-    writeln!(file)?;
-
-    // 元空间优化
-    if ctx.args.complexity == "high" {
-        writeln!(file, "# 元空间优化")?;
-        writeln!(file, "-XX:+UseCompressedClassPointers")?;
-        writeln!(file, "-XX:CompressedClassSpaceSize={}m", (ctx.metaspace_size_mb as f32 * 0.4).max(256.0) as i32)?;
-        writeln!(file, "-XX:+UnlockExperimentalVMOptions")?;
-        writeln!(file)?;
-    }
 
-    // 监控配置
-    writeln!(file, "# 监控与诊断")?;
-    writeln!(file, "-XX:NativeMemoryTracking=detail")?;
-    writeln!(file, "-XX:+PrintGCDetails -XX:+PrintGCDateStamps")?;
-    writeln!(file, "-XX:+HeapDumpOnOutOfMemoryError")?;
-    writeln!(file, "-XX:HeapDumpPath=/var/log/jvm_dumps")?;
-    writeln!(file)?;
-
-    // 大文件优化
-    if ctx.args.avg_file_size > 50.0 {
-        writeln!(file, "# 大文件优化")?;
-        writeln!(file, "-Djdk.nio.enableFastFileTransfer=true")?;
-        writeln!(file, "-Dapp.file.maxChunkSize=2097152  # 2MB分块")?;
-        writeln!(file, "-Dapp.file.useDirectIO=true")?;
+    let flags = crate::analysis::jvm::JvmFlags::recommended(
+        ctx.args,
+        ctx.direct_mem_gb,
+        ctx.heap_mem_gb,
+        ctx.metaspace_size_mb,
+        ctx.safety,
+    );
+    for group in &flags.groups {
+        writeln!(file, "# {}", group.title)?;
+        for comment in &group.comments {
+            writeln!(file, "# {comment}")?;
+        }
+        for flag in &group.flags {
+            writeln!(file, "{flag}")?;
+        }
         writeln!(file)?;
     }
 
@@ -241,35 +611,70 @@ pub fn generate_markdown_report(ctx: &ReportContext) -> anyhow::Result<()> {
     writeln!(file, "  - -XX:NativeMemoryTracking: JDK 8+ 支持")?;
     writeln!(file, "  - -XX:+HeapDumpOnOutOfMemoryError: JDK 6+ 支持")?;
 
-    // 8. 性能分析
+    if ctx.args.compat_report {
+        writeln!(
+            file,
+            "\n## 参数兼容性检查 (目标JDK {})",
+            crate::analysis::jvm::jdk_version(ctx.args)
+        )?;
+        let issues = crate::analysis::jvm::flag_compat_issues(&flags, ctx.args);
+        if issues.is_empty() {
+            writeln!(file, "- 所有推荐参数均兼容目标JDK")?;
+        }
+        for issue in issues {
+            writeln!(
+                file,
+                "- ✖ `{}`: {}，替代: `{}`",
+                issue.flag, issue.reason, issue.substitute
+            )?;
+        }
+        writeln!(file)?;
+    }
+    Ok(())
+}
+
+/// markdown报告: 各负载场景的性能分析和文件大小分布
+fn write_performance_section(file: &mut impl Write, ctx: &ReportContext) -> std::io::Result<()> {
     writeln!(file, "## 性能分析")?;
     for scenario in &ctx.performance.scenarios {
         writeln!(
             file,
             "### {} (平均文件大小: {}MB)",
-            scenario.name, scenario.avg_file_size
+            scenario.name,
+            fmt_num(scenario.avg_file_size, 1)
         )?;
 
         writeln!(file, "\n#### 资源限制分析")?;
-        writeln!(file, "| 资源类型 | 限制因素 | 最大并发量 | QPS |")?;
-        writeln!(file, "|----------|----------|------------|-----|")?;
+        writeln!(file, "| 资源类型 | 限制因素 | 最大并发量 | QPS | 持续QPS |")?;
+        writeln!(file, "|----------|----------|------------|-----|---------|")?;
         for resource in &scenario.resources {
             let limit_mark = if resource.limiting_factor { "✓" } else { "" };
             writeln!(
                 file,
-                "| {} | {} | {} | {} |",
+                "| {} | {} | {} | {} | {} |",
                 resource.name,
                 limit_mark,
                 resource.max_connections,
-                resource.qps.map_or("-".to_string(), |q| q.to_string())
+                resource.qps.map_or("-".to_string(), |q| q.to_string()),
+                resource
+                    .sustained_qps
+                    .map_or("-".to_string(), |q| q.to_string())
             )?;
         }
+        for resource in &scenario.resources {
+            if let Some(detail) = &resource.detail {
+                writeln!(file, "\n{}: {detail}", resource.name)?;
+            }
+        }
 
         writeln!(
             file,
-            "\n**最终能力:** {}并发 {} QPS",
+            "\n**最终能力:** {}并发 {} QPS (持续 {} QPS / {} MB/s，单次传输约{}秒)",
             scenario.final_capacity.max_connections,
-            scenario.final_capacity.qps.unwrap_or(0)
+            scenario.final_capacity.qps.unwrap_or(0),
+            scenario.final_capacity.sustained_qps.unwrap_or(0),
+            fmt_num(scenario.throughput_mb, 1),
+            fmt_num(scenario.transfer_secs, 2)
         )?;
 
         writeln!(file, "\n**关键发现:**")?;
@@ -279,17 +684,44 @@ pub fn generate_markdown_report(ctx: &ReportContext) -> anyhow::Result<()> {
         writeln!(file)?;
     }
 
-    // 9. 服务器扩容建议
+    if !ctx.performance.distribution.is_empty() {
+        writeln!(file, "### 文件大小分布")?;
+        writeln!(
+            file,
+            "| 文件大小 | 连接占比 | 单连接直接内存 | 直接内存占比 | 带宽占比 |"
+        )?;
+        writeln!(
+            file,
+            "|----------|----------|----------------|--------------|----------|"
+        )?;
+        for bucket in &ctx.performance.distribution {
+            writeln!(
+                file,
+                "| {}MB | {}% | {}KB | {}% | {}% |",
+                bucket.size_mb,
+                fmt_num(bucket.connection_share * 100.0, 0),
+                fmt_num(bucket.direct_kb, 0),
+                fmt_num(bucket.direct_share * 100.0, 0),
+                fmt_num(bucket.bandwidth_share * 100.0, 0)
+            )?;
+        }
+        writeln!(file)?;
+    }
+    Ok(())
+}
+
+/// markdown报告: 服务器扩容建议，满足目标时为容量评估
+fn write_scaling_section(file: &mut impl Write, ctx: &ReportContext) -> std::io::Result<()> {
     let target_conn = ctx.args.expected_connections;
     let max_conn = ctx.safety.theoretical_limits.max_connections;
-    let needs_scaling = target_conn > max_conn;
+    let needs_scaling = !ctx.safety.meets_target;
 
     if needs_scaling {
         writeln!(file, "## 服务器扩容建议")?;
         writeln!(file, "\n❗ **警告**: 当前配置无法满足目标连接数要求")?;
         writeln!(file, "⚠️ **注意**: 目标连接数超过理论最大值")?;
 
-        let scale_factor = target_conn as f64 / max_conn as f64;
+        let scale_factor = 1.0 + ctx.safety.shortfall_pct / 100.0;
         let ram_needed = (ctx.args.total_ram * scale_factor).ceil() as i32;
 
         writeln!(file, "\n- **当前配置**:")?;
@@ -309,8 +741,8 @@ pub fn generate_markdown_report(ctx: &ReportContext) -> anyhow::Result<()> {
         writeln!(file, "\n- **扩容建议**:")?;
         writeln!(
             file,
-            "  - 需要额外 {:.0}% 资源以达到目标连接数",
-            (scale_factor - 1.0) * 100.0
+            "  - 需要额外 {}% 资源以达到目标连接数",
+            fmt_num(ctx.safety.shortfall_pct, 0)
         )?;
         writeln!(
             file,
@@ -338,6 +770,22 @@ pub fn generate_markdown_report(ctx: &ReportContext) -> anyhow::Result<()> {
             )?;
         }
 
+        if let Some(rates) = CostRates::from_args(ctx.args) {
+            let suggestion = ScalingSuggestion {
+                ram_gb: Some(ram_needed as f64),
+                cpu_cores: (suggested_cores > ctx.args.cpu_cores as i32)
+                    .then_some(suggested_cores as usize),
+                net_gbps: (suggested_bandwidth > ctx.args.net_gbps as i32)
+                    .then_some(suggested_bandwidth as f64),
+            };
+            let estimate = estimate_scaling_cost(ctx.args, &rates, &suggestion);
+            writeln!(
+                file,
+                "  - 预计增量成本: {}",
+                format_cost(&estimate, &ctx.args.currency)
+            )?;
+        }
+
         // 磁盘升级建议
         match ctx.args.disk_type.as_str() {
             "sata_hdd" => writeln!(file, "  - 必须升级到SSD")?,
@@ -354,36 +802,54 @@ pub fn generate_markdown_report(ctx: &ReportContext) -> anyhow::Result<()> {
             ctx.safety.theoretical_limits.estimated_uptime
         )?;
     }
+    Ok(())
+}
 
-    // 8. 性能分析
+/// markdown报告: 性能分析、延迟SLO和性能测试建议
+fn write_performance_test_section(
+    file: &mut impl Write,
+    ctx: &ReportContext,
+) -> std::io::Result<()> {
     writeln!(file, "## 性能分析")?;
     for scenario in &ctx.performance.scenarios {
         writeln!(
             file,
             "### {} (平均文件大小: {}MB)",
-            scenario.name, scenario.avg_file_size
+            scenario.name,
+            fmt_num(scenario.avg_file_size, 1)
         )?;
 
         writeln!(file, "\n#### 资源限制分析")?;
-        writeln!(file, "| 资源类型 | 限制因素 | 最大并发量 | QPS |")?;
-        writeln!(file, "|----------|----------|------------|-----|")?;
+        writeln!(file, "| 资源类型 | 限制因素 | 最大并发量 | QPS | 持续QPS |")?;
+        writeln!(file, "|----------|----------|------------|-----|---------|")?;
         for resource in &scenario.resources {
             let limit_mark = if resource.limiting_factor { "✓" } else { "" };
             writeln!(
                 file,
-                "| {} | {} | {} | {} |",
+                "| {} | {} | {} | {} | {} |",
                 resource.name,
                 limit_mark,
                 resource.max_connections,
-                resource.qps.map_or("-".to_string(), |q| q.to_string())
+                resource.qps.map_or("-".to_string(), |q| q.to_string()),
+                resource
+                    .sustained_qps
+                    .map_or("-".to_string(), |q| q.to_string())
             )?;
         }
+        for resource in &scenario.resources {
+            if let Some(detail) = &resource.detail {
+                writeln!(file, "\n{}: {detail}", resource.name)?;
+            }
+        }
 
         writeln!(
             file,
-            "\n**最终能力:** {}并发 {} QPS",
+            "\n**最终能力:** {}并发 {} QPS (持续 {} QPS / {} MB/s，单次传输约{}秒)",
             scenario.final_capacity.max_connections,
-            scenario.final_capacity.qps.unwrap_or(0)
+            scenario.final_capacity.qps.unwrap_or(0),
+            scenario.final_capacity.sustained_qps.unwrap_or(0),
+            fmt_num(scenario.throughput_mb, 1),
+            fmt_num(scenario.transfer_secs, 2)
         )?;
 
         writeln!(file, "\n**关键发现:**")?;
@@ -393,6 +859,22 @@ pub fn generate_markdown_report(ctx: &ReportContext) -> anyhow::Result<()> {
         writeln!(file)?;
     }
 
+    if let Some(slo) = &ctx.performance.slo {
+        writeln!(file, "## 延迟SLO")?;
+        writeln!(file, "- p99目标: {} ms", fmt_num(slo.target_p99_ms, 0))?;
+        writeln!(
+            file,
+            "- 预期连接数下p99: {}{}",
+            p99_label(slo, " ms"),
+            if slo.violated() {
+                " ❗ 超过目标"
+            } else {
+                ""
+            }
+        )?;
+        writeln!(file, "- SLO安全最大连接数: {}\n", slo.max_connections)?;
+    }
+
     // 8. 测试建议
     writeln!(file, "## 性能测试建议")?;
     writeln!(file, "- 线程数: {}", ctx.performance.test_config.threads)?;
@@ -400,8 +882,8 @@ pub fn generate_markdown_report(ctx: &ReportContext) -> anyhow::Result<()> {
     writeln!(file, "- 加压时间: {}", ctx.performance.test_config.ramp_up)?;
     writeln!(
         file,
-        "- 目标吞吐量: {:.1} QPS",
-        ctx.performance.test_config.throughput_goal
+        "- 目标吞吐量: {} QPS",
+        fmt_num(ctx.performance.test_config.throughput_goal, 1)
     )?;
 
     // 测试脚本示例
@@ -419,238 +901,1424 @@ pub fn generate_markdown_report(ctx: &ReportContext) -> anyhow::Result<()> {
         writeln!(file, "```")?;
     }
 
-    // 9. 优化建议
+    writeln!(file, "\n### 测试文件准备")?;
+    writeln!(file, "```bash")?;
+    writeln!(file, "mkdir -p test_files")?;
+    for command in crate::analysis::performance::testfile_commands() {
+        writeln!(file, "{command}")?;
+    }
+    writeln!(file, "```")?;
+    Ok(())
+}
+
+/// markdown报告: 告警和优化建议
+fn write_recommendations_section(
+    file: &mut impl Write,
+    ctx: &ReportContext,
+) -> std::io::Result<()> {
+    if !ctx.safety.warnings.is_empty() {
+        writeln!(file, "\n## 告警")?;
+        for warning in ctx.safety.sorted_warnings() {
+            writeln!(
+                file,
+                "- **{}** {}",
+                severity_label(warning.severity),
+                warning.summary
+            )?;
+            if ctx.args.explain {
+                writeln!(file, "  - 依据: {}", warning.rationale)?;
+            }
+        }
+    }
+
     if !ctx.safety.recommendations.is_empty() {
         writeln!(file, "\n## 优化建议")?;
         for rec in &ctx.safety.recommendations {
-            writeln!(file, "{rec}")?;
+            writeln!(file, "- {}", rec.summary)?;
+            if ctx.args.explain {
+                writeln!(file, "  - 依据: {}", rec.rationale)?;
+            }
         }
     }
-
     Ok(())
 }
 
-fn safety_bar(value: f64) -> String {
-    let width = 30;
-    let fill = (value * width as f64) as usize;
-    let empty = width - fill;
-    format!(
-        "[{}{}] {:.0}%",
-        "■".repeat(fill),
-        " ".repeat(empty),
-        value * 100.0
-    )
+/// 根据正文中的 `##`/`###` 标题生成目录
+///
+/// 锚点按GitHub规则生成，重复标题依次追加 `-1`、`-2` 后缀，
+/// 代码块中以 `#` 开头的行不视为标题
+fn write_table_of_contents(out: &mut impl Write, body: &str) -> std::io::Result<()> {
+    let mut seen: Vec<String> = Vec::new();
+    let mut in_code_block = false;
+
+    writeln!(out, "## 目录")?;
+    for line in body.lines() {
+        if line.starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+
+        let level = line.chars().take_while(|&c| c == '#').count();
+        if level == 0 || !line[level..].starts_with(' ') {
+            continue;
+        }
+        let title = line[level..].trim();
+
+        let slug = github_slug(title);
+        let duplicates = seen.iter().filter(|s| **s == slug).count();
+        let anchor = if duplicates == 0 {
+            slug.clone()
+        } else {
+            format!("{slug}-{duplicates}")
+        };
+        seen.push(slug);
+
+        if level == 2 || level == 3 {
+            writeln!(out, "{}- [{title}](#{anchor})", "  ".repeat(level - 2))?;
+        }
+    }
+    writeln!(out)
 }
 
-pub fn print_configuration(
-    args: &crate::args::Args,
-    direct_mem_gb: f64,
-    heap_mem_gb: f64,
-    metaspace_size_mb: i32,
-    disk_read_speed: f64,
-    disk_write_speed: f64,
-) {
-    println!(
-        "\n{}{}",
-        "▬".cyan().bold().reversed(),
-        " 系统配置 ".cyan().bold().reversed()
-    );
-    println!("{}", "▬".cyan().bold().repeated(50));
+/// GitHub风格的标题锚点: 转小写，去除标点，空格替换为连字符
+fn github_slug(title: &str) -> String {
+    title
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == ' ' || *c == '-' || *c == '_')
+        .map(|c| if c == ' ' { '-' } else { c })
+        .collect()
+}
 
-    let config_table = vec![
-        ("服务器内存", format!("{:.1} GB", args.total_ram)),
-        ("CPU核心数", format!("{}", args.cpu_cores)),
-        ("网络带宽", format!("{:.1} Gbps", args.net_gbps)),
+/// 安全条的宽度(字符数)
+const SAFETY_BAR_WIDTH: usize = 30;
+
+/// 安全条的填充格数: 超出0..1的取值按边界填充(百分比仍显示实际值)，NaN时为None
+fn safety_bar_fill(value: f64) -> Option<usize> {
+    if value.is_nan() {
+        return None;
+    }
+    Some((value.clamp(0.0, 1.0) * SAFETY_BAR_WIDTH as f64) as usize)
+}
+
+fn safety_bar(value: f64) -> String {
+    match safety_bar_fill(value) {
+        Some(fill) => format!(
+            "[{}{}] {}%",
+            "■".repeat(fill),
+            " ".repeat(SAFETY_BAR_WIDTH - fill),
+            fmt_num(value * 100.0, 0)
+        ),
+        None => format!("[{}] N/A", " ".repeat(SAFETY_BAR_WIDTH)),
+    }
+}
+
+/// 磁盘类型描述，分层存储时列出热/冷两层及命中率(读写速度为混合值)
+fn disk_label(args: &crate::args::Args) -> String {
+    match (&args.hot_disk, &args.cold_disk, args.hot_ratio) {
+        (Some(hot), Some(cold), Some(ratio)) => format!(
+            "分层存储 热:{hot} {}% / 冷:{cold} {}% 混合",
+            fmt_num(ratio * 100.0, 0),
+            fmt_num((1.0 - ratio) * 100.0, 0)
+        ),
+        _ => args.disk_type.clone(),
+    }
+}
+
+/// 指定了 `--sustained-write-speed` 时附在写入速度后的持续写入速度(超过峰值时已按峰值计)
+fn sustained_write_note(args: &crate::args::Args) -> String {
+    let Some(requested) = args.sustained_write_speed else {
+        return String::new();
+    };
+    let sustained =
+        crate::config::effective_disk(args).map_or(requested, |disk| disk.sustained_write_speed);
+    if requested > sustained {
+        format!(
+            ", 持续写: {} MB/s, 指定的{} MB/s超过峰值已按峰值计",
+            fmt_num(sustained, 0),
+            fmt_num(requested, 0)
+        )
+    } else {
+        format!(", 持续写: {} MB/s", fmt_num(sustained, 0))
+    }
+}
+
+/// 硬件卸载的工作内容
+fn offload_label(args: &crate::args::Args) -> String {
+    match args.offload.as_str() {
+        "tls" => "TLS加解密".to_string(),
+        "checksum" => "校验和".to_string(),
+        "both" => "TLS加解密+校验和".to_string(),
+        _ => "无".to_string(),
+    }
+}
+
+/// 备机复制方式及其磁盘写入/网络出口倍数
+fn replication_label(args: &crate::args::Args) -> String {
+    match args.replication.as_str() {
+        mode @ ("sync" | "async") => format!(
+            "{mode} (磁盘写入/网络出口 ×{})",
+            fmt_num(crate::analysis::safety::replication_factor(mode), 1)
+        ),
+        _ => "无".to_string(),
+    }
+}
+
+/// 网络带宽，出口和入口不同时分别列出并注明按哪个方向计算
+fn net_bandwidth_label(args: &crate::args::Args) -> String {
+    let egress = args.net_egress_gbps.unwrap_or(args.net_gbps);
+    let ingress = args.net_ingress_gbps.unwrap_or(args.net_gbps);
+    if egress == ingress {
+        return format!("{} Gbps", fmt_num(egress, 1));
+    }
+    format!(
+        "出口 {} Gbps / 入口 {} Gbps (按{}计算)",
+        fmt_num(egress, 1),
+        fmt_num(ingress, 1),
+        match args.workload.as_str() {
+            "download" => "出口",
+            "upload" => "入口",
+            _ => "较小值",
+        }
+    )
+}
+
+/// 瞬时突发倍数，指定持续高峰时一并列出
+fn burst_factor_label(args: &crate::args::Args) -> String {
+    match args.sustained_burst_factor {
+        Some(factor) => format!("{}x (持续高峰 {factor}x)", args.burst_factor),
+        None => format!("{}x", args.burst_factor),
+    }
+}
+
+/// CPU架构及其每核心效率系数
+fn cpu_arch_label(args: &crate::args::Args) -> String {
+    let arch = if args.cpu_arch.is_empty() {
+        "x86_64"
+    } else {
+        &args.cpu_arch
+    };
+    let clock = args
+        .cpu_ghz
+        .map_or(String::new(), |ghz| format!(" @{ghz}GHz"));
+    format!(
+        "{arch}{clock} (每核心效率 {}x)",
+        fmt_num(crate::analysis::safety::cpu_efficiency(args), 2)
+    )
+}
+
+/// IP协议栈及其计入内存估算的单连接额外开销
+fn ip_stack_label(args: &crate::args::Args) -> String {
+    let ip_stack = if args.ip_stack.is_empty() {
+        "ipv4"
+    } else {
+        &args.ip_stack
+    };
+    format!(
+        "{ip_stack} (单连接内核开销 +{}KB)",
+        fmt_num(crate::analysis::safety::ip_stack_overhead_kb(ip_stack), 0)
+    )
+}
+
+pub fn print_configuration(
+    out: &mut impl Write,
+    args: &crate::args::Args,
+    direct_mem_gb: f64,
+    heap_mem_gb: f64,
+    metaspace_size_mb: i32,
+    disk_read_speed: f64,
+    disk_write_speed: f64,
+) -> std::io::Result<()> {
+    writeln!(
+        out,
+        "\n{}{}",
+        "▬".cyan().bold().reversed(),
+        " 系统配置 ".cyan().bold().reversed()
+    )?;
+    writeln!(out, "{}", "▬".cyan().bold().repeated(50))?;
+
+    let mut config_table = vec![
+        ("服务器内存", format!("{} GB", fmt_num(args.total_ram, 1))),
+        ("CPU核心数", format!("{}", args.cpu_cores)),
+        ("网络带宽", net_bandwidth_label(args)),
         (
             "磁盘类型",
             format!(
-                "{} (读: {:.0} MB/s, 写: {:.0} MB/s)",
-                args.disk_type, disk_read_speed, disk_write_speed
+                "{}{} (读: {} MB/s, 写: {} MB/s{})",
+                disk_label(args),
+                if args.network_storage {
+                    " [网络存储]"
+                } else {
+                    ""
+                },
+                fmt_num(disk_read_speed, 0),
+                fmt_num(disk_write_speed, 0),
+                sustained_write_note(args)
             ),
         ),
-        ("平均文件大小", format!("{:.1} MB", args.avg_file_size)),
+        (
+            "平均文件大小",
+            format!("{} MB", fmt_num(args.avg_file_size, 1)),
+        ),
         ("预期并发连接", format!("{}", args.expected_connections)),
-        ("突发流量倍数", format!("{}x", args.burst_factor)),
+        ("IP协议栈", ip_stack_label(args)),
+        ("硬件卸载", offload_label(args)),
+        ("备机复制", replication_label(args)),
+        ("CPU架构", cpu_arch_label(args)),
+        ("突发流量倍数", burst_factor_label(args)),
         ("内存防护", format!("{}", args.enable_memory_guard)),
         ("应用复杂度", args.complexity.to_string()),
     ];
+    if args.idle_connections > 0 {
+        config_table.insert(6, ("空闲连接", format!("{}", args.idle_connections)));
+    }
 
     for (label, value) in config_table {
-        println!("  {:>20}: {}", label.cyan(), value);
+        writeln!(out, "  {:>20}: {}", label.cyan(), value)?;
     }
 
-    println!("\n  {:>20}: {:.1} GB", "推荐堆内存".cyan(), heap_mem_gb);
-    println!("  {:>20}: {:.1} GB", "推荐直接内存".cyan(), direct_mem_gb);
-    println!(
+    writeln!(
+        out,
+        "\n  {:>20}: {} GB",
+        "推荐堆内存".cyan(),
+        fmt_num(heap_mem_gb, 1)
+    )?;
+    writeln!(
+        out,
+        "  {:>20}: {} GB",
+        "推荐直接内存".cyan(),
+        fmt_num(direct_mem_gb, 1)
+    )?;
+    writeln!(
+        out,
         "  {:>20}: {} MB (动态计算)",
         "元空间".cyan(),
         metaspace_size_mb
-    );
+    )?;
+
+    let reconciliation =
+        crate::analysis::reconcile_memory(args, direct_mem_gb, heap_mem_gb, metaspace_size_mb);
+    writeln!(out, "\n  {}:", "内存分配对账".cyan().bold())?;
+    for (label, gb) in &reconciliation.items {
+        writeln!(out, "    - {}: {} GB", label.cyan(), fmt_num(*gb, 2))?;
+    }
+    writeln!(
+        out,
+        "    - {}: {} GB / {} GB",
+        "合计".cyan(),
+        fmt_num(reconciliation.allocated_gb, 2),
+        fmt_num(args.total_ram, 1)
+    )?;
+    let free = format!("{} GB", fmt_num(reconciliation.free_gb, 2));
+    writeln!(
+        out,
+        "    - {}: {}",
+        "剩余".cyan(),
+        if reconciliation.free_gb < 0.0 {
+            format!("{free} (超过服务器内存)").red().bold()
+        } else if reconciliation.over_subscribed() {
+            format!(
+                "{free} (低于保留值{} GB)",
+                fmt_num(reconciliation.min_free_gb, 1)
+            )
+            .red()
+            .bold()
+        } else {
+            free.green()
+        }
+    )?;
+
+    if crate::analysis::native::uses_nmt_model(args) {
+        let estimate = crate::analysis::native::estimate_native_memory(args, heap_mem_gb);
+        writeln!(out, "\n  {}:", "Native内存(NMT分类)".cyan().bold())?;
+        for (category, mb) in &estimate.categories {
+            writeln!(out, "    - {}: {} MB", category.cyan(), fmt_num(*mb, 0))?;
+        }
+    }
+    Ok(())
 }
 
-pub fn print_performance_report(report: &crate::analysis::performance::PerformanceReport) {
-    println!(
+pub fn print_performance_report(
+    out: &mut impl Write,
+    report: &crate::analysis::performance::PerformanceReport,
+) -> std::io::Result<()> {
+    writeln!(
+        out,
         "\n{}{}",
         "▬".magenta().bold().reversed(),
         " 全链路性能分析报告 ".magenta().bold().reversed()
-    );
-    println!("{}", "▬".magenta().bold().repeated(50));
+    )?;
+    writeln!(out, "{}", "▬".magenta().bold().repeated(50))?;
 
     for scenario in &report.scenarios {
-        println!(
+        writeln!(
+            out,
             "\n  {} (平均文件大小: {}MB)",
             scenario.name.bold(),
-            scenario.avg_file_size
-        );
-
-        println!(
-            "  {:<12} {:<12} {:<12} {:<12}",
-            "资源类型".cyan(),
-            "限制因素".cyan(),
-            "最大并发量".cyan(),
-            "QPS".cyan()
-        );
+            fmt_num(scenario.avg_file_size, 1)
+        )?;
 
+        let rows: Vec<Vec<String>> = scenario
+            .resources
+            .iter()
+            .map(|resource| {
+                vec![
+                    resource.name.clone(),
+                    if resource.limiting_factor { "✓" } else { "" }.to_string(),
+                    resource.max_connections.to_string(),
+                    resource.qps.map_or("-".to_string(), |q| q.to_string()),
+                    resource
+                        .sustained_qps
+                        .map_or("-".to_string(), |q| q.to_string()),
+                ]
+            })
+            .collect();
+        print_table(
+            out,
+            &["资源类型", "限制因素", "最大并发量", "QPS", "持续QPS"],
+            &rows,
+            &[12, 12, 12, 12, 12],
+        )?;
         for resource in &scenario.resources {
-            let limit_mark = if resource.limiting_factor { "✓" } else { "" };
-            println!(
-                "  {:<12} {:<12} {:<12} {:<12}",
-                resource.name,
-                limit_mark,
-                resource.max_connections,
-                resource.qps.map_or("-".to_string(), |q| q.to_string())
-            );
+            if let Some(detail) = &resource.detail {
+                writeln!(out, "  {}: {detail}", resource.name.cyan())?;
+            }
         }
 
-        println!(
-            "\n  {}: {}并发 {} QPS",
+        writeln!(
+            out,
+            "\n  {}: {}并发 {} QPS (持续 {} QPS / {} MB/s，单次传输约{}秒)",
             "最终能力".cyan().bold(),
             scenario.final_capacity.max_connections,
-            scenario.final_capacity.qps.unwrap_or(0)
-        );
+            scenario.final_capacity.qps.unwrap_or(0),
+            scenario.final_capacity.sustained_qps.unwrap_or(0),
+            fmt_num(scenario.throughput_mb, 1),
+            fmt_num(scenario.transfer_secs, 2)
+        )?;
 
-        println!("\n  {}:", "关键发现".cyan());
+        writeln!(out, "\n  {}:", "关键发现".cyan())?;
         for finding in &scenario.key_findings {
-            println!("    - {finding}");
+            writeln!(out, "    - {finding}")?;
         }
     }
 
-    println!("\n  {}:", "性能测试建议".cyan().bold());
-    println!("    - {}: {}", "线程数".cyan(), report.test_config.threads);
-    println!(
+    if !report.distribution.is_empty() {
+        writeln!(out, "\n  {}:", "文件大小分布".cyan().bold())?;
+        let rows: Vec<Vec<String>> = report
+            .distribution
+            .iter()
+            .map(|bucket| {
+                vec![
+                    format!("{}MB", bucket.size_mb),
+                    format!("{}%", fmt_num(bucket.connection_share * 100.0, 0)),
+                    format!("{}KB", fmt_num(bucket.direct_kb, 0)),
+                    format!("{}%", fmt_num(bucket.direct_share * 100.0, 0)),
+                    format!("{}%", fmt_num(bucket.bandwidth_share * 100.0, 0)),
+                ]
+            })
+            .collect();
+        print_table(
+            out,
+            &[
+                "文件大小",
+                "连接占比",
+                "单连接直接内存",
+                "直接内存占比",
+                "带宽占比",
+            ],
+            &rows,
+            &[10, 10, 16, 14, 10],
+        )?;
+    }
+
+    if let Some(slo) = &report.slo {
+        writeln!(
+            out,
+            "\n  {}(p99 ≤ {}ms):",
+            "延迟SLO".cyan().bold(),
+            fmt_num(slo.target_p99_ms, 0)
+        )?;
+        let expected = p99_label(slo, "ms");
+        writeln!(
+            out,
+            "    - {}: {}",
+            "预期连接数下p99".cyan(),
+            if slo.violated() {
+                expected.red().bold()
+            } else {
+                expected.green()
+            }
+        )?;
+        writeln!(
+            out,
+            "    - {}: {}",
+            "SLO安全最大连接数".cyan(),
+            slo.max_connections
+        )?;
+    }
+
+    writeln!(out, "\n  {}:", "性能测试建议".cyan().bold())?;
+    writeln!(
+        out,
+        "    - {}: {}",
+        "线程数".cyan(),
+        report.test_config.threads
+    )?;
+    writeln!(
+        out,
         "    - {}: {}",
         "测试时长".cyan(),
         report.test_config.duration
-    );
-    println!(
+    )?;
+    writeln!(
+        out,
         "    - {}: {}",
         "加压时间".cyan(),
         report.test_config.ramp_up
-    );
-    println!(
-        "    - {}: {:.1} QPS",
+    )?;
+    writeln!(
+        out,
+        "    - {}: {} QPS",
         "目标吞吐量".cyan(),
-        report.test_config.throughput_goal
-    );
+        fmt_num(report.test_config.throughput_goal, 1)
+    )?;
 
-    println!("\n  {}:", "测试脚本示例".cyan().bold());
+    writeln!(out, "\n  {}:", "测试脚本示例".cyan().bold())?;
     for (i, script) in report.test_config.script_examples.iter().enumerate() {
-        println!("    {}. {}", i + 1, script);
+        writeln!(out, "    {}. {}", i + 1, script)?;
+    }
+
+    writeln!(out, "\n  {}:", "测试文件准备".cyan().bold())?;
+    writeln!(out, "    mkdir -p test_files")?;
+    for command in crate::analysis::performance::testfile_commands() {
+        writeln!(out, "    {command}")?;
     }
+    Ok(())
 }
 
-pub fn print_system_limits(safety: &crate::analysis::SafetyAnalysis) {
-    println!(
+pub fn print_system_limits(
+    out: &mut impl Write,
+    safety: &crate::analysis::SafetyAnalysis,
+) -> std::io::Result<()> {
+    writeln!(
+        out,
         "\n{}{}",
         "▬".blue().bold().reversed(),
         " 系统极限评估(6-12个月稳定标准) ".blue().bold().reversed()
-    );
-    println!("{}", "▬".blue().bold().repeated(50));
+    )?;
+    writeln!(out, "{}", "▬".blue().bold().repeated(50))?;
 
-    println!("\n  {}:", "容量评估".cyan().bold());
-    println!(
+    writeln!(out, "\n  {}:", "容量评估".cyan().bold())?;
+    writeln!(
+        out,
         "    - {}: {} 连接",
         "理论最大连接数".cyan(),
         safety.theoretical_limits.max_connections
-    );
-    println!(
+    )?;
+    writeln!(
+        out,
         "    - {}: {} 连接",
         "突发容量".cyan(),
         safety.theoretical_limits.burst_capacity
-    );
-    println!(
-        "    - {}: {:.1} MB/s",
+    )?;
+    writeln!(
+        out,
+        "    - {}: {} MB/s (受限于{})",
         "推荐吞吐量".cyan(),
-        safety.theoretical_limits.max_throughput
-    );
-    println!(
+        fmt_num(safety.theoretical_limits.max_throughput, 1),
+        safety.theoretical_limits.throughput_limit
+    )?;
+    writeln!(
+        out,
+        "    - {}: {} 连接/秒",
+        "最大建连速率".cyan(),
+        fmt_num(safety.theoretical_limits.max_accept_rate, 0)
+    )?;
+    writeln!(
+        out,
+        "    - {}: {} (LimitNOFILE / ulimit -n)",
+        "文件描述符需求".cyan(),
+        safety.theoretical_limits.required_fds
+    )?;
+    writeln!(
+        out,
         "    - {}: {}",
         "稳定运行预期".cyan(),
         safety.theoretical_limits.estimated_uptime
-    );
+    )?;
 
-    println!("\n  {}:", "瓶颈分析".cyan().bold());
-    println!(
+    writeln!(out, "\n  {}:", "瓶颈分析".cyan().bold())?;
+    writeln!(
+        out,
         "    - {}: {}",
         "主要限制因素".cyan(),
         safety.theoretical_limits.limiting_factor
+    )?;
+    if safety.theoretical_limits.burst_bound {
+        writeln!(
+            out,
+            "    - {}: 当前配置的瓶颈是突发需求本身，稳态容量更高 ({})",
+            "说明".cyan(),
+            safety.theoretical_limits.steady_state_max
+        )?;
+    }
+    writeln!(out, "    - {}:", "资源利用率".cyan())?;
+    let utilization = &safety.theoretical_limits.utilization;
+    for (label, pct) in utilization.entries() {
+        print_safety_bar(out, label, pct / 100.0)?;
+    }
+    if utilization.db_pool_pct > 0.0 {
+        print_safety_bar(out, "数据库连接池", utilization.db_pool_pct / 100.0)?;
+    }
+    if let Some(queue) = &safety.theoretical_limits.disk_queue {
+        writeln!(
+            out,
+            "    - {}: QD{} 可达 {} IOPS / 标称 {} IOPS ({}%)",
+            "磁盘队列深度".cyan(),
+            queue.queue_depth,
+            fmt_num(queue.achievable_iops, 0),
+            fmt_num(queue.rated_iops, 0),
+            fmt_num(queue.fraction() * 100.0, 0)
+        )?;
+    }
+    if let Some(cache) = &safety.theoretical_limits.page_cache {
+        writeln!(
+            out,
+            "    - {}: 命中率 {}%{}，可用页缓存 {} GB",
+            "页缓存".cyan(),
+            fmt_num(cache.hit_ratio * 100.0, 0),
+            cache.working_set_gb.map_or(String::new(), |gb| format!(
+                " (工作集 {} GB)",
+                fmt_num(gb, 1)
+            )),
+            fmt_num(cache.cache_gb, 1)
+        )?;
+    }
+
+    let budget = &safety.memory_budget;
+    writeln!(out, "\n  {}:", "内存预算(突发流量)".cyan().bold())?;
+    for (label, gb) in [
+        ("堆内存", budget.heap_gb),
+        ("直接内存", budget.direct_gb),
+        ("Native预留", budget.native_reserved_gb),
+        ("分配器开销", budget.allocator_gb),
+        ("socket缓冲区", budget.socket_buffer_gb),
+    ] {
+        writeln!(out, "    - {}: {} GB", label.cyan(), fmt_num(gb, 1))?;
+    }
+    let total = format!("{} GB", fmt_num(budget.total_gb, 1));
+    writeln!(
+        out,
+        "    - {}: {}",
+        "合计".cyan(),
+        if budget.over_committed {
+            format!("{} (超过服务器内存)", total).red().bold()
+        } else {
+            total.green()
+        }
+    )?;
+    Ok(())
+}
+
+/// 风险等级对应的终端颜色
+fn risk_color(level: &str) -> &'static str {
+    match level {
+        "低风险" => "green",
+        "中风险" => "yellow",
+        _ => "red",
+    }
+}
+
+/// 各资源维度的风险等级，如 `堆内存: 低风险, 直接内存: 高风险`
+pub fn resource_risk_summary(safety: &crate::analysis::SafetyAnalysis) -> String {
+    safety
+        .resource_risks
+        .iter()
+        .map(|(label, level)| format!("{label}: {level}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// 余量报告: 预期负载下各资源的占用比例和剩余余量
+pub fn print_headroom_report(
+    out: &mut impl Write,
+    safety: &crate::analysis::SafetyAnalysis,
+) -> std::io::Result<()> {
+    writeln!(
+        out,
+        "\n{}{}",
+        "▬".magenta().bold().reversed(),
+        " 预期负载余量 ".magenta().bold().reversed()
+    )?;
+    writeln!(out, "{}", "▬".magenta().bold().repeated(50))?;
+
+    let utilization = &safety.theoretical_limits.utilization;
+    let rows: Vec<Vec<String>> = utilization
+        .entries()
+        .iter()
+        .map(|(label, pct)| {
+            vec![
+                label.to_string(),
+                format!("{}%", fmt_num(*pct, 0)),
+                format!("{}%", fmt_num(100.0 - pct, 0)),
+            ]
+        })
+        .collect();
+    print_table(out, &["资源", "占用", "余量"], &rows, &[12, 12, 12])?;
+
+    let (tightest, pct) = utilization.tightest();
+    let line = format!(
+        "最紧张资源: {tightest} 占用{}% (余量{}%)",
+        fmt_num(pct, 0),
+        fmt_num(100.0 - pct, 0)
     );
-    println!(
-        "    - {}: \n{}",
-        "资源利用率".cyan(),
-        safety.theoretical_limits.resource_breakdown
+    writeln!(
+        out,
+        "\n  {}",
+        if pct >= 80.0 {
+            line.red().bold()
+        } else if pct >= 60.0 {
+            line.yellow().bold()
+        } else {
+            line.green().bold()
+        }
+    )?;
+    Ok(())
+}
+
+pub fn print_backlog_recommendations(
+    out: &mut impl Write,
+    backlog: &crate::analysis::backlog::BacklogRecommendation,
+) -> std::io::Result<()> {
+    writeln!(out, "\n  {}:", "连接队列调优".cyan().bold())?;
+    writeln!(
+        out,
+        "    - {}: {}",
+        "突发连接数".cyan(),
+        backlog.burst_connections
+    )?;
+    writeln!(
+        out,
+        "    - {}: {}",
+        "推荐accept backlog".cyan(),
+        backlog.backlog
+    )?;
+    writeln!(
+        out,
+        "    - {}: {}",
+        "推荐accept线程数".cyan(),
+        backlog.accept_threads
+    )?;
+    writeln!(out, "    - {}:", "建议sysctl参数".cyan())?;
+    writeln!(out, "      net.core.somaxconn = {}", backlog.somaxconn)?;
+    writeln!(
+        out,
+        "      net.ipv4.tcp_max_syn_backlog = {}",
+        backlog.tcp_max_syn_backlog
+    )?;
+    Ok(())
+}
+
+pub fn print_cluster_summary(
+    out: &mut impl Write,
+    cluster: &crate::analysis::cluster::ClusterAnalysis,
+) -> std::io::Result<()> {
+    writeln!(
+        out,
+        "\n  {} ({}节点):",
+        "集群容量".cyan().bold(),
+        cluster.nodes
+    )?;
+    writeln!(
+        out,
+        "    - {}: {} 连接 (单节点{} × {}节点 × 负载均衡效率{})",
+        "集群稳态最大连接数".cyan(),
+        cluster.max_connections,
+        cluster.node_max_connections,
+        cluster.nodes,
+        cluster.lb_efficiency
+    )?;
+    writeln!(
+        out,
+        "    - {}: {} MB/s",
+        "集群可持续吞吐量".cyan(),
+        fmt_num(cluster.max_throughput, 1)
+    )?;
+    writeln!(
+        out,
+        "    - {}: {}",
+        "集群突发连接数".cyan(),
+        cluster.burst_connections
+    )?;
+    let failover = format!(
+        "每节点需承载{}个突发连接 (单节点极限{})",
+        cluster.failover_connections, cluster.node_max_connections
     );
+    writeln!(
+        out,
+        "    - {}: {}",
+        "单节点故障(N-1)".cyan(),
+        if cluster.survives_node_failure {
+            failover.green()
+        } else {
+            failover.red().bold()
+        }
+    )?;
+    Ok(())
+}
+
+pub fn print_sensitivity_report(
+    out: &mut impl Write,
+    report: &crate::analysis::sensitivity::SensitivityReport,
+) -> std::io::Result<()> {
+    writeln!(
+        out,
+        "\n{}{}",
+        "▬".blue().bold().reversed(),
+        " 敏感性分析 ".blue().bold().reversed()
+    )?;
+    writeln!(out, "{}", "▬".blue().bold().repeated(50))?;
+
+    writeln!(
+        out,
+        "  输入扰动: 内存/文件大小/连接数 ±{}%, 采样 {} 次 (seed={})",
+        fmt_num(report.variation_pct, 0),
+        report.samples,
+        report.seed
+    )?;
+
+    writeln!(out, "\n  {}:", "风险等级分布".cyan().bold())?;
+    for (level, count) in &report.risk_counts {
+        writeln!(
+            out,
+            "    - {}: {} 次 ({}%)",
+            level,
+            count,
+            fmt_num(*count as f64 / report.samples.max(1) as f64 * 100.0, 0)
+        )?;
+    }
+
+    writeln!(out, "\n  {}:", "理论最大连接数".cyan().bold())?;
+    writeln!(
+        out,
+        "  {:<12} {:<12} {:<12}",
+        "最小".cyan(),
+        "中位数".cyan(),
+        "最大".cyan()
+    )?;
+    writeln!(
+        out,
+        "  {:<12} {:<12} {:<12}",
+        report.min_connections, report.median_connections, report.max_connections
+    )?;
+    Ok(())
 }
 
-pub fn print_safety_report(safety: &crate::analysis::SafetyAnalysis) {
-    println!(
+pub fn print_batch_summary(
+    out: &mut impl Write,
+    entries: &[crate::batch::BatchEntry],
+) -> std::io::Result<()> {
+    writeln!(
+        out,
+        "\n{}{}",
+        "▬".cyan().bold().reversed(),
+        " 批量分析汇总 ".cyan().bold().reversed()
+    )?;
+    writeln!(out, "{}", "▬".cyan().bold().repeated(50))?;
+
+    writeln!(
+        out,
+        "  {:<6} {:<10} {:<10} {:<14} {:<14} {:<10}",
+        "行".cyan(),
+        "磁盘类型".cyan(),
+        "内存(GB)".cyan(),
+        "最大连接数".cyan(),
+        "限制因素".cyan(),
+        "风险等级".cyan()
+    )?;
+
+    for entry in entries {
+        match &entry.outcome {
+            Ok(summary) => writeln!(
+                out,
+                "  {:<6} {:<10} {:<10.1} {:<14} {:<14} {:<10}",
+                entry.line,
+                summary.disk_type,
+                summary.total_ram,
+                summary.max_connections,
+                summary.limiting_factor,
+                summary.risk_level
+            )?,
+            Err(reason) => writeln!(
+                out,
+                "  {:<6} {} {} ({})",
+                entry.line,
+                "失败".red().bold(),
+                reason,
+                entry.flags
+            )?,
+        }
+    }
+
+    let failed = entries.iter().filter(|e| e.outcome.is_err()).count();
+    writeln!(
+        out,
+        "\n  共 {} 个配置, 成功 {}, 失败 {}",
+        entries.len(),
+        entries.len() - failed,
+        failed
+    )?;
+    Ok(())
+}
+
+/// 打印与历史基线的对比
+pub fn print_baseline_comparison(
+    out: &mut impl Write,
+    baseline: &crate::baseline::Baseline,
+    current: &crate::baseline::Baseline,
+) -> std::io::Result<()> {
+    writeln!(
+        out,
+        "\n{}{}",
+        "▬".cyan().bold().reversed(),
+        " 基线对比 ".cyan().bold().reversed()
+    )?;
+    writeln!(out, "{}", "▬".cyan().bold().repeated(50))?;
+    writeln!(
+        out,
+        "\n  {}: {}",
+        "基线生成时间".cyan(),
+        baseline.generated_at
+    )?;
+    print_metric_deltas(out, baseline, current)
+}
+
+/// 输出当前配置与git历史版本配置的关键指标对比
+pub fn print_git_comparison(
+    out: &mut impl Write,
+    git_ref: &str,
+    config: &std::path::Path,
+    previous: &crate::baseline::Baseline,
+    current: &crate::baseline::Baseline,
+) -> std::io::Result<()> {
+    writeln!(
+        out,
+        "\n{}{}",
+        "▬".cyan().bold().reversed(),
+        format!(" 与 {git_ref} 对比 ").cyan().bold().reversed()
+    )?;
+    writeln!(out, "{}", "▬".cyan().bold().repeated(50))?;
+    writeln!(
+        out,
+        "\n  {}: {git_ref}:{}",
+        "对比配置".cyan(),
+        config.display()
+    )?;
+    print_metric_deltas(out, previous, current)
+}
+
+/// 关键指标变化表，以及风险等级和限制因素的变化
+fn print_metric_deltas(
+    out: &mut impl Write,
+    baseline: &crate::baseline::Baseline,
+    current: &crate::baseline::Baseline,
+) -> std::io::Result<()> {
+    let rows: Vec<Vec<String>> = baseline
+        .compare(current)
+        .iter()
+        .map(|delta| {
+            vec![
+                delta.label.to_string(),
+                fmt_num(delta.baseline, 2),
+                fmt_num(delta.current, 2),
+                format!("{:+.1}%", delta.change_pct()),
+            ]
+        })
+        .collect();
+    print_table(
+        out,
+        &["指标", "基线", "当前", "变化"],
+        &rows,
+        &[18, 12, 12, 10],
+    )?;
+
+    if baseline.risk_level != current.risk_level {
+        writeln!(
+            out,
+            "\n  {}: {} -> {}",
+            "风险等级变化".yellow(),
+            baseline.risk_level,
+            current.risk_level
+        )?;
+    }
+    if baseline.limiting_factor != current.limiting_factor {
+        writeln!(
+            out,
+            "  {}: {} -> {}",
+            "限制因素变化".yellow(),
+            baseline.limiting_factor,
+            current.limiting_factor
+        )?;
+    }
+    Ok(())
+}
+
+/// 告警严重程度的显示名称
+fn severity_label(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical => "严重",
+        Severity::Warning => "警告",
+        Severity::Info => "提示",
+    }
+}
+
+/// 静默模式的输出: 低风险时不输出，否则输出风险等级、告警和告警级别的建议(命令行写到stderr)
+pub fn print_quiet_summary(
+    out: &mut impl Write,
+    safety: &crate::analysis::SafetyAnalysis,
+) -> std::io::Result<()> {
+    if safety.risk_level == "低风险" {
+        return Ok(());
+    }
+    writeln!(out, "整体风险等级: {}", safety.risk_level)?;
+    writeln!(out, "分项风险等级: {}", resource_risk_summary(safety))?;
+    for warning in safety.sorted_warnings() {
+        writeln!(
+            out,
+            "  - [{}] {}",
+            severity_label(warning.severity),
+            warning.summary
+        )?;
+    }
+    for rec in &safety.recommendations {
+        if rec.severity != Severity::Info {
+            writeln!(out, "  - {}", rec.summary)?;
+        }
+    }
+    Ok(())
+}
+
+/// 输出不成立的断言及原因
+pub fn print_assertion_failures(
+    out: &mut impl Write,
+    failures: &[(&crate::assertion::Assertion, String)],
+    total: usize,
+) -> std::io::Result<()> {
+    writeln!(
+        out,
+        "{}",
+        format!("断言失败 ({}/{total}):", failures.len())
+            .red()
+            .bold()
+    )?;
+    for (assertion, reason) in failures {
+        writeln!(out, "  - {}: {reason}", assertion.to_string().red())?;
+    }
+    Ok(())
+}
+
+/// 输出内存达到危险阈值的场景，及该内存区域超出有效上限的程度
+pub fn print_memory_fit_failures(
+    out: &mut impl Write,
+    failures: &[crate::analysis::MemoryFitFailure],
+) -> std::io::Result<()> {
+    writeln!(
+        out,
+        "{}",
+        format!("内存适配检查失败: {}处内存达到危险阈值", failures.len())
+            .red()
+            .bold()
+    )?;
+    for failure in failures {
+        let excess = if failure.ratio > 1.0 {
+            format!("超出有效上限{}%", fmt_num((failure.ratio - 1.0) * 100.0, 0))
+        } else {
+            format!(
+                "距有效上限仅剩{}%",
+                fmt_num((1.0 - failure.ratio) * 100.0, 0)
+            )
+        };
+        writeln!(
+            out,
+            "  - {}: {}为有效上限的{}%，{}",
+            failure.scenario.red(),
+            failure.pool,
+            fmt_num(failure.ratio * 100.0, 0),
+            excess
+        )?;
+    }
+    Ok(())
+}
+
+/// 输出各突发倍数下的连接数和堆/直接内存状态，以及可承受的最大倍数
+pub fn print_burst_sweep(
+    out: &mut impl Write,
+    points: &[crate::analysis::BurstPoint],
+) -> std::io::Result<()> {
+    writeln!(out, "\n  {}:", "突发倍数评估".cyan().bold())?;
+    let rows: Vec<Vec<String>> = points
+        .iter()
+        .map(|point| {
+            vec![
+                format!("{}x", point.burst_factor),
+                point.connections.to_string(),
+                fmt_num(point.heap_usage, 2),
+                point.heap_status.paint(&point.heap_status.to_string()),
+                fmt_num(point.direct_mem_usage, 2),
+                point.direct_status.paint(&point.direct_status.to_string()),
+            ]
+        })
+        .collect();
+    print_table(
+        out,
+        &[
+            "突发倍数",
+            "突发连接数",
+            "堆内存",
+            "堆状态",
+            "直接内存",
+            "直接内存状态",
+        ],
+        &rows,
+        &[10, 12, 10, 10, 10, 12],
+    )?;
+    let survived = points
+        .iter()
+        .filter(|point| point.survives())
+        .map(|point| point.burst_factor)
+        .fold(None, |max: Option<f64>, factor| {
+            Some(max.map_or(factor, |m| m.max(factor)))
+        });
+    match survived {
+        Some(factor) => writeln!(out, "\n  {}: {factor}x", "可承受的最大突发倍数".cyan())?,
+        None => writeln!(out, "\n  {}", "所有突发倍数下内存均进入危险区".red())?,
+    }
+    Ok(())
+}
+
+/// 磁盘性价比排名表
+pub fn print_disk_ranking(
+    out: &mut impl Write,
+    rankings: &[crate::analysis::cost::DiskRanking],
+    currency: &str,
+) -> std::io::Result<()> {
+    let currency = if currency.is_empty() { "CNY" } else { currency };
+    writeln!(
+        out,
+        "\n{}{}",
+        "▬".green().bold().reversed(),
+        " 磁盘性价比排名 ".green().bold().reversed()
+    )?;
+    writeln!(out, "{}", "▬".green().bold().repeated(50))?;
+    let rows: Vec<Vec<String>> = rankings
+        .iter()
+        .enumerate()
+        .map(|(i, ranking)| {
+            vec![
+                (i + 1).to_string(),
+                ranking.disk_type.clone(),
+                ranking.max_connections.to_string(),
+                if ranking.meets_target {
+                    "是".green()
+                } else {
+                    "否".red()
+                }
+                .to_string(),
+                fmt_num(ranking.monthly_cost, 2),
+                ranking
+                    .cost_per_k_conn
+                    .map_or("-".to_string(), |cost| fmt_num(cost, 2)),
+            ]
+        })
+        .collect();
+    print_table(
+        out,
+        &[
+            "排名",
+            "磁盘类型",
+            "理论最大连接数",
+            "满足目标",
+            "月成本",
+            "每千连接月成本",
+        ],
+        &rows,
+        &[6, 10, 16, 10, 12, 16],
+    )?;
+    match rankings.iter().find(|ranking| ranking.meets_target) {
+        Some(best) => writeln!(
+            out,
+            "\n  {}: {} ({} {currency}/月)",
+            "满足目标且性价比最高".cyan(),
+            best.disk_type,
+            fmt_num(best.monthly_cost, 2)
+        )?,
+        None => writeln!(out, "\n  {}", "没有磁盘类型能满足预期连接数".red())?,
+    }
+    Ok(())
+}
+
+/// 扩容路径: 每步增加的资源及随后的瓶颈
+pub fn print_upgrade_path(
+    out: &mut impl Write,
+    path: &crate::analysis::upgrade::UpgradePath,
+    currency: &str,
+) -> std::io::Result<()> {
+    const ORDINALS: [&str; 10] = ["一", "二", "三", "四", "五", "六", "七", "八", "九", "十"];
+    let currency = if currency.is_empty() { "CNY" } else { currency };
+    writeln!(
+        out,
+        "\n{}{}",
+        "▬".green().bold().reversed(),
+        " 扩容路径 ".green().bold().reversed()
+    )?;
+    writeln!(out, "{}", "▬".green().bold().repeated(50))?;
+    writeln!(
+        out,
+        "  当前稳态最大连接数: {}，目标: {}",
+        path.start_connections, path.target
+    )?;
+
+    for (index, step) in path.steps.iter().enumerate() {
+        let next = match &step.next_bottleneck {
+            Some(bottleneck) => format!("下一瓶颈: {}", bottleneck.label()).yellow(),
+            None => "达到目标".green(),
+        };
+        let cost = step.monthly_cost.map_or(String::new(), |cost| {
+            format!(", +{} {currency}/月", fmt_num(cost, 2))
+        });
+        writeln!(
+            out,
+            "  第{}步: {} → {} (最大连接数 {}{cost})",
+            ORDINALS.get(index).copied().unwrap_or("N"),
+            step.change.cyan(),
+            next,
+            step.max_connections
+        )?;
+    }
+    match &path.blocked {
+        Some(reason) => writeln!(out, "\n  {}", format!("无法继续扩容: {reason}").red())?,
+        None if path.steps.is_empty() => {
+            writeln!(out, "\n  {}", "当前配置已满足目标连接数".green())?
+        }
+        None => {}
+    }
+    Ok(())
+}
+
+pub fn print_safety_report(
+    out: &mut impl Write,
+    safety: &crate::analysis::SafetyAnalysis,
+    explain: bool,
+) -> std::io::Result<()> {
+    print_risk_assessment(out, safety)?;
+    write_advice_items(out, safety, explain)
+}
+
+/// 内存安全分析中的风险评估和安全系数，不含告警和优化建议
+pub fn print_risk_assessment(
+    out: &mut impl Write,
+    safety: &crate::analysis::SafetyAnalysis,
+) -> std::io::Result<()> {
+    writeln!(
+        out,
         "\n{}{}",
         "▬".yellow().bold().reversed(),
         " 内存安全分析 ".yellow().bold().reversed()
-    );
-    println!("{}", "▬".yellow().bold().repeated(50));
-
-    println!("\n  {}:", "风险评估".cyan().bold());
-    let risk_color = match safety.risk_level.as_str() {
-        "低风险" => "green",
-        "中风险" => "yellow",
-        _ => "red",
-    };
+    )?;
+    writeln!(out, "{}", "▬".yellow().bold().repeated(50))?;
 
-    println!(
+    writeln!(out, "\n  {}:", "风险评估".cyan().bold())?;
+    writeln!(
+        out,
         "  {:>20}: {}",
         "整体风险等级".cyan(),
-        safety.risk_level.color(risk_color).bold()
-    );
+        safety
+            .risk_level
+            .color(risk_color(&safety.risk_level))
+            .bold()
+    )?;
+    let resource_risks: Vec<String> = safety
+        .resource_risks
+        .iter()
+        .map(|(label, level)| format!("{label}: {}", level.color(risk_color(level))))
+        .collect();
+    writeln!(
+        out,
+        "  {:>20}: {}",
+        "分项风险等级".cyan(),
+        resource_risks.join(", ")
+    )?;
 
     // 安全系数图表
-    println!("\n  {}(0-1,越高越安全):", "内存安全系数".cyan());
+    writeln!(out, "\n  {}(0-1,越高越安全):", "内存安全系数".cyan())?;
 
-    print_safety_bar("堆内存安全", safety.heap_safety);
-    print_safety_bar("直接内存安全", safety.direct_mem_safety);
+    print_safety_bar(out, "堆内存安全", safety.heap_safety)?;
+    print_safety_bar(out, "直接内存安全", safety.direct_mem_safety)?;
+    Ok(())
+}
+
+/// 单独输出告警和优化建议章节(未包含内存安全分析章节时使用)
+pub fn print_safety_advice(
+    out: &mut impl Write,
+    safety: &crate::analysis::SafetyAnalysis,
+    explain: bool,
+) -> std::io::Result<()> {
+    writeln!(
+        out,
+        "\n{}{}",
+        "▬".yellow().bold().reversed(),
+        " 告警与优化建议 ".yellow().bold().reversed()
+    )?;
+    writeln!(out, "{}", "▬".yellow().bold().repeated(50))?;
+    write_advice_items(out, safety, explain)
+}
+
+fn write_advice_items(
+    out: &mut impl Write,
+    safety: &crate::analysis::SafetyAnalysis,
+    explain: bool,
+) -> std::io::Result<()> {
+    // 告警，按严重程度排序后单独输出
+    if !safety.warnings.is_empty() {
+        writeln!(out, "\n  {}:", "告警".red().bold())?;
+        for warning in safety.sorted_warnings() {
+            let label = format!("❗ [{}]", severity_label(warning.severity));
+            let line = match warning.severity {
+                Severity::Critical => format!("{} {}", label.red().bold(), warning.summary.red()),
+                _ => format!("{} {}", label.yellow().bold(), warning.summary.yellow()),
+            };
+            writeln!(out, "    - {line}")?;
+            if explain {
+                writeln!(
+                    out,
+                    "      {} {}",
+                    "依据:".dimmed(),
+                    warning.rationale.dimmed()
+                )?;
+            }
+        }
+    }
 
     // 防护建议
     if !safety.recommendations.is_empty() {
-        println!("\n  {}:", "优化建议".cyan());
+        writeln!(out, "\n  {}:", "优化建议".cyan())?;
         for rec in &safety.recommendations {
-            println!("    - {rec}");
+            let summary = match rec.severity {
+                Severity::Critical => rec.summary.red().to_string(),
+                Severity::Warning => rec.summary.yellow().to_string(),
+                Severity::Info => rec.summary.clone(),
+            };
+            writeln!(out, "    - {summary}")?;
+            if explain {
+                writeln!(out, "      {} {}", "依据:".dimmed(), rec.rationale.dimmed())?;
+            }
         }
     }
+    Ok(())
 }
 
-fn print_safety_bar(label: &str, value: f64) {
-    let width = 30;
-    let fill = (value * width as f64) as usize;
-    let empty = width - fill;
+pub(crate) fn print_safety_bar(
+    out: &mut impl Write,
+    label: &str,
+    value: f64,
+) -> std::io::Result<()> {
+    let bar = match safety_bar_fill(value) {
+        Some(fill) => format!(
+            "[{}{}] {}%",
+            "■".green().repeated(fill),
+            " ".repeated(SAFETY_BAR_WIDTH - fill),
+            fmt_num(value * 100.0, 0)
+        ),
+        None => format!("[{}] {}", " ".repeated(SAFETY_BAR_WIDTH), "N/A".yellow()),
+    };
 
-    let bar = format!(
-        "[{}{}] {:.0}%",
-        "■".green().repeated(fill),
-        " ".repeated(empty),
-        value * 100.0
-    );
+    writeln!(out, "  {:>18}: {}", label.cyan(), bar)?;
+    Ok(())
+}
+
+/// 字符串在终端中的显示宽度
+///
+/// 忽略ANSI转义序列，其余按Unicode东亚宽度计算: 中日韩文字、全角符号和emoji计为2列，
+/// 组合字符和变体选择符计为0列
+pub fn display_width(s: &str) -> usize {
+    strip_ansi_escapes::strip_str(s).width()
+}
+
+/// 按显示宽度在右侧补齐空格
+pub fn pad_display(s: &str, width: usize) -> String {
+    let padding = width.saturating_sub(display_width(s));
+    format!("{s}{}", " ".repeat(padding))
+}
+
+/// 打印左对齐的控制台表格
+///
+/// 自动列宽模式下每列宽度取表头和所有单元格显示宽度的最大值；
+/// 关闭时使用 `fixed_widths`，与 `{:<N}` 格式一致按字符数补齐。
+/// 最后一列不补齐，避免行尾多余空格
+pub(crate) fn print_table(
+    out: &mut impl Write,
+    headers: &[&str],
+    rows: &[Vec<String>],
+    fixed_widths: &[usize],
+) -> std::io::Result<()> {
+    let auto_width = AUTO_WIDTH.load(Ordering::Relaxed);
+    let widths: Vec<usize> = if auto_width {
+        headers
+            .iter()
+            .enumerate()
+            .map(|(i, header)| {
+                rows.iter()
+                    .filter_map(|row| row.get(i))
+                    .map(|cell| display_width(cell))
+                    .chain(std::iter::once(display_width(header)))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect()
+    } else {
+        fixed_widths.to_vec()
+    };
+
+    let pad = |cell: &str, i: usize| -> String {
+        let width = widths.get(i).copied().unwrap_or(0);
+        if auto_width {
+            pad_display(cell, width)
+        } else {
+            format!("{cell:<width$}")
+        }
+    };
 
-    println!("  {:>18}: {}", label.cyan(), bar);
+    let header_line: Vec<String> = headers
+        .iter()
+        .enumerate()
+        .map(|(i, header)| {
+            if i + 1 == headers.len() {
+                header.cyan().to_string()
+            } else {
+                pad(header, i).cyan().to_string()
+            }
+        })
+        .collect();
+    writeln!(out, "  {}", header_line.join(" "))?;
+
+    for row in rows {
+        let cells: Vec<String> = row
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| {
+                if i + 1 == row.len() {
+                    cell.clone()
+                } else {
+                    pad(cell, i)
+                }
+            })
+            .collect();
+        writeln!(out, "  {}", cells.join(" "))?;
+    }
+    Ok(())
 }
 
 // 扩展trait用于重复字符串
@@ -664,13 +2332,199 @@ impl Repeated for &str {
     }
 }
 
+// 关闭颜色时 `to_string` 不带ANSI转义序列，结果为纯文本
 impl Repeated for colored::ColoredString {
     fn repeated(&self, times: usize) -> String {
-        let colored_str = self.clone();
-        let mut result = String::new();
-        for _ in 0..times {
-            result.push_str(&colored_str.to_string());
-        }
-        result
+        self.to_string().repeat(times)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_width_counts_cjk_as_double() {
+        assert_eq!(display_width("QPS"), 3);
+        assert_eq!(display_width("突发流量 (3x)"), 13);
+        assert_eq!(display_width(&"安全".green().to_string()), 4);
+        assert_eq!(display_width("✅ 通过"), 7);
+        assert_eq!(display_width("e\u{301}"), 1);
+        assert_eq!(pad_display("堆内存", 8), "堆内存  ");
+    }
+
+    #[test]
+    fn test_report_is_reproducible_with_fixed_time() {
+        use clap::Parser;
+
+        let args = crate::args::Args::parse_from(["sa"]);
+        let result = crate::analysis::analyze(&args).unwrap();
+        let ctx = ReportContext {
+            args: &args,
+            direct_mem_gb: result.direct_mem_gb,
+            heap_mem_gb: result.heap_mem_gb,
+            metaspace_size_mb: result.metaspace_size_mb,
+            disk_read_speed: result.disk_read_speed,
+            disk_write_speed: result.disk_write_speed,
+            safety: &result.safety,
+            performance: &result.performance,
+            backlog: &result.backlog,
+            cluster: result.cluster.as_ref(),
+        };
+        let time = chrono::DateTime::parse_from_rfc3339("2024-01-02T03:04:05+08:00").unwrap();
+
+        let first = render_markdown_report(&ctx, time).unwrap();
+        let second = render_markdown_report(&ctx, time).unwrap();
+        assert_eq!(first, second);
+        assert!(first.contains("> 生成时间: 2024-01-02 03:04:05"));
+
+        // 写入任意输出(如标准输出)的内容与报告文件一致
+        let mut streamed = Vec::new();
+        write_markdown_report(&mut streamed, &ctx, time).unwrap();
+        assert_eq!(String::from_utf8(streamed).unwrap(), first);
+    }
+
+    #[test]
+    fn test_report_sections_filter_markdown() {
+        use clap::Parser;
+
+        let args =
+            crate::args::Args::parse_from(["sa", "--report-sections", "jvm,recommendations"]);
+        let result = crate::analysis::analyze(&args).unwrap();
+        let ctx = ReportContext {
+            args: &args,
+            direct_mem_gb: result.direct_mem_gb,
+            heap_mem_gb: result.heap_mem_gb,
+            metaspace_size_mb: result.metaspace_size_mb,
+            disk_read_speed: result.disk_read_speed,
+            disk_write_speed: result.disk_write_speed,
+            safety: &result.safety,
+            performance: &result.performance,
+            backlog: &result.backlog,
+            cluster: result.cluster.as_ref(),
+        };
+        let time = chrono::DateTime::parse_from_rfc3339("2024-01-02T03:04:05+08:00").unwrap();
+
+        let report = render_markdown_report(&ctx, time).unwrap();
+        assert!(report.contains("## JVM配置建议"));
+        assert!(report.contains("## 优化建议"));
+        assert!(!report.contains("## 系统配置"));
+        assert!(!report.contains("## 负载场景模拟"));
+    }
+
+    #[test]
+    fn test_console_report_renders_to_buffer() {
+        use clap::Parser;
+
+        let args = crate::args::Args::parse_from(["sa"]);
+        let result = crate::analysis::analyze(&args).unwrap();
+        let mut out = Vec::new();
+        print_safety_report(&mut out, &result.safety, false).unwrap();
+        print_performance_report(&mut out, &result.performance).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains("内存安全分析"));
+        assert!(text.contains(&result.safety.risk_level));
+        assert!(text.contains("全链路性能分析报告"));
+    }
+
+    #[test]
+    fn test_safety_bar_clamps_out_of_range_values() {
+        assert_eq!(
+            safety_bar(0.5),
+            format!("[{}{}] 50%", "■".repeat(15), " ".repeat(15))
+        );
+        assert_eq!(safety_bar(1.5), format!("[{}] 150%", "■".repeat(30)));
+        assert_eq!(safety_bar(-0.2), format!("[{}] -20%", " ".repeat(30)));
+        assert_eq!(safety_bar(f64::NAN), format!("[{}] N/A", " ".repeat(30)));
+
+        let mut out = Vec::new();
+        print_safety_bar(&mut out, "堆内存安全", 2.0).unwrap();
+        print_safety_bar(&mut out, "直接内存安全", f64::NAN).unwrap();
+        let text = strip_ansi_escapes::strip_str(String::from_utf8(out).unwrap());
+        assert!(text.contains("] 200%"));
+        assert!(text.contains("] N/A"));
+    }
+
+    #[test]
+    fn test_fmt_num_uses_field_default() {
+        assert_eq!(fmt_num(2.567, 1), "2.6");
+        assert_eq!(fmt_num(2.567, 0), "3");
+        assert_eq!(fmt_num(0.1, 2), "0.10");
+    }
+
+    #[test]
+    fn test_status_summary_counts_each_status() {
+        use crate::analysis::ScenarioStatus;
+
+        let scenario = |status| crate::Scenario {
+            name: String::new(),
+            connections: 0,
+            file_size: 0.0,
+            heap_usage: 0.0,
+            direct_mem_usage: 0.0,
+            status,
+        };
+        let scenarios = [
+            scenario(ScenarioStatus::Safe),
+            scenario(ScenarioStatus::Danger),
+            scenario(ScenarioStatus::Safe),
+            scenario(ScenarioStatus::Warning),
+            scenario(ScenarioStatus::Safe),
+        ];
+        assert_eq!(
+            status_summary(&scenarios, |_, text| text.to_string()),
+            "3 安全, 1 警告, 1 危险"
+        );
+    }
+
+    #[test]
+    fn test_report_path() {
+        let time = chrono::DateTime::parse_from_rfc3339("2024-01-02T03:04:05+08:00").unwrap();
+        assert_eq!(
+            report_path(Path::new("."), false, time),
+            Path::new("./sa_report.md")
+        );
+        assert_eq!(
+            report_path(Path::new("reports"), true, time),
+            Path::new("reports/sa_report_20240102_030405.md")
+        );
+    }
+
+    #[test]
+    fn test_status_ranges_follow_policy() {
+        let policy = crate::analysis::SafetyPolicy::default();
+        assert_eq!(status_ranges(&policy), ["<42%", "42-56%", "≥56%"]);
+        let policy = crate::analysis::SafetyPolicy {
+            warn_at: 0.5,
+            danger_at: 0.9,
+            effective_max_ratio: 1.0,
+            ..policy
+        };
+        assert_eq!(status_ranges(&policy), ["<50%", "50-90%", "≥90%"]);
+    }
+
+    #[test]
+    fn test_github_slug() {
+        assert_eq!(github_slug("JVM配置建议"), "jvm配置建议");
+        assert_eq!(
+            github_slug("混合文件大小 (平均文件大小: 30.0MB)"),
+            "混合文件大小-平均文件大小-300mb"
+        );
+    }
+
+    #[test]
+    fn test_table_of_contents() {
+        let body = "## 系统配置\n```ini\n## 不是标题\n```\n## 性能分析\n### 小文件\n## 性能分析\n";
+        let mut out = Vec::new();
+        write_table_of_contents(&mut out, body).unwrap();
+        let toc = String::from_utf8(out).unwrap();
+        assert_eq!(
+            toc,
+            "## 目录\n\
+             - [系统配置](#系统配置)\n\
+             - [性能分析](#性能分析)\n  \
+             - [小文件](#小文件)\n\
+             - [性能分析](#性能分析-1)\n\n"
+        );
     }
 }
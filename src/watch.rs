@@ -0,0 +1,76 @@
+use anyhow::Context;
+use colored::Colorize;
+use notify::{RecursiveMode, Watcher};
+use std::path::Path;
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// 连续保存的合并窗口: 文件在此时间内不再变化才触发重新分析
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// 监视配置文件，每次保存后清屏并调用 `on_change` 重新分析
+///
+/// 启动时立即执行一次。监视配置文件所在目录而非文件本身，
+/// 编辑器以替换方式保存(写临时文件后重命名)时同样能收到通知。
+/// `on_change` 返回的错误只打印，不会中断监视
+pub fn run(path: &Path, mut on_change: impl FnMut() -> anyhow::Result<()>) -> anyhow::Result<()> {
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).context("无法创建文件监视器")?;
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    watcher
+        .watch(dir, RecursiveMode::NonRecursive)
+        .with_context(|| format!("无法监视目录: {}", dir.display()))?;
+    rerun(path, &mut on_change);
+
+    while let Ok(event) = rx.recv() {
+        if !touches(&event, path) {
+            continue;
+        }
+
+        // 等待文件稳定，合并编辑器的多次写入
+        while let Ok(event) = rx.recv_timeout(DEBOUNCE) {
+            if let Err(e) = event {
+                log::warn!("文件监视出错: {e}");
+            }
+        }
+
+        rerun(path, &mut on_change);
+    }
+    Ok(())
+}
+
+/// 事件是否涉及被监视的配置文件，监视出错时记录日志并忽略
+fn touches(event: &notify::Result<notify::Event>, path: &Path) -> bool {
+    match event {
+        Ok(event) => {
+            !event.kind.is_access()
+                && event
+                    .paths
+                    .iter()
+                    .any(|changed| changed.file_name() == path.file_name())
+        }
+        Err(e) => {
+            log::warn!("文件监视出错: {e}");
+            false
+        }
+    }
+}
+
+fn rerun(path: &Path, on_change: &mut impl FnMut() -> anyhow::Result<()>) {
+    // 清屏并将光标移到左上角
+    print!("\x1b[2J\x1b[H");
+    println!(
+        "{} {} ({})",
+        "▶ 重新分析".cyan().bold(),
+        path.display(),
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+    );
+
+    if let Err(e) = on_change() {
+        println!("\n  {}: {e:#}", "分析失败".red().bold());
+        println!("  继续监视配置文件...");
+    }
+}